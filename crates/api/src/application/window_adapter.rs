@@ -7,7 +7,7 @@ use crate::{
     event::*,
     localization::Localization,
     render,
-    services::{Clipboard, Settings},
+    services::{Clipboard, ImageCache, Settings},
     shell,
     shell::{ShellRequest, WindowRequest, WindowSettings},
     systems::*,
@@ -224,6 +224,15 @@ pub fn create_window<F: Fn(&mut BuildContext) -> Entity + 'static>(
         .borrow_mut()
         .register("clipboard", Clipboard::new());
 
+    let image_cache = ImageCache::new();
+    registry
+        .borrow_mut()
+        .register("image_cache", image_cache.clone());
+
+    registry
+        .borrow_mut()
+        .register("frame_timer", FrameTimer::new());
+
     let context_provider = ContextProvider::new(sender, request_sender, app_name, localization);
 
     let window = {
@@ -320,6 +329,11 @@ pub fn create_window<F: Fn(&mut BuildContext) -> Entity + 'static>(
         .entity_component_manager()
         .component_store_mut()
         .register("theme", window, theme);
+    // the images of the theme are loaded by the image cache of the registry
+    world
+        .entity_component_manager()
+        .component_store_mut()
+        .register("image_cache", window, image_cache);
     world
         .entity_component_manager()
         .component_store_mut()
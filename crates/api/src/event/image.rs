@@ -0,0 +1,80 @@
+use crate::{
+    prelude::*,
+    proc_macros::{Event, IntoHandler},
+};
+
+/// This event occurs on a widget if an image it has requested from the `ImageCache` is loaded or
+/// could not be loaded.
+#[derive(Event, Clone)]
+pub struct ImageLoadedEvent {
+    pub source: String,
+}
+
+pub type ImageLoadedFn = dyn Fn(&mut StatesContext, String) -> bool + 'static;
+
+#[derive(IntoHandler)]
+pub struct ImageLoadedEventHandler {
+    pub handler: Rc<ImageLoadedFn>,
+}
+
+impl EventHandler for ImageLoadedEventHandler {
+    fn handle_event(&self, states: &mut StatesContext, event: &EventBox) -> bool {
+        if let Ok(event) = event.downcast_ref::<ImageLoadedEvent>() {
+            return (self.handler)(states, event.source.clone());
+        }
+
+        false
+    }
+
+    fn handles_event(&self, event: &EventBox) -> bool {
+        event.is_type::<ImageLoadedEvent>()
+    }
+}
+
+/// Implement this trait if you want that your widget can handle image loaded events.
+pub trait ImageLoadedHandler: Sized + Widget {
+    /// Inserts a handler for image loaded events.
+    fn on_image_loaded<H: Fn(&mut StatesContext, String) -> bool + 'static>(
+        self,
+        handler: H,
+    ) -> Self {
+        self.insert_handler(ImageLoadedEventHandler {
+            handler: Rc::new(handler),
+        })
+    }
+}
+
+/// This event occurs on a widget if the delay of the current frame of its animated image is over.
+#[derive(Event, Clone)]
+pub struct ImageFrameEvent;
+
+pub type ImageFrameFn = dyn Fn(&mut StatesContext) -> bool + 'static;
+
+#[derive(IntoHandler)]
+pub struct ImageFrameEventHandler {
+    pub handler: Rc<ImageFrameFn>,
+}
+
+impl EventHandler for ImageFrameEventHandler {
+    fn handle_event(&self, states: &mut StatesContext, event: &EventBox) -> bool {
+        if event.downcast_ref::<ImageFrameEvent>().is_ok() {
+            return (self.handler)(states);
+        }
+
+        false
+    }
+
+    fn handles_event(&self, event: &EventBox) -> bool {
+        event.is_type::<ImageFrameEvent>()
+    }
+}
+
+/// Implement this trait if you want that your widget can handle image frame events.
+pub trait ImageFrameHandler: Sized + Widget {
+    /// Inserts a handler for image frame events.
+    fn on_image_frame<H: Fn(&mut StatesContext) -> bool + 'static>(self, handler: H) -> Self {
+        self.insert_handler(ImageFrameEventHandler {
+            handler: Rc::new(handler),
+        })
+    }
+}
@@ -12,6 +12,7 @@ pub use self::event_adapter::*;
 pub use self::event_handler::*;
 pub use self::event_queue::*;
 pub use self::focus::*;
+pub use self::image::*;
 pub use self::key::*;
 pub use self::mouse::*;
 pub use self::system::*;
@@ -23,6 +24,7 @@ mod event_adapter;
 mod event_handler;
 mod event_queue;
 mod focus;
+mod image;
 mod key;
 mod mouse;
 mod system;
@@ -23,7 +23,17 @@ pub fn check_mouse_condition(mouse_position: Point, widget: &WidgetContainer<'_>
     rect.set_x(position.x());
     rect.set_y(position.y());
 
-    rect.contains(mouse_position)
+    // the mouse position is transformed back into the coordinates of the not transformed widget
+    let transform = widget.global_render_transform();
+
+    if transform.is_identity() {
+        return rect.contains(mouse_position);
+    }
+
+    match transform.invert() {
+        Some(inverse) => rect.contains(inverse.transform_point(mouse_position)),
+        None => false,
+    }
 }
 
 /// `MouseMoveEvent` indicates if the mouse position is changed on the window.
@@ -9,14 +9,15 @@ use memchr::memchr_iter;
 use crate::{
     proc_macros::IntoLayout,
     render::Image,
-    render::RenderContext2D,
+    render::{RenderContext2D, TextLayout},
+    render_object::text_layout_options,
     theming::*,
     tree::Tree,
     utils::prelude::*,
     widget_base::{mark_as_dirty, WidgetContainer},
 };
 
-use super::{component, component_try_mut, Layout};
+use super::{component, component_or_default, component_try_mut, Layout};
 
 /// Fixed size layout is defined by fixed bounds like the size of an image or the size of a text.
 #[derive(Default, IntoLayout)]
@@ -29,6 +30,37 @@ impl FixedSizeLayout {
     pub fn new() -> Self {
         FixedSizeLayout::default()
     }
+
+    // Lays out the text or the water mark of the widget in the given width.
+    fn text_layout(
+        &self,
+        render_context_2_d: &mut RenderContext2D,
+        widget: &WidgetContainer,
+        max_width: f64,
+    ) -> Option<TextLayout> {
+        let text = text(widget)?;
+        let font = widget.get::<String>("font");
+        let font_size = widget.get::<f64>("font_size");
+        render_context_2_d.set_font_weight(widget.clone_or_default::<FontWeight>("font_weight"));
+        render_context_2_d.set_font_style(widget.clone_or_default::<FontStyle>("font_style"));
+
+        let options = text_layout_options(widget, *font_size, max_width);
+        let text = if text.is_empty() {
+            widget
+                .try_get::<String>("water_mark")
+                .filter(|water_mark| !water_mark.is_empty())?
+                .clone()
+        } else {
+            text
+        };
+
+        render_context_2_d.set_font_family(font.as_str());
+        render_context_2_d.set_font_size(*font_size);
+
+        Some(TextLayout::new(&text, &options, |text| {
+            render_context_2_d.measure_text(text).width
+        }))
+    }
 }
 
 impl Layout for FixedSizeLayout {
@@ -56,52 +88,56 @@ impl Layout for FixedSizeLayout {
             self.desired_size.borrow_mut().set_dirty(true);
         }
 
+        // stretched images are measured with their aspect ratio against the constraint, their
+        // size is not stored in the constraint so they follow changes of the available size
+        let stretched_size = widget.try_get::<Image>("image").and_then(|image| {
+            let stretch = *widget.try_get::<Stretch>("stretch")?;
+
+            if stretch == Stretch::None {
+                return None;
+            }
+
+            let constraint = widget.get::<Constraint>("constraint");
+            let available = |size: f64, max_size: f64| if size > 0.0 { size } else { max_size };
+
+            Some(stretch.measure(
+                image.width(),
+                image.height(),
+                available(constraint.width(), constraint.max_width()),
+                available(constraint.height(), constraint.max_height()),
+            ))
+        });
+
+        let mut text_layout = None;
+
         let size = widget
             .try_get::<Image>("image")
+            .filter(|_| stretched_size.is_none())
             .map(|image| (image.width(), image.height()))
             .or_else(|| {
-                text(&widget).and_then(|text| {
-                    let font = widget.get::<String>("font");
-                    let font_size = widget.get::<f64>("font_size");
-
-                    if text.is_empty() {
-                        widget
-                            .try_get::<String>("water_mark")
-                            .filter(|water_mark| !water_mark.is_empty())
-                            .map(|water_mark| {
-                                let text_metrics = render_context_2_d.measure(
-                                    &water_mark,
-                                    *font_size,
-                                    font.as_str(),
-                                );
-                                (
-                                    text_metrics.width,
-                                    text_metrics.height
-                                        * ((memchr_iter(b'\n', water_mark.as_bytes()).count()
-                                            as f64)
-                                            * 1.15
-                                            + 1.0),
-                                )
-                            })
-                    } else {
-                        let text_metrics =
-                            render_context_2_d.measure(&text, *font_size, font.as_str());
-
-                        Some((
-                            text_metrics.width,
-                            text_metrics.height
-                                * ((memchr_iter(b'\n', text.as_bytes()).count() as f64) * 1.15
-                                    + 1.0),
-                        ))
-                    }
+                // vector icons are drawn at the icon size
+                widget.try_get::<Image>("icon").map(|_| {
+                    let icon_size = *widget.get::<f64>("icon_size");
+                    (icon_size, icon_size)
                 })
             })
+            .or_else(|| {
+                // the text is wrapped and trimmed at the maximal width of the widget
+                let max_width = widget.get::<Constraint>("constraint").max_width();
+                text_layout = self.text_layout(render_context_2_d, &widget, max_width);
+
+                text_layout
+                    .as_ref()
+                    .map(|text_layout| (text_layout.width(), text_layout.height()))
+            })
             .or_else(|| {
                 widget
                     .try_clone::<String>("icon")
                     .filter(|font_icon| !font_icon.is_empty())
                     .map(|font_icon| {
                         let icon_size = widget.get::<f64>("icon_size");
+                        render_context_2_d.set_font_weight(FontWeight::default());
+                        render_context_2_d.set_font_style(FontStyle::default());
                         let text_metrics = render_context_2_d.measure(
                             &font_icon,
                             *icon_size,
@@ -117,6 +153,12 @@ impl Layout for FixedSizeLayout {
                     })
             });
 
+        // the text render object draws the measured lines
+        if let Some(text_layout) = text_layout {
+            ecm.component_store_mut()
+                .register("text_layout", entity, text_layout);
+        }
+
         if let Some(size) = size {
             if let Some(constraint) = component_try_mut::<Constraint>(ecm, entity, "constraint") {
                 constraint.set_width(size.0 as f64);
@@ -124,6 +166,10 @@ impl Layout for FixedSizeLayout {
             }
         }
 
+        if let Some((width, height)) = stretched_size {
+            self.desired_size.borrow_mut().set_size(width, height);
+        }
+
         let constraint: Constraint = component(ecm, entity, "constraint");
 
         if constraint.width() > 0.0 {
@@ -154,7 +200,7 @@ impl Layout for FixedSizeLayout {
     fn arrange(
         &self,
         render_context_2_d: &mut RenderContext2D,
-        _parent_size: (f64, f64),
+        parent_size: (f64, f64),
         entity: Entity,
         ecm: &mut EntityComponentManager<Tree, StringComponentStore>,
         layouts: &BTreeMap<Entity, Box<dyn Layout>>,
@@ -165,6 +211,34 @@ impl Layout for FixedSizeLayout {
             return (0.0, 0.0);
         }
 
+        // a wrapped or trimmed text that is wider than its parent is laid out again in the
+        // width of the parent, other texts overflow it
+        let margin: Thickness = component_or_default(ecm, entity, "margin");
+        let available_width = (parent_size.0 - margin.left() - margin.right()).max(0.0);
+
+        if self.desired_size.borrow().width() > available_width
+            && ecm
+                .component_store()
+                .get::<TextLayout>("text_layout", entity)
+                .is_ok()
+        {
+            let widget = WidgetContainer::new(entity, ecm, theme, None);
+
+            if widget.clone_or_default::<TextWrap>("text_wrap") != TextWrap::NoWrap
+                || widget.clone_or_default::<TextTrimming>("text_trimming") != TextTrimming::None
+            {
+                let text_layout = self.text_layout(render_context_2_d, &widget, available_width);
+
+                if let Some(text_layout) = text_layout {
+                    self.desired_size
+                        .borrow_mut()
+                        .set_size(text_layout.width(), text_layout.height());
+                    ecm.component_store_mut()
+                        .register("text_layout", entity, text_layout);
+                }
+            }
+        }
+
         if let Some(bounds) = component_try_mut::<Rectangle>(ecm, entity, "bounds") {
             bounds.set_width(self.desired_size.borrow().width());
             bounds.set_height(self.desired_size.borrow().height());
@@ -108,8 +108,16 @@ macro_rules! widget {
             enabled: bool,
             #[property(bool)]
             clip: bool,
+            #[property(ClipMode)]
+            clip_mode: ClipMode,
             #[property(f32)]
             opacity: f32,
+            #[property(Effect)]
+            effect: Effect,
+            #[property(Effect)]
+            backdrop_effect: Effect,
+            #[property(RenderTransform)]
+            render_transform: RenderTransform,
             #[property(Visibility)]
             visibility: Visibility,
             #[property(Selector)]
@@ -222,11 +230,33 @@ macro_rules! widget {
                 self.set_property("clip", clip)
             }
 
+            /// Sets or shares the clip mode property.
+            pub fn clip_mode(self, clip_mode: impl IntoPropertySource<ClipMode>) -> Self {
+                self.set_property("clip_mode", clip_mode)
+            }
+
             /// Sets or shares the opacity property.
             pub fn opacity(self, opacity: impl IntoPropertySource<f32>) -> Self {
                 self.set_property("opacity", opacity)
             }
 
+            /// Sets or shares the effect property.
+            pub fn effect(self, effect: impl IntoPropertySource<Effect>) -> Self {
+                self.set_property("effect", effect)
+            }
+
+            /// Sets or shares the backdrop effect property. The effect is applied on the content
+            /// behind the widget inside of its bounds, e.g. to blur the window behind a dialog.
+            pub fn backdrop_effect(self, backdrop_effect: impl IntoPropertySource<Effect>) -> Self {
+                self.set_property("backdrop_effect", backdrop_effect)
+            }
+
+            /// Sets or shares the render transform property. The widget and its children are
+            /// drawn transformed without affecting the layout.
+            pub fn render_transform(self, render_transform: impl IntoPropertySource<RenderTransform>) -> Self {
+                self.set_property("render_transform", render_transform)
+            }
+
             /// Inserts a new width.
             pub fn width(mut self, width: impl Into<f64>) -> Self {
                 if !self.width.is_none() {
@@ -457,7 +487,11 @@ macro_rules! widget {
                 ctx.register_property("margin", entity, this.margin);
                 ctx.register_property("enabled", entity, this.enabled);
                 ctx.register_property("clip", entity, this.clip);
+                ctx.register_property("clip_mode", entity, this.clip_mode);
                 ctx.register_property("opacity", entity, this.opacity);
+                ctx.register_property("effect", entity, this.effect);
+                ctx.register_property("backdrop_effect", entity, this.backdrop_effect);
+                ctx.register_property("render_transform", entity, this.render_transform);
                 ctx.register_property("type_id", entity, TypeId::of::<$widget>());
                 ctx.register_property("type_name", entity, std::any::type_name::<$widget>().to_string());
                 ctx.register_property("dirty", entity, false);
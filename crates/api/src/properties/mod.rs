@@ -89,7 +89,11 @@ into_property_source!(i64);
 
 // Implementation of PropertySource for utils types
 into_property_source!(utils::Alignment: &str);
+into_property_source!(utils::BoxShadow: &str, String, utils::Value);
 into_property_source!(utils::Brush: &str, utils::Color, utils::Value);
+into_property_source!(utils::ClipMode: &str, String, utils::Value);
+into_property_source!(utils::Effect: &str, String, utils::Value);
+into_property_source!(utils::RenderTransform: &str, String, utils::Matrix, utils::Value);
 into_property_source!(utils::Orientation: &str);
 into_property_source!(utils::Point: f64, i32, (i32, i32), (f64, f64));
 into_property_source!(utils::Size: f64, i32, (i32, i32), (f64, f64));
@@ -108,16 +112,22 @@ into_property_source!(
     utils::Value
 );
 into_property_source!(utils::SelectionMode: &str);
+into_property_source!(utils::Stretch: &str, String, utils::Value);
+into_property_source!(utils::TextAlignment: &str, String, utils::Value);
+into_property_source!(utils::TextTrimming: &str, String, utils::Value);
+into_property_source!(utils::TextWrap: &str, String, utils::Value);
 into_property_source!(utils::Visibility: &str);
 into_property_source!(Vec<String>);
 into_property_source!(utils::Filter: &str, String, Vec<String>, Vec<&str>);
+into_property_source!(utils::FontStyle: &str, String, utils::Value);
+into_property_source!(utils::FontWeight: &str, String, u16, f64, utils::Value);
 
 // Implementation of css types
 into_property_source!(theming::Selector: &str, String);
 into_property_source!(theming::Theme);
 
 // Implementation of render property types
-into_property_source!(render::Image: &str, String, (u32, u32, Vec<u32>));
+into_property_source!(render::Image: &str, String, (u32, u32, Vec<u32>), utils::Value);
 
 // Implementation of custom property types
 into_property_source!(Columns: ColumnsBuilder);
@@ -129,4 +139,5 @@ into_property_source!(SelectedEntities: HashSet<Entity>);
 into_property_source!(SelectedIndices: HashSet<usize>);
 into_property_source!(TextSelection: (usize, usize));
 into_property_source!(FocusState);
+into_property_source!(ImageLoadState);
 into_property_source!(KeyboardState);
@@ -0,0 +1,21 @@
+/// Describes the loading state of the image of an `ImageWidget` that is loaded from its source.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImageLoadState {
+    /// No image source is set.
+    Empty,
+
+    /// The image is decoded, the placeholder is shown.
+    Loading,
+
+    /// The image is loaded.
+    Loaded,
+
+    /// The image could not be loaded, contains the error message.
+    Failed(String),
+}
+
+impl Default for ImageLoadState {
+    fn default() -> Self {
+        ImageLoadState::Empty
+    }
+}
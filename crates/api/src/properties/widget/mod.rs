@@ -1,5 +1,6 @@
 // Widget related properties.
 pub use self::focus_state::*;
+pub use self::image_load_state::*;
 pub use self::keyboard_state::*;
 pub use self::render_pipeline::*;
 pub use self::selected_entities::*;
@@ -7,6 +8,7 @@ pub use self::selected_indices::*;
 pub use self::text_selection::*;
 
 mod focus_state;
+mod image_load_state;
 mod keyboard_state;
 mod render_pipeline;
 mod selected_entities;
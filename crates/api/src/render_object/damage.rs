@@ -0,0 +1,346 @@
+use std::collections::BTreeMap;
+
+use dces::prelude::*;
+
+use crate::{prelude::*, tree::Tree, utils::*};
+
+// If there are more damaged regions they are merged into one.
+const MAX_DAMAGED_REGIONS: usize = 4;
+
+/// The parts of the window the visible widgets draw on, in window coordinates. Used to find the
+/// damaged regions of a frame by comparing it with the previous frame.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DrawingBounds {
+    // bounds of each widget extended by its box shadow and effect
+    widgets: BTreeMap<Entity, Rectangle>,
+    // drawing bounds of each widget joined with the ones of its (not clipped) children
+    subtrees: BTreeMap<Entity, Rectangle>,
+    // drawing bounds of widgets with an effect that reads neighboring pixels e.g. a blur, and
+    // the pixels that are read by blurred backdrops
+    blurred: Vec<Rectangle>,
+}
+
+impl DrawingBounds {
+    /// Calculates the drawing bounds of all visible widgets of the tree.
+    pub fn new(ecm: &EntityComponentManager<Tree, StringComponentStore>) -> Self {
+        let mut drawing_bounds = DrawingBounds::default();
+        drawing_bounds.insert(
+            ecm.entity_store().root(),
+            Point::default(),
+            Matrix::identity(),
+            ecm,
+        );
+        drawing_bounds
+    }
+
+    /// Gets the drawing bounds of the given widget, if it is visible.
+    pub fn bounds(&self, entity: Entity) -> Option<Rectangle> {
+        self.widgets.get(&entity).copied()
+    }
+
+    // Inserts the drawing bounds of the widget and its children and returns the bounds of the
+    // subtree. The parent transform is the render transform the parent is drawn with.
+    fn insert(
+        &mut self,
+        entity: Entity,
+        parent_position: Point,
+        parent_transform: Matrix,
+        ecm: &EntityComponentManager<Tree, StringComponentStore>,
+    ) -> Option<Rectangle> {
+        let store = ecm.component_store();
+
+        match store.get::<Visibility>("visibility", entity) {
+            Ok(visibility) if *visibility == Visibility::Visible => {}
+            _ => return None,
+        }
+
+        let bounds = *store.get::<Rectangle>("bounds", entity).ok()?;
+        let position = parent_position + bounds.position();
+        let rect = Rectangle::new(position, bounds.size());
+        let mut drawing_bounds = rect;
+
+        if let Ok(box_shadow) = store.get::<BoxShadow>("box_shadow", entity) {
+            if !box_shadow.is_transparent() {
+                drawing_bounds.join_with_rectangle(&box_shadow.bounds(rect));
+            }
+        }
+
+        let transform = store
+            .get::<RenderTransform>("render_transform", entity)
+            .map(|render_transform| render_transform.matrix_for(rect))
+            .unwrap_or_default()
+            .then(&parent_transform);
+
+        let mut blurred = false;
+
+        if let Ok(effect) = store.get::<Effect>("effect", entity) {
+            let extent = effect.extent();
+
+            if extent > 0.0 {
+                drawing_bounds.join_with_rectangle(&Rectangle::new(
+                    (rect.x() - extent, rect.y() - extent),
+                    (rect.width() + 2.0 * extent, rect.height() + 2.0 * extent),
+                ));
+                blurred = true;
+            }
+        }
+
+        if !transform.is_identity() {
+            drawing_bounds = transform.transform_rect(drawing_bounds);
+        }
+
+        if blurred {
+            self.blurred.push(drawing_bounds);
+        }
+
+        if let Ok(backdrop_effect) = store.get::<Effect>("backdrop_effect", entity) {
+            let extent = backdrop_effect.extent();
+
+            if extent > 0.0 {
+                self.blurred.push(transform.transform_rect(Rectangle::new(
+                    (rect.x() - extent, rect.y() - extent),
+                    (rect.width() + 2.0 * extent, rect.height() + 2.0 * extent),
+                )));
+            }
+        }
+
+        self.widgets.insert(entity, drawing_bounds);
+
+        // children of a clipped widget are not drawn outside of its bounds
+        let clip = store
+            .get::<bool>("clip", entity)
+            .map(|c| *c)
+            .unwrap_or(false);
+        let mut subtree = drawing_bounds;
+
+        for index in 0..ecm.entity_store().children[&entity].len() {
+            let child = ecm.entity_store().children[&entity][index];
+
+            if let Some(child_bounds) = self.insert(child, position, transform, ecm) {
+                if !clip {
+                    subtree.join_with_rectangle(&child_bounds);
+                }
+            }
+        }
+
+        self.subtrees.insert(entity, subtree);
+
+        Some(subtree)
+    }
+
+    /// Returns the regions that have to be redrawn: the old and the new drawing bounds of the
+    /// dirty widgets including their children and of all widgets that are moved, resized, shown
+    /// or hidden since the previous frame.
+    pub fn damaged_regions(
+        &self,
+        previous: &DrawingBounds,
+        dirty_widgets: &[Entity],
+    ) -> Vec<Rectangle> {
+        let mut regions = vec![];
+
+        for entity in dirty_widgets {
+            regions.extend(previous.subtrees.get(entity));
+            regions.extend(self.subtrees.get(entity));
+        }
+
+        for (entity, bounds) in &self.widgets {
+            match previous.widgets.get(entity) {
+                Some(previous_bounds) if previous_bounds == bounds => {}
+                Some(previous_bounds) => {
+                    regions.push(*previous_bounds);
+                    regions.push(*bounds);
+                }
+                None => regions.push(*bounds),
+            }
+        }
+
+        for (entity, bounds) in &previous.widgets {
+            if !self.widgets.contains_key(entity) {
+                regions.push(*bounds);
+            }
+        }
+
+        // a blurred widget or backdrop is redrawn completely, because the blur reads the pixels
+        // around
+        for bounds in &self.blurred {
+            if regions.iter().any(|region| region.intersects(bounds)) {
+                regions.push(*bounds);
+            }
+        }
+
+        regions
+    }
+}
+
+/// The region of the window that is redrawn by a render pass.
+#[derive(Debug, Copy, Clone)]
+pub struct Damage<'a> {
+    region: Rectangle,
+    drawing_bounds: &'a DrawingBounds,
+}
+
+impl<'a> Damage<'a> {
+    /// Creates a new damage for the given region and drawing bounds of the current frame.
+    pub fn new(region: Rectangle, drawing_bounds: &'a DrawingBounds) -> Self {
+        Damage {
+            region,
+            drawing_bounds,
+        }
+    }
+
+    /// Gets the redrawn region.
+    pub fn region(&self) -> Rectangle {
+        self.region
+    }
+
+    /// Returns `true` if the widget draws inside of the damaged region.
+    pub fn intersects_widget(&self, entity: Entity) -> bool {
+        self.drawing_bounds
+            .widgets
+            .get(&entity)
+            .map(|bounds| bounds.intersects(&self.region))
+            .unwrap_or(true)
+    }
+
+    /// Returns `true` if the widget or one of its children draws inside of the damaged region.
+    pub fn intersects_subtree(&self, entity: Entity) -> bool {
+        self.drawing_bounds
+            .subtrees
+            .get(&entity)
+            .map(|bounds| bounds.intersects(&self.region))
+            .unwrap_or(true)
+    }
+}
+
+// Extends the region to whole pixels (plus one pixel for anti-aliasing) inside of the window.
+fn snap_region(region: Rectangle, window: Rectangle) -> Option<Rectangle> {
+    let left = (region.x() - 1.0).floor().max(window.x());
+    let top = (region.y() - 1.0).floor().max(window.y());
+    let right = (region.x() + region.width() + 1.0)
+        .ceil()
+        .min(window.x() + window.width());
+    let bottom = (region.y() + region.height() + 1.0)
+        .ceil()
+        .min(window.y() + window.height());
+
+    if right <= left || bottom <= top {
+        return None;
+    }
+
+    Some(Rectangle::new((left, top), (right - left, bottom - top)))
+}
+
+/// Snaps the damaged regions to whole pixels inside of the window and merges the regions that
+/// overlap. If there are still more than four regions they are merged into one.
+pub fn merge_regions(regions: &[Rectangle], window: Rectangle) -> Vec<Rectangle> {
+    let mut merged: Vec<Rectangle> = vec![];
+
+    for region in regions {
+        let mut region = match snap_region(*region, window) {
+            Some(region) => region,
+            None => continue,
+        };
+
+        // the joined region could overlap further regions
+        while let Some(index) = merged.iter().position(|m| m.intersects(&region)) {
+            region.join_with_rectangle(&merged.remove(index));
+        }
+
+        merged.push(region);
+    }
+
+    if merged.len() > MAX_DAMAGED_REGIONS {
+        let mut region = merged[0];
+
+        for other in &merged[1..] {
+            region.join_with_rectangle(other);
+        }
+
+        return vec![region];
+    }
+
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_regions() {
+        let window = Rectangle::new((0.0, 0.0), (100.0, 100.0));
+
+        assert_eq!(merge_regions(&[], window), vec![]);
+
+        // snapped to pixels with one pixel border, limited to the window
+        assert_eq!(
+            merge_regions(&[Rectangle::new((10.5, 0.0), (5.0, 5.2))], window),
+            vec![Rectangle::new((9.0, 0.0), (8.0, 7.0))]
+        );
+
+        // outside of the window
+        assert_eq!(
+            merge_regions(&[Rectangle::new((120.0, 10.0), (5.0, 5.0))], window),
+            vec![]
+        );
+
+        // overlapping regions are merged
+        assert_eq!(
+            merge_regions(
+                &[
+                    Rectangle::new((10.0, 10.0), (10.0, 10.0)),
+                    Rectangle::new((80.0, 80.0), (10.0, 10.0)),
+                    Rectangle::new((15.0, 15.0), (10.0, 10.0)),
+                ],
+                window
+            ),
+            vec![
+                Rectangle::new((79.0, 79.0), (12.0, 12.0)),
+                Rectangle::new((9.0, 9.0), (17.0, 17.0)),
+            ]
+        );
+
+        // too many regions are merged into one
+        let regions: Vec<Rectangle> = (0..5)
+            .map(|i| Rectangle::new((i as f64 * 20.0, 0.0), (5.0, 5.0)))
+            .collect();
+        assert_eq!(
+            merge_regions(&regions, window),
+            vec![Rectangle::new((0.0, 0.0), (86.0, 6.0))]
+        );
+    }
+
+    #[test]
+    fn test_damaged_regions() {
+        let mut previous = DrawingBounds::default();
+        previous
+            .widgets
+            .insert(Entity(1), Rectangle::new((0.0, 0.0), (10.0, 10.0)));
+        previous
+            .widgets
+            .insert(Entity(2), Rectangle::new((20.0, 0.0), (10.0, 10.0)));
+        previous
+            .widgets
+            .insert(Entity(3), Rectangle::new((40.0, 0.0), (10.0, 10.0)));
+
+        previous
+            .subtrees
+            .insert(Entity(1), Rectangle::new((0.0, 0.0), (10.0, 10.0)));
+
+        let mut current = previous.clone();
+        current
+            .widgets
+            .insert(Entity(2), Rectangle::new((25.0, 0.0), (10.0, 10.0)));
+        current.widgets.remove(&Entity(3));
+
+        assert_eq!(current.damaged_regions(&previous, &[]).len(), 3);
+        assert_eq!(
+            current.damaged_regions(&previous, &[Entity(1)])[0..2],
+            [
+                Rectangle::new((0.0, 0.0), (10.0, 10.0)),
+                Rectangle::new((0.0, 0.0), (10.0, 10.0))
+            ]
+        );
+        assert!(previous.damaged_regions(&previous, &[]).is_empty());
+    }
+}
@@ -1,7 +1,7 @@
 use crate::{
     proc_macros::IntoRenderObject,
     render_object::*,
-    utils::{Brush, Point, Rectangle},
+    utils::{Brush, FontStyle, FontWeight, Point, Rectangle},
 };
 
 #[derive(Debug, IntoRenderObject)]
@@ -33,6 +33,10 @@ impl RenderObject for FontIconRenderObject {
             ctx.render_context_2_d().begin_path();
             ctx.render_context_2_d().set_font_family(icon_font);
             ctx.render_context_2_d().set_font_size(icon_size);
+            ctx.render_context_2_d()
+                .set_font_weight(FontWeight::default());
+            ctx.render_context_2_d()
+                .set_font_style(FontStyle::default());
             ctx.render_context_2_d().set_fill_style(icon_brush);
 
             ctx.render_context_2_d().fill_text(
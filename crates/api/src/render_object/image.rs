@@ -1,4 +1,111 @@
-use crate::{proc_macros::IntoRenderObject, render::Image, render_object::*};
+use crate::{
+    proc_macros::IntoRenderObject,
+    render::{Image, RenderContext2D},
+    render_object::*,
+};
+
+// Calculates the parts of an image with the given size that are drawn by nine-slice scaling as
+// pairs of source and destination rectangles. The corners keep their size, the edges are scaled
+// along them and the center is scaled in both directions. If the destination is smaller than the
+// corners they are scaled down.
+fn nine_slice_parts(
+    width: f64,
+    height: f64,
+    slice: Thickness,
+    destination: Rectangle,
+) -> Vec<(Rectangle, Rectangle)> {
+    let left = slice.left.max(0.0).min(width);
+    let right = slice.right.max(0.0).min(width - left);
+    let top = slice.top.max(0.0).min(height);
+    let bottom = slice.bottom.max(0.0).min(height - top);
+
+    let mut scale = 1.0_f64;
+    if left + right > destination.width() {
+        scale = scale.min(destination.width() / (left + right));
+    }
+    if top + bottom > destination.height() {
+        scale = scale.min(destination.height() / (top + bottom));
+    }
+
+    let source_columns = [
+        (0.0, left),
+        (left, width - left - right),
+        (width - right, right),
+    ];
+    let source_rows = [
+        (0.0, top),
+        (top, height - top - bottom),
+        (height - bottom, bottom),
+    ];
+    let destination_columns = [
+        (destination.x(), left * scale),
+        (
+            destination.x() + left * scale,
+            destination.width() - (left + right) * scale,
+        ),
+        (
+            destination.x() + destination.width() - right * scale,
+            right * scale,
+        ),
+    ];
+    let destination_rows = [
+        (destination.y(), top * scale),
+        (
+            destination.y() + top * scale,
+            destination.height() - (top + bottom) * scale,
+        ),
+        (
+            destination.y() + destination.height() - bottom * scale,
+            bottom * scale,
+        ),
+    ];
+
+    let mut parts = vec![];
+
+    for row in 0..3 {
+        for column in 0..3 {
+            let source = Rectangle::new(
+                (source_columns[column].0, source_rows[row].0),
+                (source_columns[column].1, source_rows[row].1),
+            );
+            let destination = Rectangle::new(
+                (destination_columns[column].0, destination_rows[row].0),
+                (destination_columns[column].1, destination_rows[row].1),
+            );
+
+            if source.width() > 0.0
+                && source.height() > 0.0
+                && destination.width() > 0.0
+                && destination.height() > 0.0
+            {
+                parts.push((source, destination));
+            }
+        }
+    }
+
+    parts
+}
+
+/// Draws the image into the destination with nine-slice scaling. The slice describes the size of
+/// the borders of the image in pixels, they are not stretched.
+pub fn draw_nine_slice(
+    render_context_2_d: &mut RenderContext2D,
+    image: &Image,
+    slice: Thickness,
+    destination: Rectangle,
+) {
+    for (source, destination) in nine_slice_parts(image.width(), image.height(), slice, destination)
+    {
+        render_context_2_d.draw_image_with_clip_and_size(
+            image,
+            source,
+            destination.x(),
+            destination.y(),
+            destination.width(),
+            destination.height(),
+        );
+    }
+}
 
 /// Used to render an image.
 #[derive(Debug, IntoRenderObject)]
@@ -6,20 +113,152 @@ pub struct ImageRenderObject;
 
 impl RenderObject for ImageRenderObject {
     fn render_self(&self, ctx: &mut Context, global_position: &Point) {
-        let (bounds, mut image) = {
+        let (
+            bounds,
+            mut image,
+            border_radius,
+            current_frame,
+            stretch,
+            image_h_align,
+            image_v_align,
+        ) = {
             let widget = ctx.widget();
             (
                 widget.clone::<Rectangle>("bounds"),
                 widget.try_clone::<Image>("image"),
+                widget.clone_or_default::<f64>("border_radius"),
+                widget.clone_or_default::<usize>("current_frame"),
+                widget.clone_or_default::<Stretch>("stretch"),
+                widget
+                    .try_clone::<Alignment>("image_h_align")
+                    .unwrap_or(Alignment::Center),
+                widget
+                    .try_clone::<Alignment>("image_v_align")
+                    .unwrap_or(Alignment::Center),
             )
         };
 
         if let Some(image) = &mut image {
-            ctx.render_context_2_d().draw_image(
-                image,
-                bounds.x() + global_position.x(),
-                bounds.y() + global_position.y(),
+            image.set_frame(current_frame);
+
+            // vector images are rasterized again at the size of the widget to keep them sharp
+            let (width, height) = (bounds.width().round(), bounds.height().round());
+
+            if image.is_vector()
+                && width >= 1.0
+                && height >= 1.0
+                && (image.width() != width || image.height() != height)
+                && image.resize(width as u32, height as u32).is_ok()
+            {
+                // stored without marking the widget as dirty, the next frame draws the same image
+                if let Ok(stored) = ctx
+                    .ecm
+                    .component_store_mut()
+                    .get_mut::<Image>("image", ctx.entity)
+                {
+                    *stored = image.clone();
+                }
+            }
+
+            let x = bounds.x() + global_position.x();
+            let y = bounds.y() + global_position.y();
+
+            let (width, height) = stretch.size(
+                image.width(),
+                image.height(),
+                bounds.width(),
+                bounds.height(),
             );
+            let image_x = x + image_h_align.align_position(bounds.width(), width, 0.0, 0.0);
+            let image_y = y + image_v_align.align_position(bounds.height(), height, 0.0, 0.0);
+
+            // clips the corners of the image and the parts that are outside of the bounds
+            let clip = border_radius > 0.0 || stretch == Stretch::UniformToFill;
+
+            if clip {
+                ctx.render_context_2_d().save();
+                ctx.render_context_2_d().begin_path();
+
+                if border_radius > 0.0 {
+                    ctx.render_context_2_d().rounded_rect(
+                        x,
+                        y,
+                        bounds.width(),
+                        bounds.height(),
+                        border_radius,
+                    );
+                } else {
+                    ctx.render_context_2_d()
+                        .rect(x, y, bounds.width(), bounds.height());
+                }
+
+                ctx.render_context_2_d().clip();
+            }
+
+            if stretch == Stretch::None {
+                ctx.render_context_2_d().draw_image(image, image_x, image_y);
+            } else {
+                ctx.render_context_2_d()
+                    .draw_image_with_size(image, image_x, image_y, width, height);
+            }
+
+            if clip {
+                ctx.render_context_2_d().restore();
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nine_slice_parts() {
+        let parts = nine_slice_parts(
+            30.0,
+            30.0,
+            Thickness::from(10.0),
+            Rectangle::new((5.0, 5.0), (100.0, 50.0)),
+        );
+
+        assert_eq!(parts.len(), 9);
+
+        // top left corner keeps its size
+        assert_eq!(
+            parts[0],
+            (
+                Rectangle::new((0.0, 0.0), (10.0, 10.0)),
+                Rectangle::new((5.0, 5.0), (10.0, 10.0))
+            )
+        );
+
+        // center is stretched
+        assert_eq!(
+            parts[4],
+            (
+                Rectangle::new((10.0, 10.0), (10.0, 10.0)),
+                Rectangle::new((15.0, 15.0), (80.0, 30.0))
+            )
+        );
+
+        // bottom right corner
+        assert_eq!(
+            parts[8],
+            (
+                Rectangle::new((20.0, 20.0), (10.0, 10.0)),
+                Rectangle::new((95.0, 45.0), (10.0, 10.0))
+            )
+        );
+
+        // corners are scaled down and the empty center is skipped
+        let parts = nine_slice_parts(
+            30.0,
+            30.0,
+            Thickness::from(10.0),
+            Rectangle::new((0.0, 0.0), (10.0, 10.0)),
+        );
+        assert_eq!(parts.len(), 4);
+        assert_eq!(parts[0].1, Rectangle::new((0.0, 0.0), (5.0, 5.0)));
+    }
+}
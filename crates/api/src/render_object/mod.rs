@@ -10,22 +10,80 @@ use crate::{
 };
 
 pub use self::cursor::*;
+pub use self::damage::*;
 pub use self::default::*;
 pub use self::font_icon::*;
 pub use self::image::*;
 pub use self::pipeline::*;
 pub use self::rectangle::*;
+pub use self::svg_icon::*;
 pub use self::text::*;
 
 mod cursor;
+mod damage;
 mod default;
 mod font_icon;
 mod image;
 mod pipeline;
 mod rectangle;
+mod svg_icon;
 mod text;
 
+// Reads the opacity property of the given entity.
+fn opacity(entity: Entity, ecm: &EntityComponentManager<Tree, StringComponentStore>) -> f32 {
+    *ecm.component_store()
+        .get::<f32>("opacity", entity)
+        .unwrap_or(&1.0)
+}
+
+// Opacity of a widget relative to the opacity of the layers it is drawn in.
+fn relative_opacity(opacity: f32, layer_opacity: f32) -> f32 {
+    if layer_opacity <= 0.0 {
+        return 0.0;
+    }
+
+    (opacity / layer_opacity).max(0.0).min(1.0)
+}
+
+// A widget is drawn in its own layer if it is faded and has children.
+fn is_layered(
+    entity: Entity,
+    ecm: &EntityComponentManager<Tree, StringComponentStore>,
+    alpha: f32,
+) -> bool {
+    alpha < 1.0 && !ecm.entity_store().children[&entity].is_empty()
+}
+
+// Calculates the accumulated opacity of all layers the given entity is drawn in, used to draw a
+// widget without drawing its parents.
+pub(crate) fn layer_opacity(
+    entity: Entity,
+    ecm: &EntityComponentManager<Tree, StringComponentStore>,
+) -> f32 {
+    let mut ancestors = vec![];
+    let mut current = ecm.entity_store().parent[&entity];
+
+    while let Some(parent) = current {
+        ancestors.push(parent);
+        current = ecm.entity_store().parent[&parent];
+    }
+
+    let mut layer_opacity = 1.0;
+
+    for ancestor in ancestors.iter().rev() {
+        let alpha = relative_opacity(opacity(*ancestor, ecm), layer_opacity);
+
+        if is_layered(*ancestor, ecm, alpha) {
+            layer_opacity *= alpha;
+        }
+    }
+
+    layer_opacity
+}
+
 pub trait RenderObject: Any {
+    /// Draws the widget and its children. The transform and the opacity of the layers the widget
+    /// is drawn in are accumulated from its parents.
     fn render(
         &self,
         render_context: &mut RenderContext2D,
@@ -34,6 +92,9 @@ pub trait RenderObject: Any {
         context_provider: &ContextProvider,
         theme: &Theme,
         offsets: &mut BTreeMap<Entity, (f64, f64)>,
+        transform: Matrix,
+        layer_opacity: f32,
+        damage: Option<&Damage>,
         debug: bool,
     ) {
         let mut global_position = Point::default();
@@ -55,33 +116,14 @@ pub trait RenderObject: Any {
             return;
         }
 
-        render_context.begin_path();
-        render_context.set_alpha(
-            *ecm.component_store()
-                .get::<f32>("opacity", entity)
-                .unwrap_or(&1.0),
-        );
-
-        // Could be unwrap because every widget has the clip property
-        let clip = *ecm.component_store().get::<bool>("clip", entity).unwrap();
-        if clip {
-            if let Ok(bounds) = ecm.component_store().get::<Rectangle>("bounds", entity) {
-                render_context.save();
-                render_context.rect(
-                    global_position.x() + bounds.x(),
-                    global_position.y() + bounds.y(),
-                    bounds.width(),
-                    bounds.height(),
-                );
-                render_context.clip();
-            }
+        // neither the widget nor its children are drawn inside of the damaged region
+        if !damage.map_or(true, |damage| damage.intersects_subtree(entity)) {
+            return;
         }
 
-        self.render_self(
-            &mut Context::new((entity, ecm), &theme, context_provider, render_context),
-            &global_position,
-        );
+        render_context.begin_path();
 
+        // the position is updated before the widget is drawn, it places the render transform
         let mut global_pos = (0.0, 0.0);
 
         if let Ok(bounds) = ecm.component_store().get::<Rectangle>("bounds", entity) {
@@ -100,6 +142,158 @@ pub trait RenderObject: Any {
             g_pos.set_y(global_pos.1);
         }
 
+        // the render transform is applied on the widget and its children, the given transform
+        // contains the transformations of the parents
+        let render_transform = ecm
+            .component_store()
+            .get::<RenderTransform>("render_transform", entity)
+            .map(|render_transform| *render_transform)
+            .unwrap_or_default();
+        let has_transform = !render_transform.is_identity();
+
+        let transform = if has_transform {
+            let size = ecm
+                .component_store()
+                .get::<Rectangle>("bounds", entity)
+                .map(|bounds| bounds.size())
+                .unwrap_or_default();
+
+            render_transform
+                .matrix_for(Rectangle::new(global_pos, size))
+                .then(&transform)
+        } else {
+            transform
+        };
+
+        if has_transform {
+            render_context.save();
+            render_context.set_transform(
+                transform.m11,
+                transform.m12,
+                transform.m21,
+                transform.m22,
+                transform.m31,
+                transform.m32,
+            );
+        }
+
+        // the opacity is relative to the layers of the parents that are already faded
+        let alpha = relative_opacity(opacity(entity, ecm), layer_opacity);
+        let has_layer = is_layered(entity, ecm, alpha);
+
+        // Could be unwrap because every widget has the clip property
+        let clip = *ecm.component_store().get::<bool>("clip", entity).unwrap();
+        let mut clip_rect = None;
+        if clip {
+            if let Ok(bounds) = ecm.component_store().get::<Rectangle>("bounds", entity) {
+                clip_rect = Some(Rectangle::new(
+                    global_position + bounds.position(),
+                    bounds.size(),
+                ));
+                let clip_mode = ecm
+                    .component_store()
+                    .get::<ClipMode>("clip_mode", entity)
+                    .map(|clip_mode| *clip_mode)
+                    .unwrap_or_default();
+                let border_radius = match clip_mode {
+                    ClipMode::BorderRadius => ecm
+                        .component_store()
+                        .get::<f64>("border_radius", entity)
+                        .map(|border_radius| *border_radius)
+                        .unwrap_or_default(),
+                    ClipMode::Bounds => 0.0,
+                };
+
+                render_context.save();
+                if border_radius > 0.0 {
+                    render_context.rounded_rect(
+                        global_position.x() + bounds.x(),
+                        global_position.y() + bounds.y(),
+                        bounds.width(),
+                        bounds.height(),
+                        border_radius,
+                    );
+                } else {
+                    render_context.rect(
+                        global_position.x() + bounds.x(),
+                        global_position.y() + bounds.y(),
+                        bounds.width(),
+                        bounds.height(),
+                    );
+                }
+                render_context.clip();
+            }
+        }
+
+        // the content behind the widget is changed before the widget is drawn
+        let backdrop_effect = ecm
+            .component_store()
+            .get::<Effect>("backdrop_effect", entity)
+            .map(|backdrop_effect| *backdrop_effect)
+            .unwrap_or_default();
+
+        if !backdrop_effect.is_none()
+            && damage.map_or(true, |damage| damage.intersects_widget(entity))
+        {
+            if let Ok(bounds) = ecm.component_store().get::<Rectangle>("bounds", entity) {
+                render_context.apply_backdrop_effect(
+                    backdrop_effect,
+                    transform.transform_rect(Rectangle::new(
+                        global_position + bounds.position(),
+                        bounds.size(),
+                    )),
+                );
+            }
+        }
+
+        // a faded widget with children is composited as a whole, so its children do not shine
+        // through each other
+        // layers are composited in window coordinates
+        if !transform.is_identity() {
+            clip_rect = clip_rect.map(|clip_rect| transform.transform_rect(clip_rect));
+        }
+
+        if has_layer {
+            render_context.push_layer(alpha, clip_rect);
+            render_context.set_alpha(1.0);
+        } else {
+            render_context.set_alpha(alpha);
+        }
+
+        let effect = ecm
+            .component_store()
+            .get::<Effect>("effect", entity)
+            .map(|effect| *effect)
+            .unwrap_or_default();
+        let mut has_effect = false;
+
+        if !effect.is_none() {
+            if let Ok(bounds) = ecm.component_store().get::<Rectangle>("bounds", entity) {
+                render_context.push_effect(
+                    effect,
+                    transform.transform_rect(Rectangle::new(
+                        global_position + bounds.position(),
+                        bounds.size(),
+                    )),
+                );
+                has_effect = true;
+            }
+        }
+
+        if damage.map_or(true, |damage| damage.intersects_widget(entity)) {
+            self.render_self(
+                &mut Context::new((entity, ecm), &theme, context_provider, render_context),
+                &global_position,
+            );
+        }
+
+        // the children are drawn in the layer of the widget
+        let layer_opacity = if has_layer {
+            layer_opacity * alpha
+        } else {
+            layer_opacity
+        };
+
         self.render_children(
             render_context,
             entity,
@@ -107,15 +301,30 @@ pub trait RenderObject: Any {
             context_provider,
             theme,
             offsets,
+            transform,
+            layer_opacity,
+            damage,
             debug,
         );
 
         render_context.close_path();
 
+        if has_effect {
+            render_context.pop_effect();
+        }
+
+        if has_layer {
+            render_context.pop_layer();
+        }
+
         if clip {
             render_context.restore();
         }
 
+        if has_transform {
+            render_context.restore();
+        }
+
         // render debug border for each widget
         if debug {
             if let Ok(bounds) = ecm.component_store().get::<Rectangle>("bounds", entity) {
@@ -142,6 +351,9 @@ pub trait RenderObject: Any {
         context_provider: &ContextProvider,
         theme: &Theme,
         offsets: &mut BTreeMap<Entity, (f64, f64)>,
+        transform: Matrix,
+        layer_opacity: f32,
+        damage: Option<&Damage>,
         debug: bool,
     ) {
         for index in 0..ecm.entity_store().children[&entity].len() {
@@ -155,9 +367,80 @@ pub trait RenderObject: Any {
                     context_provider,
                     theme,
                     offsets,
+                    transform,
+                    layer_opacity,
+                    damage,
                     debug,
                 );
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Creates a window with a faded container that has a faded child with a child.
+    fn faded_tree(
+        world: &mut World<Tree, StringComponentStore, RenderContext2D>,
+    ) -> (Entity, Entity, Entity, Entity) {
+        let ecm = world.entity_component_manager();
+        let window = ecm.create_entity().build();
+        let container = ecm.create_entity().build();
+        let child = ecm.create_entity().build();
+        let grandchild = ecm.create_entity().build();
+
+        let tree = ecm.entity_store_mut();
+        tree.append_child(window, container).unwrap();
+        tree.append_child(container, child).unwrap();
+        tree.append_child(child, grandchild).unwrap();
+
+        let store = ecm.component_store_mut();
+        store.register("opacity", window, 1.0_f32);
+        store.register("opacity", container, 0.5_f32);
+        store.register("opacity", child, 0.25_f32);
+        store.register("opacity", grandchild, 0.25_f32);
+
+        (window, container, child, grandchild)
+    }
+
+    #[test]
+    fn test_relative_opacity() {
+        assert_eq!(relative_opacity(0.5, 1.0), 0.5);
+
+        // a faded child of a faded layer is relative to the layer
+        assert_eq!(relative_opacity(0.25, 0.5), 0.5);
+
+        // opaque children are drawn with the opacity of their layer
+        assert_eq!(relative_opacity(1.0, 0.5), 1.0);
+        assert_eq!(relative_opacity(0.5, 0.0), 0.0);
+    }
+
+    #[test]
+    fn test_is_layered() {
+        let mut world = World::from_stores(Tree::default(), StringComponentStore::default());
+        let (window, container, _, grandchild) = faded_tree(&mut world);
+        let ecm = world.entity_component_manager();
+
+        // opaque widgets and widgets without children are not drawn in a layer
+        assert!(!is_layered(window, ecm, 1.0));
+        assert!(!is_layered(container, ecm, 1.0));
+        assert!(!is_layered(grandchild, ecm, 0.5));
+        assert!(is_layered(container, ecm, 0.5));
+    }
+
+    #[test]
+    fn test_layer_opacity() {
+        let mut world = World::from_stores(Tree::default(), StringComponentStore::default());
+        let (window, container, child, grandchild) = faded_tree(&mut world);
+        let ecm = world.entity_component_manager();
+
+        assert_eq!(layer_opacity(window, ecm), 1.0);
+        assert_eq!(layer_opacity(container, ecm), 1.0);
+        assert_eq!(layer_opacity(child, ecm), 0.5);
+
+        // the child is drawn in the layer of the container and in its own layer
+        assert_eq!(layer_opacity(grandchild, ecm), 0.25);
+    }
+}
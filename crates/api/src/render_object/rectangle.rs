@@ -2,10 +2,10 @@ use std::f64::consts::PI;
 
 use crate::{
     proc_macros::IntoRenderObject,
-    render::RenderContext2D,
-    render_object::*,
+    render::{Image, RenderContext2D},
+    render_object::{image::draw_nine_slice, *},
     utils,
-    utils::{Brush, Point, Rectangle, Thickness},
+    utils::{BoxShadow, Brush, Point, Rectangle, Thickness},
 };
 
 #[derive(Debug, IntoRenderObject)]
@@ -45,18 +45,7 @@ impl RectangleRenderObject {
         height: f64,
         radius: f64,
     ) {
-        let r = x + width;
-        let b = y + height;
-        render_context_2_d.move_to(x + radius, y);
-        render_context_2_d.line_to(r - radius, y);
-        render_context_2_d.quadratic_curve_to(r, y, r, y + radius);
-        render_context_2_d.line_to(r, y + height - radius);
-        render_context_2_d.quadratic_curve_to(r, b, r - radius, b);
-        render_context_2_d.line_to(x + radius, b);
-        render_context_2_d.quadratic_curve_to(x, b, x, b - radius);
-        render_context_2_d.line_to(x, y + radius);
-        render_context_2_d.quadratic_curve_to(x, y, x + radius, y);
-        render_context_2_d.close_path();
+        render_context_2_d.rounded_rect(x, y, width, height, radius);
     }
 
     fn render_circle(
@@ -128,16 +117,19 @@ impl RectangleRenderObject {
             render_context_2_d.stroke();
         }
     }
-}
 
-impl RenderObject for RectangleRenderObject {
-    fn render_self(&self, ctx: &mut Context, global_position: &Point) {
-        let (bounds, background, border_radius, border_thickness, border_brush) = {
+    // Renders the background and the border.
+    fn render_background(
+        &self,
+        ctx: &mut Context,
+        global_position: &Point,
+        bounds: Rectangle,
+        border_radius: f64,
+    ) {
+        let (background, border_thickness, border_brush) = {
             let widget = ctx.widget();
             (
-                widget.clone::<Rectangle>("bounds"),
                 widget.get::<Brush>("background").clone(),
-                widget.clone_or_default::<f64>("border_radius"),
                 widget.clone_or_default::<Thickness>("border_width"),
                 widget.clone_or_default::<Brush>("border_brush"),
             )
@@ -228,3 +220,41 @@ impl RenderObject for RectangleRenderObject {
         }
     }
 }
+
+impl RenderObject for RectangleRenderObject {
+    fn render_self(&self, ctx: &mut Context, global_position: &Point) {
+        let (bounds, border_radius, box_shadow, border_image, border_image_slice) = {
+            let widget = ctx.widget();
+            (
+                widget.clone::<Rectangle>("bounds"),
+                widget.clone_or_default::<f64>("border_radius"),
+                widget.clone_or_default::<BoxShadow>("box_shadow"),
+                widget.try_clone::<Image>("border_image"),
+                widget.clone_or_default::<Thickness>("border_image_slice"),
+            )
+        };
+
+        if bounds.width() > 0.0 && bounds.height() > 0.0 && !box_shadow.is_transparent() {
+            ctx.render_context_2_d().draw_box_shadow(
+                global_position.x() + bounds.x(),
+                global_position.y() + bounds.y(),
+                bounds.width(),
+                bounds.height(),
+                border_radius,
+                &box_shadow,
+            );
+        }
+
+        self.render_background(ctx, global_position, bounds, border_radius);
+
+        // the border image is drawn above the background, its edges are scaled by nine-slice
+        if let Some(border_image) = border_image {
+            draw_nine_slice(
+                ctx.render_context_2_d(),
+                &border_image,
+                border_image_slice,
+                Rectangle::new(*global_position + bounds.position(), bounds.size()),
+            );
+        }
+    }
+}
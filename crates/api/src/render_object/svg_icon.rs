@@ -0,0 +1,88 @@
+use std::cell::RefCell;
+
+use crate::{proc_macros::IntoRenderObject, render::Image, render_object::*};
+
+/// Used to render a vector icon in the color of the icon brush.
+#[derive(Debug, Default, IntoRenderObject)]
+pub struct SvgIconRenderObject {
+    // the rasterized icon with its color and tinted copy, it is tinted again only if the icon or
+    // the color changes
+    tinted: RefCell<Option<(Image, Color, Image)>>,
+}
+
+impl SvgIconRenderObject {
+    pub fn new() -> Self {
+        SvgIconRenderObject::default()
+    }
+}
+
+impl RenderObject for SvgIconRenderObject {
+    fn render_self(&self, ctx: &mut Context, global_position: &Point) {
+        let (bounds, icon, icon_brush, icon_size) = {
+            let widget = ctx.widget();
+            (
+                *widget.get::<Rectangle>("bounds"),
+                widget.try_clone::<Image>("icon"),
+                widget.get::<Brush>("icon_brush").clone(),
+                *widget.get::<f64>("icon_size"),
+            )
+        };
+
+        let mut icon = match icon {
+            Some(icon) => icon,
+            None => return,
+        };
+
+        let size = icon_size.round();
+
+        if bounds.width() == 0.0
+            || bounds.height() == 0.0
+            || icon_brush.is_transparent()
+            || size < 1.0
+        {
+            return;
+        }
+
+        // rasterized again if the icon size changes
+        if icon.width() != size || icon.height() != size {
+            if icon.resize(size as u32, size as u32).is_err() {
+                return;
+            }
+
+            // stored without marking the widget as dirty, the next frame draws the same icon
+            if let Ok(stored) = ctx
+                .ecm
+                .component_store_mut()
+                .get_mut::<Image>("icon", ctx.entity)
+            {
+                *stored = icon.clone();
+            }
+        }
+
+        // only solid colors are supported, other brushes keep the colors of the icon
+        if let Brush::SolidColor(color) = icon_brush {
+            let mut tinted = self.tinted.borrow_mut();
+
+            let cached = tinted
+                .as_ref()
+                .filter(|(source, tint, _)| *tint == color && source.shares_pixels(&icon))
+                .map(|(_, _, image)| image.clone());
+
+            icon = match cached {
+                Some(image) => image,
+                None => {
+                    let mut image = icon.clone();
+                    image.tint(color);
+                    *tinted = Some((icon, color, image.clone()));
+                    image
+                }
+            };
+        }
+
+        ctx.render_context_2_d().draw_image(
+            &icon,
+            global_position.x() + bounds.x(),
+            global_position.y() + bounds.y(),
+        );
+    }
+}
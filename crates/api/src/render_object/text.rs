@@ -1,10 +1,11 @@
 use crate::{
     proc_macros::IntoRenderObject,
+    render::{TextLayout, TextLayoutOptions},
     render_object::*,
-    utils::{Brush, Point, Rectangle},
+    utils::{
+        Brush, FontStyle, FontWeight, Point, Rectangle, TextAlignment, TextTrimming, TextWrap,
+    },
 };
-use memchr::memchr_iter;
-use std::iter;
 
 /// Used to render a text.
 #[derive(Debug, IntoRenderObject)]
@@ -12,7 +13,18 @@ pub struct TextRenderObject;
 
 impl RenderObject for TextRenderObject {
     fn render_self(&self, ctx: &mut Context, global_position: &Point) {
-        let (bounds, text, foreground, font, font_size, offset) = {
+        let (
+            bounds,
+            text,
+            foreground,
+            font,
+            font_size,
+            font_weight,
+            font_style,
+            offset,
+            alignment,
+            text_layout,
+        ) = {
             let widget = ctx.widget();
             let text = text(&widget);
             let offset = *widget.get::<f64>("offset");
@@ -30,7 +42,11 @@ impl RenderObject for TextRenderObject {
                 widget.get::<Brush>("foreground").clone(),
                 widget.get::<String>("font").clone(),
                 *widget.get::<f64>("font_size"),
+                widget.clone_or_default::<FontWeight>("font_weight"),
+                widget.clone_or_default::<FontStyle>("font_style"),
                 offset,
+                widget.clone_or_default::<TextAlignment>("text_align"),
+                widget.try_clone::<TextLayout>("text_layout"),
             )
         };
 
@@ -49,24 +65,59 @@ impl RenderObject for TextRenderObject {
         ctx.render_context_2_d().begin_path();
         ctx.render_context_2_d().set_font_family(font);
         ctx.render_context_2_d().set_font_size(font_size);
+        ctx.render_context_2_d().set_font_weight(font_weight);
+        ctx.render_context_2_d().set_font_style(font_style);
         ctx.render_context_2_d().set_fill_style(foreground);
 
-        let mut y_disp = 0.0;
-        let mut last_ofs = 0;
-        for i in memchr_iter(b'\n', text.as_bytes()).chain(iter::once(text.len())) {
+        // the lines are measured by the layout, widgets without a text layout are laid out in
+        // their bounds
+        let text_layout = text_layout.unwrap_or_else(|| {
+            let options = text_layout_options(&ctx.widget(), font_size, bounds.width());
+            let render_context_2_d = ctx.render_context_2_d();
+            TextLayout::new(&text, &options, |text| {
+                render_context_2_d.measure_text(text).width
+            })
+        });
+
+        for line in text_layout.lines() {
             ctx.render_context_2_d().fill_text(
-                &text[last_ofs..i],
-                global_position.x() + bounds.x() + offset,
-                global_position.y() + bounds.y() + y_disp,
+                &line.text,
+                global_position.x()
+                    + bounds.x()
+                    + offset
+                    + alignment.offset(line.width, bounds.width()),
+                global_position.y() + bounds.y() + line.y,
             );
-            y_disp += font_size * 1.15; // TODO: Make the space between lines customizable
-            last_ofs = i + 1; // + 1 to skip the end of line character
         }
 
         ctx.render_context_2_d().close_path();
     }
 }
 
+/// Reads the options of the text layout from the properties of the widget, widgets without the
+/// properties are laid out without wrapping and trimming.
+pub(crate) fn text_layout_options(
+    widget: &WidgetContainer,
+    font_size: f64,
+    max_width: f64,
+) -> TextLayoutOptions {
+    let default = TextLayoutOptions::default();
+
+    TextLayoutOptions {
+        font_size,
+        line_height: widget
+            .try_get::<f64>("line_height")
+            .cloned()
+            .filter(|line_height| *line_height > 0.0)
+            .unwrap_or(default.line_height),
+        max_width,
+        max_lines: widget.try_get::<usize>("max_lines").cloned().unwrap_or(0),
+        wrap: widget.clone_or_default::<TextWrap>("text_wrap"),
+        trimming: widget.clone_or_default::<TextTrimming>("text_trimming"),
+        alignment: widget.clone_or_default::<TextAlignment>("text_align"),
+    }
+}
+
 fn text(widget: &WidgetContainer) -> String {
     if let Some(localizable) = widget.try_get::<bool>("localizable") {
         if *localizable {
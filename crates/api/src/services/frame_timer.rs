@@ -0,0 +1,175 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::{sync::mpsc, time::Instant};
+
+use std::time::Duration;
+
+#[cfg(target_arch = "wasm32")]
+use stdweb::{js, Once};
+
+use dces::prelude::Entity;
+
+use crate::event::{EventAdapter, ImageFrameEvent};
+
+// A requested `ImageFrameEvent` with the time it is sent.
+#[cfg(not(target_arch = "wasm32"))]
+type Request = (Instant, Entity, EventAdapter);
+
+// Sends the requested events when their time is reached, until the frame timer is dropped.
+#[cfg(not(target_arch = "wasm32"))]
+fn run(receiver: mpsc::Receiver<Request>) {
+    let mut pending: Vec<Request> = vec![];
+
+    loop {
+        let request = match pending.iter().map(|(time, _, _)| *time).min() {
+            Some(time) => {
+                match receiver.recv_timeout(time.saturating_duration_since(Instant::now())) {
+                    Ok(request) => Some(request),
+                    Err(mpsc::RecvTimeoutError::Timeout) => None,
+                    Err(mpsc::RecvTimeoutError::Disconnected) => return,
+                }
+            }
+            None => match receiver.recv() {
+                Ok(request) => Some(request),
+                Err(_) => return,
+            },
+        };
+
+        pending.extend(request);
+
+        let now = Instant::now();
+        pending.retain(|(time, entity, event_adapter)| {
+            if *time > now {
+                return true;
+            }
+
+            event_adapter.push_event_direct(*entity, ImageFrameEvent);
+            false
+        });
+    }
+}
+
+/// The frame timer sends an `ImageFrameEvent` to a widget after a delay, e.g. if the current
+/// frame of an animated image is over. All widgets share one timer thread.
+///
+/// Widgets that are hidden pause their animation with `pause`, they are marked as dirty by the
+/// `EventStateSystem` when they are visible again.
+#[derive(Default)]
+pub struct FrameTimer {
+    #[cfg(not(target_arch = "wasm32"))]
+    requests: Option<mpsc::Sender<Request>>,
+    // widgets that wait until they are visible again
+    paused: Vec<Entity>,
+}
+
+impl FrameTimer {
+    /// Creates a new frame timer, the thread is started with the first request.
+    pub fn new() -> Self {
+        FrameTimer::default()
+    }
+
+    /// Sends an `ImageFrameEvent` to the given entity after the delay.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn schedule(&mut self, entity: Entity, delay: Duration, event_adapter: EventAdapter) {
+        let requests = self.requests.get_or_insert_with(|| {
+            let (sender, receiver) = mpsc::channel::<Request>();
+            std::thread::spawn(move || run(receiver));
+            sender
+        });
+
+        let _ = requests.send((Instant::now() + delay, entity, event_adapter));
+    }
+
+    /// Sends an `ImageFrameEvent` to the given entity after the delay.
+    #[cfg(target_arch = "wasm32")]
+    pub fn schedule(&mut self, entity: Entity, delay: Duration, event_adapter: EventAdapter) {
+        // there are no threads on the web, the browser calls back after the delay
+        let callback = move || event_adapter.push_event_direct(entity, ImageFrameEvent);
+
+        js!(
+            setTimeout(@{Once(callback)}, @{delay.as_millis() as f64});
+        );
+    }
+
+    /// Pauses the animation of a hidden entity until it is visible again.
+    pub fn pause(&mut self, entity: Entity) {
+        if !self.paused.contains(&entity) {
+            self.paused.push(entity);
+        }
+    }
+
+    /// Stops waiting for the given entity, e.g. if it is removed.
+    pub fn remove(&mut self, entity: Entity) {
+        self.paused.retain(|paused| *paused != entity);
+    }
+
+    /// Returns `true` if an animation is paused.
+    pub fn is_paused(&self) -> bool {
+        !self.paused.is_empty()
+    }
+
+    /// Removes and returns the paused entities that are visible again.
+    pub fn resume(&mut self, is_visible: impl Fn(Entity) -> bool) -> Vec<Entity> {
+        let (resumed, paused) = self
+            .paused
+            .drain(..)
+            .partition(|entity| is_visible(*entity));
+        self.paused = paused;
+
+        resumed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schedule() {
+        let mut frame_timer = FrameTimer::new();
+        let event_adapter = EventAdapter::default();
+
+        frame_timer.schedule(Entity(1), Duration::from_millis(20), event_adapter.clone());
+        frame_timer.schedule(Entity(2), Duration::from_millis(0), event_adapter.clone());
+
+        let start = Instant::now();
+        while event_adapter.len() < 2 && start.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        assert_eq!(event_adapter.len(), 2);
+    }
+
+    #[test]
+    fn test_schedule_delay() {
+        let mut frame_timer = FrameTimer::new();
+        let event_adapter = EventAdapter::default();
+
+        let start = Instant::now();
+        frame_timer.schedule(Entity(1), Duration::from_millis(50), event_adapter.clone());
+
+        while event_adapter.is_empty() && start.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        // the next frame is requested after the delay of the current one
+        assert_eq!(event_adapter.len(), 1);
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+
+    #[test]
+    fn test_pause_resume() {
+        let mut frame_timer = FrameTimer::new();
+        frame_timer.pause(Entity(1));
+        frame_timer.pause(Entity(2));
+        frame_timer.pause(Entity(2));
+
+        assert_eq!(
+            frame_timer.resume(|entity| entity == Entity(2)),
+            vec![Entity(2)]
+        );
+        assert!(frame_timer.is_paused());
+
+        frame_timer.remove(Entity(1));
+        assert!(!frame_timer.is_paused());
+    }
+}
@@ -0,0 +1,485 @@
+use std::{
+    collections::HashMap,
+    sync::{mpsc, Arc, Mutex, MutexGuard},
+};
+
+use dces::prelude::Entity;
+
+use crate::{
+    event::{EventAdapter, ImageLoadedEvent},
+    render::Image,
+};
+
+// Memory limit of the decoded images (64 MiB).
+const DEFAULT_MEMORY_LIMIT: usize = 64 * 1024 * 1024;
+
+/// Describes the state of an image of the `ImageCache`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ImageStatus {
+    /// The image is decoded on the worker thread.
+    Loading,
+
+    /// The image is decoded.
+    Loaded(Image),
+
+    /// The image could not be loaded, contains the error message. It is loaded again after it is
+    /// removed from the cache by `ImageCache::remove` or `ImageCache::clear`.
+    Failed(String),
+}
+
+// Memory used by the pixels of a decoded image or the message of a failed one. Images that are
+// loading are not counted, they are never removed to notify the waiting widgets.
+fn memory_of(status: &ImageStatus) -> usize {
+    match status {
+        ImageStatus::Loading => 0,
+        ImageStatus::Loaded(image) => image.width() as usize * image.height() as usize * 4,
+        ImageStatus::Failed(error) => error.len().max(1),
+    }
+}
+
+struct Entry {
+    status: ImageStatus,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct Cache {
+    entries: HashMap<String, Entry>,
+    // widgets that are notified if the image is loaded
+    waiting: HashMap<String, Vec<(Entity, EventAdapter)>>,
+    memory_limit: usize,
+    memory_usage: usize,
+    clock: u64,
+}
+
+impl Cache {
+    fn touch(&mut self, source: &str) -> Option<ImageStatus> {
+        self.clock += 1;
+        let clock = self.clock;
+
+        self.entries.get_mut(source).map(|entry| {
+            entry.last_used = clock;
+            entry.status.clone()
+        })
+    }
+
+    fn insert(&mut self, source: String, status: ImageStatus) {
+        self.remove(&source);
+        self.clock += 1;
+        self.memory_usage += memory_of(&status);
+        self.entries.insert(
+            source,
+            Entry {
+                status,
+                last_used: self.clock,
+            },
+        );
+        self.evict();
+    }
+
+    fn remove(&mut self, source: &str) {
+        if let Some(entry) = self.entries.remove(source) {
+            self.memory_usage -= memory_of(&entry.status);
+        }
+    }
+
+    // Removes the least recently used images until the memory limit is reached. The most recently
+    // used image is kept, also if it is larger than the limit.
+    fn evict(&mut self) {
+        while self.memory_usage > self.memory_limit {
+            let oldest = self
+                .entries
+                .iter()
+                .filter(|(_, entry)| entry.last_used != self.clock && memory_of(&entry.status) > 0)
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(source, _)| source.clone());
+
+            match oldest {
+                Some(source) => self.remove(&source),
+                None => break,
+            }
+        }
+    }
+}
+
+fn decode(source: &str) -> ImageStatus {
+    // shares the pixels with images that are set by their path
+    match Image::shared(source) {
+        Ok(image) => ImageStatus::Loaded(image),
+        Err(error) => ImageStatus::Failed(error),
+    }
+}
+
+// Stores the decoded image and notifies the waiting widgets. The image is dropped if it is
+// removed from the cache while it is loading.
+fn finish(cache: &Mutex<Cache>, source: String, status: ImageStatus) {
+    let waiting = {
+        let mut cache = cache
+            .lock()
+            .expect("ImageCache::finish: Cannot lock the cache.");
+
+        if let Some(ImageStatus::Loading) = cache.entries.get(&source).map(|e| &e.status) {
+            cache.insert(source.clone(), status);
+        }
+
+        cache.waiting.remove(&source).unwrap_or_default()
+    };
+
+    notify(source, waiting);
+}
+
+// Sends an `ImageLoadedEvent` to the waiting widgets.
+fn notify(source: String, waiting: Vec<(Entity, EventAdapter)>) {
+    for (entity, event_adapter) in waiting {
+        event_adapter.push_event_direct(
+            entity,
+            ImageLoadedEvent {
+                source: source.clone(),
+            },
+        );
+    }
+}
+
+/// The image cache decodes images on a worker thread and keeps them in memory, so an image that
+/// is used by several widgets or set again is decoded only once. Images are identified by their
+/// path. If the memory limit is reached the least recently used images are removed.
+///
+/// # Examples
+/// ```
+/// impl State for MyState {
+///     fn update(&mut self, registry: &mut Registry, ctx: &mut Context) {
+///         let event_adapter = ctx.event_adapter();
+///         let status = registry
+///             .get_mut::<ImageCache>("image_cache")
+///             .load("assets/logo.jpg", ctx.entity, event_adapter);
+///
+///         if let ImageStatus::Loaded(image) = status {
+///             ctx.widget().set("image", image);
+///         }
+///     }
+/// }
+/// ```
+///
+/// Clones of the image cache share the images and the worker thread.
+#[derive(Clone)]
+pub struct ImageCache {
+    cache: Arc<Mutex<Cache>>,
+    requests: Arc<Mutex<Option<mpsc::Sender<String>>>>,
+}
+
+impl Default for ImageCache {
+    fn default() -> Self {
+        ImageCache {
+            cache: Arc::new(Mutex::new(Cache {
+                memory_limit: DEFAULT_MEMORY_LIMIT,
+                ..Default::default()
+            })),
+            requests: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+impl ImageCache {
+    /// Creates a new image cache with a memory limit of 64 MiB.
+    pub fn new() -> Self {
+        ImageCache::default()
+    }
+
+    fn cache(&self) -> MutexGuard<Cache> {
+        self.cache
+            .lock()
+            .expect("ImageCache: Cannot lock the cache.")
+    }
+
+    /// Gets the memory limit of the decoded images in bytes.
+    pub fn memory_limit(&self) -> usize {
+        self.cache().memory_limit
+    }
+
+    /// Sets the memory limit of the decoded images in bytes.
+    pub fn set_memory_limit(&mut self, memory_limit: usize) {
+        let mut cache = self.cache();
+        cache.memory_limit = memory_limit;
+        cache.evict();
+    }
+
+    /// Gets the memory used by the decoded images in bytes.
+    pub fn memory_usage(&self) -> usize {
+        self.cache().memory_usage
+    }
+
+    /// Returns the state of the image with the given source, `None` if it is not in the cache.
+    pub fn get(&self, source: &str) -> Option<ImageStatus> {
+        self.cache().touch(source)
+    }
+
+    /// Returns the state of the image with the given source. If it is not in the cache it is
+    /// decoded on the worker thread and `ImageStatus::Loading` is returned. After the image is
+    /// loaded an `ImageLoadedEvent` is sent to the given entity. Images that could not be loaded
+    /// are not decoded again until they are removed from the cache.
+    pub fn load(
+        &mut self,
+        source: impl Into<String>,
+        entity: Entity,
+        event_adapter: EventAdapter,
+    ) -> ImageStatus {
+        let source = source.into();
+
+        {
+            let mut cache = self.cache();
+
+            let status = cache.touch(&source);
+
+            match status {
+                Some(ImageStatus::Loaded(image)) => return ImageStatus::Loaded(image),
+                Some(ImageStatus::Failed(error)) => return ImageStatus::Failed(error),
+                _ => {}
+            }
+
+            let waiting = cache.waiting.entry(source.clone()).or_default();
+
+            if !waiting.iter().any(|(e, _)| *e == entity) {
+                waiting.push((entity, event_adapter));
+            }
+
+            if let Some(ImageStatus::Loading) = status {
+                return ImageStatus::Loading;
+            }
+
+            cache.insert(source.clone(), ImageStatus::Loading);
+        }
+
+        self.request(source);
+
+        ImageStatus::Loading
+    }
+
+    /// Returns the image with the given source, it is decoded on the current thread if it is not
+    /// in the cache, e.g. for the images of a theme that are drawn with the next frame. Images
+    /// that could not be loaded are returned as error until they are removed from the cache.
+    pub fn load_now(&mut self, source: impl Into<String>) -> Result<Image, String> {
+        let source = source.into();
+
+        match self.cache().touch(&source) {
+            Some(ImageStatus::Loaded(image)) => return Ok(image),
+            Some(ImageStatus::Failed(error)) => return Err(error),
+            _ => {}
+        }
+
+        // an image that is loading on the worker thread is stored here first, the waiting
+        // widgets are notified when the worker is finished
+        let status = decode(&source);
+        self.cache().insert(source, status.clone());
+
+        match status {
+            ImageStatus::Loaded(image) => Ok(image),
+            ImageStatus::Failed(error) => Err(error),
+            ImageStatus::Loading => unreachable!(),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn request(&mut self, source: String) {
+        let cache = self.cache.clone();
+
+        let mut requests = self
+            .requests
+            .lock()
+            .expect("ImageCache::request: Cannot lock the worker.");
+        let requests = requests.get_or_insert_with(|| {
+            let (sender, receiver) = mpsc::channel::<String>();
+
+            std::thread::spawn(move || {
+                for source in receiver {
+                    let status = decode(&source);
+                    finish(&cache, source, status);
+                }
+            });
+
+            sender
+        });
+
+        let _ = requests.send(source);
+    }
+
+    // there are no threads on the web, the browser loads the image
+    #[cfg(target_arch = "wasm32")]
+    fn request(&mut self, source: String) {
+        let status = decode(&source);
+        finish(&self.cache, source, status);
+    }
+
+    /// Inserts an already decoded image.
+    pub fn insert(&mut self, source: impl Into<String>, image: Image) {
+        self.cache()
+            .insert(source.into(), ImageStatus::Loaded(image));
+    }
+
+    /// Removes the image with the given source from the cache. If it is loading the waiting
+    /// widgets are notified after it is loaded, but it is not stored.
+    pub fn remove(&mut self, source: &str) {
+        self.cache().remove(source);
+    }
+
+    /// Removes all images from the cache. The widgets that are waiting for images are notified,
+    /// so they could request them again.
+    pub fn clear(&mut self) {
+        let waiting = {
+            let mut cache = self.cache();
+            cache.entries.clear();
+            cache.memory_usage = 0;
+            std::mem::take(&mut cache.waiting)
+        };
+
+        for (source, waiting) in waiting {
+            notify(source, waiting);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_get() {
+        let mut image_cache = ImageCache::new();
+        image_cache.insert("a", Image::new(10, 10));
+
+        assert_eq!(image_cache.memory_usage(), 400);
+        assert_eq!(
+            image_cache.get("a"),
+            Some(ImageStatus::Loaded(Image::new(10, 10)))
+        );
+        assert_eq!(image_cache.get("b"), None);
+
+        image_cache.remove("a");
+        assert_eq!(image_cache.get("a"), None);
+        assert_eq!(image_cache.memory_usage(), 0);
+    }
+
+    #[test]
+    fn test_memory_limit() {
+        let mut image_cache = ImageCache::new();
+        image_cache.set_memory_limit(800);
+        image_cache.insert("a", Image::new(10, 10));
+        image_cache.insert("b", Image::new(10, 10));
+
+        // the least recently used image is removed
+        image_cache.get("a");
+        image_cache.insert("c", Image::new(10, 10));
+        assert!(image_cache.get("a").is_some());
+        assert!(image_cache.get("b").is_none());
+        assert!(image_cache.get("c").is_some());
+        assert_eq!(image_cache.memory_usage(), 800);
+
+        // the most recent image is kept
+        image_cache.insert("d", Image::new(20, 20));
+        assert!(image_cache.get("d").is_some());
+        assert_eq!(image_cache.memory_usage(), 1600);
+    }
+
+    // Loads the source until it is not loading anymore, waits for the loaded event in between.
+    fn load_until_finished(
+        image_cache: &mut ImageCache,
+        source: &str,
+        event_adapter: &EventAdapter,
+    ) -> ImageStatus {
+        let start = std::time::Instant::now();
+
+        loop {
+            let events = event_adapter.len();
+            let status = image_cache.load(source, Entity(1), event_adapter.clone());
+
+            if status != ImageStatus::Loading || start.elapsed().as_secs() > 5 {
+                return status;
+            }
+
+            while event_adapter.len() == events && start.elapsed().as_secs() <= 5 {
+                std::thread::sleep(std::time::Duration::from_millis(1));
+            }
+        }
+    }
+
+    #[test]
+    fn test_load_failed() {
+        let mut image_cache = ImageCache::new();
+        let event_adapter = EventAdapter::default();
+        let source = "not/existing/image.png";
+
+        assert!(matches!(
+            load_until_finished(&mut image_cache, source, &event_adapter),
+            ImageStatus::Failed(_)
+        ));
+        assert_eq!(event_adapter.len(), 1);
+
+        // the failed image is not decoded again
+        assert!(matches!(
+            image_cache.load(source, Entity(1), event_adapter.clone()),
+            ImageStatus::Failed(_)
+        ));
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        assert_eq!(event_adapter.len(), 1);
+
+        // it is loaded again after it is removed
+        image_cache.remove(source);
+        assert_eq!(
+            image_cache.load(source, Entity(1), event_adapter.clone()),
+            ImageStatus::Loading
+        );
+    }
+
+    #[test]
+    fn test_load_now() {
+        let mut image_cache = ImageCache::new();
+        image_cache.insert("a", Image::new(10, 10));
+
+        // clones share the images
+        let mut shared = image_cache.clone();
+        assert_eq!(shared.load_now("a"), Ok(Image::new(10, 10)));
+
+        assert!(shared.load_now("not/existing/image.png").is_err());
+        assert!(matches!(
+            image_cache.get("not/existing/image.png"),
+            Some(ImageStatus::Failed(_))
+        ));
+    }
+
+    #[test]
+    fn test_failed() {
+        let cache = Mutex::new(Cache {
+            memory_limit: 400,
+            ..Default::default()
+        });
+        cache
+            .lock()
+            .unwrap()
+            .insert("a".to_string(), ImageStatus::Loading);
+
+        // images that are removed while they are loading are not stored
+        finish(
+            &cache,
+            "b".to_string(),
+            ImageStatus::Failed("b".to_string()),
+        );
+        assert!(cache.lock().unwrap().touch("b").is_none());
+
+        // failed images are counted and removed like decoded ones
+        finish(
+            &cache,
+            "a".to_string(),
+            ImageStatus::Failed("a".to_string()),
+        );
+        assert_eq!(
+            cache.lock().unwrap().touch("a"),
+            Some(ImageStatus::Failed("a".to_string()))
+        );
+        assert_eq!(cache.lock().unwrap().memory_usage, 1);
+
+        cache
+            .lock()
+            .unwrap()
+            .insert("c".to_string(), ImageStatus::Loaded(Image::new(10, 10)));
+        assert!(cache.lock().unwrap().touch("a").is_none());
+    }
+}
@@ -1,7 +1,11 @@
 //! This module contains global services.
 //!
 pub use self::clipboard::*;
+pub use self::frame_timer::*;
+pub use self::image_cache::*;
 pub use self::settings::*;
 
 mod clipboard;
+mod frame_timer;
+mod image_cache;
 mod settings;
@@ -50,6 +50,35 @@ impl EventStateSystem {
             .remove(&entity);
     }
 
+    // Marks the widgets as dirty that have paused their animation while they were hidden and are
+    // visible again. Returns `true` if a widget is added to the dirty widgets.
+    fn resume_visible(&self, ecm: &mut EntityComponentManager<Tree, StringComponentStore>) -> bool {
+        let resumed = match self
+            .registry
+            .borrow_mut()
+            .try_get_mut::<FrameTimer>("frame_timer")
+        {
+            Some(frame_timer) if frame_timer.is_paused() => {
+                frame_timer.resume(|entity| is_visible(entity, ecm))
+            }
+            _ => return false,
+        };
+
+        let root = ecm.entity_store().root();
+        let dirty_count = |ecm: &EntityComponentManager<Tree, StringComponentStore>| {
+            ecm.component_store()
+                .get::<Vec<Entity>>("dirty_widgets", root)
+                .map_or(0, |dirty_widgets| dirty_widgets.len())
+        };
+        let count = dirty_count(ecm);
+
+        for entity in resumed {
+            mark_as_dirty_self(entity, ecm);
+        }
+
+        dirty_count(ecm) > count
+    }
+
     fn process_direct(
         &self,
         event: &EventBox,
@@ -388,6 +417,7 @@ impl System<Tree, StringComponentStore, RenderContext2D> for EventStateSystem {
                         .get::<Vec<Entity>>("dirty_widgets", root)
                         .unwrap()
                         .len()
+                    && !self.resume_visible(ecm)
                 {
                     break;
                 }
@@ -471,3 +501,71 @@ impl System<Tree, StringComponentStore, RenderContext2D> for EventStateSystem {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+
+    #[test]
+    fn test_resume_visible() {
+        let (window_sender, _window_receiver) = mpsc::channel();
+        let (shell_sender, _shell_receiver) = mpsc::channel();
+        let registry = Rc::new(RefCell::new(Registry::new()));
+        registry
+            .borrow_mut()
+            .register("frame_timer", FrameTimer::new());
+        let system = EventStateSystem::new(
+            ContextProvider::new(window_sender, shell_sender, "test", None),
+            registry.clone(),
+        );
+
+        let mut world: World<Tree, StringComponentStore, RenderContext2D> =
+            World::from_stores(Tree::default(), StringComponentStore::default());
+        let ecm = world.entity_component_manager();
+        let window = ecm.create_entity().build();
+        let image = ecm.create_entity().build();
+        ecm.entity_store_mut().append_child(window, image).unwrap();
+
+        let store = ecm.component_store_mut();
+        store.register("dirty_widgets", window, Vec::<Entity>::new());
+        store.register("visibility", window, Visibility::Visible);
+        store.register("visibility", image, Visibility::Hidden);
+        store.register("dirty", window, false);
+        store.register("dirty", image, false);
+
+        // the hidden image has paused its animation
+        registry
+            .borrow_mut()
+            .get_mut::<FrameTimer>("frame_timer")
+            .pause(image);
+
+        assert!(!system.resume_visible(ecm));
+        assert!(registry
+            .borrow()
+            .get::<FrameTimer>("frame_timer")
+            .is_paused());
+        assert!(!*ecm.component_store().get::<bool>("dirty", image).unwrap());
+
+        // the image is marked as dirty to play its animation again if it is visible
+        *ecm.component_store_mut()
+            .get_mut::<Visibility>("visibility", image)
+            .unwrap() = Visibility::Visible;
+
+        assert!(system.resume_visible(ecm));
+        assert!(!registry
+            .borrow()
+            .get::<FrameTimer>("frame_timer")
+            .is_paused());
+        assert!(*ecm.component_store().get::<bool>("dirty", image).unwrap());
+        assert_eq!(
+            ecm.component_store()
+                .get::<Vec<Entity>>("dirty_widgets", window)
+                .unwrap(),
+            &[image]
+        );
+
+        assert!(!system.resume_visible(ecm));
+    }
+}
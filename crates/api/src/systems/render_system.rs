@@ -1,13 +1,25 @@
-use std::collections::BTreeMap;
+use std::{cell::RefCell, collections::BTreeMap};
 
 use dces::prelude::*;
 
-use crate::{prelude::*, render::RenderContext2D, tree::Tree};
+use crate::{prelude::*, render::RenderContext2D, tree::Tree, utils::*};
 
 /// The `RenderSystem` iterates over all visual widgets and used its render objects to draw them on the screen.
-#[derive(Constructor)]
+///
+/// After the first frame only the regions damaged by dirty, moved, shown or hidden widgets are redrawn.
 pub struct RenderSystem {
     context_provider: ContextProvider,
+    drawing_bounds: RefCell<DrawingBounds>,
+}
+
+impl RenderSystem {
+    /// Creates a new render system.
+    pub fn new(context_provider: ContextProvider) -> Self {
+        RenderSystem {
+            context_provider,
+            drawing_bounds: RefCell::new(DrawingBounds::default()),
+        }
+    }
 }
 
 impl System<Tree, StringComponentStore, RenderContext2D> for RenderSystem {
@@ -29,8 +41,8 @@ impl System<Tree, StringComponentStore, RenderContext2D> for RenderSystem {
         }
 
         // reset the dirty flag of all dirty widgets to `false`
-        for widget in dirty_widgets {
-            if let Ok(dirty) = ecm.component_store_mut().get_mut::<bool>("dirty", widget) {
+        for widget in &dirty_widgets {
+            if let Ok(dirty) = ecm.component_store_mut().get_mut::<bool>("dirty", *widget) {
                 *dirty = false;
             }
         }
@@ -52,23 +64,71 @@ impl System<Tree, StringComponentStore, RenderContext2D> for RenderSystem {
             .unwrap()
             .clone();
 
-        let mut offsets = BTreeMap::new();
-        offsets.insert(root, (0.0, 0.0));
+        let window = *ecm
+            .component_store()
+            .get::<Rectangle>("bounds", root)
+            .unwrap();
+        let drawing_bounds = DrawingBounds::new(ecm);
+        let previous = self.drawing_bounds.replace(drawing_bounds.clone());
 
         // CONSOLE.time("render");
 
-        render_context.start();
-        render_context.begin_path();
-        self.context_provider.render_objects.borrow()[&root].render(
-            render_context,
-            root,
-            ecm,
-            &self.context_provider,
-            &theme,
-            &mut offsets,
-            debug,
-        );
-        render_context.finish();
+        // the whole window is redrawn on the first frame, after a resize and for debugging
+        if self.context_provider.first_run.get()
+            || debug
+            || previous.bounds(root) != drawing_bounds.bounds(root)
+        {
+            let mut offsets = BTreeMap::new();
+            offsets.insert(root, (0.0, 0.0));
+
+            render_context.start();
+            render_context.begin_path();
+            self.context_provider.render_objects.borrow()[&root].render(
+                render_context,
+                root,
+                ecm,
+                &self.context_provider,
+                &theme,
+                &mut offsets,
+                Matrix::identity(),
+                1.0,
+                None,
+                debug,
+            );
+            render_context.finish();
+        } else {
+            let regions = merge_regions(
+                &drawing_bounds.damaged_regions(&previous, &dirty_widgets),
+                window,
+            );
+
+            if regions.is_empty() {
+                return;
+            }
+
+            for region in regions {
+                let mut offsets = BTreeMap::new();
+                offsets.insert(root, (0.0, 0.0));
+
+                render_context.start_region(region);
+                render_context.begin_path();
+                self.context_provider.render_objects.borrow()[&root].render(
+                    render_context,
+                    root,
+                    ecm,
+                    &self.context_provider,
+                    &theme,
+                    &mut offsets,
+                    Matrix::identity(),
+                    1.0,
+                    Some(&Damage::new(region, &drawing_bounds)),
+                    debug,
+                );
+                render_context.finish_region();
+            }
+
+            render_context.finish();
+        }
 
         if self.context_provider.first_run.get() {
             self.context_provider.first_run.set(false);
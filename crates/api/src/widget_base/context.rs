@@ -11,6 +11,7 @@ use crate::{
     shell::{ShellRequest, WindowRequest},
     theming::prelude::*,
     tree::Tree,
+    utils::*,
 };
 
 use super::WidgetContainer;
@@ -116,6 +117,11 @@ impl<'a> Context<'a> {
             .map(move |child| self.get_widget(child))
     }
 
+    /// Returns `true` if the given widget and all of its parents are visible.
+    pub fn is_visible(&self, entity: Entity) -> bool {
+        is_visible(entity, self.ecm)
+    }
+
     /// Returns the parent of the current widget.
     /// Panics if the parent does not exists.
     pub fn parent(&mut self) -> WidgetContainer<'_> {
@@ -427,6 +433,299 @@ impl<'a> Context<'a> {
         self.render_context
     }
 
+    /// Draws the given widget and its children again and returns them as svg document. The
+    /// document shows the bounds of the widget, use the window entity to export the whole window.
+    ///
+    /// Texts are written as `<text>` elements and gradients as svg gradients.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn export_svg(&mut self, entity: Entity) -> String {
+        use crate::render::DrawCommand;
+
+        // the widget is drawn with the transformations of its parents
+        let transform = self.ecm.entity_store().parent[&entity]
+            .map(|parent| global_render_transform(parent, self.ecm))
+            .unwrap_or_else(Matrix::identity);
+        let (recorded, viewport) = self.record_widget(entity, transform);
+
+        // the widget is clipped by its parents like in the window
+        let mut display_list = self.ancestor_clips(entity);
+
+        if !display_list.is_empty() {
+            display_list.push(DrawCommand::SetTransform {
+                h_scaling: transform.m11,
+                h_skewing: transform.m12,
+                v_skewing: transform.m21,
+                v_scaling: transform.m22,
+                h_moving: transform.m31,
+                v_moving: transform.m32,
+            });
+        }
+
+        for command in recorded.commands() {
+            display_list.push(command.clone());
+        }
+
+        display_list.to_svg(viewport)
+    }
+
+    // Records the clips of the parents of the widget that have the clip property, each clip is
+    // drawn with the transformations of the parents of the clipping widget.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn ancestor_clips(&self, entity: Entity) -> crate::render::DisplayList {
+        use crate::render::{DisplayList, DrawCommand};
+
+        let mut clips = vec![];
+        let mut current = self.ecm.entity_store().parent[&entity];
+
+        while let Some(parent) = current {
+            current = self.ecm.entity_store().parent[&parent];
+            let store = self.ecm.component_store();
+
+            if !store
+                .get::<bool>("clip", parent)
+                .map_or(false, |clip| *clip)
+            {
+                continue;
+            }
+
+            let bounds = match store.get::<Rectangle>("bounds", parent) {
+                Ok(bounds) => Rectangle::new(self.global_position(parent), bounds.size()),
+                Err(_) => continue,
+            };
+
+            let border_radius = match store
+                .get::<ClipMode>("clip_mode", parent)
+                .map(|clip_mode| *clip_mode)
+                .unwrap_or_default()
+            {
+                ClipMode::BorderRadius => store
+                    .get::<f64>("border_radius", parent)
+                    .map(|border_radius| *border_radius)
+                    .unwrap_or_default(),
+                ClipMode::Bounds => 0.0,
+            };
+
+            let transform = current
+                .map(|e| global_render_transform(e, self.ecm))
+                .unwrap_or_else(Matrix::identity);
+
+            clips.push((transform, bounds, border_radius));
+        }
+
+        let mut display_list = DisplayList::new();
+
+        // the outermost clip is applied first
+        for (transform, bounds, border_radius) in clips.into_iter().rev() {
+            display_list.push(DrawCommand::SetTransform {
+                h_scaling: transform.m11,
+                h_skewing: transform.m12,
+                v_skewing: transform.m21,
+                v_scaling: transform.m22,
+                h_moving: transform.m31,
+                v_moving: transform.m32,
+            });
+            display_list.push(DrawCommand::BeginPath);
+
+            if border_radius > 0.0 {
+                display_list.push(DrawCommand::RoundedRect {
+                    x: bounds.x(),
+                    y: bounds.y(),
+                    width: bounds.width(),
+                    height: bounds.height(),
+                    radius: border_radius,
+                });
+            } else {
+                display_list.push(DrawCommand::Rect {
+                    x: bounds.x(),
+                    y: bounds.y(),
+                    width: bounds.width(),
+                    height: bounds.height(),
+                });
+            }
+
+            display_list.push(DrawCommand::Clip);
+        }
+
+        display_list
+    }
+
+    /// Draws the given widget and its children offscreen and returns them as image with the size
+    /// of the widget bounds. Parts of the widget that are not covered are transparent, use the
+    /// window entity to get a screenshot of the whole window.
+    ///
+    /// The current frame of the window is not changed.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn snapshot(&mut self, entity: Entity) -> crate::render::Image {
+        let size = self.widget_size(entity);
+        self.snapshot_with_size(
+            entity,
+            size.width().ceil() as u32,
+            size.height().ceil() as u32,
+        )
+    }
+
+    /// Draws the given widget and its children offscreen like `snapshot` at the given size, e.g.
+    /// to create a thumbnail.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn snapshot_with_size(
+        &mut self,
+        entity: Entity,
+        width: u32,
+        height: u32,
+    ) -> crate::render::Image {
+        let position = self.global_position(entity);
+        let size = self.widget_size(entity);
+
+        let scale = |image_size: u32, widget_size: f64| {
+            if widget_size > 0.0 {
+                image_size as f64 / widget_size.ceil()
+            } else {
+                1.0
+            }
+        };
+
+        // the widget is drawn at its position in the window, the transform moves it to the
+        // origin of the image and scales it to the requested size
+        let transform = Matrix::translation(-position.x(), -position.y()).then(&Matrix::scaling(
+            scale(width, size.width()),
+            scale(height, size.height()),
+        ));
+        let (display_list, _) = self.record_widget(entity, transform);
+
+        self.render_context
+            .render_to_image(&display_list, width, height)
+    }
+
+    /// Draws the whole window offscreen and returns it as image, which can be saved with
+    /// `Image::save_png`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn snapshot_window(&mut self) -> crate::render::Image {
+        let root = self.ecm.entity_store().root();
+        self.snapshot(root)
+    }
+
+    // Gets the size of the widget bounds.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn widget_size(&self, entity: Entity) -> Size {
+        self.ecm
+            .component_store()
+            .get::<Rectangle>("bounds", entity)
+            .map(|bounds| bounds.size())
+            .unwrap_or_default()
+    }
+
+    // Gets the position of the widget relative to the window.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn global_position(&self, entity: Entity) -> Point {
+        let mut position = Point::default();
+        let mut current = Some(entity);
+
+        while let Some(e) = current {
+            if let Ok(bounds) = self.ecm.component_store().get::<Rectangle>("bounds", e) {
+                position = position + bounds.position();
+            }
+
+            current = self.ecm.entity_store().parent[&e];
+        }
+
+        position
+    }
+
+    // Records the draw calls of the widget and its children with the given transform. The widget
+    // keeps its position in the window, so the positions of the widgets that are used by hit
+    // testing and dirty regions are not changed. Returns the calls and the area of the widget.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn record_widget(
+        &mut self,
+        entity: Entity,
+        transform: Matrix,
+    ) -> (crate::render::DisplayList, Rectangle) {
+        use crate::render::{DisplayList, DrawCommand};
+
+        let root = self.ecm.entity_store().root();
+        let theme = self
+            .ecm
+            .component_store()
+            .get::<Theme>("theme", root)
+            .unwrap()
+            .clone();
+
+        let position = self.global_position(entity);
+        let viewport = Rectangle::new(position, self.widget_size(entity));
+
+        let mut offsets = BTreeMap::new();
+
+        if let Some(parent) = self.ecm.entity_store().parent[&entity] {
+            let parent_position = position
+                - self
+                    .ecm
+                    .component_store()
+                    .get::<Rectangle>("bounds", entity)
+                    .map(|bounds| bounds.position())
+                    .unwrap_or_default();
+            offsets.insert(parent, (parent_position.x(), parent_position.y()));
+        }
+
+        let mut display_list = DisplayList::new();
+
+        if !transform.is_identity() {
+            display_list.push(DrawCommand::SetTransform {
+                h_scaling: transform.m11,
+                h_skewing: transform.m12,
+                v_skewing: transform.m21,
+                v_scaling: transform.m22,
+                h_moving: transform.m31,
+                v_moving: transform.m32,
+            });
+        }
+
+        // the window background is not drawn by a widget
+        if entity == root {
+            if let Ok(background) = self.ecm.component_store().get::<Brush>("background", root) {
+                display_list.push(DrawCommand::SetFillStyle {
+                    fill_style: background.clone(),
+                });
+                display_list.push(DrawCommand::FillRect {
+                    x: viewport.x(),
+                    y: viewport.y(),
+                    width: viewport.width(),
+                    height: viewport.height(),
+                });
+            }
+        }
+
+        // drawn on an invisible layer to keep the current frame
+        self.render_context.push_layer(0.0, None);
+        self.render_context.start_recording();
+
+        // the widget is drawn with the faded layers of its parents
+        let layer_opacity = crate::render_object::layer_opacity(entity, self.ecm);
+
+        if let Some(render_object) = self.provider.render_objects.borrow().get(&entity) {
+            render_object.render(
+                self.render_context,
+                entity,
+                self.ecm,
+                self.provider,
+                &theme,
+                &mut offsets,
+                transform,
+                layer_opacity,
+                None,
+                false,
+            );
+        }
+
+        let recorded = self.render_context.finish_recording();
+        self.render_context.pop_layer();
+
+        for command in recorded.commands() {
+            display_list.push(command.clone());
+        }
+
+        (display_list, viewport)
+    }
+
     /// Returns a keys collection of new added states.
     pub fn new_states_keys(&self) -> Vec<Entity> {
         self.new_states.keys().cloned().collect()
@@ -2,7 +2,10 @@ use std::any::type_name;
 
 use dces::prelude::*;
 
-use crate::{event::ChangedEvent, event::*, theming::*, tree::*, utils::prelude::*};
+use crate::{
+    event::ChangedEvent, event::*, render::Image, services::ImageCache, theming::*, tree::*,
+    utils::prelude::*,
+};
 
 /// Mark the widget and shared widgets as dirty.
 pub fn mark_as_dirty(
@@ -51,6 +54,59 @@ pub fn mark_as_dirty_self(
     }
 }
 
+/// Returns `true` if the widget and all of its parents are visible.
+pub fn is_visible(
+    entity: Entity,
+    ecm: &EntityComponentManager<Tree, StringComponentStore>,
+) -> bool {
+    let mut current = Some(entity);
+
+    while let Some(e) = current {
+        match ecm.component_store().get::<Visibility>("visibility", e) {
+            Ok(visibility) if *visibility == Visibility::Visible => {}
+            _ => return false,
+        }
+
+        current = ecm.entity_store().parent.get(&e).copied().flatten();
+    }
+
+    true
+}
+
+/// Calculates the transformation the widget is drawn with in window coordinates. It is composed
+/// of the `render_transform` of the widget and the ones of its parents and placed by the global
+/// `position` of the widgets.
+pub fn global_render_transform(
+    entity: Entity,
+    ecm: &EntityComponentManager<Tree, StringComponentStore>,
+) -> Matrix {
+    let mut matrix = Matrix::identity();
+    let mut current = Some(entity);
+
+    while let Some(entity) = current {
+        let store = ecm.component_store();
+
+        if let Ok(render_transform) = store.get::<RenderTransform>("render_transform", entity) {
+            if !render_transform.is_identity() {
+                let position = store
+                    .get::<Point>("position", entity)
+                    .map(|position| *position)
+                    .unwrap_or_default();
+                let size = store
+                    .get::<Rectangle>("bounds", entity)
+                    .map(|bounds| bounds.size())
+                    .unwrap_or_default();
+
+                matrix = matrix.then(&render_transform.matrix_for(Rectangle::new(position, size)));
+            }
+        }
+
+        current = ecm.entity_store().parent[&entity];
+    }
+
+    matrix
+}
+
 /// The `WidgetContainer` wraps the entity of a widget and provides access to its properties, its children properties and its parent properties.
 pub struct WidgetContainer<'a> {
     ecm: &'a mut EntityComponentManager<Tree, StringComponentStore>,
@@ -88,6 +144,11 @@ impl<'a> WidgetContainer<'a> {
         self.current_node
     }
 
+    /// Gets the transformation the widget is drawn with in window coordinates.
+    pub fn global_render_transform(&self) -> Matrix {
+        global_render_transform(self.current_node, self.ecm)
+    }
+
     /// Remove the dirty flag from the current widget.
     pub fn clear_dirty(&mut self) {
         let root = self.ecm.entity_store().root();
@@ -440,6 +501,36 @@ impl<'a> WidgetContainer<'a> {
         }
     }
 
+    // Sets the image with the path of the theme value, the image is only replaced if the path
+    // is changed. The image is loaded by the image cache of the window.
+    fn update_image(&mut self, key: &str, value: Value) {
+        if !self.has::<Image>(key) {
+            return;
+        }
+
+        let root = self.ecm.entity_store().root();
+        let image = match self
+            .ecm
+            .component_store_mut()
+            .get_mut::<ImageCache>("image_cache", root)
+        {
+            Ok(image_cache) => image_cache
+                .load_now(Value(value.0.clone()).get::<String>())
+                .unwrap_or_default(),
+            Err(_) => Image::from(value),
+        };
+
+        let current = self
+            .ecm
+            .component_store_mut()
+            .get_mut::<Image>(key, self.current_node)
+            .unwrap();
+
+        if *current != image {
+            *current = image;
+        }
+    }
+
     /// Update all properties from theme for the current widget.
     pub fn update(&mut self, force: bool) {
         self.update_widget(self.current_node, force, false);
@@ -489,6 +580,45 @@ impl<'a> WidgetContainer<'a> {
                             }
                         }
 
+                        if let Some(is_type) = self.is::<BoxShadow>(key) {
+                            if is_type {
+                                self.update_value::<BoxShadow, Value>(key, Value(value.clone()));
+                            }
+                        }
+
+                        if let Some(is_type) = self.is::<ClipMode>(key) {
+                            if is_type {
+                                self.update_value::<ClipMode, Value>(key, Value(value.clone()));
+                            }
+                        }
+
+                        if let Some(is_type) = self.is::<Effect>(key) {
+                            if is_type {
+                                self.update_value::<Effect, Value>(key, Value(value.clone()));
+                            }
+                        }
+
+                        if let Some(is_type) = self.is::<RenderTransform>(key) {
+                            if is_type {
+                                self.update_value::<RenderTransform, Value>(
+                                    key,
+                                    Value(value.clone()),
+                                );
+                            }
+                        }
+
+                        if let Some(is_type) = self.is::<FontStyle>(key) {
+                            if is_type {
+                                self.update_value::<FontStyle, Value>(key, Value(value.clone()));
+                            }
+                        }
+
+                        if let Some(is_type) = self.is::<FontWeight>(key) {
+                            if is_type {
+                                self.update_value::<FontWeight, Value>(key, Value(value.clone()));
+                            }
+                        }
+
                         if let Some(is_type) = self.is::<f32>(key) {
                             if is_type {
                                 self.update_value::<f32, Value>(key, Value(value.clone()));
@@ -512,6 +642,39 @@ impl<'a> WidgetContainer<'a> {
                                 self.update_value::<String, Value>(key, Value(value.clone()));
                             }
                         }
+
+                        if let Some(is_type) = self.is::<Stretch>(key) {
+                            if is_type {
+                                self.update_value::<Stretch, Value>(key, Value(value.clone()));
+                            }
+                        }
+
+                        if let Some(is_type) = self.is::<TextAlignment>(key) {
+                            if is_type {
+                                self.update_value::<TextAlignment, Value>(
+                                    key,
+                                    Value(value.clone()),
+                                );
+                            }
+                        }
+
+                        if let Some(is_type) = self.is::<TextTrimming>(key) {
+                            if is_type {
+                                self.update_value::<TextTrimming, Value>(key, Value(value.clone()));
+                            }
+                        }
+
+                        if let Some(is_type) = self.is::<TextWrap>(key) {
+                            if is_type {
+                                self.update_value::<TextWrap, Value>(key, Value(value.clone()));
+                            }
+                        }
+
+                        if let Some(is_type) = self.is::<Image>(key) {
+                            if is_type {
+                                self.update_image(key, Value(value.clone()));
+                            }
+                        }
                     }
                 }
             }
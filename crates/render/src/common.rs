@@ -1,5 +1,273 @@
+use std::{collections::HashMap, sync::Arc};
+
 use crate::utils::*;
 
+// Implements the methods of the render context that do not depend on the rasterizer: font
+// registration, text measurement, the state setters and the recording of draw calls. The render
+// context needs the fields `config: RenderConfig`, `fonts: FontRegistry` and
+// `recording: Option<DisplayList>`.
+#[cfg(not(target_arch = "wasm32"))]
+macro_rules! impl_render_context_common {
+    () => {
+        impl RenderContext2D {
+            /// Registers a new font file.
+            pub fn register_font(&mut self, family: &str, font_file: &'static [u8]) {
+                self.fonts.register(family, font_file);
+            }
+
+            /// Registers a new font from an owned buffer, e.g. a font that is loaded at runtime.
+            pub fn register_font_data(
+                &mut self,
+                family: &str,
+                data: Vec<u8>,
+            ) -> Result<(), String> {
+                self.fonts.register_data(family, data)
+            }
+
+            /// Loads a font file from the given path and registers it with the family key.
+            pub fn register_font_file<P: AsRef<Path> + std::fmt::Debug>(
+                &mut self,
+                family: &str,
+                path: P,
+            ) -> Result<(), String> {
+                self.fonts.register_file(family, path)
+            }
+
+            /// Scans the standard font directories of the system. A system font could be used by
+            /// its file name (e.g. `NotoSans-Regular`) or by its family name (e.g. `Noto Sans`),
+            /// it is loaded when it is used the first time.
+            pub fn register_system_fonts(&mut self) {
+                self.fonts.register_system_fonts();
+            }
+
+            /// Creates a render context with the given size that uses the registered fonts of
+            /// this one, e.g. to draw widgets offscreen.
+            pub fn create_offscreen(&self, width: f64, height: f64) -> Self {
+                let mut render_context = RenderContext2D::new(width, height);
+                render_context.fonts = self.fonts.share();
+                render_context
+            }
+
+            /// Draws the display list offscreen on a transparent image with the given size, with
+            /// the registered fonts of this render context.
+            pub fn render_to_image(
+                &self,
+                display_list: &DisplayList,
+                width: u32,
+                height: u32,
+            ) -> Image {
+                if width == 0 || height == 0 {
+                    return Image::default();
+                }
+
+                let mut render_context = self.create_offscreen(width as f64, height as f64);
+                render_context.start();
+                display_list.replay_offscreen(&mut render_context);
+                render_context.finish();
+
+                Image::from_data(width, height, render_context.data().to_vec()).unwrap_or_default()
+            }
+
+            pub fn measure(
+                &mut self,
+                text: &str,
+                font_size: f64,
+                family: impl Into<String>,
+            ) -> TextMetrics {
+                self.set_font_family(family);
+                self.set_font_size(font_size);
+                self.measure_text(text)
+            }
+
+            /// Returns a TextMetrics object.
+            pub fn measure_text(&mut self, text: &str) -> TextMetrics {
+                if text.is_empty() {
+                    return TextMetrics::default();
+                }
+
+                let font_chain = self.fonts.chain(&self.config.font_config);
+
+                if font_chain.is_empty() {
+                    return TextMetrics::default();
+                }
+
+                font_chain.text_metrics(
+                    text,
+                    self.config.font_config.font_size,
+                    self.config.text_baseline,
+                )
+            }
+
+            /// Measures the shaped glyphs of the text with the current font in visual order.
+            pub fn measure_glyphs(&mut self, text: &str) -> Vec<GlyphMetrics> {
+                if text.is_empty() {
+                    return vec![];
+                }
+
+                self.fonts.chain(&self.config.font_config).glyph_metrics(
+                    text,
+                    self.config.font_config.font_size,
+                    self.config.text_baseline,
+                )
+            }
+
+            /// Returns the x position of a caret placed before each char of the text, measured
+            /// with the given font. The last entry is the position after the last char. The
+            /// positions follow the shaped clusters and the bidi order of the text, so they are
+            /// not always ascending.
+            pub fn caret_positions(
+                &mut self,
+                text: &str,
+                font_size: f64,
+                family: impl Into<String>,
+            ) -> Vec<f64> {
+                self.set_font_family(family);
+                self.set_font_size(font_size);
+
+                let font_chain = self.fonts.chain(&self.config.font_config);
+
+                if font_chain.is_empty() {
+                    return vec![0.0; text.chars().count() + 1];
+                }
+
+                font_chain.caret_positions(text, self.config.font_config.font_size)
+            }
+
+            // Line styles
+
+            /// Sets the thickness of lines.
+            pub fn set_line_width(&mut self, line_width: f64) {
+                self.record(|| DrawCommand::SetLineWidth { line_width });
+                self.config.line_width = line_width;
+            }
+
+            /// Sets the shape used to draw the end points of lines.
+            pub fn set_line_cap(&mut self, line_cap: LineCap) {
+                self.record(|| DrawCommand::SetLineCap { line_cap });
+                self.config.line_cap = line_cap;
+            }
+
+            /// Sets the shape used to join two line segments where they meet.
+            pub fn set_line_join(&mut self, line_join: LineJoin) {
+                self.record(|| DrawCommand::SetLineJoin { line_join });
+                self.config.line_join = line_join;
+            }
+
+            /// Sets the miter limit ratio. Zero, negative and not finite values are ignored.
+            pub fn set_miter_limit(&mut self, miter_limit: f64) {
+                self.record(|| DrawCommand::SetMiterLimit { miter_limit });
+                if miter_limit.is_finite() && miter_limit > 0.0 {
+                    self.config.miter_limit = miter_limit;
+                }
+            }
+
+            /// Sets the line dash pattern used when stroking lines. An empty list draws solid
+            /// lines.
+            pub fn set_line_dash(&mut self, segments: &[f64]) {
+                self.record(|| DrawCommand::SetLineDash {
+                    line_dash: segments.to_vec(),
+                });
+                if let Some(line_dash) = line_dash_segments(segments) {
+                    self.config.line_dash = line_dash;
+                }
+            }
+
+            /// Sets the line dash offset.
+            pub fn set_line_dash_offset(&mut self, line_dash_offset: f64) {
+                self.record(|| DrawCommand::SetLineDashOffset { line_dash_offset });
+                if line_dash_offset.is_finite() {
+                    self.config.line_dash_offset = line_dash_offset;
+                }
+            }
+
+            /// Sets the alpha value,
+            pub fn set_alpha(&mut self, alpha: f32) {
+                self.record(|| DrawCommand::SetAlpha { alpha });
+                self.config.alpha = alpha;
+            }
+
+            /// Specifies the font family.
+            pub fn set_font_family(&mut self, family: impl Into<String>) {
+                let family = family.into();
+                self.record(|| DrawCommand::SetFontFamily {
+                    family: family.clone(),
+                });
+                self.config.font_config.family = family;
+            }
+
+            /// Specifies the font size.
+            pub fn set_font_size(&mut self, size: f64) {
+                self.record(|| DrawCommand::SetFontSize { size });
+                self.config.font_config.font_size = size + 4.0;
+            }
+
+            /// Specifies the font weight. The registered face of the font family with the nearest
+            /// weight is used.
+            pub fn set_font_weight(&mut self, weight: FontWeight) {
+                self.record(|| DrawCommand::SetFontWeight { weight });
+                self.config.font_config.weight = weight;
+            }
+
+            /// Specifies the font style (normal, italic or oblique).
+            pub fn set_font_style(&mut self, style: FontStyle) {
+                self.record(|| DrawCommand::SetFontStyle { style });
+                self.config.font_config.style = style;
+            }
+
+            /// Specifies the font stretch (condensed or expanded width).
+            pub fn set_font_stretch(&mut self, stretch: FontStretch) {
+                self.record(|| DrawCommand::SetFontStretch { stretch });
+                self.config.font_config.stretch = stretch;
+            }
+
+            /// Specifies the baseline the y position of `fill_text` refers to,
+            /// `TextBaseline::Top` by default.
+            pub fn set_text_baseline(&mut self, text_baseline: TextBaseline) {
+                self.record(|| DrawCommand::SetTextBaseline { text_baseline });
+                self.config.text_baseline = text_baseline;
+            }
+
+            // Fill and stroke style
+
+            /// Specifies the fill color to use inside shapes.
+            pub fn set_fill_style(&mut self, fill_style: Brush) {
+                self.record(|| DrawCommand::SetFillStyle {
+                    fill_style: fill_style.clone(),
+                });
+                self.config.fill_style = fill_style;
+            }
+
+            /// Specifies the fill stroke to use inside shapes.
+            pub fn set_stroke_style(&mut self, stroke_style: Brush) {
+                self.record(|| DrawCommand::SetStrokeStyle {
+                    stroke_style: stroke_style.clone(),
+                });
+                self.config.stroke_style = stroke_style;
+            }
+
+            // Recording
+
+            /// Starts to record all following draw calls into a display list. The calls are
+            /// still drawn.
+            pub fn start_recording(&mut self) {
+                self.recording = Some(DisplayList::new());
+            }
+
+            /// Stops the recording and returns the draw calls recorded since `start_recording`.
+            pub fn finish_recording(&mut self) -> DisplayList {
+                self.recording.take().unwrap_or_default()
+            }
+
+            // Records a draw call if the recording is started.
+            fn record(&mut self, command: impl FnOnce() -> DrawCommand) {
+                if let Some(display_list) = &mut self.recording {
+                    display_list.push(command());
+                }
+            }
+        }
+    };
+}
+
 /// Calculates the AABB of a arc.
 pub fn arc_rect(x: f64, y: f64, radius: f64, start_angle: f64, end_angle: f64) -> Rectangle {
     // Lazy calculate the AABB of its ends.
@@ -317,9 +585,1144 @@ where
     r_stops
 }
 
+// Number of stops that are added between two stops of a gradient that is interpolated in
+// linear light. The rasterizers interpolate linearly between them.
+const LINEAR_RGB_STEPS: usize = 8;
+
+fn srgb_to_linear(component: u8) -> f64 {
+    let c = component as f64 / 255.0;
+
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f64) -> u8 {
+    let c = if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    };
+
+    (c * 255.0).round().clamp(0.0, 255.0) as u8
+}
+
+/// Mixes two colors in linear light, `t` is the weight of `to` (0.0 to 1.0). Like css
+/// gradients the premultiplied components are mixed, so transparent colors do not darken
+/// the mix.
+pub fn mix_linear_rgb(from: Color, to: Color, t: f64) -> Color {
+    let from_a = from.a() as f64 / 255.0;
+    let to_a = to.a() as f64 / 255.0;
+    let a = from_a + (to_a - from_a) * t;
+
+    if a <= 0.0 {
+        return Color::rgba(0, 0, 0, 0);
+    }
+
+    let mix = |from_c: u8, to_c: u8| {
+        let c = srgb_to_linear(from_c) * from_a * (1.0 - t) + srgb_to_linear(to_c) * to_a * t;
+        linear_to_srgb(c / a)
+    };
+
+    Color::rgba(
+        mix(from.r(), to.r()),
+        mix(from.g(), to.g()),
+        mix(from.b(), to.b()),
+        (a * 255.0).round() as u8,
+    )
+}
+
+/// Resolves the stops of a gradient like `build_unit_percent_gradient`. If the gradient is
+/// interpolated in linear light, stops are added between each pair of stops, so rasterizers
+/// that interpolate in sRGB draw the gradient close to a linear-light gradient.
+pub fn build_gradient_stops<F, R>(
+    stops: &[GradientStop],
+    length: f64,
+    interpolation: ColorInterpolation,
+    f: F,
+) -> Vec<R>
+where
+    F: Fn(f64, Color) -> R,
+{
+    if interpolation == ColorInterpolation::Srgb {
+        return build_unit_percent_gradient(stops, length, f);
+    }
+
+    let stops = build_unit_percent_gradient(stops, length, |p, c| (p, c));
+    let mut r_stops = Vec::with_capacity(stops.len() * LINEAR_RGB_STEPS);
+
+    for (i, (pos, color)) in stops.iter().enumerate() {
+        if i > 0 {
+            let (from_pos, from_color) = stops[i - 1];
+
+            if *pos > from_pos && from_color != *color {
+                for step in 1..LINEAR_RGB_STEPS {
+                    let t = step as f64 / LINEAR_RGB_STEPS as f64;
+                    r_stops.push(f(
+                        from_pos + (pos - from_pos) * t,
+                        mix_linear_rgb(from_color, *color, t),
+                    ));
+                }
+            }
+        }
+
+        r_stops.push(f(*pos, *color));
+    }
+
+    r_stops
+}
+
+/// Repeats the unit stops of a gradient for the periods `first..first + count` and scales them
+/// to the unit range, to draw the repeat and reflect spread methods with rasterizers that only
+/// pad gradients. The gradient has to be stretched over the periods by the caller. Reflected
+/// periods with an odd index are mirrored.
+#[cfg(any(target_arch = "wasm32", test))]
+pub fn spread_gradient_stops(
+    stops: &[(f64, Color)],
+    spread: SpreadMethod,
+    first: i64,
+    count: usize,
+) -> Vec<(f64, Color)> {
+    if spread == SpreadMethod::Pad || count == 0 {
+        return stops.to_vec();
+    }
+
+    let mut spread_stops = Vec::with_capacity(stops.len() * count);
+
+    for i in 0..count {
+        let mirrored = spread == SpreadMethod::Reflect && (first + i as i64) % 2 != 0;
+
+        for j in 0..stops.len() {
+            let (pos, color) = if mirrored {
+                let (pos, color) = stops[stops.len() - 1 - j];
+                (1.0 - pos, color)
+            } else {
+                stops[j]
+            };
+
+            spread_stops.push(((i as f64 + pos) / count as f64, color));
+        }
+    }
+
+    spread_stops
+}
+
+/// Validates a line dash segment list like the `setLineDash` method of the canvas 2D api.
+/// Returns `None` if one of the segments is negative or not finite. If the number of segments
+/// is odd, the segments are copied and concatenated to get an even number of segments.
+pub fn line_dash_segments(segments: &[f64]) -> Option<Vec<f64>> {
+    if segments.iter().any(|s| !s.is_finite() || *s < 0.0) {
+        return None;
+    }
+
+    let mut line_dash = segments.to_vec();
+
+    if line_dash.len() % 2 != 0 {
+        line_dash.extend_from_slice(segments);
+    }
+
+    Some(line_dash)
+}
+
+/// A cluster of shaped glyphs that represents the chars `start..end` (byte indices) of a text,
+/// e.g. a ligature or a base char with its marks.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GlyphCluster {
+    pub start: usize,
+    pub end: usize,
+    pub x: f64,
+    pub width: f64,
+    pub rtl: bool,
+}
+
+/// Maps each char index of the text to the x position of a caret placed before the char. The
+/// last entry is the position after the last char. The chars of a cluster share its width, so a
+/// caret could be placed inside of a ligature.
+pub fn caret_positions(text: &str, clusters: &[GlyphCluster]) -> Vec<f64> {
+    let char_starts: Vec<usize> = text.char_indices().map(|(i, _)| i).collect();
+    let len = char_starts.len();
+    let mut positions = vec![None; len + 1];
+
+    let char_index = |byte_index: usize| match char_starts.binary_search(&byte_index) {
+        Ok(index) | Err(index) => index,
+    };
+
+    for cluster in clusters {
+        let first = char_index(cluster.start);
+        let last = char_index(cluster.end);
+        let count = last.saturating_sub(first);
+
+        for (i, position) in positions[first..last].iter_mut().enumerate() {
+            let offset = cluster.width * i as f64 / count as f64;
+
+            *position = Some(if cluster.rtl {
+                cluster.x + cluster.width - offset
+            } else {
+                cluster.x + offset
+            });
+        }
+
+        if count > 0 && last == len {
+            positions[len] = Some(if cluster.rtl {
+                cluster.x
+            } else {
+                cluster.x + cluster.width
+            });
+        }
+    }
+
+    // chars without glyphs, e.g. control chars, are placed at the position of the previous char
+    let mut previous = 0.0;
+    positions
+        .iter()
+        .map(|position| {
+            if let Some(position) = position {
+                previous = *position;
+            }
+            previous
+        })
+        .collect()
+}
+
+/// Weight, slant and width of a font face, used to select the best face of a family.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct FaceProperties {
+    pub weight: FontWeight,
+    pub style: FontStyle,
+    pub stretch: FontStretch,
+}
+
+// Orders the candidate stretch by the css font matching rules: narrower widths are preferred for
+// condensed and normal requests, wider ones for expanded requests.
+fn stretch_distance(desired: f64, candidate: f64) -> (u8, f64) {
+    if (candidate - desired).abs() < std::f64::EPSILON {
+        (0, 0.0)
+    } else if (desired <= 100.0) == (candidate < desired) {
+        (1, (candidate - desired).abs())
+    } else {
+        (2, (candidate - desired).abs())
+    }
+}
+
+fn style_rank(desired: FontStyle, candidate: FontStyle) -> u8 {
+    let order = match desired {
+        FontStyle::Normal => [FontStyle::Normal, FontStyle::Oblique, FontStyle::Italic],
+        FontStyle::Italic => [FontStyle::Italic, FontStyle::Oblique, FontStyle::Normal],
+        FontStyle::Oblique => [FontStyle::Oblique, FontStyle::Italic, FontStyle::Normal],
+    };
+
+    order.iter().position(|s| *s == candidate).unwrap_or(0) as u8
+}
+
+// Orders the candidate weight by the css font matching rules.
+fn weight_distance(desired: u16, candidate: u16) -> (u8, u16) {
+    let (desired, candidate) = (i32::from(desired), i32::from(candidate));
+    let distance = (candidate - desired).abs() as u16;
+
+    if (400..=500).contains(&desired) {
+        if candidate >= desired && candidate <= 500 {
+            (0, distance)
+        } else if candidate < desired {
+            (1, distance)
+        } else {
+            (2, distance)
+        }
+    } else if desired < 400 {
+        (if candidate <= desired { 0 } else { 1 }, distance)
+    } else {
+        (if candidate >= desired { 0 } else { 1 }, distance)
+    }
+}
+
+/// Selects the face that matches the requested properties best, following the css font matching
+/// algorithm: the nearest stretch is selected first, then the style and at last the weight.
+/// Returns the index of the face or `None` if `faces` is empty.
+pub fn best_face_match(faces: &[FaceProperties], desired: FaceProperties) -> Option<usize> {
+    let key = |face: &FaceProperties| {
+        let (stretch_class, stretch) =
+            stretch_distance(desired.stretch.percentage(), face.stretch.percentage());
+        let style = style_rank(desired.style, face.style);
+        let (weight_class, weight) = weight_distance(desired.weight.value(), face.weight.value());
+
+        (stretch_class, stretch, style, weight_class, weight)
+    };
+
+    faces
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| {
+            key(a)
+                .partial_cmp(&key(b))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+        .map(|(index, _)| index)
+}
+
+/// Limits the corner radius of a rounded rectangle, so that opposite corners do not overlap.
+pub fn rounded_rect_radius(width: f64, height: f64, radius: f64) -> f64 {
+    radius
+        .min(width.abs() / 2.0)
+        .min(height.abs() / 2.0)
+        .max(0.0)
+}
+
+// Sizes of three box blurs whose combination approximates a gaussian blur with the given
+// standard deviation.
+fn gaussian_box_sizes(sigma: f64) -> [usize; 3] {
+    let n = 3.0;
+    let ideal_width = (12.0 * sigma * sigma / n + 1.0).sqrt();
+
+    let mut lower = ideal_width.floor() as usize;
+    if lower % 2 == 0 {
+        lower -= 1;
+    }
+    let upper = lower + 2;
+
+    let l = lower as f64;
+    let ideal_count = (12.0 * sigma * sigma - n * l * l - 4.0 * n * l - 3.0 * n) / (-4.0 * l - 4.0);
+    let count = ideal_count.round().max(0.0) as usize;
+
+    let mut sizes = [upper; 3];
+    for size in sizes.iter_mut().take(count) {
+        *size = lower;
+    }
+    sizes
+}
+
+fn box_blur_horizontal(src: &[f32], dst: &mut [f32], width: usize, height: usize, radius: usize) {
+    let scale = 1.0 / (2 * radius + 1) as f32;
+
+    for y in 0..height {
+        let row = &src[y * width..(y + 1) * width];
+        let mut sum: f32 = row[..radius.min(width)].iter().sum();
+
+        for x in 0..width {
+            if x + radius < width {
+                sum += row[x + radius];
+            }
+            if x > radius {
+                sum -= row[x - radius - 1];
+            }
+            dst[y * width + x] = sum * scale;
+        }
+    }
+}
+
+fn box_blur_vertical(src: &[f32], dst: &mut [f32], width: usize, height: usize, radius: usize) {
+    let scale = 1.0 / (2 * radius + 1) as f32;
+
+    for x in 0..width {
+        let mut sum: f32 = (0..radius.min(height)).map(|y| src[y * width + x]).sum();
+
+        for y in 0..height {
+            if y + radius < height {
+                sum += src[(y + radius) * width + x];
+            }
+            if y > radius {
+                sum -= src[(y - radius - 1) * width + x];
+            }
+            dst[y * width + x] = sum * scale;
+        }
+    }
+}
+
+/// Blurs a single channel image in place. The gaussian blur with the standard deviation `sigma`
+/// is approximated by three box blurs. Pixels outside of the image are treated as zero.
+pub fn gaussian_blur(channel: &mut [f32], width: usize, height: usize, sigma: f64) {
+    if sigma <= 0.0 || width == 0 || height == 0 || channel.len() < width * height {
+        return;
+    }
+
+    let mut buffer = vec![0.0; width * height];
+
+    for size in gaussian_box_sizes(sigma).iter() {
+        let radius = (size - 1) / 2;
+        if radius == 0 {
+            continue;
+        }
+        box_blur_horizontal(channel, &mut buffer, width, height, radius);
+        box_blur_vertical(&buffer, channel, width, height, radius);
+    }
+}
+
+/// Builds the coverage mask (0.0 to 1.0) of a blurred rounded rectangle with the given size and
+/// corner radius, like it is used for box shadows. The `blur` radius is twice the standard
+/// deviation of the gaussian blur. The mask is extended on each side by the returned padding so
+/// the blurred edges are not cut. Returns the mask, its width, its height and the padding.
+pub fn box_shadow_mask(
+    width: f64,
+    height: f64,
+    radius: f64,
+    blur: f64,
+) -> (Vec<f32>, usize, usize, f64) {
+    let blur = blur.max(0.0);
+    let padding = (blur * 1.5).ceil();
+    let mask_width = (width.max(0.0).ceil() + 2.0 * padding) as usize;
+    let mask_height = (height.max(0.0).ceil() + 2.0 * padding) as usize;
+    let half_width = width / 2.0;
+    let half_height = height / 2.0;
+    let radius = radius.max(0.0).min(half_width).min(half_height);
+
+    let mut mask = Vec::with_capacity(mask_width * mask_height);
+
+    for y in 0..mask_height {
+        for x in 0..mask_width {
+            // signed distance of the pixel center to the rounded rectangle
+            let qx = (x as f64 + 0.5 - padding - half_width).abs() - (half_width - radius);
+            let qy = (y as f64 + 0.5 - padding - half_height).abs() - (half_height - radius);
+            let outside = (qx.max(0.0).powi(2) + qy.max(0.0).powi(2)).sqrt();
+            let distance = outside + qx.max(qy).min(0.0) - radius;
+
+            mask.push((0.5 - distance).max(0.0).min(1.0) as f32);
+        }
+    }
+
+    gaussian_blur(&mut mask, mask_width, mask_height, blur / 2.0);
+
+    (mask, mask_width, mask_height, padding)
+}
+
+// Number of box shadows that are kept in the box shadow cache.
+const BOX_SHADOW_CACHE_SIZE: usize = 64;
+
+// Identifies the pixels of a box shadow by the bits of its size, corner radius, blur and spread
+// and its color.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+struct BoxShadowKey {
+    width: u64,
+    height: u64,
+    radius: u64,
+    blur: u64,
+    spread: u64,
+    color: u32,
+}
+
+/// The premultiplied ARGB pixels of a box shadow. They are extended on each side by the padding
+/// so the blurred edges are not cut.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoxShadowPixels {
+    pub data: Vec<u32>,
+    pub width: usize,
+    pub height: usize,
+    pub padding: f64,
+}
+
+/// Cache of box shadow pixels. The shadows of widgets that are drawn on each frame are not built
+/// and blurred again, the least recently used shadow is removed if the cache is full.
+#[derive(Debug, Default)]
+pub struct BoxShadowCache {
+    shadows: HashMap<BoxShadowKey, (Arc<BoxShadowPixels>, usize)>,
+    // increased on each access to find the least recently used shadow
+    clock: usize,
+}
+
+impl BoxShadowCache {
+    /// Creates a new empty box shadow cache.
+    pub fn new() -> Self {
+        BoxShadowCache::default()
+    }
+
+    /// Returns the pixels of the shadow of a box with the given size (without spread) and corner
+    /// radius from the cache or builds them.
+    pub fn get(
+        &mut self,
+        width: f64,
+        height: f64,
+        radius: f64,
+        box_shadow: &BoxShadow,
+    ) -> Arc<BoxShadowPixels> {
+        let key = BoxShadowKey {
+            width: width.to_bits(),
+            height: height.to_bits(),
+            radius: radius.to_bits(),
+            blur: box_shadow.blur.to_bits(),
+            spread: box_shadow.spread.to_bits(),
+            color: box_shadow.color.data,
+        };
+        self.clock += 1;
+
+        if let Some((pixels, used)) = self.shadows.get_mut(&key) {
+            *used = self.clock;
+            return pixels.clone();
+        }
+
+        if self.shadows.len() >= BOX_SHADOW_CACHE_SIZE {
+            if let Some(oldest) = self
+                .shadows
+                .iter()
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(key, _)| *key)
+            {
+                self.shadows.remove(&oldest);
+            }
+        }
+
+        let pixels = Arc::new(box_shadow_pixels(width, height, radius, box_shadow));
+        self.shadows.insert(key, (pixels.clone(), self.clock));
+        pixels
+    }
+}
+
+// Number of images of image brushes that are kept in the brush image cache.
+const BRUSH_IMAGE_CACHE_SIZE: usize = 32;
+
+/// Cache of the images of image brushes by their path. The least recently used image is removed
+/// if the cache is full, paths that could not be loaded are removed the same way and are tried
+/// again when they are used the next time.
+#[derive(Debug)]
+pub struct BrushImageCache<I> {
+    // `None` for paths that could not be loaded
+    images: HashMap<String, (Option<I>, usize)>,
+    // increased on each access to find the least recently used image
+    clock: usize,
+}
+
+impl<I> Default for BrushImageCache<I> {
+    fn default() -> Self {
+        BrushImageCache {
+            images: HashMap::new(),
+            clock: 0,
+        }
+    }
+}
+
+impl<I> BrushImageCache<I> {
+    /// Creates a new empty brush image cache.
+    pub fn new() -> Self {
+        BrushImageCache::default()
+    }
+
+    /// Loads the image with the given path if it is not in the cache, `load` returns `None` if
+    /// the image could not be loaded.
+    pub fn load(&mut self, path: &str, load: impl FnOnce(&str) -> Option<I>) {
+        self.clock += 1;
+
+        if let Some((_, used)) = self.images.get_mut(path) {
+            *used = self.clock;
+            return;
+        }
+
+        if self.images.len() >= BRUSH_IMAGE_CACHE_SIZE {
+            if let Some(oldest) = self
+                .images
+                .iter()
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(path, _)| path.clone())
+            {
+                self.images.remove(&oldest);
+            }
+        }
+
+        self.images
+            .insert(path.to_string(), (load(path), self.clock));
+    }
+
+    /// Returns the image with the given path, `None` if it is not loaded.
+    pub fn get(&self, path: &str) -> Option<&I> {
+        self.images.get(path)?.0.as_ref()
+    }
+}
+
+// Builds the pixels of the shadow of a box with the given size and corner radius, the size and
+// radius are grown by the spread of the shadow.
+fn box_shadow_pixels(
+    width: f64,
+    height: f64,
+    radius: f64,
+    box_shadow: &BoxShadow,
+) -> BoxShadowPixels {
+    let (mask, mask_width, mask_height, padding) = box_shadow_mask(
+        width + 2.0 * box_shadow.spread,
+        height + 2.0 * box_shadow.spread,
+        radius + box_shadow.spread,
+        box_shadow.blur,
+    );
+
+    let color = box_shadow.color;
+    let data = mask
+        .iter()
+        .map(|coverage| {
+            let a = (color.a() as f32 * coverage).round() as u32;
+            let premultiply = |c: u8| (c as u32 * a + 127) / 255;
+            (a << 24)
+                | (premultiply(color.r()) << 16)
+                | (premultiply(color.g()) << 8)
+                | premultiply(color.b())
+        })
+        .collect();
+
+    BoxShadowPixels {
+        data,
+        width: mask_width,
+        height: mask_height,
+        padding,
+    }
+}
+
+/// Returns the pixel region of the rectangle clamped to an image with the given size as
+/// (x, y, width, height).
+pub fn pixel_region(
+    region: Rectangle,
+    width: usize,
+    height: usize,
+) -> (usize, usize, usize, usize) {
+    let left = region.x().floor().max(0.0).min(width as f64) as usize;
+    let top = region.y().floor().max(0.0).min(height as f64) as usize;
+    let right = (region.x() + region.width())
+        .ceil()
+        .max(0.0)
+        .min(width as f64) as usize;
+    let bottom = (region.y() + region.height())
+        .ceil()
+        .max(0.0)
+        .min(height as f64) as usize;
+
+    (
+        left,
+        top,
+        right.saturating_sub(left),
+        bottom.saturating_sub(top),
+    )
+}
+
+/// Blurs the given region of an image with premultiplied ARGB pixels in place. The gaussian blur
+/// has the standard deviation `sigma`, pixels outside of the region are not read or changed.
+pub fn blur_argb(data: &mut [u32], width: usize, height: usize, region: Rectangle, sigma: f64) {
+    let (x, y, region_width, region_height) = pixel_region(region, width, height);
+
+    if region_width == 0 || region_height == 0 || data.len() < width * height {
+        return;
+    }
+
+    let mut channels = vec![vec![0.0; region_width * region_height]; 4];
+
+    for row in 0..region_height {
+        for column in 0..region_width {
+            let pixel = data[(y + row) * width + x + column];
+            for (i, channel) in channels.iter_mut().enumerate() {
+                channel[row * region_width + column] = ((pixel >> (24 - i * 8)) & 0xff) as f32;
+            }
+        }
+    }
+
+    for channel in channels.iter_mut() {
+        gaussian_blur(channel, region_width, region_height, sigma);
+    }
+
+    for row in 0..region_height {
+        for column in 0..region_width {
+            let index = row * region_width + column;
+            let component = |i: usize| channels[i][index].round().max(0.0).min(255.0) as u32;
+            let a = component(0);
+            // premultiplied components could not be greater than alpha
+            data[(y + row) * width + x + column] = (a << 24)
+                | (component(1).min(a) << 16)
+                | (component(2).min(a) << 8)
+                | component(3).min(a);
+        }
+    }
+}
+
+/// Transforms the colors of the given region of an image with premultiplied ARGB pixels in place.
+/// The row major 4x5 color matrix is applied on not premultiplied colors, see `Effect::ColorMatrix`.
+pub fn apply_color_matrix(
+    data: &mut [u32],
+    width: usize,
+    height: usize,
+    region: Rectangle,
+    matrix: &[f64; 20],
+) {
+    let (x, y, region_width, region_height) = pixel_region(region, width, height);
+
+    if data.len() < width * height {
+        return;
+    }
+
+    for row in y..y + region_height {
+        for pixel in data[row * width + x..row * width + x + region_width].iter_mut() {
+            let a = (*pixel >> 24) as f64 / 255.0;
+            let (r, g, b) = if a > 0.0 {
+                (
+                    ((*pixel >> 16) & 0xff) as f64 / 255.0 / a,
+                    ((*pixel >> 8) & 0xff) as f64 / 255.0 / a,
+                    (*pixel & 0xff) as f64 / 255.0 / a,
+                )
+            } else {
+                (0.0, 0.0, 0.0)
+            };
+
+            let component = |row: usize| {
+                let m = &matrix[row * 5..row * 5 + 5];
+                (m[0] * r + m[1] * g + m[2] * b + m[3] * a + m[4])
+                    .max(0.0)
+                    .min(1.0)
+            };
+
+            let new_a = component(3);
+            let premultiply = |c: f64| (c * new_a * 255.0).round() as u32;
+
+            *pixel = ((new_a * 255.0).round() as u32) << 24
+                | premultiply(component(0)) << 16
+                | premultiply(component(1)) << 8
+                | premultiply(component(2));
+        }
+    }
+}
+
+/// Applies the effect on a copy of the pixels inside of the bounds of an image with premultiplied
+/// ARGB pixels, e.g. to blur the backdrop of a widget. A blur reads the pixels around the bounds.
+/// Returns the bounds in whole pixels and the changed pixels, `None` if the bounds are outside of
+/// the image.
+pub fn backdrop_effect(
+    data: &[u32],
+    width: usize,
+    height: usize,
+    effect: Effect,
+    bounds: Rectangle,
+) -> Option<(Rectangle, Vec<u32>)> {
+    let (x, y, bounds_width, bounds_height) = pixel_region(bounds, width, height);
+
+    if bounds_width == 0 || bounds_height == 0 || data.len() < width * height {
+        return None;
+    }
+
+    // the pixels the blur reads
+    let extent = effect.extent();
+    let (area_x, area_y, area_width, area_height) = pixel_region(
+        Rectangle::new(
+            (x as f64 - extent, y as f64 - extent),
+            (
+                bounds_width as f64 + 2.0 * extent,
+                bounds_height as f64 + 2.0 * extent,
+            ),
+        ),
+        width,
+        height,
+    );
+    let mut area = Vec::with_capacity(area_width * area_height);
+
+    for row in area_y..area_y + area_height {
+        area.extend_from_slice(&data[row * width + area_x..row * width + area_x + area_width]);
+    }
+
+    let area_rect = Rectangle::new((0.0, 0.0), (area_width as f64, area_height as f64));
+
+    if let Effect::Blur(radius) = effect {
+        blur_argb(&mut area, area_width, area_height, area_rect, radius / 2.0);
+    }
+
+    if let Some(matrix) = effect.color_matrix() {
+        apply_color_matrix(&mut area, area_width, area_height, area_rect, &matrix);
+    }
+
+    let opacity = effect.opacity();
+    let (left, top) = (x - area_x, y - area_y);
+    let mut pixels = Vec::with_capacity(bounds_width * bounds_height);
+
+    for row in top..top + bounds_height {
+        for pixel in &area[row * area_width + left..row * area_width + left + bounds_width] {
+            pixels.push(if opacity < 1.0 {
+                let [a, r, g, b] = pixel.to_be_bytes();
+                let scale = |c: u8| (c as f64 * opacity).round() as u8;
+                u32::from_be_bytes([scale(a), scale(r), scale(g), scale(b)])
+            } else {
+                *pixel
+            });
+        }
+    }
+
+    Some((
+        Rectangle::new(
+            (x as f64, y as f64),
+            (bounds_width as f64, bounds_height as f64),
+        ),
+        pixels,
+    ))
+}
+
 #[cfg(test)]
 mod tests {
-    use super::PathRect;
+    use super::{
+        apply_color_matrix, backdrop_effect, best_face_match, blur_argb, box_shadow_mask,
+        build_gradient_stops, caret_positions, gaussian_blur, line_dash_segments, mix_linear_rgb,
+        rounded_rect_radius, spread_gradient_stops, BoxShadowCache, BrushImageCache,
+        FaceProperties, GlyphCluster, PathRect, BRUSH_IMAGE_CACHE_SIZE,
+    };
+    use crate::utils::{
+        BoxShadow, Color, ColorInterpolation, Effect, FontStretch, FontStyle, FontWeight,
+        GradientStop, Rectangle, SpreadMethod,
+    };
+
+    #[test]
+    fn test_line_dash_segments() {
+        assert_eq!(line_dash_segments(&[]), Some(vec![]));
+        assert_eq!(line_dash_segments(&[4.0, 2.0]), Some(vec![4.0, 2.0]));
+        assert_eq!(
+            line_dash_segments(&[5.0, 15.0, 25.0]),
+            Some(vec![5.0, 15.0, 25.0, 5.0, 15.0, 25.0])
+        );
+        assert_eq!(line_dash_segments(&[4.0, -2.0]), None);
+        assert_eq!(line_dash_segments(&[4.0, std::f64::NAN]), None);
+        assert_eq!(line_dash_segments(&[std::f64::INFINITY]), None);
+    }
+
+    #[test]
+    fn test_mix_linear_rgb() {
+        // the middle of black and white is brighter than the sRGB middle
+        let color = mix_linear_rgb(Color::rgb(0, 0, 0), Color::rgb(255, 255, 255), 0.5);
+        assert_eq!(color, Color::rgb(188, 188, 188));
+
+        // transparent stops do not darken the mix
+        let color = mix_linear_rgb(Color::rgba(0, 0, 0, 0), Color::rgb(255, 0, 0), 0.5);
+        assert_eq!(color, Color::rgba(255, 0, 0, 128));
+
+        assert_eq!(
+            mix_linear_rgb(Color::rgb(255, 0, 0), Color::rgb(0, 0, 255), 1.0),
+            Color::rgb(0, 0, 255)
+        );
+    }
+
+    #[test]
+    fn test_build_gradient_stops() {
+        let stops = [
+            GradientStop {
+                pos: None,
+                color: Color::rgb(0, 0, 0),
+            },
+            GradientStop {
+                pos: None,
+                color: Color::rgb(255, 255, 255),
+            },
+        ];
+
+        let srgb = build_gradient_stops(&stops, 100.0, ColorInterpolation::Srgb, |p, c| (p, c));
+        assert_eq!(
+            srgb,
+            vec![(0.0, Color::rgb(0, 0, 0)), (1.0, Color::rgb(255, 255, 255))]
+        );
+
+        let linear =
+            build_gradient_stops(&stops, 100.0, ColorInterpolation::LinearRgb, |p, c| (p, c));
+        assert_eq!(linear.len(), 9);
+        assert_eq!(linear[0], srgb[0]);
+        assert_eq!(linear[4], (0.5, Color::rgb(188, 188, 188)));
+        assert_eq!(linear[8], srgb[1]);
+    }
+
+    #[test]
+    fn test_backdrop_effect() {
+        // a white column between black ones
+        let mut data = vec![0xFF00_0000; 5 * 3];
+        for row in 0..3 {
+            data[row * 5 + 2] = 0xFFFF_FFFF;
+        }
+
+        let (region, pixels) = backdrop_effect(
+            &data,
+            5,
+            3,
+            Effect::Blur(2.0),
+            Rectangle::new((1.5, 0.0), (1.0, 3.0)),
+        )
+        .unwrap();
+        assert_eq!(region, Rectangle::new((1.0, 0.0), (2.0, 3.0)));
+        assert_eq!(pixels.len(), 6);
+
+        // the blur reads the pixels around the bounds, the image is not changed
+        assert!(pixels[0] & 0xFF > 0 && pixels[1] & 0xFF < 0xFF);
+        assert_eq!(data[2], 0xFFFF_FFFF);
+
+        let (_, pixels) = backdrop_effect(
+            &data,
+            5,
+            3,
+            Effect::Opacity(0.5),
+            Rectangle::new((2.0, 0.0), (1.0, 1.0)),
+        )
+        .unwrap();
+        assert_eq!(pixels, vec![0x8080_8080]);
+
+        assert!(backdrop_effect(
+            &data,
+            5,
+            3,
+            Effect::Blur(2.0),
+            Rectangle::new((6.0, 0.0), (1.0, 1.0))
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_spread_gradient_stops() {
+        let black = Color::rgb(0, 0, 0);
+        let white = Color::rgb(255, 255, 255);
+        let stops = [(0.0, black), (0.5, white)];
+
+        assert_eq!(
+            spread_gradient_stops(&stops, SpreadMethod::Pad, -1, 2),
+            stops.to_vec()
+        );
+        assert_eq!(
+            spread_gradient_stops(&stops, SpreadMethod::Repeat, -1, 2),
+            vec![(0.0, black), (0.25, white), (0.5, black), (0.75, white)]
+        );
+
+        // the period -1 is mirrored
+        assert_eq!(
+            spread_gradient_stops(&stops, SpreadMethod::Reflect, -1, 2),
+            vec![(0.25, white), (0.5, black), (0.5, black), (0.75, white)]
+        );
+    }
+
+    fn cluster(start: usize, end: usize, x: f64, width: f64, rtl: bool) -> GlyphCluster {
+        GlyphCluster {
+            start,
+            end,
+            x,
+            width,
+            rtl,
+        }
+    }
+
+    #[test]
+    fn test_caret_positions() {
+        assert_eq!(caret_positions("", &[]), vec![0.0]);
+
+        let clusters = [
+            cluster(0, 1, 0.0, 4.0, false),
+            cluster(1, 2, 4.0, 6.0, false),
+        ];
+        assert_eq!(caret_positions("ab", &clusters), vec![0.0, 4.0, 10.0]);
+
+        // ligature of three chars
+        let clusters = [cluster(0, 3, 0.0, 9.0, false)];
+        assert_eq!(caret_positions("ffi", &clusters), vec![0.0, 3.0, 6.0, 9.0]);
+
+        // right to left, the glyph of the last char is painted first
+        let text = "\u{5d0}\u{5d1}";
+        let clusters = [cluster(2, 4, 0.0, 5.0, true), cluster(0, 2, 5.0, 5.0, true)];
+        assert_eq!(caret_positions(text, &clusters), vec![10.0, 5.0, 0.0]);
+
+        // mixed, "a" followed by a right to left char
+        let text = "a\u{5d0}";
+        let clusters = [
+            cluster(0, 1, 0.0, 4.0, false),
+            cluster(1, 3, 4.0, 6.0, true),
+        ];
+        assert_eq!(caret_positions(text, &clusters), vec![0.0, 10.0, 4.0]);
+    }
+
+    #[test]
+    fn test_rounded_rect_radius() {
+        assert_eq!(rounded_rect_radius(100.0, 50.0, 8.0), 8.0);
+        assert_eq!(rounded_rect_radius(100.0, 50.0, 40.0), 25.0);
+        assert_eq!(rounded_rect_radius(10.0, 50.0, 40.0), 5.0);
+        assert_eq!(rounded_rect_radius(100.0, 50.0, -2.0), 0.0);
+    }
+
+    fn face(weight: u16, style: FontStyle, stretch: FontStretch) -> FaceProperties {
+        FaceProperties {
+            weight: FontWeight(weight),
+            style,
+            stretch,
+        }
+    }
+
+    #[test]
+    fn test_best_face_match() {
+        let faces = vec![
+            face(300, FontStyle::Normal, FontStretch::Normal),
+            face(400, FontStyle::Normal, FontStretch::Normal),
+            face(700, FontStyle::Normal, FontStretch::Normal),
+            face(400, FontStyle::Italic, FontStretch::Normal),
+            face(400, FontStyle::Normal, FontStretch::Condensed),
+        ];
+
+        assert_eq!(
+            best_face_match(&[], face(400, FontStyle::Normal, FontStretch::Normal)),
+            None
+        );
+        assert_eq!(
+            best_face_match(&faces, face(400, FontStyle::Normal, FontStretch::Normal)),
+            Some(1)
+        );
+
+        // 400 - 500 prefers heavier weights up to 500, then lighter ones
+        assert_eq!(
+            best_face_match(&faces, face(500, FontStyle::Normal, FontStretch::Normal)),
+            Some(1)
+        );
+        assert_eq!(
+            best_face_match(&faces, face(600, FontStyle::Normal, FontStretch::Normal)),
+            Some(2)
+        );
+        assert_eq!(
+            best_face_match(&faces, face(900, FontStyle::Normal, FontStretch::Normal)),
+            Some(2)
+        );
+        assert_eq!(
+            best_face_match(&faces, face(350, FontStyle::Normal, FontStretch::Normal)),
+            Some(0)
+        );
+        assert_eq!(
+            best_face_match(&faces, face(100, FontStyle::Normal, FontStretch::Normal)),
+            Some(0)
+        );
+
+        // oblique falls back to italic
+        assert_eq!(
+            best_face_match(&faces, face(700, FontStyle::Oblique, FontStretch::Normal)),
+            Some(3)
+        );
+
+        // stretch is matched before style and weight
+        assert_eq!(
+            best_face_match(
+                &faces,
+                face(700, FontStyle::Italic, FontStretch::UltraCondensed)
+            ),
+            Some(4)
+        );
+        assert_eq!(
+            best_face_match(&faces, face(400, FontStyle::Normal, FontStretch::Expanded)),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn test_gaussian_blur() {
+        let mut channel = vec![0.0; 21 * 21];
+        channel[10 * 21 + 10] = 1.0;
+        gaussian_blur(&mut channel, 21, 21, 2.0);
+
+        let sum: f32 = channel.iter().sum();
+        assert!((sum - 1.0).abs() < 0.001);
+        assert!(channel[10 * 21 + 10] > channel[10 * 21 + 12]);
+        assert!((channel[10 * 21 + 8] - channel[10 * 21 + 12]).abs() < 0.0001);
+        assert!((channel[8 * 21 + 10] - channel[12 * 21 + 10]).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_blur_argb() {
+        let mut data = vec![0; 9 * 9];
+        data[4 * 9 + 4] = 0xff_ff_00_00;
+        data[0] = 0xff_00_00_ff;
+        blur_argb(&mut data, 9, 9, Rectangle::new((1.0, 1.0), (7.0, 7.0)), 2.0);
+
+        // outside of the region
+        assert_eq!(data[0], 0xff_00_00_ff);
+        assert_eq!(data[1], 0);
+
+        let center = data[4 * 9 + 4];
+        let neighbor = data[4 * 9 + 5];
+        assert!(center >> 24 < 0xff && center >> 24 > neighbor >> 24);
+        assert!(neighbor >> 24 > 0);
+        // pixels stay red
+        assert_eq!(neighbor & 0xff, 0);
+        assert_eq!((neighbor >> 16) & 0xff, neighbor >> 24);
+    }
+
+    #[test]
+    fn test_apply_color_matrix() {
+        let mut data = vec![0xff_ff_00_00, 0x80_80_00_00, 0];
+        let matrix = Effect::Grayscale(1.0).color_matrix().unwrap();
+        apply_color_matrix(
+            &mut data,
+            3,
+            1,
+            Rectangle::new((0.0, 0.0), (2.0, 1.0)),
+            &matrix,
+        );
+
+        // red is converted to a gray of the same luminance
+        assert_eq!(data[0], 0xff_36_36_36);
+        assert_eq!(data[1] >> 24, 0x80);
+        assert_eq!((data[1] >> 16) & 0xff, data[1] & 0xff);
+        assert_eq!(data[2], 0);
+    }
+
+    #[test]
+    fn test_box_shadow_mask() {
+        let (mask, width, height, padding) = box_shadow_mask(10.0, 10.0, 0.0, 0.0);
+        assert_eq!((width, height), (10, 10));
+        assert!(padding.abs() < f64::EPSILON);
+        assert!(mask.iter().all(|m| (m - 1.0).abs() < f32::EPSILON));
+
+        let (mask, width, height, padding) = box_shadow_mask(10.0, 10.0, 5.0, 0.0);
+        assert_eq!((width, height), (10, 10));
+        assert!(padding.abs() < f64::EPSILON);
+        assert!(mask[0] < 0.01);
+        assert!((mask[5 * 10 + 5] - 1.0).abs() < f32::EPSILON);
+
+        let (mask, width, height, padding) = box_shadow_mask(10.0, 10.0, 0.0, 4.0);
+        assert_eq!((width, height), (22, 22));
+        assert!((padding - 6.0).abs() < f64::EPSILON);
+        let sum: f32 = mask.iter().sum();
+        assert!((sum - 100.0).abs() < 1.0);
+        assert!(mask[0] < mask[11 * 22 + 11]);
+    }
+
+    #[test]
+    fn test_box_shadow_cache() {
+        let mut cache = BoxShadowCache::new();
+        let box_shadow = BoxShadow::new((0.0, 2.0), 4.0, 1.0, Color::rgba(0, 0, 0, 128));
+
+        let pixels = cache.get(10.0, 10.0, 2.0, &box_shadow);
+        assert_eq!((pixels.width, pixels.height), (24, 24));
+        assert!((pixels.padding - 6.0).abs() < f64::EPSILON);
+        assert_eq!(pixels.data[12 * 24 + 12], 0x8000_0000);
+
+        // the same shadow is not built again
+        assert!(std::sync::Arc::ptr_eq(
+            &pixels,
+            &cache.get(10.0, 10.0, 2.0, &box_shadow)
+        ));
+        assert_eq!(cache.shadows.len(), 1);
+
+        cache.get(
+            10.0,
+            10.0,
+            2.0,
+            &BoxShadow::new((0.0, 2.0), 4.0, 1.0, "#000000"),
+        );
+        assert_eq!(cache.shadows.len(), 2);
+
+        for size in 0..100 {
+            cache.get(size as f64, 10.0, 2.0, &box_shadow);
+        }
+        assert!(cache.shadows.len() <= 64);
+    }
+
+    #[test]
+    fn test_brush_image_cache() {
+        let mut cache = BrushImageCache::new();
+        let mut loads = 0;
+
+        cache.load("image.png", |_| {
+            loads += 1;
+            Some(1)
+        });
+        cache.load("image.png", |_| {
+            loads += 1;
+            Some(2)
+        });
+        assert_eq!(loads, 1);
+        assert_eq!(cache.get("image.png"), Some(&1));
+
+        // failed paths are cached without an image
+        cache.load("missing.png", |_| None);
+        assert_eq!(cache.get("missing.png"), None);
+        assert_eq!(cache.images.len(), 2);
+
+        for i in 0..100 {
+            cache.load(&i.to_string(), |_| Some(i));
+        }
+        assert_eq!(cache.images.len(), BRUSH_IMAGE_CACHE_SIZE);
+        assert_eq!(cache.get("image.png"), None);
+        assert_eq!(cache.get("99"), Some(&99));
+    }
 
     #[test]
     fn test_pathrect_lines() {
@@ -1,13 +1,22 @@
 use std::{
+    path::{Path, PathBuf},
     sync::{mpsc, Arc, Mutex},
     thread,
 };
 
-use crate::{platform, utils::*, PipelineTrait, RenderTarget, TextMetrics};
+use crate::{
+    platform, utils::*, DisplayList, GlyphMetrics, PipelineTrait, RenderTarget, TextMetrics,
+};
 use platform::Image;
 
+use self::tiles::TilePool;
+pub use self::tiles::Tiling;
+
+mod tiles;
+
+// The pipeline is shared by the threads that draw the tiles of a frame.
 #[derive(Clone)]
-struct PipelineWrapper(pub Box<dyn PipelineTrait>);
+struct PipelineWrapper(Arc<Mutex<Box<dyn PipelineTrait>>>);
 
 impl PartialEq for PipelineWrapper {
     fn eq(&self, _: &Self) -> bool {
@@ -29,6 +38,16 @@ enum RenderTask {
         family: String,
         font_file: &'static [u8],
     },
+    RegisterFontData {
+        family: String,
+        data: Vec<u8>,
+    },
+    RegisterFontFile {
+        family: String,
+        path: PathBuf,
+    },
+    RegisterSystemFonts(),
+    SetTiling(Option<Tiling>),
 
     // Multi tasks
     FillRect {
@@ -43,6 +62,14 @@ enum RenderTask {
         width: f64,
         height: f64,
     },
+    DrawBoxShadow {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        border_radius: f64,
+        box_shadow: BoxShadow,
+    },
     FillText {
         text: String,
         x: f64,
@@ -58,6 +85,13 @@ enum RenderTask {
         width: f64,
         height: f64,
     },
+    RoundedRectangle {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        radius: f64,
+    },
     Arc {
         x: f64,
         y: f64,
@@ -103,6 +137,21 @@ enum RenderTask {
         x: f64,
         y: f64,
     },
+    DrawImageWithSize {
+        image: Image,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    },
+    DrawImageWithClipAndSize {
+        image: Image,
+        clip: Rectangle,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    },
     DrawPipeline {
         x: f64,
         y: f64,
@@ -114,15 +163,56 @@ enum RenderTask {
     SetLineWidth {
         line_width: f64,
     },
+    SetLineCap {
+        line_cap: LineCap,
+    },
+    SetLineJoin {
+        line_join: LineJoin,
+    },
+    SetMiterLimit {
+        miter_limit: f64,
+    },
+    SetLineDash {
+        line_dash: Vec<f64>,
+    },
+    SetLineDashOffset {
+        line_dash_offset: f64,
+    },
     SetAlpha {
         alpha: f32,
     },
+    PushLayer {
+        opacity: f32,
+        clip: Option<Rectangle>,
+    },
+    PopLayer(),
+    PushEffect {
+        effect: Effect,
+        bounds: Rectangle,
+    },
+    PopEffect(),
+    BackdropEffect {
+        effect: Effect,
+        bounds: Rectangle,
+    },
     SetFontFamily {
         family: String,
     },
     SetFontSize {
         size: f64,
     },
+    SetFontWeight {
+        weight: FontWeight,
+    },
+    SetFontStyle {
+        style: FontStyle,
+    },
+    SetFontStretch {
+        stretch: FontStretch,
+    },
+    SetTextBaseline {
+        text_baseline: TextBaseline,
+    },
     SetFillStyle {
         fill_style: Brush,
     },
@@ -142,6 +232,12 @@ enum RenderTask {
         h_moving: f64,
         v_moving: f64,
     },
+    StartRegion {
+        region: Rectangle,
+    },
+    FinishRegion(),
+    StartRecording(),
+    FinishRecording(),
     Finish(),
     Terminate(),
 }
@@ -149,6 +245,7 @@ enum RenderTask {
 // Used to send results to the main thread.
 enum RenderResult {
     Finish { data: Vec<u32> },
+    DisplayList(DisplayList),
 }
 
 // Wrapper for the render thread.
@@ -156,19 +253,295 @@ struct RenderWorker {
     render_thread: Option<thread::JoinHandle<()>>,
 }
 
-fn is_single_tasks(task: &RenderTask) -> bool {
+// Tasks that are registered on the tile threads when tiling is enabled.
+fn is_setup_task(task: &RenderTask) -> bool {
+    matches!(
+        task,
+        RenderTask::SetBackground(_)
+            | RenderTask::RegisterFont { .. }
+            | RenderTask::RegisterFontData { .. }
+            | RenderTask::RegisterFontFile { .. }
+            | RenderTask::RegisterSystemFonts()
+    )
+}
+
+fn move_rect(rect: Rectangle, offset: Point) -> Rectangle {
+    Rectangle::new((rect.x() + offset.x(), rect.y() + offset.y()), rect.size())
+}
+
+// Executes the task on the given render context. All coordinates are moved by the offset, it is
+// used to draw a tile of the frame.
+fn execute(render_context_2_d: &mut platform::RenderContext2D, task: &RenderTask, offset: Point) {
+    let (ox, oy) = (offset.x(), offset.y());
+
     match task {
-        RenderTask::Start() => true,
-        RenderTask::SetBackground(_) => true,
-        RenderTask::Resize { .. } => true,
-        RenderTask::RegisterFont { .. } => true,
-        RenderTask::DrawRenderTarget { .. } => true,
-        RenderTask::DrawImage { .. } => true,
-        RenderTask::DrawImageWithClip { .. } => true,
-        RenderTask::DrawPipeline { .. } => true,
-        RenderTask::SetTransform { .. } => true,
-        RenderTask::Terminate { .. } => true,
-        _ => false,
+        RenderTask::Start() => {
+            render_context_2_d.start();
+        }
+        RenderTask::SetBackground(background) => {
+            render_context_2_d.set_background(*background);
+        }
+        RenderTask::RegisterFont { family, font_file } => {
+            render_context_2_d.register_font(family.as_str(), font_file);
+        }
+        RenderTask::RegisterFontData { family, data } => {
+            // errors are already reported by the measure context
+            let _ = render_context_2_d.register_font_data(family.as_str(), data.clone());
+        }
+        RenderTask::RegisterFontFile { family, path } => {
+            let _ = render_context_2_d.register_font_file(family.as_str(), path);
+        }
+        RenderTask::RegisterSystemFonts() => {
+            render_context_2_d.register_system_fonts();
+        }
+        RenderTask::FillRect {
+            x,
+            y,
+            width,
+            height,
+        } => {
+            render_context_2_d.fill_rect(x + ox, y + oy, *width, *height);
+        }
+        RenderTask::StrokeRect {
+            x,
+            y,
+            width,
+            height,
+        } => {
+            render_context_2_d.stroke_rect(x + ox, y + oy, *width, *height);
+        }
+        RenderTask::DrawBoxShadow {
+            x,
+            y,
+            width,
+            height,
+            border_radius,
+            box_shadow,
+        } => {
+            render_context_2_d.draw_box_shadow(
+                x + ox,
+                y + oy,
+                *width,
+                *height,
+                *border_radius,
+                box_shadow,
+            );
+        }
+        RenderTask::FillText { text, x, y } => {
+            render_context_2_d.fill_text(text.as_str(), x + ox, y + oy);
+        }
+        RenderTask::Fill() => {
+            render_context_2_d.fill();
+        }
+        RenderTask::Stroke() => {
+            render_context_2_d.stroke();
+        }
+        RenderTask::BeginPath() => {
+            render_context_2_d.begin_path();
+        }
+        RenderTask::ClosePath() => {
+            render_context_2_d.close_path();
+        }
+        RenderTask::Rectangle {
+            x,
+            y,
+            width,
+            height,
+        } => {
+            render_context_2_d.rect(x + ox, y + oy, *width, *height);
+        }
+        RenderTask::RoundedRectangle {
+            x,
+            y,
+            width,
+            height,
+            radius,
+        } => {
+            render_context_2_d.rounded_rect(x + ox, y + oy, *width, *height, *radius);
+        }
+        RenderTask::Arc {
+            x,
+            y,
+            radius,
+            start_angle,
+            end_angle,
+        } => {
+            render_context_2_d.arc(x + ox, y + oy, *radius, *start_angle, *end_angle);
+        }
+        RenderTask::MoveTo { x, y } => {
+            render_context_2_d.move_to(x + ox, y + oy);
+        }
+        RenderTask::LineTo { x, y } => {
+            render_context_2_d.line_to(x + ox, y + oy);
+        }
+        RenderTask::QuadraticCurveTo { cpx, cpy, x, y } => {
+            render_context_2_d.quadratic_curve_to(cpx + ox, cpy + oy, x + ox, y + oy);
+        }
+        RenderTask::BesierCurveTo {
+            cp1x,
+            cp1y,
+            cp2x,
+            cp2y,
+            x,
+            y,
+        } => {
+            render_context_2_d.bezier_curve_to(
+                cp1x + ox,
+                cp1y + oy,
+                cp2x + ox,
+                cp2y + oy,
+                x + ox,
+                y + oy,
+            );
+        }
+        RenderTask::DrawRenderTarget {
+            render_target,
+            x,
+            y,
+        } => {
+            render_context_2_d.draw_render_target(render_target, x + ox, y + oy);
+        }
+        RenderTask::DrawImage { image, x, y } => {
+            render_context_2_d.draw_image(image, x + ox, y + oy);
+        }
+        RenderTask::DrawImageWithClip { image, clip, x, y } => {
+            render_context_2_d.draw_image_with_clip(image, *clip, x + ox, y + oy);
+        }
+        RenderTask::DrawImageWithSize {
+            image,
+            x,
+            y,
+            width,
+            height,
+        } => {
+            render_context_2_d.draw_image_with_size(image, x + ox, y + oy, *width, *height);
+        }
+        RenderTask::DrawImageWithClipAndSize {
+            image,
+            clip,
+            x,
+            y,
+            width,
+            height,
+        } => {
+            render_context_2_d.draw_image_with_clip_and_size(
+                image,
+                *clip,
+                x + ox,
+                y + oy,
+                *width,
+                *height,
+            );
+        }
+        RenderTask::DrawPipeline {
+            x,
+            y,
+            width,
+            height,
+            pipeline,
+        } => {
+            let pipeline = pipeline.0.lock().unwrap().clone_box();
+            render_context_2_d.draw_pipeline(x + ox, y + oy, *width, *height, pipeline);
+        }
+        RenderTask::SetLineWidth { line_width } => {
+            render_context_2_d.set_line_width(*line_width);
+        }
+        RenderTask::SetLineCap { line_cap } => {
+            render_context_2_d.set_line_cap(*line_cap);
+        }
+        RenderTask::SetLineJoin { line_join } => {
+            render_context_2_d.set_line_join(*line_join);
+        }
+        RenderTask::SetMiterLimit { miter_limit } => {
+            render_context_2_d.set_miter_limit(*miter_limit);
+        }
+        RenderTask::SetLineDash { line_dash } => {
+            render_context_2_d.set_line_dash(line_dash);
+        }
+        RenderTask::SetLineDashOffset { line_dash_offset } => {
+            render_context_2_d.set_line_dash_offset(*line_dash_offset);
+        }
+        RenderTask::SetAlpha { alpha } => {
+            render_context_2_d.set_alpha(*alpha);
+        }
+        RenderTask::PushLayer { opacity, clip } => {
+            render_context_2_d.push_layer(*opacity, clip.map(|clip| move_rect(clip, offset)));
+        }
+        RenderTask::PopLayer() => {
+            render_context_2_d.pop_layer();
+        }
+        RenderTask::PushEffect { effect, bounds } => {
+            render_context_2_d.push_effect(*effect, move_rect(*bounds, offset));
+        }
+        RenderTask::PopEffect() => {
+            render_context_2_d.pop_effect();
+        }
+        RenderTask::BackdropEffect { effect, bounds } => {
+            render_context_2_d.apply_backdrop_effect(*effect, move_rect(*bounds, offset));
+        }
+        RenderTask::Clip() => {
+            render_context_2_d.clip();
+        }
+        RenderTask::SetFontFamily { family } => {
+            render_context_2_d.set_font_family(family.as_str());
+        }
+        RenderTask::SetFontSize { size } => {
+            render_context_2_d.set_font_size(*size);
+        }
+        RenderTask::SetFontWeight { weight } => {
+            render_context_2_d.set_font_weight(*weight);
+        }
+        RenderTask::SetFontStyle { style } => {
+            render_context_2_d.set_font_style(*style);
+        }
+        RenderTask::SetFontStretch { stretch } => {
+            render_context_2_d.set_font_stretch(*stretch);
+        }
+        RenderTask::SetTextBaseline { text_baseline } => {
+            render_context_2_d.set_text_baseline(*text_baseline);
+        }
+        RenderTask::SetFillStyle { fill_style } => {
+            render_context_2_d.set_fill_style(fill_style.clone());
+        }
+        RenderTask::SetStrokeStyle { stroke_style } => {
+            render_context_2_d.set_stroke_style(stroke_style.clone());
+        }
+        RenderTask::Save() => {
+            render_context_2_d.save();
+        }
+        RenderTask::Restore() => {
+            render_context_2_d.restore();
+        }
+        RenderTask::Clear { brush } => {
+            render_context_2_d.clear(brush);
+        }
+        RenderTask::SetTransform {
+            h_scaling,
+            h_skewing,
+            v_skewing,
+            v_scaling,
+            h_moving,
+            v_moving,
+        } => {
+            // the coordinates are moved before they are transformed, the offset is removed before
+            // and added after the transformation
+            let matrix = Matrix::translation(-ox, -oy)
+                .then(&Matrix::new(
+                    *h_scaling, *h_skewing, *v_skewing, *v_scaling, *h_moving, *v_moving,
+                ))
+                .then(&Matrix::translation(ox, oy));
+
+            render_context_2_d.set_transform(
+                matrix.m11, matrix.m12, matrix.m21, matrix.m22, matrix.m31, matrix.m32,
+            );
+        }
+        RenderTask::StartRegion { region } => {
+            render_context_2_d.start_region(move_rect(*region, offset));
+        }
+        RenderTask::FinishRegion() => {
+            render_context_2_d.finish_region();
+        }
+        _ => {}
     }
 }
 
@@ -181,190 +554,93 @@ impl RenderWorker {
         sender: Arc<Mutex<mpsc::Sender<RenderResult>>>,
     ) -> Self {
         let render_thread = thread::spawn(move || {
-            let mut tasks_collection = vec![];
-
             let mut render_context_2_d = platform::RenderContext2D::new(width, height);
+            let mut size = (width, height);
+
+            // fonts and background that are set up on new tile threads
+            let mut setup_tasks = vec![];
+            let mut tile_pool: Option<TilePool> = None;
+
+            // tasks of the current frame that are drawn in tiles when the frame is finished
+            let mut frame = vec![];
+            // frames with a recording are drawn without tiles
+            let mut recording = false;
 
             loop {
-                let mut tasks = receiver.lock().unwrap().recv().unwrap();
-
-                // single tasks
-                if tasks.len() == 1 && is_single_tasks(tasks.get(0).unwrap()) {
-                    match tasks.remove(0) {
-                        RenderTask::Start() => {
-                            tasks_collection.clear();
-                            render_context_2_d.start();
-                            continue;
+                let tasks = receiver.lock().unwrap().recv().unwrap();
+
+                for task in tasks {
+                    if is_setup_task(&task) {
+                        if let Some(tile_pool) = &tile_pool {
+                            tile_pool.broadcast(&task);
                         }
-                        RenderTask::SetBackground(background) => {
-                            render_context_2_d.set_background(background);
-                            continue;
+                        execute(&mut render_context_2_d, &task, Point::default());
+                        setup_tasks.push(task);
+                        continue;
+                    }
+
+                    match task {
+                        RenderTask::SetTiling(tiling) => {
+                            if tile_pool.as_ref().map(|tile_pool| tile_pool.tiling()) != tiling {
+                                tile_pool =
+                                    tiling.map(|tiling| TilePool::new(tiling, &setup_tasks));
+                            }
                         }
                         RenderTask::Resize { width, height } => {
+                            size = (width, height);
                             render_context_2_d.resize(width, height);
-                            continue;
-                        }
-                        RenderTask::RegisterFont { family, font_file } => {
-                            render_context_2_d.register_font(family.as_str(), font_file);
-                            continue;
                         }
-                        RenderTask::DrawRenderTarget {
-                            render_target,
-                            x,
-                            y,
-                        } => {
-                            render_context_2_d.draw_render_target(&render_target, x, y);
-                        }
-                        RenderTask::DrawImage { image, x, y } => {
-                            render_context_2_d.draw_image(&image, x, y);
-                        }
-                        RenderTask::DrawImageWithClip { image, clip, x, y } => {
-                            render_context_2_d.draw_image_with_clip(&image, clip, x, y);
+                        RenderTask::StartRecording() => {
+                            for task in frame.drain(..) {
+                                execute(&mut render_context_2_d, &task, Point::default());
+                            }
+                            recording = true;
+                            render_context_2_d.start_recording();
                         }
-                        RenderTask::DrawPipeline {
-                            x,
-                            y,
-                            width,
-                            height,
-                            pipeline,
-                        } => {
-                            render_context_2_d.draw_pipeline(x, y, width, height, pipeline.0);
+                        RenderTask::FinishRecording() => {
+                            sender
+                                .lock()
+                                .unwrap()
+                                .send(RenderResult::DisplayList(
+                                    render_context_2_d.finish_recording(),
+                                ))
+                                .expect("Could not send display list to main thread.");
                         }
-                        RenderTask::SetTransform {
-                            h_scaling,
-                            h_skewing,
-                            v_skewing,
-                            v_scaling,
-                            h_moving,
-                            v_moving,
-                        } => {
-                            render_context_2_d.set_transform(
-                                h_scaling, h_skewing, v_skewing, v_scaling, h_moving, v_moving,
-                            );
+                        RenderTask::Finish() => {
+                            if let Some(tile_pool) = &tile_pool {
+                                tile_pool.render(
+                                    &mut render_context_2_d,
+                                    size.0 as u32,
+                                    size.1 as u32,
+                                    std::mem::take(&mut frame),
+                                );
+                            }
+                            recording = false;
+
+                            render_context_2_d.finish();
+                            // the whole frame is sent to the main thread
+                            render_context_2_d.take_damaged_regions();
+                            sender
+                                .lock()
+                                .unwrap()
+                                .send(RenderResult::Finish {
+                                    data: render_context_2_d.data().to_vec(),
+                                })
+                                .expect("Could not send render result to main thread.");
+                            finish_sender
+                                .send(true)
+                                .expect("Could not send render result to main thread.");
                         }
                         RenderTask::Terminate() => {
                             return;
                         }
-                        _ => {}
-                    };
-                }
-
-                tasks_collection.push(tasks);
-
-                if !tasks_collection.is_empty() {
-                    for task in tasks_collection.remove(0) {
-                        match task {
-                            RenderTask::FillRect {
-                                x,
-                                y,
-                                width,
-                                height,
-                            } => {
-                                render_context_2_d.fill_rect(x, y, width, height);
-                            }
-                            RenderTask::StrokeRect {
-                                x,
-                                y,
-                                width,
-                                height,
-                            } => {
-                                render_context_2_d.stroke_rect(x, y, width, height);
-                            }
-                            RenderTask::FillText { text, x, y } => {
-                                render_context_2_d.fill_text(text.as_str(), x, y);
-                            }
-                            RenderTask::Fill() => {
-                                render_context_2_d.fill();
-                            }
-                            RenderTask::Stroke() => {
-                                render_context_2_d.stroke();
-                            }
-                            RenderTask::BeginPath() => {
-                                render_context_2_d.begin_path();
-                            }
-                            RenderTask::ClosePath() => {
-                                render_context_2_d.close_path();
-                            }
-                            RenderTask::Rectangle {
-                                x,
-                                y,
-                                width,
-                                height,
-                            } => {
-                                render_context_2_d.rect(x, y, width, height);
-                            }
-                            RenderTask::Arc {
-                                x,
-                                y,
-                                radius,
-                                start_angle,
-                                end_angle,
-                            } => {
-                                render_context_2_d.arc(x, y, radius, start_angle, end_angle);
-                            }
-                            RenderTask::MoveTo { x, y } => {
-                                render_context_2_d.move_to(x, y);
-                            }
-                            RenderTask::LineTo { x, y } => {
-                                render_context_2_d.line_to(x, y);
-                            }
-                            RenderTask::QuadraticCurveTo { cpx, cpy, x, y } => {
-                                render_context_2_d.quadratic_curve_to(cpx, cpy, x, y);
-                            }
-                            RenderTask::BesierCurveTo {
-                                cp1x,
-                                cp1y,
-                                cp2x,
-                                cp2y,
-                                x,
-                                y,
-                            } => {
-                                render_context_2_d.bezier_curve_to(cp1x, cp1y, cp2x, cp2y, x, y);
-                            }
-                            RenderTask::SetLineWidth { line_width } => {
-                                render_context_2_d.set_line_width(line_width);
-                            }
-                            RenderTask::SetAlpha { alpha } => {
-                                render_context_2_d.set_alpha(alpha);
-                            }
-                            RenderTask::Clip() => {
-                                render_context_2_d.clip();
+                        task => {
+                            if tile_pool.is_some() && !recording {
+                                frame.push(task);
+                            } else {
+                                execute(&mut render_context_2_d, &task, Point::default());
                             }
-                            RenderTask::SetFontFamily { family } => {
-                                render_context_2_d.set_font_family(family);
-                            }
-                            RenderTask::SetFontSize { size } => {
-                                render_context_2_d.set_font_size(size);
-                            }
-                            RenderTask::SetFillStyle { fill_style } => {
-                                render_context_2_d.set_fill_style(fill_style);
-                            }
-                            RenderTask::SetStrokeStyle { stroke_style } => {
-                                render_context_2_d.set_stroke_style(stroke_style);
-                            }
-                            RenderTask::Save() => {
-                                render_context_2_d.save();
-                            }
-                            RenderTask::Restore() => {
-                                render_context_2_d.restore();
-                            }
-                            RenderTask::Clear { brush } => {
-                                render_context_2_d.clear(&brush);
-                            }
-                            RenderTask::Finish() => {
-                                sender
-                                    .lock()
-                                    .unwrap()
-                                    .send(RenderResult::Finish {
-                                        data: render_context_2_d.data().iter().copied().collect(),
-                                    })
-                                    .expect("Could not send render result to main thread.");
-                                finish_sender
-                                    .send(true)
-                                    .expect("Could not send render result to main thread.");
-                            }
-                            _ => {}
-                        };
+                        }
                     }
                 }
             }
@@ -385,6 +661,8 @@ pub struct RenderContext2D {
     finish_receiver: mpsc::Receiver<bool>,
     tasks: Vec<RenderTask>,
     measure_context: platform::RenderContext2D,
+    // frame that is received while waiting for a display list
+    unread_output: Option<Vec<u32>>,
 }
 
 impl Drop for RenderContext2D {
@@ -419,6 +697,7 @@ impl RenderContext2D {
             finish_receiver,
             tasks: vec![],
             measure_context: platform::RenderContext2D::new(width, height),
+            unread_output: None,
         }
     }
 
@@ -432,6 +711,16 @@ impl RenderContext2D {
             .expect("Could not send set background to render thread.");
     }
 
+    /// Enables the tiled rasterization. The frames are split into tiles that are drawn in
+    /// parallel by a pool of threads, which uses the cores of the machine for large windows.
+    /// `None` draws the frames on the render thread only (default).
+    pub fn set_tiling(&mut self, tiling: impl Into<Option<Tiling>>) {
+        self.send_tasks();
+        self.sender
+            .send(vec![RenderTask::SetTiling(tiling.into())])
+            .expect("Could not send tiling to render thread.");
+    }
+
     // Sends a render task to the render thread.
     fn send_tasks(&mut self) {
         if !self.tasks.is_empty() {
@@ -455,6 +744,40 @@ impl RenderContext2D {
         self.send_tasks();
     }
 
+    /// Starts to redraw the given region. The region is cleared with the background and
+    /// everything that is drawn until `finish_region` is clipped to it.
+    pub fn start_region(&mut self, region: Rectangle) {
+        self.tasks.push(RenderTask::StartRegion { region });
+    }
+
+    /// Finishes the redraw of the region that is started with `start_region`.
+    pub fn finish_region(&mut self) {
+        self.tasks.push(RenderTask::FinishRegion());
+    }
+
+    /// Starts to record all following draw calls into a display list. The calls are still drawn.
+    pub fn start_recording(&mut self) {
+        self.tasks.push(RenderTask::StartRecording());
+    }
+
+    /// Stops the recording and returns the draw calls recorded since `start_recording`. Waits
+    /// until the render thread has drawn all calls.
+    pub fn finish_recording(&mut self) -> DisplayList {
+        self.tasks.push(RenderTask::FinishRecording());
+        self.send_tasks();
+
+        loop {
+            match self
+                .result_receiver
+                .recv()
+                .expect("Could not receive display list from render thread.")
+            {
+                RenderResult::DisplayList(display_list) => return display_list,
+                RenderResult::Finish { data } => self.unread_output = Some(data),
+            }
+        }
+    }
+
     /// Resizes the render ctx.
     pub fn resize(&mut self, width: f64, height: f64) {
         self.sender
@@ -473,6 +796,58 @@ impl RenderContext2D {
             .expect("Could not send register font to render thread.");
     }
 
+    /// Registers a new font from an owned buffer, e.g. a font that is loaded at runtime.
+    pub fn register_font_data(&mut self, family: &str, data: Vec<u8>) -> Result<(), String> {
+        self.measure_context
+            .register_font_data(family, data.clone())?;
+        self.sender
+            .send(vec![RenderTask::RegisterFontData {
+                family: family.to_string(),
+                data,
+            }])
+            .expect("Could not send register font to render thread.");
+        Ok(())
+    }
+
+    /// Loads a font file from the given path and registers it with the family key.
+    pub fn register_font_file<P: AsRef<Path> + std::fmt::Debug>(
+        &mut self,
+        family: &str,
+        path: P,
+    ) -> Result<(), String> {
+        self.measure_context.register_font_file(family, &path)?;
+        self.sender
+            .send(vec![RenderTask::RegisterFontFile {
+                family: family.to_string(),
+                path: path.as_ref().to_path_buf(),
+            }])
+            .expect("Could not send register font to render thread.");
+        Ok(())
+    }
+
+    /// Scans the standard font directories of the system. A system font could be used by its file
+    /// name (e.g. `NotoSans-Regular`) or by its family name (e.g. `Noto Sans`), it is loaded
+    /// when it is used the first time.
+    pub fn register_system_fonts(&mut self) {
+        self.measure_context.register_system_fonts();
+        self.sender
+            .send(vec![RenderTask::RegisterSystemFonts()])
+            .expect("Could not send register system fonts to render thread.");
+    }
+
+    /// Creates a single threaded render context with the given size that uses the registered
+    /// fonts of this one, e.g. to draw widgets offscreen.
+    pub fn create_offscreen(&self, width: f64, height: f64) -> platform::RenderContext2D {
+        self.measure_context.create_offscreen(width, height)
+    }
+
+    /// Draws the display list offscreen on a transparent image with the given size, without
+    /// waiting for the render thread.
+    pub fn render_to_image(&self, display_list: &DisplayList, width: u32, height: u32) -> Image {
+        self.measure_context
+            .render_to_image(display_list, width, height)
+    }
+
     // Rectangles
 
     /// Draws a filled rectangle whose starting point is at the coordinates {x, y} with the
@@ -496,6 +871,29 @@ impl RenderContext2D {
         });
     }
 
+    // Shadows
+
+    /// Draws the blurred shadow of a (rounded) rectangle with the given position, size and corner
+    /// radius. The shadow is moved by the offset of the box shadow and grows or shrinks by its spread.
+    pub fn draw_box_shadow(
+        &mut self,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        border_radius: f64,
+        box_shadow: &BoxShadow,
+    ) {
+        self.tasks.push(RenderTask::DrawBoxShadow {
+            x,
+            y,
+            width,
+            height,
+            border_radius,
+            box_shadow: *box_shadow,
+        });
+    }
+
     // Text
 
     /// Draws (fills) a given text at the given (x, y) position.
@@ -523,6 +921,24 @@ impl RenderContext2D {
         self.measure_context.measure_text(text)
     }
 
+    /// Measures the shaped glyphs of the text with the current font in visual order.
+    pub fn measure_glyphs(&mut self, text: &str) -> Vec<GlyphMetrics> {
+        self.measure_context.measure_glyphs(text)
+    }
+
+    /// Returns the x position of a caret placed before each char of the text, measured with the
+    /// given font. The last entry is the position after the last char. The positions follow the
+    /// shaped clusters and the bidi order of the text, so they are not always ascending.
+    pub fn caret_positions(
+        &mut self,
+        text: &str,
+        font_size: f64,
+        family: impl Into<String>,
+    ) -> Vec<f64> {
+        self.measure_context
+            .caret_positions(text, font_size, family)
+    }
+
     /// Fills the current or given path with the current file style.
     pub fn fill(&mut self) {
         self.tasks.push(RenderTask::Fill());
@@ -555,6 +971,18 @@ impl RenderContext2D {
         });
     }
 
+    /// Adds a rectangle with rounded corners to the current path. The radius is limited to half of
+    /// the width and height. Could be used with `clip` to clip to the shape of a rounded border.
+    pub fn rounded_rect(&mut self, x: f64, y: f64, width: f64, height: f64, radius: f64) {
+        self.tasks.push(RenderTask::RoundedRectangle {
+            x,
+            y,
+            width,
+            height,
+            radius,
+        });
+    }
+
     /// Creates a circular arc centered at (x, y) with a radius of radius.
     /// The path starts at startAngle and ends at endAngle.
     pub fn arc(&mut self, x: f64, y: f64, radius: f64, start_angle: f64, end_angle: f64) {
@@ -603,6 +1031,7 @@ impl RenderContext2D {
     // Draw image
 
     pub fn draw_render_target(&mut self, render_target: &RenderTarget, x: f64, y: f64) {
+        self.send_tasks();
         self.sender
             .send(vec![RenderTask::DrawRenderTarget {
                 render_target: render_target.clone(),
@@ -613,7 +1042,8 @@ impl RenderContext2D {
     }
 
     /// Draws the image.
-    pub fn draw_image(&mut self, image: &mut Image, x: f64, y: f64) {
+    pub fn draw_image(&mut self, image: &Image, x: f64, y: f64) {
+        self.send_tasks();
         self.sender
             .send(vec![RenderTask::DrawImage {
                 image: image.clone(),
@@ -624,7 +1054,8 @@ impl RenderContext2D {
     }
 
     /// Draws the given part of the image.
-    pub fn draw_image_with_clip(&mut self, image: &mut Image, clip: Rectangle, x: f64, y: f64) {
+    pub fn draw_image_with_clip(&mut self, image: &Image, clip: Rectangle, x: f64, y: f64) {
+        self.send_tasks();
         self.sender
             .send(vec![RenderTask::DrawImageWithClip {
                 image: image.clone(),
@@ -635,6 +1066,43 @@ impl RenderContext2D {
             .expect("Could not send clipped image to render thread.");
     }
 
+    /// Draws the image scaled to the given size.
+    pub fn draw_image_with_size(&mut self, image: &Image, x: f64, y: f64, width: f64, height: f64) {
+        self.send_tasks();
+        self.sender
+            .send(vec![RenderTask::DrawImageWithSize {
+                image: image.clone(),
+                x,
+                y,
+                width,
+                height,
+            }])
+            .expect("Could not send scaled image to render thread.");
+    }
+
+    /// Draws the given part of the image scaled to the given size.
+    pub fn draw_image_with_clip_and_size(
+        &mut self,
+        image: &Image,
+        clip: Rectangle,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    ) {
+        self.send_tasks();
+        self.sender
+            .send(vec![RenderTask::DrawImageWithClipAndSize {
+                image: image.clone(),
+                clip,
+                x,
+                y,
+                width,
+                height,
+            }])
+            .expect("Could not send scaled image to render thread.");
+    }
+
     pub fn draw_pipeline(
         &mut self,
         x: f64,
@@ -643,13 +1111,14 @@ impl RenderContext2D {
         height: f64,
         pipeline: Box<dyn PipelineTrait>,
     ) {
+        self.send_tasks();
         self.sender
             .send(vec![RenderTask::DrawPipeline {
                 x,
                 y,
                 width,
                 height,
-                pipeline: PipelineWrapper(pipeline),
+                pipeline: PipelineWrapper(Arc::new(Mutex::new(pipeline))),
             }])
             .expect("Could not send draw_pipeline to render thread.");
     }
@@ -660,6 +1129,40 @@ impl RenderContext2D {
         self.tasks.push(RenderTask::Clip());
     }
 
+    // Layers
+
+    /// Starts an offscreen layer. Everything drawn until `pop_layer` is called is rendered into the
+    /// layer and then composited as a whole with the given opacity, so overlapping shapes do not
+    /// shine through each other. If `clip` is set only this part of the layer is composited.
+    /// Calls of `save` and `restore` must be balanced inside of the layer.
+    pub fn push_layer(&mut self, opacity: f32, clip: Option<Rectangle>) {
+        self.tasks.push(RenderTask::PushLayer { opacity, clip });
+    }
+
+    /// Starts an offscreen layer like `push_layer`. When the layer is popped the effect is applied
+    /// on the given bounds (extended by the extent of the effect).
+    pub fn push_effect(&mut self, effect: Effect, bounds: Rectangle) {
+        self.tasks.push(RenderTask::PushEffect { effect, bounds });
+    }
+
+    /// Composites the current layer with its opacity on the previous draw target. The effect of a
+    /// layer that is started by `push_effect` is applied before.
+    pub fn pop_layer(&mut self) {
+        self.tasks.push(RenderTask::PopLayer());
+    }
+
+    /// Pops the current layer, same as `pop_layer`.
+    pub fn pop_effect(&mut self) {
+        self.tasks.push(RenderTask::PopEffect());
+    }
+
+    /// Applies the effect on the pixels that are already drawn inside of the bounds, e.g. to blur
+    /// the content behind a widget.
+    pub fn apply_backdrop_effect(&mut self, effect: Effect, bounds: Rectangle) {
+        self.tasks
+            .push(RenderTask::BackdropEffect { effect, bounds });
+    }
+
     // Line styles
 
     /// Sets the thickness of lines.
@@ -667,6 +1170,34 @@ impl RenderContext2D {
         self.tasks.push(RenderTask::SetLineWidth { line_width });
     }
 
+    /// Sets the shape used to draw the end points of lines.
+    pub fn set_line_cap(&mut self, line_cap: LineCap) {
+        self.tasks.push(RenderTask::SetLineCap { line_cap });
+    }
+
+    /// Sets the shape used to join two line segments where they meet.
+    pub fn set_line_join(&mut self, line_join: LineJoin) {
+        self.tasks.push(RenderTask::SetLineJoin { line_join });
+    }
+
+    /// Sets the miter limit ratio. Zero, negative and not finite values are ignored.
+    pub fn set_miter_limit(&mut self, miter_limit: f64) {
+        self.tasks.push(RenderTask::SetMiterLimit { miter_limit });
+    }
+
+    /// Sets the line dash pattern used when stroking lines. An empty list draws solid lines.
+    pub fn set_line_dash(&mut self, segments: &[f64]) {
+        self.tasks.push(RenderTask::SetLineDash {
+            line_dash: segments.to_vec(),
+        });
+    }
+
+    /// Sets the line dash offset.
+    pub fn set_line_dash_offset(&mut self, line_dash_offset: f64) {
+        self.tasks
+            .push(RenderTask::SetLineDashOffset { line_dash_offset });
+    }
+
     /// Sets the alpha value,
     pub fn set_alpha(&mut self, alpha: f32) {
         self.tasks.push(RenderTask::SetAlpha { alpha });
@@ -683,6 +1214,32 @@ impl RenderContext2D {
         self.tasks.push(RenderTask::SetFontSize { size });
     }
 
+    /// Specifies the font weight. The registered face of the font family with the nearest weight
+    /// is used.
+    pub fn set_font_weight(&mut self, weight: FontWeight) {
+        self.measure_context.set_font_weight(weight);
+        self.tasks.push(RenderTask::SetFontWeight { weight });
+    }
+
+    /// Specifies the font style (normal, italic or oblique).
+    pub fn set_font_style(&mut self, style: FontStyle) {
+        self.measure_context.set_font_style(style);
+        self.tasks.push(RenderTask::SetFontStyle { style });
+    }
+
+    /// Specifies the font stretch (condensed or expanded width).
+    pub fn set_font_stretch(&mut self, stretch: FontStretch) {
+        self.measure_context.set_font_stretch(stretch);
+        self.tasks.push(RenderTask::SetFontStretch { stretch });
+    }
+
+    /// Specifies the baseline the y position of `fill_text` refers to.
+    pub fn set_text_baseline(&mut self, text_baseline: TextBaseline) {
+        self.measure_context.set_text_baseline(text_baseline);
+        self.tasks
+            .push(RenderTask::SetTextBaseline { text_baseline });
+    }
+
     // Fill and stroke style
 
     /// Specifies the fill color to use inside shapes.
@@ -721,12 +1278,14 @@ impl RenderContext2D {
 
     /// Saves the entire state of the canvas by pushing the current state onto a stack.
     pub fn save(&mut self) {
+        self.measure_context.save();
         self.tasks.push(RenderTask::Save());
     }
 
     /// Restores the most recently saved canvas state by popping the top entry in the drawing state stack.
     /// If there is no saved state, this method does nothing.
     pub fn restore(&mut self) {
+        self.measure_context.restore();
         self.tasks.push(RenderTask::Restore());
     }
 
@@ -736,7 +1295,10 @@ impl RenderContext2D {
     }
 
     pub fn data(&mut self) -> Option<&[u32]> {
-        if let Ok(RenderResult::Finish { data }) = self.result_receiver.try_recv() {
+        if let Some(data) = self.unread_output.take() {
+            self.output = data;
+            Some(&self.output)
+        } else if let Ok(RenderResult::Finish { data }) = self.result_receiver.try_recv() {
             self.output = data;
             Some(&self.output)
         } else {
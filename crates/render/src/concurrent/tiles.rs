@@ -0,0 +1,725 @@
+use std::{
+    sync::{mpsc, Arc, Mutex},
+    thread,
+};
+
+use super::{execute, RenderTask};
+use crate::{platform, utils::*, FontConfig};
+
+// Pixels around the bounds of a draw call that could be touched by anti-aliasing.
+const ANTI_ALIASING_MARGIN: f64 = 1.0;
+
+/// Describes how the concurrent render context splits a frame into tiles that are rasterized in
+/// parallel.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Tiling {
+    /// The width and height of a tile in pixels.
+    pub tile_size: u32,
+
+    /// The number of threads that rasterize the tiles.
+    pub threads: usize,
+}
+
+impl Default for Tiling {
+    fn default() -> Self {
+        Tiling {
+            tile_size: 256,
+            threads: thread::available_parallelism().map_or(1, |threads| threads.get()),
+        }
+    }
+}
+
+impl Tiling {
+    /// Creates a tiling with the given tile size that uses all available cores.
+    pub fn new(tile_size: u32) -> Self {
+        Tiling {
+            tile_size,
+            ..Default::default()
+        }
+    }
+
+    /// Builder method that sets the number of threads.
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+}
+
+// Describes which part of the frame is changed by a render task.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Coverage {
+    // changes only the state of the context, the task is executed on every tile
+    State,
+    // draws on the whole frame
+    Frame,
+    // draws inside of the bounds
+    Bounds(Rectangle),
+    // changes the state and draws inside of the bounds, like the start of a region
+    StateAndBounds(Rectangle),
+}
+
+// Tracks the state of the context that is needed to calculate the coverage of the render tasks.
+struct CoverageState {
+    transform: Matrix,
+    line_width: f64,
+    miter_limit: f64,
+    font_size: f64,
+    path: Option<Rectangle>,
+    saved: Vec<(Matrix, f64, f64, f64)>,
+}
+
+impl CoverageState {
+    fn new() -> Self {
+        CoverageState {
+            transform: Matrix::identity(),
+            line_width: 1.0,
+            miter_limit: 10.0,
+            font_size: FontConfig::default().font_size,
+            path: None,
+            saved: vec![],
+        }
+    }
+
+    fn add_to_path(&mut self, rect: Rectangle) {
+        match &mut self.path {
+            Some(path) => path.join_with_rectangle(&rect),
+            None => self.path = Some(rect),
+        }
+    }
+
+    fn add_points_to_path(&mut self, points: &[(f64, f64)]) {
+        for point in points {
+            self.add_to_path(Rectangle::new(*point, (0.0, 0.0)));
+        }
+    }
+
+    // Transforms the bounds into the coordinates of the frame.
+    fn bounds(&self, bounds: Rectangle, extent: f64) -> Coverage {
+        let bounds = self.transform.transform_rect(expand(bounds, extent));
+        Coverage::Bounds(expand(bounds, ANTI_ALIASING_MARGIN))
+    }
+
+    // How far a stroke spreads beyond its path, miters are the longest possible joins.
+    fn stroke_extent(&self) -> f64 {
+        self.line_width / 2.0 * self.miter_limit.max(std::f64::consts::SQRT_2)
+    }
+
+    fn coverage(&mut self, task: &RenderTask) -> Coverage {
+        match task {
+            RenderTask::Start() | RenderTask::Clear { .. } => Coverage::Frame,
+            RenderTask::FillRect {
+                x,
+                y,
+                width,
+                height,
+            } => self.bounds(Rectangle::new((*x, *y), (*width, *height)), 0.0),
+            RenderTask::StrokeRect {
+                x,
+                y,
+                width,
+                height,
+            } => self.bounds(
+                Rectangle::new((*x, *y), (*width, *height)),
+                self.stroke_extent(),
+            ),
+            RenderTask::DrawBoxShadow {
+                x,
+                y,
+                width,
+                height,
+                box_shadow,
+                ..
+            } => self.bounds(
+                box_shadow.bounds(Rectangle::new((*x, *y), (*width, *height))),
+                0.0,
+            ),
+            // text is not measured here, the estimation is large enough for the glyphs of most
+            // fonts
+            RenderTask::FillText { text, x, y } => {
+                let size = self.font_size.max(1.0);
+                self.bounds(
+                    Rectangle::new(
+                        (x - size, y - size),
+                        ((text.chars().count() + 2) as f64 * size, 3.0 * size),
+                    ),
+                    0.0,
+                )
+            }
+            RenderTask::Fill() => match self.path {
+                Some(path) => self.bounds(path, 0.0),
+                None => Coverage::State,
+            },
+            RenderTask::Stroke() => match self.path {
+                Some(path) => self.bounds(path, self.stroke_extent()),
+                None => Coverage::State,
+            },
+            RenderTask::BeginPath() => {
+                self.path = None;
+                Coverage::State
+            }
+            RenderTask::Rectangle {
+                x,
+                y,
+                width,
+                height,
+            }
+            | RenderTask::RoundedRectangle {
+                x,
+                y,
+                width,
+                height,
+                ..
+            } => {
+                self.add_to_path(Rectangle::new((*x, *y), (*width, *height)));
+                Coverage::State
+            }
+            RenderTask::Arc { x, y, radius, .. } => {
+                self.add_to_path(Rectangle::new(
+                    (x - radius, y - radius),
+                    (2.0 * radius, 2.0 * radius),
+                ));
+                Coverage::State
+            }
+            RenderTask::MoveTo { x, y } | RenderTask::LineTo { x, y } => {
+                self.add_points_to_path(&[(*x, *y)]);
+                Coverage::State
+            }
+            RenderTask::QuadraticCurveTo { cpx, cpy, x, y } => {
+                self.add_points_to_path(&[(*cpx, *cpy), (*x, *y)]);
+                Coverage::State
+            }
+            RenderTask::BesierCurveTo {
+                cp1x,
+                cp1y,
+                cp2x,
+                cp2y,
+                x,
+                y,
+            } => {
+                self.add_points_to_path(&[(*cp1x, *cp1y), (*cp2x, *cp2y), (*x, *y)]);
+                Coverage::State
+            }
+            RenderTask::DrawRenderTarget {
+                render_target,
+                x,
+                y,
+            } => self.bounds(
+                Rectangle::new((*x, *y), (render_target.width(), render_target.height())),
+                0.0,
+            ),
+            RenderTask::DrawImage { image, x, y } => self.bounds(
+                Rectangle::new((*x, *y), (image.width(), image.height())),
+                0.0,
+            ),
+            RenderTask::DrawImageWithClip { clip, x, y, .. } => {
+                self.bounds(Rectangle::new((*x, *y), (clip.width(), clip.height())), 0.0)
+            }
+            RenderTask::DrawImageWithSize {
+                x,
+                y,
+                width,
+                height,
+                ..
+            }
+            | RenderTask::DrawImageWithClipAndSize {
+                x,
+                y,
+                width,
+                height,
+                ..
+            }
+            | RenderTask::DrawPipeline {
+                x,
+                y,
+                width,
+                height,
+                ..
+            } => self.bounds(Rectangle::new((*x, *y), (*width, *height)), 0.0),
+            RenderTask::SetLineWidth { line_width } => {
+                self.line_width = *line_width;
+                Coverage::State
+            }
+            RenderTask::SetMiterLimit { miter_limit } => {
+                if miter_limit.is_finite() && *miter_limit > 0.0 {
+                    self.miter_limit = *miter_limit;
+                }
+                Coverage::State
+            }
+            RenderTask::SetFontSize { size } => {
+                self.font_size = *size;
+                Coverage::State
+            }
+            RenderTask::SetTransform {
+                h_scaling,
+                h_skewing,
+                v_skewing,
+                v_scaling,
+                h_moving,
+                v_moving,
+            } => {
+                self.transform = Matrix::new(
+                    *h_scaling, *h_skewing, *v_skewing, *v_scaling, *h_moving, *v_moving,
+                );
+                Coverage::State
+            }
+            RenderTask::Save() => {
+                self.saved.push((
+                    self.transform,
+                    self.line_width,
+                    self.miter_limit,
+                    self.font_size,
+                ));
+                Coverage::State
+            }
+            RenderTask::Restore() => {
+                if let Some((transform, line_width, miter_limit, font_size)) = self.saved.pop() {
+                    self.transform = transform;
+                    self.line_width = line_width;
+                    self.miter_limit = miter_limit;
+                    self.font_size = font_size;
+                }
+                Coverage::State
+            }
+            // the bounds are in window coordinates
+            RenderTask::BackdropEffect { bounds, .. } => {
+                Coverage::Bounds(expand(*bounds, ANTI_ALIASING_MARGIN))
+            }
+            RenderTask::StartRegion { region } => match self.bounds(*region, 0.0) {
+                Coverage::Bounds(bounds) => Coverage::StateAndBounds(bounds),
+                coverage => coverage,
+            },
+            // layers that are not drawn on are composited without changes
+            _ => Coverage::State,
+        }
+    }
+}
+
+fn expand(rect: Rectangle, extent: f64) -> Rectangle {
+    Rectangle::new(
+        (rect.x() - extent, rect.y() - extent),
+        (rect.width() + 2.0 * extent, rect.height() + 2.0 * extent),
+    )
+}
+
+// Splits a frame with the given size into tiles, row by row.
+fn tiles(width: u32, height: u32, tile_size: u32) -> Vec<Rectangle> {
+    let tile_size = tile_size.max(1);
+    let mut tiles = vec![];
+
+    for y in (0..height).step_by(tile_size as usize) {
+        for x in (0..width).step_by(tile_size as usize) {
+            tiles.push(Rectangle::new(
+                (x as f64, y as f64),
+                (
+                    tile_size.min(width - x) as f64,
+                    tile_size.min(height - y) as f64,
+                ),
+            ));
+        }
+    }
+
+    tiles
+}
+
+// Returns the number of pixels the effects of the frame read around a pixel. Tiles are rendered
+// with this margin, so blurred layers and backdrops see the same neighbors as without tiling.
+fn effect_margin(tasks: &[RenderTask]) -> f64 {
+    let mut extents = vec![];
+    let mut margin = 0.0_f64;
+
+    for task in tasks {
+        match task {
+            RenderTask::PushEffect { effect, .. } => {
+                extents.push(effect.extent());
+                margin = margin.max(extents.iter().sum());
+            }
+            RenderTask::BackdropEffect { effect, .. } => {
+                margin = margin.max(extents.iter().sum::<f64>() + effect.extent());
+            }
+            RenderTask::PushLayer { .. } => extents.push(0.0),
+            RenderTask::PopEffect() | RenderTask::PopLayer() => {
+                extents.pop();
+            }
+            _ => {}
+        }
+    }
+
+    margin.ceil()
+}
+
+// Assigns the render tasks of a frame to the areas of the tiles. Every area gets the tasks that
+// change the state and the draw tasks that touch it. Areas without any draw task get an empty
+// list, they are not changed by the frame.
+fn bin_tasks(tasks: &[RenderTask], areas: &[Rectangle]) -> Vec<Vec<usize>> {
+    let mut state = CoverageState::new();
+    let mut bins = vec![vec![]; areas.len()];
+    let mut drawn = vec![false; areas.len()];
+
+    for (index, task) in tasks.iter().enumerate() {
+        let coverage = state.coverage(task);
+
+        for (area, (bin, drawn)) in areas.iter().zip(bins.iter_mut().zip(drawn.iter_mut())) {
+            let (include, draws) = match coverage {
+                Coverage::State => (true, false),
+                Coverage::Frame => (true, true),
+                Coverage::Bounds(bounds) => {
+                    let intersects = area.intersects(&bounds);
+                    (intersects, intersects)
+                }
+                Coverage::StateAndBounds(bounds) => (true, area.intersects(&bounds)),
+            };
+
+            if include {
+                bin.push(index);
+            }
+
+            *drawn |= draws;
+        }
+    }
+
+    for (bin, drawn) in bins.iter_mut().zip(drawn) {
+        if !drawn {
+            bin.clear();
+        }
+    }
+
+    bins
+}
+
+// The part of the frame that is rasterized by one tile thread. The area is the tile extended by
+// the margin of the effects.
+struct TileJob {
+    tile: Rectangle,
+    area: Rectangle,
+    // pixels of the area before the frame is drawn
+    data: Vec<u32>,
+    tasks: Vec<usize>,
+}
+
+struct TileFrame {
+    tasks: Vec<RenderTask>,
+    jobs: Mutex<Vec<TileJob>>,
+}
+
+enum TileMessage {
+    Task(RenderTask),
+    Frame(Arc<TileFrame>),
+    Terminate,
+}
+
+// Copies the given area out of a buffer with the given width.
+fn copy_area(data: &[u32], width: usize, area: Rectangle) -> Vec<u32> {
+    let (x, y) = (area.x() as usize, area.y() as usize);
+    let (area_width, area_height) = (area.width() as usize, area.height() as usize);
+    let mut result = Vec::with_capacity(area_width * area_height);
+
+    for row in y..y + area_height {
+        let start = row * width + x;
+        result.extend_from_slice(&data[start..start + area_width]);
+    }
+
+    result
+}
+
+// Rasterizes the jobs of the frames it receives until all jobs of a frame are taken.
+fn tile_thread(receiver: mpsc::Receiver<TileMessage>, sender: mpsc::Sender<(Rectangle, Vec<u32>)>) {
+    let mut render_context_2_d = platform::RenderContext2D::new(1.0, 1.0);
+    let mut size = (1.0, 1.0);
+
+    for message in receiver {
+        let frame = match message {
+            TileMessage::Task(task) => {
+                execute(&mut render_context_2_d, &task, Point::default());
+                continue;
+            }
+            TileMessage::Frame(frame) => frame,
+            TileMessage::Terminate => return,
+        };
+
+        loop {
+            let job = match frame.jobs.lock().unwrap().pop() {
+                Some(job) => job,
+                None => break,
+            };
+
+            if size != (job.area.width(), job.area.height()) {
+                size = (job.area.width(), job.area.height());
+                render_context_2_d.resize(size.0, size.1);
+            }
+
+            // every job starts with the state of a new context like the frame, not with the
+            // state the previous job ended with
+            render_context_2_d.reset_state();
+            render_context_2_d.data_mut().copy_from_slice(&job.data);
+
+            let offset = Point::new(-job.area.x(), -job.area.y());
+            for index in job.tasks {
+                execute(&mut render_context_2_d, &frame.tasks[index], offset);
+            }
+
+            render_context_2_d.finish();
+            render_context_2_d.take_damaged_regions();
+
+            let tile = Rectangle::new(
+                (job.tile.x() - job.area.x(), job.tile.y() - job.area.y()),
+                job.tile.size(),
+            );
+            let data = copy_area(render_context_2_d.data(), size.0 as usize, tile);
+
+            if sender.send((job.tile, data)).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+// Pool of threads that rasterize the tiles of a frame.
+pub(super) struct TilePool {
+    tiling: Tiling,
+    senders: Vec<mpsc::Sender<TileMessage>>,
+    threads: Vec<thread::JoinHandle<()>>,
+    result_receiver: mpsc::Receiver<(Rectangle, Vec<u32>)>,
+}
+
+impl TilePool {
+    /// Creates the threads of the pool, the given tasks (e.g. font registrations) are executed on
+    /// every thread.
+    pub(super) fn new(tiling: Tiling, tasks: &[RenderTask]) -> Self {
+        let (result_sender, result_receiver) = mpsc::channel();
+        let mut senders = vec![];
+        let mut threads = vec![];
+
+        for _ in 0..tiling.threads.max(1) {
+            let (sender, receiver) = mpsc::channel();
+            let result_sender = result_sender.clone();
+
+            for task in tasks {
+                sender
+                    .send(TileMessage::Task(task.clone()))
+                    .expect("Could not send task to tile thread.");
+            }
+
+            threads.push(thread::spawn(move || tile_thread(receiver, result_sender)));
+            senders.push(sender);
+        }
+
+        TilePool {
+            tiling,
+            senders,
+            threads,
+            result_receiver,
+        }
+    }
+
+    pub(super) fn tiling(&self) -> Tiling {
+        self.tiling
+    }
+
+    /// Executes the given task on every thread of the pool.
+    pub(super) fn broadcast(&self, task: &RenderTask) {
+        for sender in &self.senders {
+            sender
+                .send(TileMessage::Task(task.clone()))
+                .expect("Could not send task to tile thread.");
+        }
+    }
+
+    /// Draws the render tasks of a frame on the given render context. The tiles are rasterized in
+    /// parallel and copied back into the context.
+    pub(super) fn render(
+        &self,
+        render_context_2_d: &mut platform::RenderContext2D,
+        width: u32,
+        height: u32,
+        tasks: Vec<RenderTask>,
+    ) {
+        let frame = Rectangle::new((0.0, 0.0), (width as f64, height as f64));
+        let margin = effect_margin(&tasks);
+
+        let tiles = tiles(width, height, self.tiling.tile_size);
+        let areas: Vec<Rectangle> = tiles
+            .iter()
+            .map(|tile| intersection(expand(*tile, margin), frame))
+            .collect();
+
+        let jobs: Vec<TileJob> = bin_tasks(&tasks, &areas)
+            .into_iter()
+            .zip(tiles.into_iter().zip(areas))
+            .filter(|(tasks, _)| !tasks.is_empty())
+            .map(|(tasks, (tile, area))| TileJob {
+                tile,
+                area,
+                data: copy_area(render_context_2_d.data(), width as usize, area),
+                tasks,
+            })
+            .collect();
+
+        let count = jobs.len();
+
+        if count == 0 {
+            return;
+        }
+
+        let frame = Arc::new(TileFrame {
+            tasks,
+            jobs: Mutex::new(jobs),
+        });
+
+        for sender in self.senders.iter().take(count) {
+            sender
+                .send(TileMessage::Frame(frame.clone()))
+                .expect("Could not send frame to tile thread.");
+        }
+
+        let data = render_context_2_d.data_mut();
+
+        for _ in 0..count {
+            let (tile, tile_data) = self
+                .result_receiver
+                .recv()
+                .expect("Could not receive tile from tile thread.");
+
+            let tile_width = tile.width() as usize;
+            for (row, line) in tile_data.chunks(tile_width).enumerate() {
+                let start = (tile.y() as usize + row) * width as usize + tile.x() as usize;
+                data[start..start + tile_width].copy_from_slice(line);
+            }
+        }
+    }
+}
+
+impl Drop for TilePool {
+    fn drop(&mut self) {
+        for sender in &self.senders {
+            let _ = sender.send(TileMessage::Terminate);
+        }
+
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
+
+// Returns the part of the rectangle that is inside of the frame, rounded to whole pixels.
+fn intersection(rect: Rectangle, frame: Rectangle) -> Rectangle {
+    let x = rect.x().max(frame.x()).floor();
+    let y = rect.y().max(frame.y()).floor();
+    let right = (rect.x() + rect.width())
+        .min(frame.x() + frame.width())
+        .ceil();
+    let bottom = (rect.y() + rect.height())
+        .min(frame.y() + frame.height())
+        .ceil();
+
+    Rectangle::new((x, y), ((right - x).max(0.0), (bottom - y).max(0.0)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tiles() {
+        let tiles = tiles(300, 100, 128);
+
+        assert_eq!(tiles.len(), 3);
+        assert_eq!(tiles[0], Rectangle::new((0.0, 0.0), (128.0, 100.0)));
+        assert_eq!(tiles[2], Rectangle::new((256.0, 0.0), (44.0, 100.0)));
+    }
+
+    #[test]
+    fn test_bin_tasks() {
+        let areas = vec![
+            Rectangle::new((0.0, 0.0), (100.0, 100.0)),
+            Rectangle::new((100.0, 0.0), (100.0, 100.0)),
+            Rectangle::new((200.0, 0.0), (100.0, 100.0)),
+        ];
+
+        let tasks = vec![
+            RenderTask::SetFillStyle {
+                fill_style: Brush::from("#ff0000"),
+            },
+            RenderTask::FillRect {
+                x: 10.0,
+                y: 10.0,
+                width: 20.0,
+                height: 20.0,
+            },
+            RenderTask::Save(),
+            RenderTask::SetTransform {
+                h_scaling: 1.0,
+                h_skewing: 0.0,
+                v_skewing: 0.0,
+                v_scaling: 1.0,
+                h_moving: 100.0,
+                v_moving: 0.0,
+            },
+            RenderTask::BeginPath(),
+            RenderTask::Rectangle {
+                x: 10.0,
+                y: 10.0,
+                width: 20.0,
+                height: 20.0,
+            },
+            RenderTask::Fill(),
+            RenderTask::Restore(),
+        ];
+
+        let bins = bin_tasks(&tasks, &areas);
+
+        // the rectangles are only drawn on the tiles they touch
+        assert_eq!(bins[0], vec![0, 1, 2, 3, 4, 5, 7]);
+        assert_eq!(bins[1], vec![0, 2, 3, 4, 5, 6, 7]);
+
+        // nothing is drawn on the last tile
+        assert!(bins[2].is_empty());
+
+        // the frame is cleared on every tile
+        let bins = bin_tasks(&[RenderTask::Start()], &areas);
+        assert!(bins.iter().all(|bin| bin == &vec![0]));
+
+        // text is estimated with the default font size if no size is set
+        let bins = bin_tasks(
+            &[RenderTask::FillText {
+                text: "a".to_string(),
+                x: 95.0,
+                y: 10.0,
+            }],
+            &areas,
+        );
+        assert_eq!(bins[1], vec![0]);
+    }
+
+    #[test]
+    fn test_effect_margin() {
+        let tasks = vec![
+            RenderTask::PushEffect {
+                effect: Effect::Blur(4.0),
+                bounds: Rectangle::new((0.0, 0.0), (10.0, 10.0)),
+            },
+            RenderTask::PushEffect {
+                effect: Effect::Blur(2.0),
+                bounds: Rectangle::new((0.0, 0.0), (10.0, 10.0)),
+            },
+            RenderTask::PopEffect(),
+            RenderTask::PopEffect(),
+            RenderTask::PushLayer {
+                opacity: 0.5,
+                clip: None,
+            },
+            RenderTask::PopLayer(),
+            RenderTask::PushEffect {
+                effect: Effect::Blur(2.0),
+                bounds: Rectangle::new((0.0, 0.0), (10.0, 10.0)),
+            },
+            RenderTask::BackdropEffect {
+                effect: Effect::Blur(6.0),
+                bounds: Rectangle::new((0.0, 0.0), (10.0, 10.0)),
+            },
+            RenderTask::PopEffect(),
+        ];
+
+        assert_eq!(effect_margin(&tasks), 12.0);
+        assert_eq!(effect_margin(&[]), 0.0);
+    }
+}
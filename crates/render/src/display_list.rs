@@ -0,0 +1,1017 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    platform::{self, Image},
+    utils::*,
+    GlyphMetrics, PipelineTrait, RenderContext2D, RenderTarget, TextMetrics,
+};
+
+// Draws the commands with a render context, used for the render context of the crate and the
+// single threaded one that draws offscreen.
+macro_rules! replay_commands {
+    ($commands:expr, $render_context:ident) => {
+        for command in $commands {
+            match command {
+                DrawCommand::FillRect {
+                    x,
+                    y,
+                    width,
+                    height,
+                } => $render_context.fill_rect(*x, *y, *width, *height),
+                DrawCommand::StrokeRect {
+                    x,
+                    y,
+                    width,
+                    height,
+                } => $render_context.stroke_rect(*x, *y, *width, *height),
+                DrawCommand::DrawBoxShadow {
+                    x,
+                    y,
+                    width,
+                    height,
+                    border_radius,
+                    box_shadow,
+                } => $render_context.draw_box_shadow(
+                    *x,
+                    *y,
+                    *width,
+                    *height,
+                    *border_radius,
+                    box_shadow,
+                ),
+                DrawCommand::FillText { text, x, y } => $render_context.fill_text(text, *x, *y),
+                DrawCommand::Fill => $render_context.fill(),
+                DrawCommand::Stroke => $render_context.stroke(),
+                DrawCommand::BeginPath => $render_context.begin_path(),
+                DrawCommand::ClosePath => $render_context.close_path(),
+                DrawCommand::Rect {
+                    x,
+                    y,
+                    width,
+                    height,
+                } => $render_context.rect(*x, *y, *width, *height),
+                DrawCommand::RoundedRect {
+                    x,
+                    y,
+                    width,
+                    height,
+                    radius,
+                } => $render_context.rounded_rect(*x, *y, *width, *height, *radius),
+                DrawCommand::Arc {
+                    x,
+                    y,
+                    radius,
+                    start_angle,
+                    end_angle,
+                } => $render_context.arc(*x, *y, *radius, *start_angle, *end_angle),
+                DrawCommand::MoveTo { x, y } => $render_context.move_to(*x, *y),
+                DrawCommand::LineTo { x, y } => $render_context.line_to(*x, *y),
+                DrawCommand::QuadraticCurveTo { cpx, cpy, x, y } => {
+                    $render_context.quadratic_curve_to(*cpx, *cpy, *x, *y)
+                }
+                DrawCommand::BezierCurveTo {
+                    cp1x,
+                    cp1y,
+                    cp2x,
+                    cp2y,
+                    x,
+                    y,
+                } => $render_context.bezier_curve_to(*cp1x, *cp1y, *cp2x, *cp2y, *x, *y),
+                DrawCommand::DrawImage {
+                    image: source,
+                    x,
+                    y,
+                } => {
+                    if let Some(image) = image(source) {
+                        $render_context.draw_image(&image, *x, *y);
+                    }
+                }
+                DrawCommand::DrawImageWithClip {
+                    image: source,
+                    clip,
+                    x,
+                    y,
+                } => {
+                    if let Some(image) = image(source) {
+                        $render_context.draw_image_with_clip(&image, *clip, *x, *y);
+                    }
+                }
+                DrawCommand::DrawImageWithSize {
+                    image: source,
+                    x,
+                    y,
+                    width,
+                    height,
+                } => {
+                    if let Some(image) = image(source) {
+                        $render_context.draw_image_with_size(&image, *x, *y, *width, *height);
+                    }
+                }
+                DrawCommand::DrawImageWithClipAndSize {
+                    image: source,
+                    clip,
+                    x,
+                    y,
+                    width,
+                    height,
+                } => {
+                    if let Some(image) = image(source) {
+                        $render_context
+                            .draw_image_with_clip_and_size(&image, *clip, *x, *y, *width, *height);
+                    }
+                }
+                DrawCommand::Clip => $render_context.clip(),
+                DrawCommand::PushLayer { opacity, clip } => {
+                    $render_context.push_layer(*opacity, *clip)
+                }
+                DrawCommand::PopLayer => $render_context.pop_layer(),
+                DrawCommand::PushEffect { effect, bounds } => {
+                    $render_context.push_effect(*effect, *bounds)
+                }
+                DrawCommand::PopEffect => $render_context.pop_effect(),
+                DrawCommand::BackdropEffect { effect, bounds } => {
+                    $render_context.apply_backdrop_effect(*effect, *bounds)
+                }
+                DrawCommand::SetLineWidth { line_width } => {
+                    $render_context.set_line_width(*line_width)
+                }
+                DrawCommand::SetLineCap { line_cap } => $render_context.set_line_cap(*line_cap),
+                DrawCommand::SetLineJoin { line_join } => $render_context.set_line_join(*line_join),
+                DrawCommand::SetMiterLimit { miter_limit } => {
+                    $render_context.set_miter_limit(*miter_limit)
+                }
+                DrawCommand::SetLineDash { line_dash } => $render_context.set_line_dash(line_dash),
+                DrawCommand::SetLineDashOffset { line_dash_offset } => {
+                    $render_context.set_line_dash_offset(*line_dash_offset)
+                }
+                DrawCommand::SetAlpha { alpha } => $render_context.set_alpha(*alpha),
+                DrawCommand::SetFontFamily { family } => {
+                    $render_context.set_font_family(family.as_str())
+                }
+                DrawCommand::SetFontSize { size } => $render_context.set_font_size(*size),
+                DrawCommand::SetFontWeight { weight } => $render_context.set_font_weight(*weight),
+                DrawCommand::SetFontStyle { style } => $render_context.set_font_style(*style),
+                DrawCommand::SetFontStretch { stretch } => {
+                    $render_context.set_font_stretch(*stretch)
+                }
+                DrawCommand::SetTextBaseline { text_baseline } => {
+                    $render_context.set_text_baseline(*text_baseline)
+                }
+                DrawCommand::SetFillStyle { fill_style } => {
+                    $render_context.set_fill_style(fill_style.clone())
+                }
+                DrawCommand::SetStrokeStyle { stroke_style } => {
+                    $render_context.set_stroke_style(stroke_style.clone())
+                }
+                DrawCommand::SetTransform {
+                    h_scaling,
+                    h_skewing,
+                    v_skewing,
+                    v_scaling,
+                    h_moving,
+                    v_moving,
+                } => $render_context.set_transform(
+                    *h_scaling, *h_skewing, *v_skewing, *v_scaling, *h_moving, *v_moving,
+                ),
+                DrawCommand::Save => $render_context.save(),
+                DrawCommand::Restore => $render_context.restore(),
+                DrawCommand::Clear { brush } => $render_context.clear(brush),
+            }
+        }
+    };
+}
+
+/// Describes one call on a `RenderContext2D`, e.g. filling a path or changing the fill style.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DrawCommand {
+    FillRect {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    },
+    StrokeRect {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    },
+    DrawBoxShadow {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        border_radius: f64,
+        box_shadow: BoxShadow,
+    },
+    FillText {
+        text: String,
+        x: f64,
+        y: f64,
+    },
+    Fill,
+    Stroke,
+    BeginPath,
+    ClosePath,
+    Rect {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    },
+    RoundedRect {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        radius: f64,
+    },
+    Arc {
+        x: f64,
+        y: f64,
+        radius: f64,
+        start_angle: f64,
+        end_angle: f64,
+    },
+    MoveTo {
+        x: f64,
+        y: f64,
+    },
+    LineTo {
+        x: f64,
+        y: f64,
+    },
+    QuadraticCurveTo {
+        cpx: f64,
+        cpy: f64,
+        x: f64,
+        y: f64,
+    },
+    BezierCurveTo {
+        cp1x: f64,
+        cp1y: f64,
+        cp2x: f64,
+        cp2y: f64,
+        x: f64,
+        y: f64,
+    },
+    /// Draws an image, render target or the output of a pipeline.
+    DrawImage {
+        image: ImageSource,
+        x: f64,
+        y: f64,
+    },
+    DrawImageWithClip {
+        image: ImageSource,
+        clip: Rectangle,
+        x: f64,
+        y: f64,
+    },
+    DrawImageWithSize {
+        image: ImageSource,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    },
+    DrawImageWithClipAndSize {
+        image: ImageSource,
+        clip: Rectangle,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    },
+    Clip,
+    PushLayer {
+        opacity: f32,
+        clip: Option<Rectangle>,
+    },
+    PopLayer,
+    PushEffect {
+        effect: Effect,
+        bounds: Rectangle,
+    },
+    PopEffect,
+    BackdropEffect {
+        effect: Effect,
+        bounds: Rectangle,
+    },
+    SetLineWidth {
+        line_width: f64,
+    },
+    SetLineCap {
+        line_cap: LineCap,
+    },
+    SetLineJoin {
+        line_join: LineJoin,
+    },
+    SetMiterLimit {
+        miter_limit: f64,
+    },
+    SetLineDash {
+        line_dash: Vec<f64>,
+    },
+    SetLineDashOffset {
+        line_dash_offset: f64,
+    },
+    SetAlpha {
+        alpha: f32,
+    },
+    SetFontFamily {
+        family: String,
+    },
+    SetFontSize {
+        size: f64,
+    },
+    SetFontWeight {
+        weight: FontWeight,
+    },
+    SetFontStyle {
+        style: FontStyle,
+    },
+    SetFontStretch {
+        stretch: FontStretch,
+    },
+    SetTextBaseline {
+        text_baseline: TextBaseline,
+    },
+    SetFillStyle {
+        fill_style: Brush,
+    },
+    SetStrokeStyle {
+        stroke_style: Brush,
+    },
+    SetTransform {
+        h_scaling: f64,
+        h_skewing: f64,
+        v_skewing: f64,
+        v_scaling: f64,
+        h_moving: f64,
+        v_moving: f64,
+    },
+    Save,
+    Restore,
+    Clear {
+        brush: Brush,
+    },
+}
+
+// Creates an image that could be drawn by the render context.
+fn image(source: &ImageSource) -> Option<Image> {
+    match source {
+        ImageSource::Path(path) => Image::from_path(path).ok(),
+        ImageSource::Data {
+            width,
+            height,
+            data,
+        } => Image::from_data(*width, *height, data.to_vec()).ok(),
+    }
+}
+
+/// A serializable list of draw commands, recorded by a `RecordingContext2D`. The list could be
+/// replayed into a render context as often as needed, e.g. to draw a cached subtree, to compare
+/// frames in tests or to render on a remote machine.
+///
+/// Fonts are not part of the list, they have to be registered on the render context the list is
+/// replayed into.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DisplayList {
+    commands: Vec<DrawCommand>,
+}
+
+impl DisplayList {
+    /// Creates an empty display list.
+    pub fn new() -> Self {
+        DisplayList::default()
+    }
+
+    /// Appends a command to the list.
+    pub fn push(&mut self, command: DrawCommand) {
+        self.commands.push(command);
+    }
+
+    /// Gets the recorded commands.
+    pub fn commands(&self) -> &[DrawCommand] {
+        &self.commands
+    }
+
+    /// Gets the number of commands.
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    /// Returns `true` if the list contains no commands.
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Removes all commands.
+    pub fn clear(&mut self) {
+        self.commands.clear();
+    }
+
+    /// Draws the commands of the list with the given render context. Images that could not be
+    /// loaded are skipped.
+    // the concurrent render context takes images as mutable reference
+    #[allow(clippy::unnecessary_mut_passed)]
+    pub fn replay(&self, render_context: &mut RenderContext2D) {
+        replay_commands!(&self.commands, render_context);
+    }
+
+    // Draws the commands with a single threaded render context, e.g. an offscreen one.
+    #[allow(clippy::unnecessary_mut_passed)]
+    pub(crate) fn replay_offscreen(&self, render_context: &mut platform::RenderContext2D) {
+        replay_commands!(&self.commands, render_context);
+    }
+}
+
+/// A render context that draws nothing but records all calls into a `DisplayList`. It has the
+/// same drawing api as `RenderContext2D`. Texts are measured with the registered fonts.
+pub struct RecordingContext2D {
+    display_list: DisplayList,
+    measure_context: platform::RenderContext2D,
+    background: Color,
+}
+
+impl Default for RecordingContext2D {
+    fn default() -> Self {
+        RecordingContext2D::new()
+    }
+}
+
+impl RecordingContext2D {
+    /// Creates a new recording context with an empty display list.
+    pub fn new() -> Self {
+        RecordingContext2D {
+            display_list: DisplayList::new(),
+            // only used to measure texts
+            measure_context: platform::RenderContext2D::new(1.0, 1.0),
+            background: Color::default(),
+        }
+    }
+
+    /// Gets the recorded display list.
+    pub fn display_list(&self) -> &DisplayList {
+        &self.display_list
+    }
+
+    /// Returns the recorded display list and starts a new one.
+    pub fn take_display_list(&mut self) -> DisplayList {
+        std::mem::take(&mut self.display_list)
+    }
+
+    fn record(&mut self, command: DrawCommand) {
+        self.display_list.push(command);
+    }
+
+    /// Set the background of the render context.
+    pub fn set_background(&mut self, background: Color) {
+        self.background = background;
+    }
+
+    /// Records the clearing with the background.
+    pub fn start(&mut self) {
+        self.record(DrawCommand::Clear {
+            brush: Brush::from(self.background),
+        });
+    }
+
+    pub fn finish(&mut self) {}
+
+    /// Registers a new font file. The font is only used to measure texts.
+    pub fn register_font(&mut self, family: &str, font_file: &'static [u8]) {
+        self.measure_context.register_font(family, font_file);
+    }
+
+    /// Registers a font from the given font data. The font is only used to measure texts.
+    pub fn register_font_data(&mut self, family: &str, data: Vec<u8>) -> Result<(), String> {
+        self.measure_context.register_font_data(family, data)
+    }
+
+    /// Registers a font from the given font file. The font is only used to measure texts.
+    pub fn register_font_file<P: AsRef<Path> + std::fmt::Debug>(
+        &mut self,
+        family: &str,
+        path: P,
+    ) -> Result<(), String> {
+        self.measure_context.register_font_file(family, path)
+    }
+
+    /// Makes the installed fonts of the system available to measure texts.
+    pub fn register_system_fonts(&mut self) {
+        self.measure_context.register_system_fonts();
+    }
+
+    // Rectangles
+
+    /// Draws a filled rectangle whose starting point is at the coordinates {x, y} with the specified width and height and whose style is determined by the fillStyle attribute.
+    pub fn fill_rect(&mut self, x: f64, y: f64, width: f64, height: f64) {
+        self.record(DrawCommand::FillRect {
+            x,
+            y,
+            width,
+            height,
+        });
+    }
+
+    /// Draws a rectangle that is stroked (outlined) according to the current strokeStyle and other ctx settings.
+    pub fn stroke_rect(&mut self, x: f64, y: f64, width: f64, height: f64) {
+        self.record(DrawCommand::StrokeRect {
+            x,
+            y,
+            width,
+            height,
+        });
+    }
+
+    // Shadows
+
+    /// Draws the blurred shadow of a (rounded) rectangle with the given position, size and corner
+    /// radius.
+    pub fn draw_box_shadow(
+        &mut self,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        border_radius: f64,
+        box_shadow: &BoxShadow,
+    ) {
+        self.record(DrawCommand::DrawBoxShadow {
+            x,
+            y,
+            width,
+            height,
+            border_radius,
+            box_shadow: *box_shadow,
+        });
+    }
+
+    // Text
+
+    /// Draws (fills) a given text at the given (x, y) position.
+    pub fn fill_text(&mut self, text: &str, x: f64, y: f64) {
+        self.record(DrawCommand::FillText {
+            text: text.to_string(),
+            x,
+            y,
+        });
+    }
+
+    pub fn measure(
+        &mut self,
+        text: &str,
+        font_size: f64,
+        family: impl Into<String>,
+    ) -> TextMetrics {
+        self.set_font_family(family);
+        self.set_font_size(font_size);
+        self.measure_text(text)
+    }
+
+    /// Returns a TextMetrics object.
+    pub fn measure_text(&mut self, text: &str) -> TextMetrics {
+        self.measure_context.measure_text(text)
+    }
+
+    /// Measures the shaped glyphs of the text with the current font in visual order.
+    pub fn measure_glyphs(&mut self, text: &str) -> Vec<GlyphMetrics> {
+        self.measure_context.measure_glyphs(text)
+    }
+
+    /// Returns the x position of a caret placed before each char of the text, measured with the
+    /// given font. The last entry is the position after the last char.
+    pub fn caret_positions(
+        &mut self,
+        text: &str,
+        font_size: f64,
+        family: impl Into<String>,
+    ) -> Vec<f64> {
+        let family = family.into();
+        self.set_font_family(family.as_str());
+        self.set_font_size(font_size);
+        self.measure_context
+            .caret_positions(text, font_size, family)
+    }
+
+    /// Fills the current or given path with the current file style.
+    pub fn fill(&mut self) {
+        self.record(DrawCommand::Fill);
+    }
+
+    /// Strokes {outlines} the current or given path with the current stroke style.
+    pub fn stroke(&mut self) {
+        self.record(DrawCommand::Stroke);
+    }
+
+    /// Starts a new path by emptying the list of sub-paths. Call this when you want to create a new path.
+    pub fn begin_path(&mut self) {
+        self.record(DrawCommand::BeginPath);
+    }
+
+    /// Attempts to add a straight line from the current point to the start of the current sub-path. If the shape has already been closed or has only one point, this function does nothing.
+    pub fn close_path(&mut self) {
+        self.record(DrawCommand::ClosePath);
+    }
+
+    /// Adds a rectangle to the current path.
+    pub fn rect(&mut self, x: f64, y: f64, width: f64, height: f64) {
+        self.record(DrawCommand::Rect {
+            x,
+            y,
+            width,
+            height,
+        });
+    }
+
+    /// Creates a rounded rectangle with the given radius.
+    pub fn rounded_rect(&mut self, x: f64, y: f64, width: f64, height: f64, radius: f64) {
+        self.record(DrawCommand::RoundedRect {
+            x,
+            y,
+            width,
+            height,
+            radius,
+        });
+    }
+
+    /// Creates a circular arc centered at (x, y) with a radius of radius. The path starts at startAngle and ends at endAngle.
+    pub fn arc(&mut self, x: f64, y: f64, radius: f64, start_angle: f64, end_angle: f64) {
+        self.record(DrawCommand::Arc {
+            x,
+            y,
+            radius,
+            start_angle,
+            end_angle,
+        });
+    }
+
+    /// Begins a new sub-path at the point specified by the given {x, y} coordinates.
+    pub fn move_to(&mut self, x: f64, y: f64) {
+        self.record(DrawCommand::MoveTo { x, y });
+    }
+
+    /// Adds a straight line to the current sub-path by connecting the sub-path's last point to the specified {x, y} coordinates.
+    pub fn line_to(&mut self, x: f64, y: f64) {
+        self.record(DrawCommand::LineTo { x, y });
+    }
+
+    /// Adds a quadratic Bézier curve to the current sub-path.
+    pub fn quadratic_curve_to(&mut self, cpx: f64, cpy: f64, x: f64, y: f64) {
+        self.record(DrawCommand::QuadraticCurveTo { cpx, cpy, x, y });
+    }
+
+    /// Adds a cubic Bézier curve to the current sub-path.
+    /// It requires three points: the first two are control points and the third one is the end point.
+    /// The starting point is the latest point in the current path, which can be changed using MoveTo{} before creating the Bézier curve.
+    pub fn bezier_curve_to(&mut self, cp1x: f64, cp1y: f64, cp2x: f64, cp2y: f64, x: f64, y: f64) {
+        self.record(DrawCommand::BezierCurveTo {
+            cp1x,
+            cp1y,
+            cp2x,
+            cp2y,
+            x,
+            y,
+        });
+    }
+
+    /// Draws a render target. Its pixels are shared with the display list.
+    pub fn draw_render_target(&mut self, render_target: &RenderTarget, x: f64, y: f64) {
+        self.record(DrawCommand::DrawImage {
+            image: ImageSource::Data {
+                width: render_target.width() as u32,
+                height: render_target.height() as u32,
+                data: render_target.data.clone(),
+            },
+            x,
+            y,
+        });
+    }
+
+    /// Draws the image. Its pixels are shared with the display list.
+    pub fn draw_image(&mut self, image: &Image, x: f64, y: f64) {
+        self.record(DrawCommand::DrawImage {
+            image: image_source(image),
+            x,
+            y,
+        });
+    }
+
+    /// Draws the given part of the image. Its pixels are copied into the display list.
+    pub fn draw_image_with_clip(&mut self, image: &Image, clip: Rectangle, x: f64, y: f64) {
+        self.record(DrawCommand::DrawImageWithClip {
+            image: image_source(image),
+            clip,
+            x,
+            y,
+        });
+    }
+
+    /// Draws the image scaled to the given size. Its pixels are copied into the display list.
+    pub fn draw_image_with_size(&mut self, image: &Image, x: f64, y: f64, width: f64, height: f64) {
+        self.record(DrawCommand::DrawImageWithSize {
+            image: image_source(image),
+            x,
+            y,
+            width,
+            height,
+        });
+    }
+
+    /// Draws the given part of the image scaled to the given size. Its pixels are copied into the
+    /// display list.
+    pub fn draw_image_with_clip_and_size(
+        &mut self,
+        image: &Image,
+        clip: Rectangle,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    ) {
+        self.record(DrawCommand::DrawImageWithClipAndSize {
+            image: image_source(image),
+            clip,
+            x,
+            y,
+            width,
+            height,
+        });
+    }
+
+    /// Draws the pipeline. The output of the pipeline is recorded as image.
+    pub fn draw_pipeline(
+        &mut self,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        pipeline: Box<dyn PipelineTrait>,
+    ) {
+        let mut render_target = RenderTarget::new(width as u32, height as u32);
+        pipeline.draw_pipeline(&mut render_target);
+        self.draw_render_target(&render_target, x, y);
+    }
+
+    /// Creates a clipping path from the current sub-paths. Everything drawn after clip() is called appears inside the clipping path only.
+    pub fn clip(&mut self) {
+        self.record(DrawCommand::Clip);
+    }
+
+    // Layers
+
+    /// Starts an offscreen layer that is composited with the given opacity by `pop_layer`.
+    pub fn push_layer(&mut self, opacity: f32, clip: Option<Rectangle>) {
+        self.record(DrawCommand::PushLayer { opacity, clip });
+    }
+
+    /// Starts an offscreen layer the effect is applied on by `pop_effect`.
+    pub fn push_effect(&mut self, effect: Effect, bounds: Rectangle) {
+        self.record(DrawCommand::PushEffect { effect, bounds });
+    }
+
+    /// Composites the layer that is started by the last `push_layer`.
+    pub fn pop_layer(&mut self) {
+        self.record(DrawCommand::PopLayer);
+    }
+
+    /// Applies the effect of the layer that is started by the last `push_effect`.
+    pub fn pop_effect(&mut self) {
+        self.record(DrawCommand::PopEffect);
+    }
+
+    /// Applies the effect on the pixels that are already drawn inside of the bounds.
+    pub fn apply_backdrop_effect(&mut self, effect: Effect, bounds: Rectangle) {
+        self.record(DrawCommand::BackdropEffect { effect, bounds });
+    }
+
+    // Line styles
+
+    /// Sets the thickness of lines.
+    pub fn set_line_width(&mut self, line_width: f64) {
+        self.record(DrawCommand::SetLineWidth { line_width });
+    }
+
+    /// Sets how the end points of lines are drawn.
+    pub fn set_line_cap(&mut self, line_cap: LineCap) {
+        self.record(DrawCommand::SetLineCap { line_cap });
+    }
+
+    /// Sets how two connecting segments of a line are joined together.
+    pub fn set_line_join(&mut self, line_join: LineJoin) {
+        self.record(DrawCommand::SetLineJoin { line_join });
+    }
+
+    /// Sets the miter limit ratio.
+    pub fn set_miter_limit(&mut self, miter_limit: f64) {
+        self.record(DrawCommand::SetMiterLimit { miter_limit });
+    }
+
+    /// Sets the line dash pattern used when stroking lines.
+    pub fn set_line_dash(&mut self, segments: &[f64]) {
+        self.record(DrawCommand::SetLineDash {
+            line_dash: segments.to_vec(),
+        });
+    }
+
+    /// Sets the line dash offset.
+    pub fn set_line_dash_offset(&mut self, line_dash_offset: f64) {
+        self.record(DrawCommand::SetLineDashOffset { line_dash_offset });
+    }
+
+    /// Sets the alpha value,
+    pub fn set_alpha(&mut self, alpha: f32) {
+        self.record(DrawCommand::SetAlpha { alpha });
+    }
+
+    /// Specifies the font family.
+    pub fn set_font_family(&mut self, family: impl Into<String>) {
+        let family = family.into();
+        self.measure_context.set_font_family(family.as_str());
+        self.record(DrawCommand::SetFontFamily { family });
+    }
+
+    /// Specifies the font size.
+    pub fn set_font_size(&mut self, size: f64) {
+        self.measure_context.set_font_size(size);
+        self.record(DrawCommand::SetFontSize { size });
+    }
+
+    /// Specifies the font weight.
+    pub fn set_font_weight(&mut self, weight: FontWeight) {
+        self.measure_context.set_font_weight(weight);
+        self.record(DrawCommand::SetFontWeight { weight });
+    }
+
+    /// Specifies the font style.
+    pub fn set_font_style(&mut self, style: FontStyle) {
+        self.measure_context.set_font_style(style);
+        self.record(DrawCommand::SetFontStyle { style });
+    }
+
+    /// Specifies the font stretch.
+    pub fn set_font_stretch(&mut self, stretch: FontStretch) {
+        self.measure_context.set_font_stretch(stretch);
+        self.record(DrawCommand::SetFontStretch { stretch });
+    }
+
+    /// Specifies the baseline the y position of `fill_text` refers to.
+    pub fn set_text_baseline(&mut self, text_baseline: TextBaseline) {
+        self.measure_context.set_text_baseline(text_baseline);
+        self.record(DrawCommand::SetTextBaseline { text_baseline });
+    }
+
+    // Fill and stroke style
+
+    /// Specifies the fill color to use inside shapes.
+    pub fn set_fill_style(&mut self, fill_style: Brush) {
+        self.record(DrawCommand::SetFillStyle { fill_style });
+    }
+
+    /// Specifies the fill stroke to use inside shapes.
+    pub fn set_stroke_style(&mut self, stroke_style: Brush) {
+        self.record(DrawCommand::SetStrokeStyle { stroke_style });
+    }
+
+    // Transformations
+
+    /// Sets the transformation.
+    pub fn set_transform(
+        &mut self,
+        h_scaling: f64,
+        h_skewing: f64,
+        v_skewing: f64,
+        v_scaling: f64,
+        h_moving: f64,
+        v_moving: f64,
+    ) {
+        self.record(DrawCommand::SetTransform {
+            h_scaling,
+            h_skewing,
+            v_skewing,
+            v_scaling,
+            h_moving,
+            v_moving,
+        });
+    }
+
+    // Canvas states
+
+    /// Saves the entire state of the canvas by pushing the current state onto a stack.
+    pub fn save(&mut self) {
+        self.measure_context.save();
+        self.record(DrawCommand::Save);
+    }
+
+    /// Restores the most recently saved canvas state by popping the top entry in the drawing state stack.
+    pub fn restore(&mut self) {
+        self.measure_context.restore();
+        self.record(DrawCommand::Restore);
+    }
+
+    pub fn clear(&mut self, brush: &Brush) {
+        self.record(DrawCommand::Clear {
+            brush: brush.clone(),
+        });
+    }
+}
+
+// Shares the pixels of the image.
+fn image_source(image: &Image) -> ImageSource {
+    ImageBrush::from(image.clone()).source
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    fn draw(render_context: &mut RecordingContext2D) {
+        render_context.set_background(Color::rgb(255, 255, 255));
+        render_context.start();
+        render_context.set_fill_style(Brush::from("#4D4C4C"));
+        render_context.fill_rect(2.0, 2.0, 8.0, 4.0);
+        render_context.save();
+        render_context.begin_path();
+        render_context.arc(10.0, 10.0, 5.0, 0.0, std::f64::consts::PI);
+        render_context.clip();
+        render_context.set_stroke_style(Brush::from("#FF0000"));
+        render_context.set_line_width(2.0);
+        render_context.stroke_rect(4.0, 4.0, 12.0, 12.0);
+        render_context.restore();
+        render_context.draw_image(
+            &Image::from_data(2, 1, vec![0xFF00_FF00; 2]).unwrap(),
+            0.0,
+            0.0,
+        );
+    }
+
+    #[test]
+    fn test_record() {
+        let mut render_context = RecordingContext2D::new();
+        draw(&mut render_context);
+
+        let display_list = render_context.take_display_list();
+        assert!(render_context.display_list().is_empty());
+        assert_eq!(display_list.len(), 12);
+        assert_eq!(
+            display_list.commands()[0],
+            DrawCommand::Clear {
+                brush: Brush::from(Color::rgb(255, 255, 255))
+            }
+        );
+        assert_eq!(
+            display_list.commands()[11],
+            DrawCommand::DrawImage {
+                image: ImageSource::Data {
+                    width: 2,
+                    height: 1,
+                    data: Arc::new(vec![0xFF00_FF00; 2])
+                },
+                x: 0.0,
+                y: 0.0
+            }
+        );
+
+        // serialized and deserialized lists are equal
+        let serialized = ron::ser::to_string(&display_list).unwrap();
+        let deserialized: DisplayList = ron::de::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, display_list);
+    }
+
+    #[test]
+    // the images of the concurrent render context are passed mutable
+    #[allow(clippy::unnecessary_mut_passed)]
+    fn test_replay() {
+        let mut recording_context = RecordingContext2D::new();
+        draw(&mut recording_context);
+
+        let mut render_context = RenderContext2D::new(20.0, 20.0);
+        recording_context.display_list().replay(&mut render_context);
+
+        let mut expected = RenderContext2D::new(20.0, 20.0);
+        expected.set_background(Color::rgb(255, 255, 255));
+        expected.start();
+        expected.set_fill_style(Brush::from("#4D4C4C"));
+        expected.fill_rect(2.0, 2.0, 8.0, 4.0);
+        expected.save();
+        expected.begin_path();
+        expected.arc(10.0, 10.0, 5.0, 0.0, std::f64::consts::PI);
+        expected.clip();
+        expected.set_stroke_style(Brush::from("#FF0000"));
+        expected.set_line_width(2.0);
+        expected.stroke_rect(4.0, 4.0, 12.0, 12.0);
+        expected.restore();
+        expected.draw_image(
+            &mut Image::from_data(2, 1, vec![0xFF00_FF00; 2]).unwrap(),
+            0.0,
+            0.0,
+        );
+
+        assert_eq!(render_context.data(), expected.data());
+    }
+}
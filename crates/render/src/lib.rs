@@ -6,25 +6,78 @@ pub mod prelude;
 
 pub use orbtk_utils::prelude as utils;
 
-#[cfg(all(any(feature = "default", feature = "orbraq", feature = "miniraq")))]
+#[cfg(all(any(
+    feature = "default",
+    feature = "orbraq",
+    feature = "orbskia",
+    feature = "miniraq"
+)))]
+#[macro_use]
 mod common;
 
 #[cfg(all(
     not(feature = "miniraq"),
-    any(feature = "default", feature = "orbraq", target_arch = "wasm32"),
+    any(
+        feature = "default",
+        feature = "orbraq",
+        feature = "orbskia",
+        target_arch = "wasm32"
+    ),
 ))]
 pub use self::platform::*;
 
 #[cfg(all(
     not(target_arch = "wasm32"),
     any(feature = "default", feature = "orbraq", feature = "miniraq"),
+    any(not(feature = "orbskia"), feature = "miniraq"),
 ))]
 #[path = "raqote/mod.rs"]
 pub mod platform;
 
+#[cfg(all(
+    not(target_arch = "wasm32"),
+    feature = "orbskia",
+    not(feature = "miniraq")
+))]
+#[path = "tiny_skia/mod.rs"]
+pub mod platform;
+
 #[cfg(all(not(target_arch = "wasm32"), feature = "miniraq"))]
 pub mod concurrent;
 
+#[cfg(all(
+    not(target_arch = "wasm32"),
+    any(
+        feature = "default",
+        feature = "orbraq",
+        feature = "orbskia",
+        feature = "miniraq"
+    ),
+))]
+pub use self::display_list::*;
+
+#[cfg(all(
+    not(target_arch = "wasm32"),
+    any(
+        feature = "default",
+        feature = "orbraq",
+        feature = "orbskia",
+        feature = "miniraq"
+    ),
+))]
+mod display_list;
+
+#[cfg(all(
+    not(target_arch = "wasm32"),
+    any(
+        feature = "default",
+        feature = "orbraq",
+        feature = "orbskia",
+        feature = "miniraq"
+    ),
+))]
+mod svg;
+
 #[cfg(all(
     feature = "miniraq",
     any(
@@ -43,8 +96,10 @@ pub mod platform;
 pub use platform::RenderContext2D;
 
 pub use self::render_target::*;
+pub use self::text_layout::*;
 
 mod render_target;
+mod text_layout;
 
 /// Defines the current configuration of the render ctx.
 #[derive(Debug, Clone)]
@@ -52,7 +107,14 @@ pub struct RenderConfig {
     pub fill_style: utils::Brush,
     pub stroke_style: utils::Brush,
     pub line_width: f64,
+    pub line_cap: utils::LineCap,
+    pub line_join: utils::LineJoin,
+    pub miter_limit: f64,
+    pub line_dash: Vec<f64>,
+    pub line_dash_offset: f64,
     pub font_config: FontConfig,
+    /// The baseline the y position of `fill_text` refers to.
+    pub text_baseline: utils::TextBaseline,
     pub alpha: f32,
 }
 
@@ -62,29 +124,124 @@ impl Default for RenderConfig {
             fill_style: utils::Brush::default(),
             stroke_style: utils::Brush::default(),
             line_width: 1.,
+            line_cap: utils::LineCap::default(),
+            line_join: utils::LineJoin::default(),
+            miter_limit: 10.,
+            line_dash: vec![],
+            line_dash_offset: 0.,
             font_config: FontConfig::default(),
+            // texts are placed by the top of their line, like the layouts of the widgets expect
+            text_baseline: utils::TextBaseline::Top,
             alpha: 1.,
         }
     }
 }
 
 /// The TextMetrics struct represents the dimension of a text.
-#[derive(Clone, Copy, Default, Debug)]
+///
+/// Vertical values are distances in pixels, `baseline` and the bounds are relative to the
+/// position the text is drawn at with the current `TextBaseline`.
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
 pub struct TextMetrics {
+    /// The advance width of the text.
     pub width: f64,
+    /// The height of the line, `ascent` plus `descent` rounded up.
     pub height: f64,
+    /// Distance from the alphabetic baseline to the top of the line.
+    pub ascent: f64,
+    /// Distance from the alphabetic baseline to the bottom of the line.
+    pub descent: f64,
+    /// The recommended gap between two lines.
+    pub line_gap: f64,
+    /// Distance from the y position of the text to its alphabetic baseline, negative if the
+    /// baseline is above the position.
+    pub baseline: f64,
+    /// The bounds of the painted glyphs.
+    pub bounds: utils::Rectangle,
+}
+
+/// Describes a shaped glyph of a measured text, see `measure_glyphs` of the render context.
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub struct GlyphMetrics {
+    /// Byte index of the first char of the text the glyph belongs to.
+    pub index: usize,
+    /// Horizontal position of the glyph relative to the start of the text.
+    pub x: f64,
+    /// The advance width of the glyph.
+    pub advance: f64,
+    /// The bounds of the painted glyph, empty for glyphs without outline, e.g. spaces.
+    pub bounds: utils::Rectangle,
 }
 
 // Internal font helper.
-#[derive(Default, Clone, PartialEq, Debug)]
+#[derive(Clone, PartialEq, Debug)]
 pub struct FontConfig {
+    /// Comma separated list of font families, e.g. `Roboto, Noto Sans CJK, Noto Emoji`. Chars
+    /// that are missing in the first family are taken from the following ones.
     pub family: String,
     pub font_size: f64,
+    /// The preferred weight, the nearest available face of the family is used.
+    pub weight: utils::FontWeight,
+    /// The preferred slant.
+    pub style: utils::FontStyle,
+    /// The preferred width.
+    pub stretch: utils::FontStretch,
+}
+
+impl Default for FontConfig {
+    fn default() -> Self {
+        FontConfig {
+            family: String::default(),
+            // the font size of the default theme
+            font_size: 16.0,
+            weight: utils::FontWeight::default(),
+            style: utils::FontStyle::default(),
+            stretch: utils::FontStretch::default(),
+        }
+    }
+}
+
+impl FontConfig {
+    /// Returns the font families of the fallback list, the first one is the preferred family.
+    pub fn families(&self) -> Vec<&str> {
+        self.family
+            .split(',')
+            .map(|family| family.trim().trim_matches(|c| c == '"' || c == '\''))
+            .filter(|family| !family.is_empty())
+            .collect()
+    }
 }
 
 impl ToString for FontConfig {
     fn to_string(&self) -> String {
-        format!("{}px {}", self.font_size, self.family)
+        // css needs quotes around family names with spaces
+        let families: Vec<String> = self
+            .families()
+            .iter()
+            .map(|family| {
+                if family.contains(char::is_whitespace) {
+                    format!("\"{}\"", family)
+                } else {
+                    family.to_string()
+                }
+            })
+            .collect();
+
+        let mut font = String::new();
+
+        if self.style != utils::FontStyle::Normal {
+            font.push_str(&format!("{} ", self.style.to_string()));
+        }
+
+        if self.weight != utils::FontWeight::NORMAL {
+            font.push_str(&format!("{} ", self.weight.to_string()));
+        }
+
+        if self.stretch != utils::FontStretch::Normal {
+            font.push_str(&format!("{} ", self.stretch.to_string()));
+        }
+
+        format!("{}{}px {}", font, self.font_size, families.join(", "))
     }
 }
 
@@ -127,3 +284,39 @@ impl fmt::Debug for Box<dyn PipelineTrait> {
         write!(f, "Box<dyn PipelineTrait>")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_font_config_families() {
+        let font_config = FontConfig {
+            family: "Roboto-Regular, \"Noto Sans CJK\", Noto Emoji,".to_string(),
+            font_size: 12.0,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            font_config.families(),
+            vec!["Roboto-Regular", "Noto Sans CJK", "Noto Emoji"]
+        );
+        assert_eq!(
+            font_config.to_string(),
+            "12px Roboto-Regular, \"Noto Sans CJK\", \"Noto Emoji\""
+        );
+    }
+
+    #[test]
+    fn test_font_config_to_string() {
+        let font_config = FontConfig {
+            family: "Roboto".to_string(),
+            font_size: 16.0,
+            weight: utils::FontWeight::BOLD,
+            style: utils::FontStyle::Italic,
+            stretch: utils::FontStretch::Condensed,
+        };
+
+        assert_eq!(font_config.to_string(), "italic 700 condensed 16px Roboto");
+    }
+}
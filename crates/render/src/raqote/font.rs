@@ -1,37 +1,614 @@
-use crate::utils::{Color, Rectangle};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    env, fs,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use lru::LruCache;
+use unicode_bidi::BidiInfo;
+
+use crate::{
+    common::{best_face_match, caret_positions, FaceProperties, GlyphCluster},
+    utils::{Color, FontStretch, FontStyle, FontWeight, Rectangle, TextBaseline},
+    FontConfig, GlyphMetrics, TextMetrics,
+};
+
+// Directories that are scanned for system fonts.
+static FONT_DIRECTORIES: [&str; 3] = [
+    "/usr/share/fonts",
+    "/usr/local/share/fonts",
+    "/usr/lib/X11/fonts",
+];
+
+// Font directories inside of the home directory.
+static HOME_FONT_DIRECTORIES: [&str; 2] = [".local/share/fonts", ".fonts"];
+
+// Used to identify fonts in the text cache.
+static NEXT_FONT_ID: AtomicUsize = AtomicUsize::new(0);
+
+// Number of rasterized glyphs that are kept in the text cache.
+const GLYPH_CACHE_SIZE: usize = 2048;
+
+// Number of shaped texts that are kept in the text cache.
+const SHAPE_CACHE_SIZE: usize = 512;
+
+// Glyphs are rasterized at quarter pixel offsets, so cached glyphs could be reused at any position.
+const SUBPIXEL_STEPS: f32 = 4.0;
+
+// A glyph that is placed by the shaper. The position is relative to the start of the text on
+// the baseline.
+#[derive(Debug, Copy, Clone)]
+struct ShapedGlyph {
+    font: usize,
+    id: rusttype::GlyphId,
+    cluster: usize,
+    x: f32,
+    y: f32,
+    advance: f32,
+}
+
+// A run of glyphs with the same direction and font, runs are ordered visually from left to right.
+#[derive(Debug, Clone)]
+struct ShapedRun {
+    range: Range<usize>,
+    rtl: bool,
+    glyphs: Vec<ShapedGlyph>,
+}
+
+// Identifies a rasterized glyph in the text cache.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+struct GlyphKey {
+    font: usize,
+    id: u16,
+    // bits of the f32 scale
+    size: u32,
+    // subpixel offset in `SUBPIXEL_STEPS`
+    offset: (u8, u8),
+}
+
+// Coverage of a rasterized glyph. The bounds are relative to the pixel the glyph is placed on.
+#[derive(Debug, Clone, PartialEq)]
+struct GlyphBitmap {
+    left: i32,
+    top: i32,
+    width: i32,
+    height: i32,
+    coverage: Vec<f32>,
+}
+
+// Identifies a shaped text in the text cache.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ShapeKey {
+    fonts: Vec<usize>,
+    // bits of the f64 size
+    size: u64,
+    text: String,
+}
+
+/// Least recently used cache of rasterized glyphs and shaped texts. Texts that are drawn or
+/// measured again (e.g. on each frame or for each caret position) are not shaped and rasterized
+/// again.
+#[derive(Debug)]
+pub struct TextCache {
+    // `None` for glyphs without outline, e.g. spaces
+    glyphs: LruCache<GlyphKey, Option<Arc<GlyphBitmap>>>,
+    shapes: LruCache<ShapeKey, Arc<Vec<ShapedRun>>>,
+}
+
+impl TextCache {
+    /// Creates a new empty text cache.
+    pub fn new() -> Self {
+        TextCache {
+            glyphs: LruCache::new(GLYPH_CACHE_SIZE),
+            shapes: LruCache::new(SHAPE_CACHE_SIZE),
+        }
+    }
+
+    /// Removes all cached glyphs and texts.
+    pub fn clear(&mut self) {
+        self.glyphs.clear();
+        self.shapes.clear();
+    }
+}
+
+impl Default for TextCache {
+    fn default() -> Self {
+        TextCache::new()
+    }
+}
+
+// Rasterizes the glyph placed at the given subpixel offset.
+fn rasterize_glyph(
+    font: &Font,
+    id: rusttype::GlyphId,
+    scale: rusttype::Scale,
+    offset: (u8, u8),
+) -> Option<GlyphBitmap> {
+    let glyph = font
+        .inner
+        .glyph(id)
+        .scaled(scale)
+        .positioned(rusttype::point(
+            offset.0 as f32 / SUBPIXEL_STEPS,
+            offset.1 as f32 / SUBPIXEL_STEPS,
+        ));
+    let bb = glyph.pixel_bounding_box()?;
+    let (width, height) = (bb.width(), bb.height());
+    let mut coverage = vec![0.0; (width * height) as usize];
+
+    glyph.draw(|x, y, v| coverage[(y as i32 * width + x as i32) as usize] = v);
+
+    Some(GlyphBitmap {
+        left: bb.min.x,
+        top: bb.min.y,
+        width,
+        height,
+        coverage,
+    })
+}
+
+// Splits a position in the pixel and the subpixel offset in `SUBPIXEL_STEPS`.
+fn pixel_offset(position: f32) -> (i32, u8) {
+    let pixel = position.floor();
+    let offset = ((position - pixel) * SUBPIXEL_STEPS).round();
+
+    if offset >= SUBPIXEL_STEPS {
+        (pixel as i32 + 1, 0)
+    } else {
+        (pixel as i32, offset as u8)
+    }
+}
+
+// Reads the family name and the weight, slant and width of the face.
+fn face_properties(data: &[u8]) -> (String, FaceProperties) {
+    let face = match ttf_parser::Face::from_slice(data, 0) {
+        Ok(face) => face,
+        Err(_) => return (String::new(), FaceProperties::default()),
+    };
+
+    let name = |id: u16| {
+        face.names()
+            .filter(|name| name.name_id() == id)
+            .find_map(|name| name.to_string())
+    };
+
+    let family = name(ttf_parser::name_id::TYPOGRAPHIC_FAMILY)
+        .or_else(|| name(ttf_parser::name_id::FAMILY))
+        .unwrap_or_default();
+
+    let style = if face.is_italic() {
+        FontStyle::Italic
+    } else if face.is_oblique() {
+        FontStyle::Oblique
+    } else {
+        FontStyle::Normal
+    };
+
+    (
+        family,
+        FaceProperties {
+            weight: FontWeight::from(face.weight().to_number()),
+            style,
+            stretch: FontStretch::from_width_class(face.width().to_number()),
+        },
+    )
+}
+
+// A face that is parsed once for text shaping.
+struct ShapingFace(rustybuzz::Face<'static>);
+
+impl std::fmt::Debug for ShapingFace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ShapingFace").finish()
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct Font {
+    // borrows the bytes of `inner`, so it has to be declared and dropped first
+    shaping_face: Option<Arc<ShapingFace>>,
+    // owns the bytes of fonts that are loaded at runtime
     inner: rusttype::Font<'static>,
+    id: usize,
+    family: String,
+    properties: FaceProperties,
 }
 
 impl Font {
     pub fn from_bytes(bytes: &'static [u8]) -> Result<Self, &'static str> {
+        let (family, properties) = face_properties(bytes);
+
         rusttype::Font::try_from_bytes(bytes)
-            .map(|font| Font { inner: font })
+            .map(|font| Font {
+                shaping_face: rustybuzz::Face::from_slice(bytes, 0)
+                    .map(|face| Arc::new(ShapingFace(face))),
+                inner: font,
+                id: NEXT_FONT_ID.fetch_add(1, Ordering::Relaxed),
+                family,
+                properties,
+            })
             .ok_or("Could not load font from bytes")
     }
 
-    pub fn measure_text(&self, text: &str, size: f64) -> (f64, f64) {
-        let scale = rusttype::Scale::uniform(size as f32);
-        let v_metrics = self.inner.v_metrics(scale);
-        let offset = rusttype::point(0.0, v_metrics.ascent);
+    /// Creates a font from an owned buffer, e.g. a font file that is loaded at runtime. The
+    /// buffer is moved into the font and not copied.
+    pub fn from_data(data: Vec<u8>) -> Result<Self, &'static str> {
+        let (family, properties) = face_properties(&data);
+
+        let inner = rusttype::Font::try_from_vec(data).ok_or("Could not load font from data")?;
+
+        let shaping_face = match &inner {
+            rusttype::Font::Owned(face) => {
+                let data = face.as_slice();
+
+                // SAFETY: the bytes are owned by the heap allocated face of `inner` and are never
+                // moved or mutated. The shaping face is declared before `inner`, so it is
+                // dropped first and all clones of the font keep both alive together.
+                let data: &'static [u8] =
+                    unsafe { std::slice::from_raw_parts(data.as_ptr(), data.len()) };
+
+                rustybuzz::Face::from_slice(data, 0).map(|face| Arc::new(ShapingFace(face)))
+            }
+            rusttype::Font::Ref(_) => None,
+        };
+
+        Ok(Font {
+            shaping_face,
+            inner,
+            id: NEXT_FONT_ID.fetch_add(1, Ordering::Relaxed),
+            family,
+            properties,
+        })
+    }
+
+    /// Gets the family name of the face, e.g. `Roboto` for `Roboto-Medium`.
+    pub fn family_name(&self) -> &str {
+        &self.family
+    }
+
+    /// Gets the weight, slant and width of the face.
+    pub fn properties(&self) -> FaceProperties {
+        self.properties
+    }
+
+    /// Loads a font from the given file path. Supports TrueType and OpenType fonts.
+    pub fn from_path<P: AsRef<Path> + std::fmt::Debug>(path: P) -> Result<Self, String> {
+        let data = fs::read(&path)
+            .map_err(|e| format!("Could not load font with path: {:?}, {}", path, e))?;
+
+        Font::from_data(data).map_err(|e| format!("{}: {:?}", e, path))
+    }
+
+    // Returns the parsed face that is used to shape texts.
+    fn shaping_face(&self) -> Option<&rustybuzz::Face<'_>> {
+        self.shaping_face.as_ref().map(|face| &face.0)
+    }
+
+    // Returns `true` if the font contains a glyph for the given char.
+    fn has_glyph(&self, c: char) -> bool {
+        self.inner.glyph(c).id().0 != 0
+    }
+}
+
+/// The fonts that are used to render a text. Chars that are missing in the first font are taken
+/// from the next font of the chain that contains them.
+#[derive(Debug, Clone)]
+pub struct FontChain<'a> {
+    fonts: Vec<&'a Font>,
+    cache: Option<&'a RefCell<TextCache>>,
+}
+
+impl<'a> FontChain<'a> {
+    /// Creates a chain from the given fonts, the first one is the preferred font.
+    pub fn new(fonts: Vec<&'a Font>) -> Self {
+        FontChain { fonts, cache: None }
+    }
+
+    /// Uses the given cache for shaped texts and rasterized glyphs.
+    pub fn with_cache(mut self, cache: &'a RefCell<TextCache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
 
-        let pixel_height = size.ceil();
+    /// Returns `true` if the chain contains no font.
+    pub fn is_empty(&self) -> bool {
+        self.fonts.is_empty()
+    }
+
+    // Returns the index of the first font that contains the given char.
+    fn font_index(&self, c: char) -> usize {
+        self.fonts
+            .iter()
+            .position(|font| font.has_glyph(c))
+            .unwrap_or(0)
+    }
+
+    // Splits the given part of the text in ranges of the same font.
+    fn font_runs(&self, text: &str, range: Range<usize>) -> Vec<(Range<usize>, usize)> {
+        let mut runs: Vec<(Range<usize>, usize)> = vec![];
+
+        for (index, c) in text[range.clone()].char_indices() {
+            let start = range.start + index;
+            let end = start + c.len_utf8();
+
+            // whitespaces and control chars are kept in the font of the previous char
+            let font = match runs.last() {
+                Some((_, font)) if c.is_whitespace() || c.is_control() => *font,
+                _ => self.font_index(c),
+            };
+
+            match runs.last_mut() {
+                Some((run, run_font)) if *run_font == font => run.end = end,
+                _ => runs.push((start..end, font)),
+            }
+        }
+
+        runs
+    }
+
+    // Returns the shaped runs of the text from the cache or shapes the text.
+    fn shape(&self, text: &str, size: f64) -> Arc<Vec<ShapedRun>> {
+        let cache = match self.cache {
+            Some(cache) => cache,
+            None => return Arc::new(self.shape_text(text, size)),
+        };
+
+        let key = ShapeKey {
+            fonts: self.fonts.iter().map(|font| font.id).collect(),
+            size: size.to_bits(),
+            text: text.to_string(),
+        };
+
+        if let Some(runs) = cache.borrow_mut().shapes.get(&key) {
+            return runs.clone();
+        }
+
+        let runs = Arc::new(self.shape_text(text, size));
+        cache.borrow_mut().shapes.put(key, runs.clone());
+        runs
+    }
+
+    // Splits the text in runs of the same direction, reorders them with the unicode bidi
+    // algorithm and shapes each run with rustybuzz and the first font that contains its chars.
+    fn shape_text(&self, text: &str, size: f64) -> Vec<ShapedRun> {
+        let bidi_info = BidiInfo::new(text, None);
+        let mut runs = vec![];
+        let mut x = 0.0;
+
+        for paragraph in &bidi_info.paragraphs {
+            let (levels, level_runs) = bidi_info.visual_runs(paragraph, paragraph.range.clone());
 
-        // Glyphs to draw for "RustType". Feel free to try other strings.
-        let glyphs: Vec<rusttype::PositionedGlyph> =
-            self.inner.layout(text, scale, offset).collect();
+            for level_run in level_runs {
+                let rtl = levels[level_run.start].is_rtl();
+                let mut font_runs = self.font_runs(text, level_run);
 
-        let width = glyphs
+                if rtl {
+                    font_runs.reverse();
+                }
+
+                for (range, font) in font_runs {
+                    let face = match self.fonts[font].shaping_face() {
+                        Some(face) => face,
+                        None => continue,
+                    };
+
+                    // rusttype scales by the pixel height of the font and not by units per em
+                    let factor = self.fonts[font].inner.scale_for_pixel_height(size as f32);
+
+                    let mut buffer = rustybuzz::UnicodeBuffer::new();
+                    buffer.push_str(&text[range.clone()]);
+                    buffer.set_direction(if rtl {
+                        rustybuzz::Direction::RightToLeft
+                    } else {
+                        rustybuzz::Direction::LeftToRight
+                    });
+                    buffer.guess_segment_properties();
+
+                    let output = rustybuzz::shape(face, &[], buffer);
+                    let mut glyphs = Vec::with_capacity(output.len());
+
+                    // glyphs of right to left runs are already in visual order
+                    for (info, position) in
+                        output.glyph_infos().iter().zip(output.glyph_positions())
+                    {
+                        let advance = position.x_advance as f32 * factor;
+
+                        glyphs.push(ShapedGlyph {
+                            font,
+                            id: rusttype::GlyphId(info.codepoint as u16),
+                            cluster: range.start + info.cluster as usize,
+                            x: x + position.x_offset as f32 * factor,
+                            y: -position.y_offset as f32 * factor,
+                            advance,
+                        });
+
+                        x += advance;
+                    }
+
+                    runs.push(ShapedRun { range, rtl, glyphs });
+                }
+            }
+        }
+
+        runs
+    }
+
+    // Returns the width of the shaped text.
+    fn shaped_width(runs: &[ShapedRun]) -> f32 {
+        runs.iter()
+            .flat_map(|run| run.glyphs.iter())
+            .map(|g| g.advance)
+            .sum()
+    }
+
+    // Returns the ascent, descent and line gap of the line, the largest values of the fonts the
+    // glyphs are taken from. The descent is positive.
+    fn line_metrics(&self, runs: &[ShapedRun], size: f64) -> (f32, f32, f32) {
+        let scale = rusttype::Scale::uniform(size as f32);
+        let mut fonts: Vec<usize> = runs
             .iter()
-            .rev()
-            .map(|g| g.position().x as f32 + g.unpositioned().h_metrics().advance_width)
-            .next()
-            .unwrap_or(0.0)
-            .ceil() as f64;
+            .flat_map(|run| run.glyphs.iter())
+            .map(|g| g.font)
+            .collect();
+        fonts.sort_unstable();
+        fonts.dedup();
 
-        (width, pixel_height)
+        if fonts.is_empty() {
+            fonts.push(0);
+        }
+
+        fonts
+            .iter()
+            .map(|font| self.fonts[*font].inner.v_metrics(scale))
+            .fold((0.0, 0.0, 0.0), |(ascent, descent, line_gap), v| {
+                (
+                    ascent.max(v.ascent),
+                    descent.max(-v.descent),
+                    line_gap.max(v.line_gap),
+                )
+            })
+    }
+
+    pub fn measure_text(&self, text: &str, size: f64) -> (f64, f64) {
+        let runs = self.shape(text, size);
+        let width = FontChain::shaped_width(&runs).ceil() as f64;
+        let (ascent, descent, _) = self.line_metrics(&runs, size);
+
+        (width, line_height(ascent, descent))
+    }
+
+    /// Measures the text with the line metrics and the bounds of its glyphs. The vertical values
+    /// are relative to the position the text is drawn at with the given baseline.
+    pub fn text_metrics(&self, text: &str, size: f64, baseline: TextBaseline) -> TextMetrics {
+        let runs = self.shape(text, size);
+        let (ascent, descent, line_gap) = self.line_metrics(&runs, size);
+        let baseline = baseline.offset(ascent as f64, descent as f64);
+
+        let mut bounds: Option<Rectangle> = None;
+
+        for glyph in runs.iter().flat_map(|run| run.glyphs.iter()) {
+            if let Some(glyph_bounds) = self.glyph_bounds(glyph, size, baseline) {
+                bounds = Some(match bounds {
+                    Some(mut bounds) => {
+                        bounds.join_with_rectangle(&glyph_bounds);
+                        bounds
+                    }
+                    None => glyph_bounds,
+                });
+            }
+        }
+
+        TextMetrics {
+            width: FontChain::shaped_width(&runs).ceil() as f64,
+            height: line_height(ascent, descent),
+            ascent: ascent as f64,
+            descent: descent as f64,
+            line_gap: line_gap as f64,
+            baseline,
+            bounds: bounds.unwrap_or_default(),
+        }
+    }
+
+    /// Measures the shaped glyphs of the text in visual order. The vertical values are relative
+    /// to the position the text is drawn at with the given baseline.
+    pub fn glyph_metrics(
+        &self,
+        text: &str,
+        size: f64,
+        baseline: TextBaseline,
+    ) -> Vec<GlyphMetrics> {
+        let runs = self.shape(text, size);
+        let (ascent, descent, _) = self.line_metrics(&runs, size);
+        let baseline = baseline.offset(ascent as f64, descent as f64);
+
+        runs.iter()
+            .flat_map(|run| run.glyphs.iter())
+            .map(|glyph| GlyphMetrics {
+                index: glyph.cluster,
+                x: glyph.x as f64,
+                advance: glyph.advance as f64,
+                bounds: self.glyph_bounds(glyph, size, baseline).unwrap_or_default(),
+            })
+            .collect()
+    }
+
+    // The bounds of the painted glyph relative to the start of the text, `None` for glyphs
+    // without outline.
+    fn glyph_bounds(&self, glyph: &ShapedGlyph, size: f64, baseline: f64) -> Option<Rectangle> {
+        self.fonts[glyph.font]
+            .inner
+            .glyph(glyph.id)
+            .scaled(rusttype::Scale::uniform(size as f32))
+            .exact_bounding_box()
+            .map(|rect| {
+                Rectangle::new(
+                    (
+                        (glyph.x + rect.min.x) as f64,
+                        baseline + (glyph.y + rect.min.y) as f64,
+                    ),
+                    (rect.width() as f64, rect.height() as f64),
+                )
+            })
+    }
+
+    /// Returns the ascent and descent of the line the text is drawn in, e.g. to place it on a
+    /// baseline.
+    pub fn vertical_metrics(&self, text: &str, size: f64) -> (f64, f64) {
+        let (ascent, descent, _) = self.line_metrics(&self.shape(text, size), size);
+        (ascent as f64, descent as f64)
+    }
+
+    /// Returns the x position of a caret placed before each char of the text. The last entry is
+    /// the position after the last char.
+    pub fn caret_positions(&self, text: &str, size: f64) -> Vec<f64> {
+        let mut clusters: Vec<GlyphCluster> = vec![];
+
+        for run in self.shape(text, size).iter() {
+            let mut starts: Vec<usize> = run.glyphs.iter().map(|g| g.cluster).collect();
+            starts.sort_unstable();
+            starts.dedup();
+
+            let first = clusters.len();
+
+            for glyph in &run.glyphs {
+                let x = glyph.x as f64;
+                let right = x + glyph.advance as f64;
+
+                match clusters[first..]
+                    .iter_mut()
+                    .find(|c| c.start == glyph.cluster)
+                {
+                    Some(cluster) => {
+                        let left = cluster.x.min(x);
+                        cluster.width = (cluster.x + cluster.width).max(right) - left;
+                        cluster.x = left;
+                    }
+                    None => {
+                        let end = starts
+                            .iter()
+                            .find(|start| **start > glyph.cluster)
+                            .cloned()
+                            .unwrap_or(run.range.end);
+
+                        clusters.push(GlyphCluster {
+                            start: glyph.cluster,
+                            end,
+                            x,
+                            width: glyph.advance as f64,
+                            rtl: run.rtl,
+                        });
+                    }
+                }
+            }
+        }
+
+        caret_positions(text, &clusters)
     }
 
     pub fn render_text(
@@ -64,72 +641,577 @@ impl Font {
         position: (f64, f64),
         clip: Rectangle,
     ) {
+        if self.fonts.is_empty() {
+            return;
+        }
+
         let scale = rusttype::Scale::uniform(config.0 as f32);
 
         // The origin of a line of text is at the baseline (roughly where non-descending letters sit).
         // We don't want to clip the text, so we shift it down with an offset when laying it out.
-        // v_metrics.ascent is the distance between the baseline and the highest edge of any glyph in
-        // the font. That's enough to guarantee that there's no clipping.
-        let v_metrics = self.inner.v_metrics(scale);
-        let offset = rusttype::point(0.0, v_metrics.ascent);
+        // The ascent is the distance between the baseline and the highest edge of any glyph in the
+        // fonts of the line. That's enough to guarantee that there's no clipping.
+        let runs = self.shape(text, config.0);
+        let (ascent, descent, _) = self.line_metrics(&runs, config.0);
+        let offset = rusttype::point(0.0, ascent);
 
-        // Glyphs to draw for "RustType". Feel free to try other strings.
-        let glyphs: Vec<rusttype::PositionedGlyph> =
-            self.inner.layout(text, scale, offset).collect();
+        let pixel_width = FontChain::shaped_width(&runs).ceil() as i32;
+        let pixel_height = line_height(ascent, descent) as i32;
 
-        let pixel_width = glyphs
-            .iter()
-            .rev()
-            .map(|g| g.position().x as f32 + g.unpositioned().h_metrics().advance_width)
-            .next()
-            .unwrap_or(0.0)
-            .ceil() as i32;
-
-        let pixel_height = config.0.ceil() as i32;
-
-        for g in glyphs.iter() {
-            if let Some(bb) = g.pixel_bounding_box() {
-                g.draw(|off_x, off_y, v| {
-                    let off_x = off_x as i32 + bb.min.x;
-                    let off_y = off_y as i32 + bb.min.y;
-
-                    if off_x >= 0
-                        && off_x < pixel_width
-                        && off_y >= 0
-                        && off_y < pixel_height
-                        && position.0 + off_x as f64 >= clip.x()
-                        && position.0 + off_x as f64 <= clip.x() + clip.width()
-                        && position.1 + off_y as f64 >= clip.y()
-                        && position.1 + off_y as f64 <= clip.y() + clip.height()
-                        && position.0 + (off_x as f64) < width
-                        && position.1 + (off_y as f64) < height
-                    {
-                        // Alpha blending from orbclient
-                        let alpha = (config.2 * v * 255.0) as u32;
-                        let new = (alpha << 24) | (config.1.data & 0x00FF_FFFF);
-
-                        let index = ((position.1 as i32 + off_y) * width as i32
-                            + position.0 as i32
-                            + off_x) as usize;
-                        if index >= data.len() {
-                            return;
-                        }
-                        let old = &mut data[index];
-                        if alpha >= 255 {
-                            *old = new;
-                        } else if alpha > 0 {
-                            let n_alpha = 255 - alpha;
-                            let rb = ((n_alpha * (*old & 0x00FF_00FF))
-                                + (alpha * (new & 0x00FF_00FF)))
-                                >> 8;
-                            let ag = (n_alpha * ((*old & 0xFF00_FF00) >> 8))
-                                + (alpha * (0x0100_0000 | ((new & 0x0000_FF00) >> 8)));
-
-                            *old = (rb & 0x00FF_00FF) | (ag & 0xFF00_FF00);
-                        }
+        for glyph in runs.iter().flat_map(|run| run.glyphs.iter()) {
+            let (x, offset_x) = pixel_offset(offset.x + glyph.x);
+            let (y, offset_y) = pixel_offset(offset.y + glyph.y);
+
+            let bitmap = match self.glyph_bitmap(glyph, scale, (offset_x, offset_y)) {
+                Some(bitmap) => bitmap,
+                None => continue,
+            };
+
+            for (i, v) in bitmap.coverage.iter().enumerate() {
+                let off_x = x + bitmap.left + i as i32 % bitmap.width;
+                let off_y = y + bitmap.top + i as i32 / bitmap.width;
+
+                if off_x >= 0
+                    && off_x < pixel_width
+                    && off_y >= 0
+                    && off_y < pixel_height
+                    && position.0 + off_x as f64 >= clip.x()
+                    && position.0 + off_x as f64 <= clip.x() + clip.width()
+                    && position.1 + off_y as f64 >= clip.y()
+                    && position.1 + off_y as f64 <= clip.y() + clip.height()
+                    && position.0 + (off_x as f64) < width
+                    && position.1 + (off_y as f64) < height
+                {
+                    // Alpha blending from orbclient
+                    let alpha = (config.2 * v * 255.0) as u32;
+                    let new = (alpha << 24) | (config.1.data & 0x00FF_FFFF);
+
+                    let index = ((position.1 as i32 + off_y) * width as i32
+                        + position.0 as i32
+                        + off_x) as usize;
+                    if index >= data.len() {
+                        break;
                     }
-                });
+                    let old = &mut data[index];
+                    if alpha >= 255 {
+                        *old = new;
+                    } else if alpha > 0 {
+                        let n_alpha = 255 - alpha;
+                        let rb =
+                            ((n_alpha * (*old & 0x00FF_00FF)) + (alpha * (new & 0x00FF_00FF))) >> 8;
+                        let ag = (n_alpha * ((*old & 0xFF00_FF00) >> 8))
+                            + (alpha * (0x0100_0000 | ((new & 0x0000_FF00) >> 8)));
+
+                        *old = (rb & 0x00FF_00FF) | (ag & 0xFF00_FF00);
+                    }
+                }
             }
         }
     }
+
+    // Returns the rasterized glyph from the cache or rasterizes it.
+    fn glyph_bitmap(
+        &self,
+        glyph: &ShapedGlyph,
+        scale: rusttype::Scale,
+        offset: (u8, u8),
+    ) -> Option<Arc<GlyphBitmap>> {
+        let font = self.fonts[glyph.font];
+
+        let cache = match self.cache {
+            Some(cache) => cache,
+            None => return rasterize_glyph(font, glyph.id, scale, offset).map(Arc::new),
+        };
+
+        let key = GlyphKey {
+            font: font.id,
+            id: glyph.id.0,
+            size: scale.y.to_bits(),
+            offset,
+        };
+
+        if let Some(bitmap) = cache.borrow_mut().glyphs.get(&key) {
+            return bitmap.clone();
+        }
+
+        let bitmap = rasterize_glyph(font, glyph.id, scale, offset).map(Arc::new);
+        cache.borrow_mut().glyphs.put(key, bitmap.clone());
+        bitmap
+    }
+}
+
+// Returns the height of a line rounded up to full pixels, the ascent and descent of a font scaled
+// by its pixel height add up to the font size apart from float errors.
+fn line_height(ascent: f32, descent: f32) -> f64 {
+    ((ascent + descent) as f64 - 0.001).ceil()
+}
+
+/// Returns the font files (TrueType and OpenType) of the standard font directories, mapped by
+/// their file name without extension, e.g. `Roboto-Regular`.
+pub fn system_fonts() -> HashMap<String, PathBuf> {
+    let mut directories: Vec<PathBuf> = FONT_DIRECTORIES.iter().map(PathBuf::from).collect();
+
+    if let Some(home) = env::var_os("HOME") {
+        for directory in HOME_FONT_DIRECTORIES.iter() {
+            directories.push(Path::new(&home).join(directory));
+        }
+    }
+
+    let mut fonts = HashMap::new();
+
+    for directory in directories {
+        scan_font_directory(&directory, &mut fonts);
+    }
+
+    fonts
+}
+
+// Adds the font files of the directory and its sub directories to the map.
+fn scan_font_directory(directory: &Path, fonts: &mut HashMap<String, PathBuf>) {
+    let entries = match fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+
+        // symbolic links to directories are not followed to avoid loops
+        if entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+            scan_font_directory(&path, fonts);
+            continue;
+        }
+
+        let is_font = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| matches!(e.to_lowercase().as_str(), "ttf" | "otf" | "ttc"))
+            .unwrap_or(false);
+
+        if !is_font {
+            continue;
+        }
+
+        if let Some(name) = path.file_stem().and_then(|n| n.to_str()) {
+            fonts.entry(name.to_string()).or_insert(path);
+        }
+    }
+}
+
+// Removes all chars that are not alphanumeric and converts the name to lower case.
+fn normalize_font_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Finds the file of the given font family in a map of font files. The family matches a file
+/// name exactly or ignoring case, spaces and dashes. The regular style is used if the family
+/// names no style, e.g. `Noto Sans` matches `NotoSans-Regular`.
+pub fn find_font_file<'a>(
+    fonts: &'a HashMap<String, PathBuf>,
+    family: &str,
+) -> Option<&'a PathBuf> {
+    if let Some(path) = fonts.get(family) {
+        return Some(path);
+    }
+
+    let family = normalize_font_name(family);
+    let regular = format!("{}regular", family);
+
+    fonts
+        .iter()
+        .filter(|(name, _)| {
+            let name = normalize_font_name(name);
+            name == family || name == regular
+        })
+        .min_by_key(|(name, _)| name.len())
+        .map(|(_, path)| path)
+}
+
+// Removes the style suffix from a font file name, e.g. `NotoSans` for `NotoSans-Bold`.
+fn file_family_name(name: &str) -> &str {
+    match name.rfind('-') {
+        Some(index) => &name[..index],
+        None => name,
+    }
+}
+
+/// Finds the files of all faces of the given font family in a map of font files, e.g. the family
+/// `Noto Sans` matches `NotoSans-Regular` and `NotoSans-Bold`. The names are compared ignoring
+/// case, spaces and dashes.
+pub fn find_font_family_files<'a>(
+    fonts: &'a HashMap<String, PathBuf>,
+    family: &str,
+) -> Vec<(&'a String, &'a PathBuf)> {
+    let family = normalize_font_name(family);
+
+    let mut files: Vec<(&String, &PathBuf)> = fonts
+        .iter()
+        .filter(|(name, _)| {
+            normalize_font_name(name) == family
+                || normalize_font_name(file_family_name(name)) == family
+        })
+        .collect();
+
+    files.sort();
+    files
+}
+
+/// Selects the registered font that is used for the given family and properties. A family that
+/// names a registered font (e.g. `Roboto-Medium`) is used as it is if the default properties are
+/// requested. Otherwise the face that matches the properties best is selected from all fonts of
+/// the same family.
+pub fn select_font<'a>(
+    fonts: &'a HashMap<String, Font>,
+    family: &str,
+    properties: FaceProperties,
+) -> Option<&'a str> {
+    let family_name = match fonts.get_key_value(family) {
+        Some((key, _)) if properties == FaceProperties::default() => return Some(key),
+        Some((_, font)) => normalize_font_name(font.family_name()),
+        None => normalize_font_name(family),
+    };
+
+    let mut faces: Vec<(&String, &Font)> = fonts
+        .iter()
+        .filter(|(key, font)| {
+            key.as_str() == family
+                || normalize_font_name(font.family_name()) == family_name
+                || normalize_font_name(file_family_name(key)) == family_name
+        })
+        .collect();
+    faces.sort_by(|a, b| a.0.cmp(b.0));
+
+    let face_properties: Vec<FaceProperties> =
+        faces.iter().map(|(_, font)| font.properties()).collect();
+
+    best_face_match(&face_properties, properties).map(|index| faces[index].0.as_str())
+}
+
+// The font config a chain of fonts is resolved for.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ChainKey {
+    family: String,
+    properties: FaceProperties,
+}
+
+/// The registered fonts and the system fonts of a render context, with the caches that are used
+/// to shape and draw texts.
+#[derive(Debug, Default)]
+pub struct FontRegistry {
+    fonts: HashMap<String, Font>,
+    // font files that are loaded when their family is used the first time
+    system_fonts: HashMap<String, PathBuf>,
+    text_cache: RefCell<TextCache>,
+    // keys of the fonts that are selected for a family list and face properties
+    chains: HashMap<ChainKey, Vec<String>>,
+}
+
+impl FontRegistry {
+    /// Creates an empty font registry.
+    pub fn new() -> Self {
+        FontRegistry::default()
+    }
+
+    /// Creates a registry with the fonts of this one and empty caches.
+    pub fn share(&self) -> Self {
+        FontRegistry {
+            fonts: self.fonts.clone(),
+            system_fonts: self.system_fonts.clone(),
+            ..FontRegistry::default()
+        }
+    }
+
+    /// Registers a font from static bytes, a family that is already registered is not replaced.
+    pub fn register(&mut self, family: &str, font_file: &'static [u8]) {
+        if self.fonts.contains_key(family) {
+            return;
+        }
+
+        if let Ok(font) = Font::from_bytes(font_file) {
+            self.insert(family.to_string(), font);
+        }
+    }
+
+    /// Registers a font from an owned buffer, e.g. a font that is loaded at runtime.
+    pub fn register_data(&mut self, family: &str, data: Vec<u8>) -> Result<(), String> {
+        if self.fonts.contains_key(family) {
+            return Ok(());
+        }
+
+        let font = Font::from_data(data)?;
+        self.insert(family.to_string(), font);
+        Ok(())
+    }
+
+    /// Loads a font file from the given path and registers it with the family key.
+    pub fn register_file<P: AsRef<Path> + std::fmt::Debug>(
+        &mut self,
+        family: &str,
+        path: P,
+    ) -> Result<(), String> {
+        if self.fonts.contains_key(family) {
+            return Ok(());
+        }
+
+        let font = Font::from_path(path)?;
+        self.insert(family.to_string(), font);
+        Ok(())
+    }
+
+    /// Scans the standard font directories of the system, a system font is loaded when it is
+    /// used the first time.
+    pub fn register_system_fonts(&mut self) {
+        self.system_fonts = system_fonts();
+        self.chains.clear();
+    }
+
+    /// Returns the chain of fonts of the family list of the font config. The face of each family
+    /// is selected by the weight, style and stretch of the config and system fonts are loaded on
+    /// first use. The selected fonts are cached per family list and face properties.
+    pub fn chain(&mut self, font_config: &FontConfig) -> FontChain<'_> {
+        let key = ChainKey {
+            family: font_config.family.clone(),
+            properties: FaceProperties {
+                weight: font_config.weight,
+                style: font_config.style,
+                stretch: font_config.stretch,
+            },
+        };
+
+        if !self.chains.contains_key(&key) {
+            let keys = self.select_fonts(font_config, key.properties);
+            self.chains.insert(key.clone(), keys);
+        }
+
+        let fonts = &self.fonts;
+
+        FontChain::new(
+            self.chains[&key]
+                .iter()
+                .filter_map(|key| fonts.get(key))
+                .collect(),
+        )
+        .with_cache(&self.text_cache)
+    }
+
+    // Returns the keys of the fonts of the families of the font config.
+    fn select_fonts(
+        &mut self,
+        font_config: &FontConfig,
+        properties: FaceProperties,
+    ) -> Vec<String> {
+        let mut keys = vec![];
+
+        for family in font_config.families() {
+            if select_font(&self.fonts, family, properties).is_none() {
+                self.load_system_font_family(family);
+            }
+
+            if let Some(key) = select_font(&self.fonts, family, properties) {
+                keys.push(key.to_string());
+            }
+        }
+
+        keys
+    }
+
+    // Loads all faces of the given family from the system fonts.
+    fn load_system_font_family(&mut self, family: &str) {
+        let mut files: Vec<(String, PathBuf)> = find_font_family_files(&self.system_fonts, family)
+            .into_iter()
+            .map(|(name, path)| (name.clone(), path.clone()))
+            .collect();
+
+        if files.is_empty() {
+            if let Some(path) = find_font_file(&self.system_fonts, family) {
+                files.push((family.to_string(), path.clone()));
+            }
+        }
+
+        for (name, path) in files {
+            if let Ok(font) = Font::from_path(&path) {
+                if !self.fonts.contains_key(&name) {
+                    self.insert(name, font);
+                }
+            }
+
+            // loaded and broken files are not loaded again
+            self.system_fonts.retain(|_, p| *p != path);
+        }
+    }
+
+    // Adds a font, the cached chains could select it and are resolved again.
+    fn insert(&mut self, key: String, font: Font) {
+        self.fonts.insert(key, font);
+        self.chains.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static ROBOTO: &[u8] = include_bytes!("../../../theme/src/fonts/Roboto-Regular.ttf");
+
+    #[test]
+    fn test_text_cache() {
+        let font = Font::from_bytes(ROBOTO).unwrap();
+        let text_cache = RefCell::new(TextCache::new());
+        let cached_chain = FontChain::new(vec![&font]).with_cache(&text_cache);
+        let chain = FontChain::new(vec![&font]);
+
+        assert_eq!(
+            cached_chain.measure_text("Hello World", 16.0),
+            chain.measure_text("Hello World", 16.0)
+        );
+        cached_chain.measure_text("Hello World", 16.0);
+        assert_eq!(text_cache.borrow().shapes.len(), 1);
+
+        let color = Color::rgb(0, 0, 0);
+        let mut cached_data = vec![0; 100 * 20];
+        let mut data = vec![0; 100 * 20];
+
+        cached_chain.render_text(
+            "Hello",
+            &mut cached_data,
+            100.0,
+            20.0,
+            (16.0, color, 1.0),
+            (0.5, 0.0),
+        );
+        let glyphs = text_cache.borrow().glyphs.len();
+
+        // the second call draws the cached glyphs
+        cached_data.iter_mut().for_each(|p| *p = 0);
+        cached_chain.render_text(
+            "Hello",
+            &mut cached_data,
+            100.0,
+            20.0,
+            (16.0, color, 1.0),
+            (0.5, 0.0),
+        );
+        assert_eq!(text_cache.borrow().glyphs.len(), glyphs);
+
+        chain.render_text(
+            "Hello",
+            &mut data,
+            100.0,
+            20.0,
+            (16.0, color, 1.0),
+            (0.5, 0.0),
+        );
+
+        assert!(data.iter().any(|p| *p != 0));
+        assert_eq!(cached_data, data);
+    }
+
+    #[test]
+    fn test_font_registry_chain() {
+        let mut fonts = FontRegistry::new();
+        fonts.register("Roboto-Regular", ROBOTO);
+
+        let font_config = FontConfig {
+            family: "Roboto-Regular, Missing".to_string(),
+            ..FontConfig::default()
+        };
+
+        assert_eq!(fonts.chain(&font_config).fonts.len(), 1);
+        assert_eq!(fonts.chains.len(), 1);
+        assert_eq!(fonts.chain(&font_config).fonts.len(), 1);
+        assert_eq!(fonts.chains.len(), 1);
+
+        // a new font could be selected by the cached chains
+        fonts.register("Missing", ROBOTO);
+        assert!(fonts.chains.is_empty());
+        assert_eq!(fonts.chain(&font_config).fonts.len(), 2);
+    }
+
+    #[test]
+    fn test_text_metrics() {
+        let font = Font::from_bytes(ROBOTO).unwrap();
+        let chain = FontChain::new(vec![&font]);
+
+        let top = chain.text_metrics("Hello", 16.0, TextBaseline::Top);
+        assert_eq!(top.height, 16.0);
+        assert_eq!(top.width, chain.measure_text("Hello", 16.0).0);
+        assert!((top.ascent + top.descent - 16.0).abs() < 0.01);
+        assert_eq!(top.baseline, top.ascent);
+
+        let glyphs = chain.glyph_metrics("Hello", 16.0, TextBaseline::Top);
+        assert_eq!(glyphs.len(), 5);
+        assert_eq!(glyphs[1].index, 1);
+        assert!(glyphs[1].x > 0.0 && glyphs[1].advance > 0.0);
+        assert!(top.bounds.contains_rect(&glyphs[1].bounds));
+
+        // the glyphs of the text are inside of the line and sit on the baseline
+        assert!(top.bounds.y() >= 0.0);
+        assert!((top.bounds.y() + top.bounds.height() - top.baseline).abs() < 1.0);
+
+        let alphabetic = chain.text_metrics("Hello", 16.0, TextBaseline::Alphabetic);
+        assert_eq!(alphabetic.baseline, 0.0);
+        assert!((alphabetic.bounds.y() - (top.bounds.y() - top.ascent)).abs() < 0.01);
+
+        let space = chain.glyph_metrics(" ", 16.0, TextBaseline::Top);
+        assert_eq!(space[0].bounds, Rectangle::default());
+    }
+
+    #[test]
+    fn test_find_font_file() {
+        let mut fonts = HashMap::new();
+        fonts.insert(
+            "NotoSansCJK-Regular".to_string(),
+            PathBuf::from("/fonts/NotoSansCJK-Regular.ttc"),
+        );
+        fonts.insert(
+            "NotoSansCJK-Bold".to_string(),
+            PathBuf::from("/fonts/NotoSansCJK-Bold.ttc"),
+        );
+        fonts.insert(
+            "DejaVuSans".to_string(),
+            PathBuf::from("/fonts/DejaVuSans.ttf"),
+        );
+
+        assert_eq!(
+            find_font_file(&fonts, "NotoSansCJK-Bold"),
+            Some(&PathBuf::from("/fonts/NotoSansCJK-Bold.ttc"))
+        );
+        assert_eq!(
+            find_font_file(&fonts, "Noto Sans CJK"),
+            Some(&PathBuf::from("/fonts/NotoSansCJK-Regular.ttc"))
+        );
+        assert_eq!(
+            find_font_file(&fonts, "dejavu sans"),
+            Some(&PathBuf::from("/fonts/DejaVuSans.ttf"))
+        );
+        assert_eq!(find_font_file(&fonts, "Noto Emoji"), None);
+    }
+
+    #[test]
+    fn test_find_font_family_files() {
+        let mut fonts = HashMap::new();
+        fonts.insert(
+            "NotoSans-Regular".to_string(),
+            PathBuf::from("/fonts/NotoSans-Regular.ttf"),
+        );
+        fonts.insert(
+            "NotoSans-Bold".to_string(),
+            PathBuf::from("/fonts/NotoSans-Bold.ttf"),
+        );
+        fonts.insert(
+            "NotoSansCJK-Regular".to_string(),
+            PathBuf::from("/fonts/NotoSansCJK-Regular.ttc"),
+        );
+
+        let names: Vec<&String> = find_font_family_files(&fonts, "Noto Sans")
+            .iter()
+            .map(|(name, _)| *name)
+            .collect();
+
+        assert_eq!(names, vec!["NotoSans-Bold", "NotoSans-Regular"]);
+        assert_eq!(find_font_family_files(&fonts, "Noto Emoji").len(), 0);
+    }
 }
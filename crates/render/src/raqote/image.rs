@@ -1,11 +1,33 @@
-use std::{fmt, path::Path};
+use std::{
+    collections::HashMap,
+    fmt,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
-use crate::RenderTarget;
+use crate::{
+    premultiply, unpremultiply,
+    utils::{Color, ImageBrush, Value},
+    RenderTarget,
+};
 
+// Images loaded by `Image::shared` by their path, they are kept while they are used.
+static SHARED_IMAGES: Mutex<Option<HashMap<String, Image>>> = Mutex::new(None);
+
+/// A raster image. The pixels are stored in premultiplied ARGB format like the pixels of a
+/// `RenderTarget`, straight alpha of decoded files is converted when they are loaded.
+///
+/// Clones share the pixels, they are copied only if a shared image is changed.
 #[derive(Clone, Default)]
 pub struct Image {
     render_target: RenderTarget,
     source: String,
+    // source of vector images, they are rasterized again if they are resized
+    svg: Option<Arc<Vec<u8>>>,
+    // pixels and delays of the frames of animated images
+    frames: Option<Arc<Vec<(Arc<Vec<u32>>, Duration)>>>,
+    frame: usize,
 }
 
 impl fmt::Debug for Image {
@@ -26,44 +48,263 @@ impl Image {
         Image {
             render_target: RenderTarget::new(width, height),
             source: String::default(),
+            svg: None,
+            frames: None,
+            frame: 0,
         }
     }
 
     /// Draws a u32 slice into the image.
     pub fn draw(&mut self, data: &[u32]) {
-        self.render_target.data.clone_from_slice(data);
+        self.render_target.draw(data);
     }
 
-    /// Create a new image from a boxed slice of colors
+    /// Create a new image from a boxed slice of premultiplied ARGB pixels.
     pub fn from_data(width: u32, height: u32, data: Vec<u32>) -> Result<Self, String> {
         Ok(Image {
             render_target: RenderTarget::from_data(width, height, data).unwrap(),
             source: String::new(),
+            svg: None,
+            frames: None,
+            frame: 0,
         })
     }
 
-    /// Creates a new image from an `RgbaImage`.
+    /// Creates a new animated image from the premultiplied pixels and delays of its frames. All
+    /// frames must have the given size.
+    pub fn from_frames(
+        width: u32,
+        height: u32,
+        frames: Vec<(Vec<u32>, Duration)>,
+    ) -> Result<Self, String> {
+        let size = width as usize * height as usize;
+
+        match frames.first() {
+            Some(_) if frames.iter().all(|(data, _)| data.len() == size) => {}
+            _ => return Err(
+                "Could not create animated image without frames or with frames of different sizes."
+                    .to_string(),
+            ),
+        }
+
+        let frames: Vec<_> = frames
+            .into_iter()
+            .map(|(data, delay)| (Arc::new(data), delay))
+            .collect();
+        let mut image = Self::from_data(width, height, Vec::new())?;
+        image.render_target.data = frames[0].0.clone();
+        image.frames = Some(Arc::new(frames));
+
+        Ok(image)
+    }
+
+    /// Creates a new image from an `RgbaImage` with straight alpha.
     pub fn from_rgba_image(image: image::RgbaImage) -> Result<Self, String> {
-        let data: Vec<u32> = image
+        Self::from_data(image.width(), image.height(), argb_data(&image))
+    }
+
+    /// Converts the image to an `RgbaImage` with straight alpha, e.g. to save it as file.
+    pub fn to_rgba_image(&self) -> image::RgbaImage {
+        let data = self
+            .data()
+            .iter()
+            .flat_map(|pixel| {
+                let [a, r, g, b] = unpremultiply(*pixel).to_be_bytes();
+                vec![r, g, b, a]
+            })
+            .collect();
+
+        image::RgbaImage::from_raw(self.width() as u32, self.height() as u32, data)
+            .unwrap_or_default()
+    }
+
+    /// Saves the current frame of the image as png file.
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        self.to_rgba_image()
+            .save_with_format(path.as_ref(), image::ImageFormat::Png)
+            .map_err(|e| format!("Could not save image to path: {:?}, {}", path.as_ref(), e))
+    }
+
+    /// Returns a copy of the current frame scaled to the given size. Vector images are
+    /// rasterized again, raster images are filtered linearly.
+    pub fn scaled(&self, width: u32, height: u32) -> Image {
+        if self.is_vector() {
+            let mut image = self.clone();
+            if image.resize(width, height).is_ok() {
+                image.frames = None;
+                image.frame = 0;
+                return image;
+            }
+        }
+
+        if width == 0 || height == 0 || self.data().is_empty() {
+            return Image::new(width, height);
+        }
+
+        // filtering premultiplied pixels keeps the color of translucent edges
+        let bytes = self
+            .data()
+            .iter()
+            .flat_map(|pixel| pixel.to_be_bytes().to_vec())
+            .collect();
+        let source = image::RgbaImage::from_raw(self.width() as u32, self.height() as u32, bytes)
+            .expect("Image data does not match its size.");
+        let scaled = image::imageops::resize(
+            &source,
+            width,
+            height,
+            image::imageops::FilterType::Triangle,
+        );
+        let data = scaled
             .pixels()
-            .map(|p| {
-                ((p[3] as u32) << 24) | ((p[0] as u32) << 16) | ((p[1] as u32) << 8) | (p[2] as u32)
+            .map(|p| u32::from_be_bytes([p[0], p[1], p[2], p[3]]))
+            .collect();
+
+        Image::from_data(width, height, data).unwrap_or_default()
+    }
+
+    // Decodes the frames of animated gif and png files, `None` if the file is not animated.
+    fn load_animation(path: &Path) -> Option<Self> {
+        use image::AnimationDecoder;
+
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        let reader = std::io::BufReader::new(std::fs::File::open(path).ok()?);
+
+        let frames = match extension.as_str() {
+            "gif" => image::gif::GifDecoder::new(reader).ok()?.into_frames(),
+            "png" | "apng" => {
+                let decoder = image::png::PngDecoder::new(reader).ok()?;
+
+                if !decoder.is_apng() {
+                    return None;
+                }
+
+                decoder.apng().into_frames()
+            }
+            _ => return None,
+        }
+        .collect_frames()
+        .ok()?;
+
+        if frames.len() < 2 {
+            return None;
+        }
+
+        let (width, height) = frames[0].buffer().dimensions();
+        let frames = frames
+            .iter()
+            .map(|frame| {
+                let (numer, denom) = frame.delay().numer_denom_ms();
+                let delay = if denom == 0 { 0 } else { numer / denom };
+
+                // very short delays are played slower like in browsers
+                let delay = if delay <= 10 { 100 } else { delay };
+
+                (
+                    argb_data(frame.buffer()),
+                    Duration::from_millis(delay as u64),
+                )
             })
             .collect();
-        Self::from_data(image.width(), image.height(), data)
+
+        let mut image = Self::from_frames(width, height, frames).ok()?;
+        image.source = path.to_string_lossy().to_string();
+
+        Some(image)
+    }
+
+    /// Creates a new vector image from the data of a svg or svgz file. It is rasterized at the
+    /// size defined by the svg document.
+    ///
+    /// Texts of the document are not drawn, they have to be converted to paths.
+    pub fn from_svg_data(data: impl Into<Vec<u8>>) -> Result<Self, String> {
+        let data = data.into();
+        let (width, height, pixels) = rasterize_svg(&data, None)?;
+        let mut image = Self::from_data(width, height, pixels)?;
+        image.svg = Some(Arc::new(data));
+
+        Ok(image)
     }
 
-    /// Load an image from file path. Supports BMP and PNG
+    /// Creates a new vector image from a svg document.
+    pub fn from_svg_str(svg: &str) -> Result<Self, String> {
+        Self::from_svg_data(svg.as_bytes())
+    }
+
+    /// Load an image from file path. Supports BMP, PNG, ICO, JPEG, GIF, WebP and SVG (svg and svgz
+    /// extensions).
     pub fn from_path<P: AsRef<Path> + std::fmt::Debug + Clone>(path: P) -> Result<Self, String> {
+        let is_svg = path
+            .as_ref()
+            .extension()
+            .and_then(|e| e.to_str())
+            .map_or(false, |e| {
+                e.eq_ignore_ascii_case("svg") || e.eq_ignore_ascii_case("svgz")
+            });
+
+        if is_svg {
+            let data = std::fs::read(path.as_ref())
+                .map_err(|e| format!("Could not load image width path: {:?}, {}", path, e))?;
+            let mut image = Self::from_svg_data(data)?;
+            image.source = path.as_ref().to_string_lossy().to_string();
+
+            return Ok(image);
+        }
+
+        // the source identifies the image, e.g. to skip loading it again
+        let source = path.as_ref().to_string_lossy().to_string();
+
+        if let Some(mut image) = Self::load_animation(path.as_ref()) {
+            image.source = source;
+            return Ok(image);
+        }
+
         let img = image::open(path.clone());
 
         if let Ok(img) = img {
-            return Self::from_rgba_image(img.to_rgba());
+            let mut image = Self::from_rgba_image(img.to_rgba())?;
+            image.source = source;
+            return Ok(image);
         }
 
         Err(format!("Could not load image width path: {:?}", path))
     }
 
+    /// Loads an image from the file path like `from_path`. If an image with the same path is
+    /// still used, e.g. by another widget, its pixels are shared instead of decoding it again.
+    pub fn shared(path: impl Into<String>) -> Result<Self, String> {
+        let path = path.into();
+
+        if let Some(image) = SHARED_IMAGES
+            .lock()
+            .expect("Image::shared: Cannot lock the shared images.")
+            .as_ref()
+            .and_then(|images| images.get(&path))
+        {
+            return Ok(image.clone());
+        }
+
+        // decoded without the lock, other images could be loaded at the same time
+        let image = Self::from_path(path.clone())?;
+
+        let mut images = SHARED_IMAGES
+            .lock()
+            .expect("Image::shared: Cannot lock the shared images.");
+        let images = images.get_or_insert_with(HashMap::new);
+        images.retain(|_, image| image.is_used());
+        images.insert(path, image.clone());
+
+        Ok(image)
+    }
+
+    // Returns `true` if the pixels of the image are shared with other clones.
+    fn is_used(&self) -> bool {
+        match &self.frames {
+            Some(frames) => Arc::strong_count(frames) > 1,
+            None => Arc::strong_count(&self.render_target.data) > 1,
+        }
+    }
+
     /// Gets the width.
     pub fn width(&self) -> f64 {
         self.render_target.width() as f64
@@ -74,12 +315,90 @@ impl Image {
         self.render_target.height() as f64
     }
 
+    /// Returns `true` if the image has more than one frame.
+    pub fn is_animated(&self) -> bool {
+        self.frame_count() > 1
+    }
+
+    /// Gets the number of frames, `1` for still images.
+    pub fn frame_count(&self) -> usize {
+        self.frames.as_ref().map_or(1, |frames| frames.len())
+    }
+
+    /// Gets the index of the frame that is drawn.
+    pub fn current_frame(&self) -> usize {
+        self.frame
+    }
+
+    /// Gets the time the frame with the given index is shown, `None` for still images.
+    pub fn frame_delay(&self, index: usize) -> Option<Duration> {
+        self.frames
+            .as_ref()
+            .and_then(|frames| frames.get(index))
+            .map(|(_, delay)| *delay)
+    }
+
+    /// Sets the frame of an animated image that is drawn. Indices out of range are ignored.
+    pub fn set_frame(&mut self, index: usize) {
+        if index == self.frame {
+            return;
+        }
+
+        if let Some((data, _)) = self.frames.as_ref().and_then(|frames| frames.get(index)) {
+            self.render_target.data = data.clone();
+            self.frame = index;
+        }
+    }
+
+    /// Returns `true` if the image is created from a svg document.
+    pub fn is_vector(&self) -> bool {
+        self.svg.is_some()
+    }
+
+    /// Rasterizes a vector image again at the given size, the svg document is scaled to fit
+    /// into the size and centered. Raster images are not changed.
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<(), String> {
+        let svg = match &self.svg {
+            Some(svg) => svg.clone(),
+            None => return Ok(()),
+        };
+
+        if self.width() == width as f64 && self.height() == height as f64 {
+            return Ok(());
+        }
+
+        let (width, height, pixels) = rasterize_svg(&svg, Some((width, height)))?;
+        self.render_target = RenderTarget::from_data(width, height, pixels)?;
+
+        Ok(())
+    }
+
+    /// Fills the image with the given color, only the alpha channel of the image is kept. Used
+    /// to draw vector icons in the color of a brush.
+    pub fn tint(&mut self, color: Color) {
+        let (r, g, b) = (color.r() as u32, color.g() as u32, color.b() as u32);
+
+        for pixel in self.render_target.data_mut() {
+            let alpha = (*pixel >> 24) * color.a() as u32 / 255;
+            *pixel =
+                alpha << 24 | (r * alpha / 255) << 16 | (g * alpha / 255) << 8 | b * alpha / 255;
+        }
+    }
+
+    /// Returns `true` if both images share their pixels, e.g. if one is an unchanged clone of
+    /// the other.
+    pub fn shares_pixels(&self, other: &Image) -> bool {
+        Arc::ptr_eq(&self.render_target.data, &other.render_target.data)
+    }
+
+    /// Gets the premultiplied ARGB pixels.
     pub fn data(&self) -> &[u32] {
         &self.render_target.data
     }
 
+    /// Gets the premultiplied ARGB pixels to change them.
     pub fn data_mut(&mut self) -> &mut [u32] {
-        &mut self.render_target.data
+        self.render_target.data_mut()
     }
 }
 
@@ -89,6 +408,64 @@ impl From<(u32, u32, Vec<u32>)> for Image {
     }
 }
 
+// Converts RGBA pixels with straight alpha to premultiplied ARGB.
+fn argb_data(image: &image::RgbaImage) -> Vec<u32> {
+    image
+        .pixels()
+        .map(|p| premultiply(u32::from_be_bytes([p[3], p[0], p[1], p[2]])))
+        .collect()
+}
+
+// Renders the svg document to premultiplied ARGB pixels, at the size of the document or
+// scaled to fit into the given size.
+fn rasterize_svg(data: &[u8], size: Option<(u32, u32)>) -> Result<(u32, u32, Vec<u32>), String> {
+    let tree = usvg::Tree::from_data(data, &usvg::Options::default().to_ref())
+        .map_err(|e| format!("Could not parse svg: {}", e))?;
+    let svg_size = tree.svg_node().size.to_screen_size();
+
+    let (fit_to, width, height) = match size {
+        Some((width, height)) => (usvg::FitTo::Size(width, height), width, height),
+        None => (usvg::FitTo::Original, svg_size.width(), svg_size.height()),
+    };
+
+    let fitted = fit_to
+        .fit_to(svg_size)
+        .ok_or_else(|| "Could not rasterize svg with an empty size.".to_string())?;
+    let mut pixmap = tiny_skia::Pixmap::new(fitted.width(), fitted.height())
+        .ok_or_else(|| "Could not rasterize svg with an empty size.".to_string())?;
+    resvg::render(
+        &tree,
+        fit_to,
+        tiny_skia::Transform::default(),
+        pixmap.as_mut(),
+    )
+    .ok_or_else(|| "Could not rasterize svg.".to_string())?;
+
+    // centers the document if its aspect ratio is different from the one of the size
+    let offset_x = (width.saturating_sub(fitted.width()) / 2) as usize;
+    let offset_y = (height.saturating_sub(fitted.height()) / 2) as usize;
+    let mut pixels = vec![0; width as usize * height as usize];
+
+    for (y, row) in pixmap
+        .data()
+        .chunks_exact(fitted.width() as usize * 4)
+        .enumerate()
+        .take(height as usize - offset_y)
+    {
+        let start = (y + offset_y) * width as usize + offset_x;
+
+        for (pixel, p) in pixels[start..]
+            .iter_mut()
+            .zip(row.chunks_exact(4))
+            .take(width as usize - offset_x)
+        {
+            *pixel = u32::from_be_bytes([p[3], p[0], p[1], p[2]]);
+        }
+    }
+
+    Ok((width, height, pixels))
+}
+
 pub fn os_path(path: String) -> String {
     if cfg!(windows) {
         path.replace("/", "\\")
@@ -101,14 +478,180 @@ pub fn os_path(path: String) -> String {
 
 impl From<&str> for Image {
     fn from(s: &str) -> Image {
-        Image::from_path(os_path(s.to_string())).unwrap()
+        Image::shared(os_path(s.to_string())).unwrap()
     }
 }
 
 impl From<String> for Image {
     fn from(s: String) -> Image {
-        Image::from_path(os_path(s)).unwrap()
+        Image::shared(os_path(s)).unwrap()
+    }
+}
+
+// Used by themes, an image that could not be loaded is drawn empty.
+impl From<Value> for Image {
+    fn from(v: Value) -> Image {
+        Image::shared(os_path(v.get::<String>())).unwrap_or_default()
+    }
+}
+
+impl From<Image> for ImageBrush {
+    fn from(image: Image) -> ImageBrush {
+        ImageBrush::from(image.render_target)
     }
 }
 
 // --- Conversions ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SVG: &str = r##"<svg xmlns="http://www.w3.org/2000/svg" width="4" height="2" viewBox="0 0 4 2">
+        <rect width="4" height="2" fill="#ff0000"/>
+    </svg>"##;
+
+    #[test]
+    fn test_from_svg_str() {
+        let image = Image::from_svg_str(SVG).unwrap();
+
+        assert!(image.is_vector());
+        assert_eq!(image.width(), 4.0);
+        assert_eq!(image.height(), 2.0);
+        assert_eq!(image.data()[0], 0xFFFF_0000);
+
+        assert!(Image::from_svg_str("no svg").is_err());
+    }
+
+    #[test]
+    fn test_resize() {
+        let mut image = Image::from_svg_str(SVG).unwrap();
+        image.resize(8, 8).unwrap();
+
+        assert_eq!(image.width(), 8.0);
+        assert_eq!(image.height(), 8.0);
+
+        // the document is centered
+        assert_eq!(image.data()[0], 0);
+        assert_eq!(image.data()[4 * 8], 0xFFFF_0000);
+
+        let mut image = Image::new(2, 2);
+        image.resize(8, 8).unwrap();
+        assert_eq!(image.width(), 2.0);
+    }
+
+    #[test]
+    fn test_frames() {
+        let mut image = Image::from_frames(
+            1,
+            1,
+            vec![
+                (vec![0xFFFF_0000], Duration::from_millis(50)),
+                (vec![0xFF00_FF00], Duration::from_millis(100)),
+            ],
+        )
+        .unwrap();
+
+        assert!(image.is_animated());
+        assert_eq!(image.frame_count(), 2);
+        assert_eq!(image.data(), &[0xFFFF_0000]);
+        assert_eq!(image.frame_delay(1), Some(Duration::from_millis(100)));
+
+        image.set_frame(1);
+        assert_eq!(image.current_frame(), 1);
+        assert_eq!(image.data(), &[0xFF00_FF00]);
+
+        image.set_frame(2);
+        assert_eq!(image.current_frame(), 1);
+
+        assert!(!Image::new(1, 1).is_animated());
+        assert!(Image::from_frames(2, 1, vec![(vec![0], Duration::default())]).is_err());
+    }
+
+    #[test]
+    fn test_tint() {
+        let original = Image::from_data(2, 1, vec![0xFFFF_0000, 0x8080_0000]).unwrap();
+        let mut image = original.clone();
+        assert!(image.shares_pixels(&original));
+
+        image.tint(Color::rgb(0, 0, 255));
+
+        assert_eq!(image.data(), &[0xFF00_00FF, 0x8000_0080]);
+        assert!(!image.shares_pixels(&original));
+    }
+
+    #[test]
+    fn test_rgba_image() {
+        let rgba =
+            image::RgbaImage::from_raw(2, 1, vec![255, 128, 0, 255, 255, 128, 0, 128]).unwrap();
+        let image = Image::from_rgba_image(rgba.clone()).unwrap();
+
+        // translucent pixels are premultiplied when they are loaded
+        assert_eq!(image.data(), &[0xFFFF_8000, 0x8080_4000]);
+        assert_eq!(image.to_rgba_image(), rgba);
+    }
+
+    #[test]
+    fn test_scaled() {
+        let image = Image::from_data(2, 1, vec![0xFFFF_0000, 0xFFFF_0000]).unwrap();
+        let scaled = image.scaled(4, 2);
+
+        assert_eq!(scaled.width(), 4.0);
+        assert_eq!(scaled.height(), 2.0);
+        assert!(scaled.data().iter().all(|p| *p == 0xFFFF_0000));
+        assert!(image.scaled(0, 2).data().is_empty());
+    }
+
+    #[test]
+    fn test_shared() {
+        let temp_path = |name: &str| {
+            std::env::temp_dir()
+                .join(name)
+                .to_string_lossy()
+                .to_string()
+        };
+        let path = temp_path("orbtk_render_test_shared.png");
+        let other_path = temp_path("orbtk_render_test_shared_other.png");
+        let pixel = Image::from_data(1, 1, vec![0xFFFF_0000]).unwrap();
+        pixel.save_png(&path).unwrap();
+        pixel.save_png(&other_path).unwrap();
+
+        let image = Image::shared(path.clone()).unwrap();
+        let shared = Image::shared(path.clone()).unwrap();
+
+        // the pixels are shared until one of the images is changed
+        assert!(Arc::ptr_eq(
+            &image.render_target.data,
+            &shared.render_target.data
+        ));
+
+        let mut changed = shared.clone();
+        changed.data_mut()[0] = 0;
+        assert_eq!(image.data(), &[0xFFFF_0000]);
+
+        // unused images are removed if another image is loaded
+        drop((image, shared, changed));
+        let other = Image::shared(other_path.clone());
+        let _ = std::fs::remove_file(path.clone());
+        let _ = std::fs::remove_file(other_path);
+
+        assert!(other.is_ok());
+        assert!(!SHARED_IMAGES
+            .lock()
+            .unwrap()
+            .as_ref()
+            .unwrap()
+            .contains_key(&path));
+    }
+
+    #[test]
+    fn test_save_png() {
+        let path = std::env::temp_dir().join("orbtk_render_test_save_png.png");
+        let image = Image::from_data(2, 1, vec![0xFFFF_8000, 0x8080_4000]).unwrap();
+        image.save_png(&path).unwrap();
+        let loaded = Image::from_path(path.clone()).unwrap();
+        let _ = std::fs::remove_file(path);
+
+        assert_eq!(loaded.data(), image.data());
+    }
+}
@@ -1,7 +1,10 @@
 use smallvec::SmallVec;
-use std::{cmp, collections::HashMap};
+use std::{cmp, path::Path};
 
-use crate::{common::*, utils::*, PipelineTrait, RenderConfig, RenderTarget, TextMetrics};
+use crate::{
+    common::*, utils::*, DisplayList, DrawCommand, GlyphMetrics, PipelineTrait, RenderConfig,
+    RenderTarget, TextMetrics,
+};
 
 pub use self::font::*;
 pub use self::image::Image;
@@ -9,7 +12,21 @@ pub use self::image::Image;
 mod font;
 mod image;
 
-type StatesOnStack = [(RenderConfig, PathRect, usize); 2];
+type StatesOnStack = [(RenderConfig, PathRect, usize, raqote::Transform); 2];
+
+// Offscreen layer that is composited on its parent draw target when it is popped.
+struct Layer {
+    // the draw target the layer is composited on
+    draw_target: raqote::DrawTarget,
+    clips_count: usize,
+    effect: Effect,
+    opacity: f32,
+    // position and size of the layer in the parent draw target
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+}
 
 /// The RenderContext2D trait, provides the rendering ctx. It is used for drawing shapes, text, images, and other objects.
 pub struct RenderContext2D {
@@ -17,9 +34,16 @@ pub struct RenderContext2D {
     path: raqote::Path,
     config: RenderConfig,
     saved_states: SmallVec<StatesOnStack>,
-    fonts: HashMap<String, Font>,
+    fonts: FontRegistry,
+    box_shadows: BoxShadowCache,
+    brush_images: BrushImageCache<Image>,
     path_rect: PathRect,
     clips_count: usize,
+    layers: Vec<Layer>,
+    // parts of the draw target that are redrawn since the last `take_damaged_regions`
+    damaged_regions: Vec<Rectangle>,
+    // draw calls that are recorded since `start_recording`
+    recording: Option<DisplayList>,
 
     background: Color,
 }
@@ -35,9 +59,14 @@ impl RenderContext2D {
             },
             config: RenderConfig::default(),
             saved_states: SmallVec::<StatesOnStack>::new(),
-            fonts: HashMap::new(),
+            fonts: FontRegistry::new(),
+            box_shadows: BoxShadowCache::new(),
+            brush_images: BrushImageCache::new(),
             path_rect: PathRect::new(None),
             clips_count: 0,
+            layers: vec![],
+            damaged_regions: vec![],
+            recording: None,
             background: Color::default(),
         }
     }
@@ -49,37 +78,40 @@ impl RenderContext2D {
 
     pub fn resize(&mut self, width: f64, height: f64) {
         self.draw_target = raqote::DrawTarget::new(width as i32, height as i32);
-    }
-
-    /// Registers a new font file.
-    pub fn register_font(&mut self, family: &str, font_file: &'static [u8]) {
-        if self.fonts.contains_key(family) {
-            return;
-        }
-
-        if let Ok(font) = Font::from_bytes(font_file) {
-            self.fonts.insert(family.to_string(), font);
-        }
+        // the clips are removed with the former draw target
+        self.clips_count = 0;
     }
 
     // Rectangles
 
     /// Draws a filled rectangle whose starting point is at the coordinates {x, y} with the specified width and height and whose style is determined by the fillStyle attribute.
     pub fn fill_rect(&mut self, x: f64, y: f64, width: f64, height: f64) {
+        self.record(|| DrawCommand::FillRect {
+            x,
+            y,
+            width,
+            height,
+        });
+        let rect = Rectangle::new((x, y), (width, height));
+        self.load_brush_image(true);
+        let brush_clip = image_brush_clip(&self.config.fill_style, rect, &self.brush_images);
+        if let Some(brush_clip) = &brush_clip {
+            self.draw_target.push_clip(brush_clip);
+        }
         self.draw_target.fill_rect(
             x as f32,
             y as f32,
             width as f32,
             height as f32,
-            &brush_to_source(
-                &self.config.fill_style,
-                Rectangle::new((x, y), (width, height)),
-            ),
+            &brush_to_source(&self.config.fill_style, rect, &self.brush_images),
             &raqote::DrawOptions {
                 alpha: self.config.alpha,
                 ..Default::default()
             },
         );
+        if brush_clip.is_some() {
+            self.draw_target.pop_clip();
+        }
     }
 
     /// Draws a rectangle that is stroked (outlined) according to the current strokeStyle and other ctx settings.
@@ -88,10 +120,63 @@ impl RenderContext2D {
         self.stroke();
     }
 
+    // Shadows
+
+    /// Draws the blurred shadow of a (rounded) rectangle with the given position, size and corner
+    /// radius. The shadow is moved by the offset of the box shadow and grows or shrinks by its spread.
+    pub fn draw_box_shadow(
+        &mut self,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        border_radius: f64,
+        box_shadow: &BoxShadow,
+    ) {
+        self.record(|| DrawCommand::DrawBoxShadow {
+            x,
+            y,
+            width,
+            height,
+            border_radius,
+            box_shadow: *box_shadow,
+        });
+        if box_shadow.is_transparent()
+            || width + 2.0 * box_shadow.spread <= 0.0
+            || height + 2.0 * box_shadow.spread <= 0.0
+        {
+            return;
+        }
+
+        let shadow = self
+            .box_shadows
+            .get(width, height, border_radius, box_shadow);
+
+        self.draw_target.draw_image_at(
+            (x + box_shadow.offset.x() - box_shadow.spread - shadow.padding) as f32,
+            (y + box_shadow.offset.y() - box_shadow.spread - shadow.padding) as f32,
+            &raqote::Image {
+                data: &shadow.data,
+                width: shadow.width as i32,
+                height: shadow.height as i32,
+            },
+            &raqote::DrawOptions {
+                alpha: self.config.alpha,
+                ..Default::default()
+            },
+        );
+    }
+
     // Text
 
-    /// Draws (fills) a given text at the given (x, y) position.
+    /// Draws (fills) a given text at the given (x, y) position. The text is transformed and
+    /// clipped like all other draw calls.
     pub fn fill_text(&mut self, text: &str, x: f64, y: f64) {
+        self.record(|| DrawCommand::FillText {
+            text: text.to_string(),
+            x,
+            y,
+        });
         if text.is_empty() {
             return;
         }
@@ -105,100 +190,136 @@ impl RenderContext2D {
             return;
         }
 
-        if let Some(font) = self.fonts.get(&self.config.font_config.family) {
-            let width = self.draw_target.width() as f64;
-            let height = self.draw_target.height() as f64;
-
-            if let Some(rect) = self.path_rect.get_clip() {
-                font.render_text_clipped(
-                    text,
-                    self.draw_target.get_data_mut(),
-                    width,
-                    height,
-                    (self.config.font_config.font_size, color, self.config.alpha),
-                    (x, y),
-                    rect,
-                );
-            } else {
-                font.render_text(
-                    text,
-                    self.draw_target.get_data_mut(),
-                    width,
-                    height,
-                    (self.config.font_config.font_size, color, self.config.alpha),
-                    (x, y),
-                );
-            }
-        }
-    }
-
-    pub fn measure(
-        &mut self,
-        text: &str,
-        font_size: f64,
-        family: impl Into<String>,
-    ) -> TextMetrics {
-        self.set_font_family(family);
-        self.set_font_size(font_size);
-        self.measure_text(text)
-    }
+        let font_size = self.config.font_config.font_size;
 
-    /// Returns a TextMetrics object.
-    pub fn measure_text(&mut self, text: &str) -> TextMetrics {
-        let mut text_metrics = TextMetrics::default();
+        // the text is rendered into its own pixels, they are drawn like an image so the text is
+        // transformed and clipped like all other draw calls
+        let (data, width, height, left, top) = {
+            let font_chain = self.fonts.chain(&self.config.font_config);
 
-        if text.is_empty() {
-            return text_metrics;
-        }
+            if font_chain.is_empty() {
+                return;
+            }
 
-        if let Some(font) = self.fonts.get(&self.config.font_config.family) {
-            let (width, height) = font.measure_text(text, self.config.font_config.font_size);
+            // the pixels start at the top of the line
+            let (ascent, descent) = font_chain.vertical_metrics(text, font_size);
+            let top = y + self.config.text_baseline.offset(ascent, descent) - ascent;
+
+            // the pixels are placed on whole pixels and the glyphs keep their subpixel offset, so
+            // the text is not resampled if it is not transformed
+            let (left, offset_x) = (x.floor(), x - x.floor());
+            let (top, offset_y) = (top.floor(), top - top.floor());
+
+            let (width, height) = font_chain.measure_text(text, font_size);
+            let width = (width + offset_x).ceil().max(1.0);
+            let height = (height + offset_y).ceil().max(1.0);
+            let mut data = vec![0; (width * height) as usize];
+
+            font_chain.render_text(
+                text,
+                &mut data,
+                width,
+                height,
+                (font_size, color, self.config.alpha),
+                (offset_x, offset_y),
+            );
 
-            text_metrics.width = width;
-            text_metrics.height = height;
-        }
+            (data, width as i32, height as i32, left, top)
+        };
 
-        text_metrics
+        self.draw_target.draw_image_at(
+            left as f32,
+            top as f32,
+            &raqote::Image {
+                data: &data,
+                width,
+                height,
+            },
+            &raqote::DrawOptions::default(),
+        );
     }
 
     /// Fills the current or given path with the current file style.
     pub fn fill(&mut self) {
+        self.record(|| DrawCommand::Fill);
         let rect = match self.path_rect.get_rect() {
             Some(rect) => rect,
             None => return, // The path is empty, do nothing
         };
+        self.load_brush_image(true);
+        let brush_clip = image_brush_clip(&self.config.fill_style, rect, &self.brush_images);
+        if let Some(brush_clip) = &brush_clip {
+            self.draw_target.push_clip(brush_clip);
+        }
         self.draw_target.fill(
             &self.path,
-            &brush_to_source(&self.config.fill_style, rect),
+            &brush_to_source(&self.config.fill_style, rect, &self.brush_images),
             &raqote::DrawOptions {
                 alpha: self.config.alpha,
                 ..Default::default()
             },
         );
+        if brush_clip.is_some() {
+            self.draw_target.pop_clip();
+        }
     }
 
     /// Strokes {outlines} the current or given path with the current stroke style.
     pub fn stroke(&mut self) {
+        self.record(|| DrawCommand::Stroke);
         let rect = match self.path_rect.get_rect() {
             Some(rect) => rect,
             None => return, // The path is empty, do nothing
         };
+        self.load_brush_image(false);
+        let brush_clip = image_brush_clip(&self.config.stroke_style, rect, &self.brush_images);
+        if let Some(brush_clip) = &brush_clip {
+            self.draw_target.push_clip(brush_clip);
+        }
         self.draw_target.stroke(
             &self.path,
-            &brush_to_source(&self.config.stroke_style, rect),
+            &brush_to_source(&self.config.stroke_style, rect, &self.brush_images),
             &raqote::StrokeStyle {
                 width: self.config.line_width as f32,
-                ..Default::default()
+                cap: line_cap_to_raqote(self.config.line_cap),
+                join: line_join_to_raqote(self.config.line_join),
+                miter_limit: self.config.miter_limit as f32,
+                dash_array: self.config.line_dash.iter().map(|d| *d as f32).collect(),
+                dash_offset: self.config.line_dash_offset as f32,
             },
             &raqote::DrawOptions {
                 alpha: self.config.alpha,
                 ..Default::default()
             },
         );
+        if brush_clip.is_some() {
+            self.draw_target.pop_clip();
+        }
+    }
+
+    // Loads the image of the fill or stroke style if it is an image brush with a not yet loaded path.
+    fn load_brush_image(&mut self, fill: bool) {
+        let brush = if fill {
+            &self.config.fill_style
+        } else {
+            &self.config.stroke_style
+        };
+
+        if let Brush::Image(ImageBrush {
+            source: ImageSource::Path(path),
+            ..
+        }) = brush
+        {
+            // shares the pixels with images of the same path that are still used
+            self.brush_images.load(path, |path| {
+                Image::shared(image::os_path(path.to_string())).ok()
+            });
+        }
     }
 
     /// Starts a new path by emptying the list of sub-paths. Call this when you want to create a new path.
     pub fn begin_path(&mut self) {
+        self.record(|| DrawCommand::BeginPath);
         self.path = raqote::Path {
             ops: Vec::new(),
             winding: raqote::Winding::NonZero,
@@ -208,6 +329,7 @@ impl RenderContext2D {
 
     /// Attempts to add a straight line from the current point to the start of the current sub-path. If the shape has already been closed or has only one point, this function does nothing.
     pub fn close_path(&mut self) {
+        self.record(|| DrawCommand::ClosePath);
         let mut path_builder = raqote::PathBuilder::from(self.path.clone());
         path_builder.close();
         self.path = path_builder.finish();
@@ -216,14 +338,58 @@ impl RenderContext2D {
 
     /// Adds a rectangle to the current path.
     pub fn rect(&mut self, x: f64, y: f64, width: f64, height: f64) {
+        self.record(|| DrawCommand::Rect {
+            x,
+            y,
+            width,
+            height,
+        });
         let mut path_builder = raqote::PathBuilder::from(self.path.clone());
         path_builder.rect(x as f32, y as f32, width as f32, height as f32);
         self.path = path_builder.finish();
         self.path_rect.record_rect(x, y, width, height);
     }
 
+    /// Adds a rectangle with rounded corners to the current path. The radius is limited to half of
+    /// the width and height. Could be used with `clip` to clip to the shape of a rounded border.
+    pub fn rounded_rect(&mut self, x: f64, y: f64, width: f64, height: f64, radius: f64) {
+        self.record(|| DrawCommand::RoundedRect {
+            x,
+            y,
+            width,
+            height,
+            radius,
+        });
+        self.path_rect.record_rect(x, y, width, height);
+
+        let radius = rounded_rect_radius(width, height, radius);
+        let r = (x + width) as f32;
+        let b = (y + height) as f32;
+        let (x, y, radius) = (x as f32, y as f32, radius as f32);
+
+        let mut path_builder = raqote::PathBuilder::from(self.path.clone());
+        path_builder.move_to(x + radius, y);
+        path_builder.line_to(r - radius, y);
+        path_builder.quad_to(r, y, r, y + radius);
+        path_builder.line_to(r, b - radius);
+        path_builder.quad_to(r, b, r - radius, b);
+        path_builder.line_to(x + radius, b);
+        path_builder.quad_to(x, b, x, b - radius);
+        path_builder.line_to(x, y + radius);
+        path_builder.quad_to(x, y, x + radius, y);
+        path_builder.close();
+        self.path = path_builder.finish();
+    }
+
     /// Creates a circular arc centered at (x, y) with a radius of radius. The path starts at startAngle and ends at endAngle.
     pub fn arc(&mut self, x: f64, y: f64, radius: f64, start_angle: f64, end_angle: f64) {
+        self.record(|| DrawCommand::Arc {
+            x,
+            y,
+            radius,
+            start_angle,
+            end_angle,
+        });
         let mut path_builder = raqote::PathBuilder::from(self.path.clone());
         path_builder.arc(
             x as f32,
@@ -240,6 +406,7 @@ impl RenderContext2D {
     /// Begins a new sub-path at the point specified by the given {x, y} coordinates.
 
     pub fn move_to(&mut self, x: f64, y: f64) {
+        self.record(|| DrawCommand::MoveTo { x, y });
         let mut path_builder = raqote::PathBuilder::from(self.path.clone());
         path_builder.move_to(x as f32, y as f32);
         self.path = path_builder.finish();
@@ -248,6 +415,7 @@ impl RenderContext2D {
 
     /// Adds a straight line to the current sub-path by connecting the sub-path's last point to the specified {x, y} coordinates.
     pub fn line_to(&mut self, x: f64, y: f64) {
+        self.record(|| DrawCommand::LineTo { x, y });
         let mut path_builder = raqote::PathBuilder::from(self.path.clone());
         path_builder.line_to(x as f32, y as f32);
         self.path = path_builder.finish();
@@ -256,6 +424,7 @@ impl RenderContext2D {
 
     /// Adds a quadratic Bézier curve to the current sub-path.
     pub fn quadratic_curve_to(&mut self, cpx: f64, cpy: f64, x: f64, y: f64) {
+        self.record(|| DrawCommand::QuadraticCurveTo { cpx, cpy, x, y });
         let mut path_builder = raqote::PathBuilder::from(self.path.clone());
         path_builder.quad_to(cpx as f32, cpy as f32, x as f32, y as f32);
         self.path = path_builder.finish();
@@ -266,6 +435,14 @@ impl RenderContext2D {
     /// It requires three points: the first two are control points and the third one is the end point.
     /// The starting point is the latest point in the current path, which can be changed using MoveTo{} before creating the Bézier curve.
     pub fn bezier_curve_to(&mut self, cp1x: f64, cp1y: f64, cp2x: f64, cp2y: f64, x: f64, y: f64) {
+        self.record(|| DrawCommand::BezierCurveTo {
+            cp1x,
+            cp1y,
+            cp2x,
+            cp2y,
+            x,
+            y,
+        });
         let mut path_builder = raqote::PathBuilder::from(self.path.clone());
         path_builder.cubic_to(
             cp1x as f32,
@@ -281,6 +458,11 @@ impl RenderContext2D {
 
     /// Draws a render target.
     pub fn draw_render_target(&mut self, render_target: &RenderTarget, x: f64, y: f64) {
+        self.record(|| DrawCommand::DrawImage {
+            image: ImageBrush::from(render_target.clone()).source,
+            x,
+            y,
+        });
         self.draw_target.draw_image_at(
             x as f32,
             y as f32,
@@ -298,6 +480,11 @@ impl RenderContext2D {
 
     /// Draws the image.
     pub fn draw_image(&mut self, image: &Image, x: f64, y: f64) {
+        self.record(|| DrawCommand::DrawImage {
+            image: ImageBrush::from(image.clone()).source,
+            x,
+            y,
+        });
         self.draw_target.draw_image_at(
             x as f32,
             y as f32,
@@ -315,6 +502,12 @@ impl RenderContext2D {
 
     /// Draws the given part of the image.
     pub fn draw_image_with_clip(&mut self, image: &Image, clip: Rectangle, x: f64, y: f64) {
+        self.record(|| DrawCommand::DrawImageWithClip {
+            image: ImageBrush::from(image.clone()).source,
+            clip,
+            x,
+            y,
+        });
         let mut y = y as i32;
         let stride = image.width();
         let mut offset = clip.y().mul_add(stride, clip.x()) as usize;
@@ -343,6 +536,92 @@ impl RenderContext2D {
         }
     }
 
+    /// Draws the image scaled to the given size.
+    pub fn draw_image_with_size(&mut self, image: &Image, x: f64, y: f64, width: f64, height: f64) {
+        self.record(|| DrawCommand::DrawImageWithSize {
+            image: ImageBrush::from(image.clone()).source,
+            x,
+            y,
+            width,
+            height,
+        });
+
+        if width <= 0.0 || height <= 0.0 {
+            return;
+        }
+
+        self.draw_target.draw_image_with_size_at(
+            width as f32,
+            height as f32,
+            x as f32,
+            y as f32,
+            &raqote::Image {
+                data: &image.data(),
+                width: image.width() as i32,
+                height: image.height() as i32,
+            },
+            &raqote::DrawOptions {
+                alpha: self.config.alpha,
+                ..Default::default()
+            },
+        );
+    }
+
+    /// Draws the given part of the image scaled to the given size.
+    pub fn draw_image_with_clip_and_size(
+        &mut self,
+        image: &Image,
+        clip: Rectangle,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    ) {
+        self.record(|| DrawCommand::DrawImageWithClipAndSize {
+            image: ImageBrush::from(image.clone()).source,
+            clip,
+            x,
+            y,
+            width,
+            height,
+        });
+
+        let stride = image.width() as usize;
+        let clip_x = clip.x().max(0.0) as usize;
+        let clip_y = clip.y().max(0.0) as usize;
+        let clip_width =
+            ((clip.x() + clip.width()).min(image.width()) as usize).saturating_sub(clip_x);
+        let clip_height =
+            ((clip.y() + clip.height()).min(image.height()) as usize).saturating_sub(clip_y);
+
+        if clip_width == 0 || clip_height == 0 || width <= 0.0 || height <= 0.0 {
+            return;
+        }
+
+        // raqote images are continuous, the rows of the part are copied
+        let mut data = Vec::with_capacity(clip_width * clip_height);
+        for row in clip_y..clip_y + clip_height {
+            let offset = row * stride + clip_x;
+            data.extend_from_slice(&image.data()[offset..offset + clip_width]);
+        }
+
+        self.draw_target.draw_image_with_size_at(
+            width as f32,
+            height as f32,
+            x as f32,
+            y as f32,
+            &raqote::Image {
+                data: &data,
+                width: clip_width as i32,
+                height: clip_height as i32,
+            },
+            &raqote::DrawOptions {
+                alpha: self.config.alpha,
+                ..Default::default()
+            },
+        );
+    }
+
     pub fn draw_pipeline(
         &mut self,
         x: f64,
@@ -358,43 +637,225 @@ impl RenderContext2D {
 
     /// Creates a clipping path from the current sub-paths. Everything drawn after clip() is called appears inside the clipping path only.
     pub fn clip(&mut self) {
+        self.record(|| DrawCommand::Clip);
         self.draw_target.push_clip(&self.path);
         self.path_rect.record_clip();
         self.clips_count += 1;
     }
 
-    // Line styles
+    // Layers
 
-    /// Sets the thickness of lines.
-    pub fn set_line_width(&mut self, line_width: f64) {
-        self.config.line_width = line_width;
+    /// Starts an offscreen layer. Everything drawn until `pop_layer` is called is rendered into the
+    /// layer and then composited as a whole with the given opacity, so overlapping shapes do not
+    /// shine through each other. If `clip` is set only this part of the layer is composited.
+    /// Calls of `save` and `restore` must be balanced inside of the layer.
+    pub fn push_layer(&mut self, opacity: f32, clip: Option<Rectangle>) {
+        self.record(|| DrawCommand::PushLayer { opacity, clip });
+        self.push_layer_with_effect(Effect::None, opacity, clip);
+    }
+
+    /// Starts an offscreen layer like `push_layer`. When the layer is popped the effect is applied
+    /// on the given bounds (extended by the extent of the effect).
+    pub fn push_effect(&mut self, effect: Effect, bounds: Rectangle) {
+        self.record(|| DrawCommand::PushEffect { effect, bounds });
+        let extent = effect.extent();
+        let region = Rectangle::new(
+            (bounds.x() - extent, bounds.y() - extent),
+            (
+                bounds.width() + 2.0 * extent,
+                bounds.height() + 2.0 * extent,
+            ),
+        );
+        self.push_layer_with_effect(effect, 1.0, Some(region));
     }
 
-    /// Sets the alpha value,
-    pub fn set_alpha(&mut self, alpha: f32) {
-        self.config.alpha = alpha;
+    // The layer only covers the region that is composited, the region is in window coordinates.
+    fn push_layer_with_effect(&mut self, effect: Effect, opacity: f32, region: Option<Rectangle>) {
+        let (width, height) = (self.draw_target.width(), self.draw_target.height());
+        let (x, y, layer_width, layer_height) = match region {
+            Some(region) => {
+                let (x, y, layer_width, layer_height) =
+                    pixel_region(self.to_layer(region), width as usize, height as usize);
+                (x as i32, y as i32, layer_width as i32, layer_height as i32)
+            }
+            None => (0, 0, width, height),
+        };
+
+        // the content of the layer is moved to its position
+        let mut draw_target = raqote::DrawTarget::new(layer_width.max(1), layer_height.max(1));
+        let transform = *self.draw_target.get_transform();
+        draw_target.set_transform(&raqote::Transform::row_major(
+            transform.m11,
+            transform.m12,
+            transform.m21,
+            transform.m22,
+            transform.m31 - x as f32,
+            transform.m32 - y as f32,
+        ));
+
+        let parent = std::mem::replace(&mut self.draw_target, draw_target);
+        self.layers.push(Layer {
+            draw_target: parent,
+            clips_count: self.clips_count,
+            effect,
+            opacity,
+            x,
+            y,
+            width: layer_width,
+            height: layer_height,
+        });
+        self.clips_count = 0;
     }
 
-    /// Specifies the font family.
-    pub fn set_font_family(&mut self, family: impl Into<String>) {
-        self.config.font_config.family = family.into();
+    // Gets the position of the current draw target in the window.
+    fn layer_origin(&self) -> (i32, i32) {
+        self.layers
+            .iter()
+            .fold((0, 0), |(x, y), layer| (x + layer.x, y + layer.y))
     }
 
-    /// Specifies the font size.
-    pub fn set_font_size(&mut self, size: f64) {
-        self.config.font_config.font_size = size + 4.0;
+    // Moves a rectangle in window coordinates into the current draw target.
+    fn to_layer(&self, rect: Rectangle) -> Rectangle {
+        let (x, y) = self.layer_origin();
+        Rectangle::new((rect.x() - x as f64, rect.y() - y as f64), rect.size())
     }
 
-    // Fill and stroke style
+    /// Composites the current layer with its opacity on the previous draw target. The effect of a
+    /// layer that is started by `push_effect` is applied before.
+    pub fn pop_layer(&mut self) {
+        let layer = match self.layers.pop() {
+            Some(layer) => layer,
+            None => return,
+        };
+        // also records the end of effects, `pop_effect` pops the layer
+        self.record(|| DrawCommand::PopLayer);
+
+        let mut content = std::mem::replace(&mut self.draw_target, layer.draw_target);
+        self.clips_count = layer.clips_count;
+
+        let (width, height) = (layer.width, layer.height);
+
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        let region = Rectangle::new((0.0, 0.0), (width as f64, height as f64));
+
+        if let Effect::Blur(radius) = layer.effect {
+            blur_argb(
+                content.get_data_mut(),
+                width as usize,
+                height as usize,
+                region,
+                radius / 2.0,
+            );
+        }
+
+        if let Some(matrix) = layer.effect.color_matrix() {
+            apply_color_matrix(
+                content.get_data_mut(),
+                width as usize,
+                height as usize,
+                region,
+                &matrix,
+            );
+        }
+
+        // the layer is already transformed
+        let (x, y) = (layer.x as f32, layer.y as f32);
+        let transform = *self.draw_target.get_transform();
+        self.draw_target
+            .set_transform(&raqote::Transform::identity());
+        self.draw_target.fill_rect(
+            x,
+            y,
+            width as f32,
+            height as f32,
+            &raqote::Source::Image(
+                raqote::Image {
+                    width,
+                    height,
+                    data: content.get_data(),
+                },
+                raqote::ExtendMode::Pad,
+                raqote::FilterMode::Nearest,
+                raqote::Transform::row_major(1.0, 0.0, 0.0, 1.0, -x, -y),
+            ),
+            &raqote::DrawOptions {
+                alpha: layer.opacity * layer.effect.opacity() as f32,
+                ..Default::default()
+            },
+        );
+        self.draw_target.set_transform(&transform);
+    }
 
-    /// Specifies the fill color to use inside shapes.
-    pub fn set_fill_style(&mut self, fill_style: Brush) {
-        self.config.fill_style = fill_style;
+    /// Pops the current layer, same as `pop_layer`.
+    pub fn pop_effect(&mut self) {
+        self.pop_layer();
     }
 
-    /// Specifies the fill stroke to use inside shapes.
-    pub fn set_stroke_style(&mut self, stroke_style: Brush) {
-        self.config.stroke_style = stroke_style;
+    /// Applies the effect on the pixels that are already drawn inside of the bounds, e.g. to blur
+    /// the content behind a widget. The bounds are in window coordinates, the changed pixels are
+    /// clipped like other draw calls.
+    pub fn apply_backdrop_effect(&mut self, effect: Effect, bounds: Rectangle) {
+        self.record(|| DrawCommand::BackdropEffect { effect, bounds });
+
+        if effect.is_none() {
+            return;
+        }
+
+        let (region, pixels) = match backdrop_effect(
+            self.draw_target.get_data(),
+            self.draw_target.width() as usize,
+            self.draw_target.height() as usize,
+            effect,
+            self.to_layer(bounds),
+        ) {
+            Some(backdrop) => backdrop,
+            None => return,
+        };
+
+        let (x, y) = (region.x() as f32, region.y() as f32);
+        let (width, height) = (region.width() as f32, region.height() as f32);
+        let transform = *self.draw_target.get_transform();
+        self.draw_target
+            .set_transform(&raqote::Transform::identity());
+
+        // the pixels inside of the clip are cleared and replaced by the backdrop
+        self.draw_target.fill_rect(
+            x,
+            y,
+            width,
+            height,
+            &raqote::Source::Solid(raqote::SolidSource {
+                r: 0,
+                g: 0,
+                b: 0,
+                a: 255,
+            }),
+            &raqote::DrawOptions {
+                blend_mode: raqote::BlendMode::DstOut,
+                ..Default::default()
+            },
+        );
+        self.draw_target.fill_rect(
+            x,
+            y,
+            width,
+            height,
+            &raqote::Source::Image(
+                raqote::Image {
+                    width: region.width() as i32,
+                    height: region.height() as i32,
+                    data: &pixels,
+                },
+                raqote::ExtendMode::Pad,
+                raqote::FilterMode::Nearest,
+                raqote::Transform::row_major(1.0, 0.0, 0.0, 1.0, -x, -y),
+            ),
+            &raqote::DrawOptions::default(),
+        );
+        self.draw_target.set_transform(&transform);
     }
 
     // Transformations
@@ -409,14 +870,25 @@ impl RenderContext2D {
         h_moving: f64,
         v_moving: f64,
     ) {
+        self.record(|| DrawCommand::SetTransform {
+            h_scaling,
+            h_skewing,
+            v_skewing,
+            v_scaling,
+            h_moving,
+            v_moving,
+        });
+
+        // the transform is given in window coordinates
+        let (x, y) = self.layer_origin();
         self.draw_target
             .set_transform(&raqote::Transform::row_major(
                 h_scaling as f32,
                 h_skewing as f32,
                 v_skewing as f32,
                 v_scaling as f32,
-                h_moving as f32,
-                v_moving as f32,
+                h_moving as f32 - x as f32,
+                v_moving as f32 - y as f32,
             ));
     }
 
@@ -424,16 +896,23 @@ impl RenderContext2D {
 
     /// Saves the entire state of the canvas by pushing the current state onto a stack.
     pub fn save(&mut self) {
-        self.saved_states
-            .push((self.config.clone(), self.path_rect, self.clips_count));
+        self.record(|| DrawCommand::Save);
+        self.saved_states.push((
+            self.config.clone(),
+            self.path_rect,
+            self.clips_count,
+            *self.draw_target.get_transform(),
+        ));
     }
 
     /// Restores the most recently saved canvas state by popping the top entry in the drawing state stack.
     /// If there is no saved state, this method does nothing.
     pub fn restore(&mut self) {
-        if let Some((config, path_rect, former_clips_count)) = self.saved_states.pop() {
+        self.record(|| DrawCommand::Restore);
+        if let Some((config, path_rect, former_clips_count, transform)) = self.saved_states.pop() {
             self.config = config;
             self.path_rect = path_rect;
+            self.draw_target.set_transform(&transform);
             for _ in former_clips_count..self.clips_count {
                 self.draw_target.pop_clip();
             }
@@ -441,7 +920,27 @@ impl RenderContext2D {
         }
     }
 
+    /// Sets the drawing state back to the one of a new render context. The styles, the
+    /// transform, the clips and the saved states are reset, open layers are composited.
+    pub fn reset_state(&mut self) {
+        self.finish();
+
+        for _ in 0..self.clips_count {
+            self.draw_target.pop_clip();
+        }
+
+        self.clips_count = 0;
+        self.saved_states.clear();
+        self.path_rect = PathRect::new(None);
+        self.config = RenderConfig::default();
+        self.draw_target
+            .set_transform(&raqote::Transform::identity());
+    }
+
     pub fn clear(&mut self, brush: &Brush) {
+        self.record(|| DrawCommand::Clear {
+            brush: brush.clone(),
+        });
         let solid = match *brush {
             Brush::SolidColor(color) => raqote::SolidSource {
                 r: color.r(),
@@ -475,11 +974,188 @@ impl RenderContext2D {
 
     pub fn start(&mut self) {
         self.clear(&Brush::from(self.background));
+        self.damaged_regions = vec![Rectangle::new(
+            (0.0, 0.0),
+            (
+                self.draw_target.width() as f64,
+                self.draw_target.height() as f64,
+            ),
+        )];
+    }
+
+    pub fn finish(&mut self) {
+        while !self.layers.is_empty() {
+            self.pop_layer();
+        }
+    }
+
+    /// Starts to redraw the given region of the draw target. The region is cleared with the
+    /// background and everything that is drawn until `finish_region` is clipped to it.
+    pub fn start_region(&mut self, region: Rectangle) {
+        self.damaged_regions.push(region);
+        self.save();
+        self.begin_path();
+        self.rect(region.x(), region.y(), region.width(), region.height());
+        self.clip();
+
+        self.draw_target.fill_rect(
+            region.x() as f32,
+            region.y() as f32,
+            region.width() as f32,
+            region.height() as f32,
+            &raqote::Source::Solid(raqote::SolidSource {
+                r: self.background.r(),
+                g: self.background.g(),
+                b: self.background.b(),
+                a: self.background.a(),
+            }),
+            &raqote::DrawOptions {
+                blend_mode: raqote::BlendMode::Src,
+                ..Default::default()
+            },
+        );
+    }
+
+    /// Finishes the redraw of the region that is started with `start_region`.
+    pub fn finish_region(&mut self) {
+        self.finish();
+        self.restore();
+    }
+
+    /// Returns the regions of the draw target that are redrawn since the last call, to present
+    /// only the changed parts of a frame. A full frame is returned as one region.
+    pub fn take_damaged_regions(&mut self) -> Vec<Rectangle> {
+        std::mem::replace(&mut self.damaged_regions, vec![])
+    }
+}
+
+impl_render_context_common!();
+
+fn line_cap_to_raqote(line_cap: LineCap) -> raqote::LineCap {
+    match line_cap {
+        LineCap::Butt => raqote::LineCap::Butt,
+        LineCap::Round => raqote::LineCap::Round,
+        LineCap::Square => raqote::LineCap::Square,
+    }
+}
+
+fn line_join_to_raqote(line_join: LineJoin) -> raqote::LineJoin {
+    match line_join {
+        LineJoin::Miter => raqote::LineJoin::Miter,
+        LineJoin::Round => raqote::LineJoin::Round,
+        LineJoin::Bevel => raqote::LineJoin::Bevel,
     }
-    pub fn finish(&mut self) {}
 }
 
-fn brush_to_source<'a>(brush: &Brush, frame: Rectangle) -> raqote::Source<'a> {
+fn gradient_stops(
+    stops: &[GradientStop],
+    length: f64,
+    interpolation: ColorInterpolation,
+) -> raqote::Gradient {
+    raqote::Gradient {
+        stops: build_gradient_stops(stops, length, interpolation, |p, c| raqote::GradientStop {
+            position: p as f32,
+            color: raqote::Color::new(c.a(), c.r(), c.g(), c.b()),
+        }),
+    }
+}
+
+// Returns the pixels of an image brush and the transformation from pattern space to path space.
+fn image_brush_data<'a>(
+    image_brush: &'a ImageBrush,
+    frame: Rectangle,
+    brush_images: &'a BrushImageCache<Image>,
+) -> Option<(raqote::Image<'a>, Matrix)> {
+    let (width, height, data) = match &image_brush.source {
+        ImageSource::Path(path) => {
+            let image = brush_images.get(path)?;
+            (image.width() as i32, image.height() as i32, image.data())
+        }
+        ImageSource::Data {
+            width,
+            height,
+            data,
+        } => (*width as i32, *height as i32, &data[..]),
+    };
+
+    if width <= 0 || height <= 0 || data.len() < (width * height) as usize {
+        return None;
+    }
+
+    let transform = image_brush
+        .transform
+        .then(&Matrix::translation(frame.x(), frame.y()));
+
+    Some((
+        raqote::Image {
+            width,
+            height,
+            data,
+        },
+        transform,
+    ))
+}
+
+// Raqote could only repeat an image in both directions. Image brushes that are not repeated in
+// one or both directions are clipped to the image bounds in pattern space.
+fn image_brush_clip(
+    brush: &Brush,
+    frame: Rectangle,
+    brush_images: &BrushImageCache<Image>,
+) -> Option<raqote::Path> {
+    // used as "infinite" extent of the repeated directions
+    const EXTENT: f64 = 1_000_000.0;
+
+    let image_brush = match brush {
+        Brush::Image(image_brush) if image_brush.repeat != ImageRepeat::Repeat => image_brush,
+        _ => return None,
+    };
+
+    let (width, height) = match &image_brush.source {
+        ImageSource::Path(path) => {
+            let image = brush_images.get(path)?;
+            (image.width(), image.height())
+        }
+        ImageSource::Data { width, height, .. } => (*width as f64, *height as f64),
+    };
+
+    let (left, right) = if image_brush.repeat.repeat_x() {
+        (-EXTENT, EXTENT)
+    } else {
+        (0.0, width)
+    };
+    let (top, bottom) = if image_brush.repeat.repeat_y() {
+        (-EXTENT, EXTENT)
+    } else {
+        (0.0, height)
+    };
+
+    let transform = image_brush
+        .transform
+        .then(&Matrix::translation(frame.x(), frame.y()));
+
+    let mut path_builder = raqote::PathBuilder::new();
+    for (i, corner) in [(left, top), (right, top), (right, bottom), (left, bottom)]
+        .iter()
+        .enumerate()
+    {
+        let corner = transform.transform_point(*corner);
+        if i == 0 {
+            path_builder.move_to(corner.x() as f32, corner.y() as f32);
+        } else {
+            path_builder.line_to(corner.x() as f32, corner.y() as f32);
+        }
+    }
+    path_builder.close();
+
+    Some(path_builder.finish())
+}
+
+fn brush_to_source<'a>(
+    brush: &'a Brush,
+    frame: Rectangle,
+    brush_images: &'a BrushImageCache<Image>,
+) -> raqote::Source<'a> {
     match brush {
         Brush::SolidColor(color) => raqote::Source::Solid(raqote::SolidSource {
             r: color.r(),
@@ -488,33 +1164,93 @@ fn brush_to_source<'a>(brush: &Brush, frame: Rectangle) -> raqote::Source<'a> {
             a: color.a(),
         }),
         Brush::Gradient(Gradient {
-            kind: GradientKind::Linear(coords),
+            kind,
             stops,
-            repeat,
+            spread,
+            interpolation,
         }) => {
-            let spread = match repeat {
-                true => raqote::Spread::Repeat,
-                false => raqote::Spread::Pad,
+            let spread = match spread {
+                SpreadMethod::Pad => raqote::Spread::Pad,
+                SpreadMethod::Repeat => raqote::Spread::Repeat,
+                SpreadMethod::Reflect => raqote::Spread::Reflect,
             };
-            match coords {
-                LinearGradientCoords::Ends { start, end } => {
-                    let g_stops =
-                        build_unit_percent_gradient(&stops, end.distance(*start), |p, c| {
-                            raqote::GradientStop {
-                                position: p as f32,
-                                color: raqote::Color::new(c.a(), c.r(), c.g(), c.b()),
-                            }
-                        });
+            match kind {
+                GradientKind::Linear(LinearGradientCoords::Ends { start, end }) => {
+                    let gradient = gradient_stops(&stops, end.distance(*start), *interpolation);
                     let start = frame.position() + *start;
                     let end = frame.position() + *end;
                     raqote::Source::new_linear_gradient(
-                        raqote::Gradient { stops: g_stops },
+                        gradient,
                         raqote::Point::new(start.x() as f32, start.y() as f32),
                         raqote::Point::new(end.x() as f32, end.y() as f32),
                         spread,
                     )
                 }
+                GradientKind::Radial(radial) => {
+                    let (center, focal, radius) = radial.resolve(frame.size());
+                    let gradient = gradient_stops(&stops, radius, *interpolation);
+                    let center = frame.position() + center;
+                    let focal = frame.position() + focal;
+                    raqote::Source::new_two_circle_radial_gradient(
+                        gradient,
+                        raqote::Point::new(focal.x() as f32, focal.y() as f32),
+                        0.0,
+                        raqote::Point::new(center.x() as f32, center.y() as f32),
+                        radius as f32,
+                        spread,
+                    )
+                }
+                GradientKind::Conic(conic) => {
+                    // pixel positions of conic stops are interpreted as degrees
+                    let gradient = gradient_stops(&stops, 360.0, *interpolation);
+                    let center = frame.position() + conic.center.pixels(frame.size());
+                    // raqote starts the sweep on the positive x axis, css like conic gradients on the top
+                    let start_angle = conic.angle.to_degrees() - 90.0;
+                    raqote::Source::new_sweep_gradient(
+                        gradient,
+                        raqote::Point::new(center.x() as f32, center.y() as f32),
+                        start_angle as f32,
+                        (start_angle + 360.0) as f32,
+                        spread,
+                    )
+                }
             }
         }
+        Brush::Image(image_brush) => {
+            // raqote expects the transformation from path space to pattern space
+            let (image, transform) = match image_brush_data(image_brush, frame, brush_images)
+                .and_then(|(image, transform)| Some((image, transform.invert()?)))
+            {
+                Some(image_data) => image_data,
+                None => {
+                    return raqote::Source::Solid(raqote::SolidSource {
+                        r: 0x0,
+                        g: 0x0,
+                        b: 0x0,
+                        a: 0x0,
+                    })
+                }
+            };
+
+            let extend = if image_brush.repeat == ImageRepeat::NoRepeat {
+                raqote::ExtendMode::Pad
+            } else {
+                raqote::ExtendMode::Repeat
+            };
+
+            raqote::Source::Image(
+                image,
+                extend,
+                raqote::FilterMode::Bilinear,
+                raqote::Transform::row_major(
+                    transform.m11 as f32,
+                    transform.m12 as f32,
+                    transform.m21 as f32,
+                    transform.m22 as f32,
+                    transform.m31 as f32,
+                    transform.m32 as f32,
+                ),
+            )
+        }
     }
 }
@@ -1,12 +1,19 @@
-use std::fmt;
+use std::{fmt, sync::Arc};
 
 use crate::utils::*;
 
+/// Pixels that are drawn by a pipeline or used as image.
+///
+/// Each pixel is a `u32` in premultiplied ARGB format (`0xAARRGGBB`), the color components are
+/// already multiplied by the alpha and could not be greater than it. Pixels with straight alpha,
+/// e.g. decoded from image files, are converted with `premultiply`.
+///
+/// Clones share the pixels, they are copied only if a shared render target is changed.
 #[derive(Clone, Default)]
 pub struct RenderTarget {
     width: u32,
     height: u32,
-    pub data: Vec<u32>,
+    pub data: Arc<Vec<u32>>,
 }
 
 impl fmt::Debug for RenderTarget {
@@ -31,21 +38,24 @@ impl RenderTarget {
         RenderTarget {
             width,
             height,
-            data: vec![Color::rgba(0, 0, 0, 0).data; width as usize * height as usize],
+            data: Arc::new(vec![
+                Color::rgba(0, 0, 0, 0).data;
+                width as usize * height as usize
+            ]),
         }
     }
 
     /// Draws a u32 slice into the image.
     pub fn draw(&mut self, data: &[u32]) {
-        self.data.clone_from_slice(data);
+        Arc::make_mut(&mut self.data).clone_from_slice(data);
     }
 
-    /// Create a new image from a boxed slice of colors
+    /// Create a new image from a boxed slice of premultiplied ARGB pixels.
     pub fn from_data(width: u32, height: u32, data: Vec<u32>) -> Result<Self, String> {
         Ok(RenderTarget {
             width,
             height,
-            data,
+            data: Arc::new(data),
         })
     }
 
@@ -59,11 +69,77 @@ impl RenderTarget {
         self.height as f64
     }
 
+    /// Gets the premultiplied ARGB pixels.
     pub fn data(&self) -> &[u32] {
         &self.data
     }
 
+    /// Gets the premultiplied ARGB pixels to change them.
     pub fn data_mut(&mut self) -> &mut [u32] {
-        &mut self.data
+        Arc::make_mut(&mut self.data).as_mut_slice()
+    }
+}
+
+/// Converts an ARGB pixel with straight alpha to premultiplied ARGB.
+pub fn premultiply(pixel: u32) -> u32 {
+    let [a, r, g, b] = pixel.to_be_bytes();
+
+    match a {
+        255 => pixel,
+        0 => 0,
+        _ => {
+            let multiply = |c: u8| ((c as u32 * a as u32 + 127) / 255) as u8;
+            u32::from_be_bytes([a, multiply(r), multiply(g), multiply(b)])
+        }
+    }
+}
+
+/// Converts a premultiplied ARGB pixel to straight alpha, e.g. to export it to an image file.
+pub fn unpremultiply(pixel: u32) -> u32 {
+    let [a, r, g, b] = pixel.to_be_bytes();
+
+    match a {
+        255 => pixel,
+        0 => 0,
+        _ => {
+            let divide = |c: u8| ((c as u32 * 255 + a as u32 / 2) / a as u32).min(255) as u8;
+            u32::from_be_bytes([a, divide(r), divide(g), divide(b)])
+        }
+    }
+}
+
+// --- Conversions ---
+
+impl From<RenderTarget> for ImageBrush {
+    fn from(render_target: RenderTarget) -> ImageBrush {
+        ImageBrush::from_data(
+            render_target.width,
+            render_target.height,
+            render_target.data,
+        )
+    }
+}
+
+// --- Conversions ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_premultiply() {
+        assert_eq!(premultiply(0xFFFF_8000), 0xFFFF_8000);
+        assert_eq!(premultiply(0x80FF_8000), 0x8080_4000);
+        assert_eq!(premultiply(0x00FF_FFFF), 0);
+    }
+
+    #[test]
+    fn test_unpremultiply() {
+        assert_eq!(unpremultiply(0x8080_4000), 0x80FF_8000);
+        assert_eq!(unpremultiply(0xFF12_3456), 0xFF12_3456);
+        assert_eq!(unpremultiply(0), 0);
+
+        // invalid components greater than alpha are clamped
+        assert_eq!(unpremultiply(0x10FF_0000), 0x10FF_0000);
     }
 }
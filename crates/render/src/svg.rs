@@ -0,0 +1,1124 @@
+use std::{f64::consts::PI, fmt::Write};
+
+use crate::{common::*, unpremultiply, utils::*, DisplayList, DrawCommand, FontConfig};
+
+// Formats a number with at most three decimals.
+fn number(value: f64) -> String {
+    let value = format!("{:.3}", value);
+    let value = value.trim_end_matches('0').trim_end_matches('.');
+
+    if value == "-0" {
+        return "0".to_string();
+    }
+
+    value.to_string()
+}
+
+// Escapes the special chars of xml in texts and attribute values.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+// Formats the rgb part of the color as hex string.
+fn hex(color: Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b())
+}
+
+// Encodes premultiplied ARGB pixels as png data url, png stores straight alpha.
+fn png_data_url(width: u32, height: u32, data: &[u32]) -> Option<String> {
+    if width == 0 || height == 0 || data.len() < (width * height) as usize {
+        return None;
+    }
+
+    let rgba: Vec<u8> = data[..(width * height) as usize]
+        .iter()
+        .flat_map(|pixel| {
+            let [a, r, g, b] = unpremultiply(*pixel).to_be_bytes();
+            vec![r, g, b, a]
+        })
+        .collect();
+
+    let mut png = vec![];
+    image::png::PngEncoder::new(&mut png)
+        .encode(&rgba, width, height, image::ColorType::Rgba8)
+        .ok()?;
+
+    Some(format!("data:image/png;base64,{}", base64::encode(&png)))
+}
+
+// Returns the link and the size of the image, the size is unknown for images loaded from a path.
+fn image_href(image: &ImageSource) -> Option<(String, Option<(u32, u32)>)> {
+    match image {
+        ImageSource::Path(path) => Some((escape(path), None)),
+        ImageSource::Data {
+            width,
+            height,
+            data,
+        } => png_data_url(*width, *height, data).map(|url| (url, Some((*width, *height)))),
+    }
+}
+
+// Canvas state that is saved by `save` and restored by `restore`.
+#[derive(Clone)]
+struct State {
+    fill_style: Brush,
+    stroke_style: Brush,
+    line_width: f64,
+    line_cap: LineCap,
+    line_join: LineJoin,
+    miter_limit: f64,
+    line_dash: Vec<f64>,
+    line_dash_offset: f64,
+    alpha: f32,
+    font_config: FontConfig,
+    text_baseline: TextBaseline,
+    transform: Matrix,
+    // number of groups that are opened by clips since the last save
+    clip_groups: usize,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        State {
+            fill_style: Brush::default(),
+            stroke_style: Brush::default(),
+            line_width: 1.0,
+            line_cap: LineCap::default(),
+            line_join: LineJoin::default(),
+            miter_limit: 10.0,
+            line_dash: vec![],
+            line_dash_offset: 0.0,
+            alpha: 1.0,
+            font_config: FontConfig::default(),
+            text_baseline: TextBaseline::Top,
+            transform: Matrix::identity(),
+            clip_groups: 0,
+        }
+    }
+}
+
+// Converts draw commands to svg elements.
+#[derive(Default)]
+struct SvgWriter {
+    defs: String,
+    body: String,
+    next_id: usize,
+    state: State,
+    saved_states: Vec<State>,
+    path: String,
+    path_rect: Option<PathRect>,
+    // number of open groups
+    groups: usize,
+    // open groups and clip groups of the current state when a layer is pushed
+    layers: Vec<(usize, usize)>,
+}
+
+impl SvgWriter {
+    fn id(&mut self, prefix: &str) -> String {
+        self.next_id += 1;
+        format!("{}{}", prefix, self.next_id)
+    }
+
+    fn open_group(&mut self, attributes: &str) {
+        let _ = writeln!(self.body, "<g{}>", attributes);
+        self.groups += 1;
+    }
+
+    fn close_groups(&mut self, count: usize) {
+        for _ in 0..count.min(self.groups) {
+            self.body.push_str("</g>\n");
+            self.groups -= 1;
+        }
+    }
+
+    fn path_rect(&mut self) -> &mut PathRect {
+        self.path_rect.get_or_insert_with(|| PathRect::new(None))
+    }
+
+    fn transform_attribute(&self) -> String {
+        let t = self.state.transform;
+
+        if t.is_identity() {
+            return String::new();
+        }
+
+        format!(
+            " transform=\"matrix({} {} {} {} {} {})\"",
+            number(t.m11),
+            number(t.m12),
+            number(t.m21),
+            number(t.m22),
+            number(t.m31),
+            number(t.m32)
+        )
+    }
+
+    fn alpha_attribute(&self) -> String {
+        if self.state.alpha < 1.0 {
+            return format!(" opacity=\"{}\"", number(self.state.alpha as f64));
+        }
+
+        String::new()
+    }
+
+    // Adds the gradient stops to the defs. Gradients that are interpolated in linear light get
+    // intermediate stops like in the rasterizers.
+    fn gradient_stops(
+        &mut self,
+        stops: &[GradientStop],
+        length: f64,
+        interpolation: ColorInterpolation,
+    ) {
+        for stop in build_gradient_stops(stops, length, interpolation, |p, c| (p, c)) {
+            let _ = write!(
+                self.defs,
+                "<stop offset=\"{}\" stop-color=\"{}\"",
+                number(stop.0),
+                hex(stop.1)
+            );
+
+            if stop.1.a() < 255 {
+                let _ = write!(
+                    self.defs,
+                    " stop-opacity=\"{}\"",
+                    number(stop.1.a() as f64 / 255.0)
+                );
+            }
+
+            self.defs.push_str("/>\n");
+        }
+    }
+
+    // Returns the paint (color or reference to a paint server) and opacity attributes of a
+    // brush that is painted on the given frame.
+    fn paint(&mut self, attribute: &str, brush: &Brush, frame: Rectangle) -> String {
+        let color_paint = |color: Color| {
+            let mut paint = format!(" {}=\"{}\"", attribute, hex(color));
+
+            if color.a() < 255 {
+                let _ = write!(
+                    paint,
+                    " {}-opacity=\"{}\"",
+                    attribute,
+                    number(color.a() as f64 / 255.0)
+                );
+            }
+
+            paint
+        };
+
+        match brush {
+            Brush::SolidColor(color) => color_paint(*color),
+            Brush::Gradient(Gradient {
+                kind,
+                stops,
+                spread,
+                interpolation,
+            }) => {
+                let spread = match spread {
+                    SpreadMethod::Pad => "pad",
+                    SpreadMethod::Repeat => "repeat",
+                    SpreadMethod::Reflect => "reflect",
+                };
+
+                let id = self.id("gradient");
+
+                match kind {
+                    GradientKind::Linear(LinearGradientCoords::Ends { start, end }) => {
+                        let length = end.distance(*start);
+                        let start = frame.position() + *start;
+                        let end = frame.position() + *end;
+                        let _ = writeln!(
+                            self.defs,
+                            "<linearGradient id=\"{}\" gradientUnits=\"userSpaceOnUse\" x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" spreadMethod=\"{}\">",
+                            id,
+                            number(start.x()),
+                            number(start.y()),
+                            number(end.x()),
+                            number(end.y()),
+                            spread
+                        );
+                        self.gradient_stops(stops, length, *interpolation);
+                        self.defs.push_str("</linearGradient>\n");
+                    }
+                    GradientKind::Radial(radial) => {
+                        let (center, focal, radius) = radial.resolve(frame.size());
+                        let center = frame.position() + center;
+                        let focal = frame.position() + focal;
+                        let _ = writeln!(
+                            self.defs,
+                            "<radialGradient id=\"{}\" gradientUnits=\"userSpaceOnUse\" cx=\"{}\" cy=\"{}\" r=\"{}\" fx=\"{}\" fy=\"{}\" spreadMethod=\"{}\">",
+                            id,
+                            number(center.x()),
+                            number(center.y()),
+                            number(radius),
+                            number(focal.x()),
+                            number(focal.y()),
+                            spread
+                        );
+                        self.gradient_stops(stops, radius, *interpolation);
+                        self.defs.push_str("</radialGradient>\n");
+                    }
+                    // svg has no conic gradients, the color of the first stop is used
+                    GradientKind::Conic(_) => {
+                        return color_paint(stops.first().map(|s| s.color).unwrap_or_default());
+                    }
+                }
+
+                format!(" {}=\"url(#{})\"", attribute, id)
+            }
+            Brush::Image(image_brush) => {
+                // the size of images loaded from a path is unknown, they are not painted
+                let (href, width, height) = match image_href(&image_brush.source) {
+                    Some((href, Some((width, height)))) => (href, width, height),
+                    _ => return format!(" {}=\"none\"", attribute),
+                };
+
+                // the image is always repeated in both directions
+                let id = self.id("pattern");
+                let t = image_brush
+                    .transform
+                    .then(&Matrix::translation(frame.x(), frame.y()));
+                let _ = writeln!(
+                    self.defs,
+                    "<pattern id=\"{}\" patternUnits=\"userSpaceOnUse\" width=\"{}\" height=\"{}\" patternTransform=\"matrix({} {} {} {} {} {})\"><image width=\"{}\" height=\"{}\" href=\"{}\"/></pattern>",
+                    id,
+                    width,
+                    height,
+                    number(t.m11),
+                    number(t.m12),
+                    number(t.m21),
+                    number(t.m22),
+                    number(t.m31),
+                    number(t.m32),
+                    width,
+                    height,
+                    href
+                );
+
+                format!(" {}=\"url(#{})\"", attribute, id)
+            }
+        }
+    }
+
+    fn stroke_attributes(&self) -> String {
+        let state = &self.state;
+        let mut attributes = format!(" stroke-width=\"{}\"", number(state.line_width));
+
+        match state.line_cap {
+            LineCap::Butt => {}
+            LineCap::Round => attributes.push_str(" stroke-linecap=\"round\""),
+            LineCap::Square => attributes.push_str(" stroke-linecap=\"square\""),
+        }
+
+        match state.line_join {
+            LineJoin::Miter => {}
+            LineJoin::Round => attributes.push_str(" stroke-linejoin=\"round\""),
+            LineJoin::Bevel => attributes.push_str(" stroke-linejoin=\"bevel\""),
+        }
+
+        if (state.miter_limit - 4.0).abs() > std::f64::EPSILON {
+            let _ = write!(
+                attributes,
+                " stroke-miterlimit=\"{}\"",
+                number(state.miter_limit)
+            );
+        }
+
+        if !state.line_dash.is_empty() {
+            let dash: Vec<String> = state.line_dash.iter().map(|d| number(*d)).collect();
+            let _ = write!(attributes, " stroke-dasharray=\"{}\"", dash.join(" "));
+
+            if state.line_dash_offset != 0.0 {
+                let _ = write!(
+                    attributes,
+                    " stroke-dashoffset=\"{}\"",
+                    number(state.line_dash_offset)
+                );
+            }
+        }
+
+        attributes
+    }
+
+    fn begin_path(&mut self) {
+        self.path.clear();
+        self.path_rect = None;
+    }
+
+    fn move_to(&mut self, x: f64, y: f64) {
+        let _ = write!(self.path, "M{} {} ", number(x), number(y));
+        self.path_rect().record_move_to(x, y);
+    }
+
+    fn line_to(&mut self, x: f64, y: f64) {
+        // a line without start point starts at the point itself like on a canvas
+        let command = if self.path.is_empty() { "M" } else { "L" };
+        let _ = write!(self.path, "{}{} {} ", command, number(x), number(y));
+        self.path_rect().record_line_to(x, y);
+    }
+
+    fn rect(&mut self, x: f64, y: f64, width: f64, height: f64) {
+        let _ = write!(
+            self.path,
+            "M{} {} h{} v{} h{} Z ",
+            number(x),
+            number(y),
+            number(width),
+            number(height),
+            number(-width)
+        );
+        self.path_rect().record_rect(x, y, width, height);
+    }
+
+    fn rounded_rect(&mut self, x: f64, y: f64, width: f64, height: f64, radius: f64) {
+        let radius = rounded_rect_radius(width, height, radius);
+        let r = x + width;
+        let b = y + height;
+        let n = number;
+
+        let _ = write!(
+            self.path,
+            "M{} {} L{} {} Q{} {} {} {} L{} {} Q{} {} {} {} L{} {} Q{} {} {} {} L{} {} Q{} {} {} {} Z ",
+            n(x + radius), n(y),
+            n(r - radius), n(y),
+            n(r), n(y), n(r), n(y + radius),
+            n(r), n(b - radius),
+            n(r), n(b), n(r - radius), n(b),
+            n(x + radius), n(b),
+            n(x), n(b), n(x), n(b - radius),
+            n(x), n(y + radius),
+            n(x), n(y), n(x + radius), n(y)
+        );
+        self.path_rect().record_rect(x, y, width, height);
+    }
+
+    fn arc(&mut self, x: f64, y: f64, radius: f64, start_angle: f64, end_angle: f64) {
+        let point = |angle: f64| (x + radius * angle.cos(), y + radius * angle.sin());
+        let start = point(start_angle);
+        let command = if self.path.is_empty() { "M" } else { "L" };
+        let _ = write!(
+            self.path,
+            "{}{} {} ",
+            command,
+            number(start.0),
+            number(start.1)
+        );
+
+        // svg arcs could not be closed, the arc is split in parts of at most a half circle
+        let sweep = (end_angle - start_angle).max(-2.0 * PI).min(2.0 * PI);
+        let parts = (sweep.abs() / PI).ceil().max(1.0) as usize;
+
+        for i in 1..=parts {
+            let end = point(start_angle + sweep * i as f64 / parts as f64);
+            let _ = write!(
+                self.path,
+                "A{} {} 0 0 {} {} {} ",
+                number(radius),
+                number(radius),
+                if sweep >= 0.0 { 1 } else { 0 },
+                number(end.0),
+                number(end.1)
+            );
+        }
+
+        self.path_rect()
+            .record_arc(x, y, radius, start_angle, end_angle);
+    }
+
+    fn fill(&mut self) {
+        let frame = match self.path_rect.and_then(|r| r.get_rect()) {
+            Some(frame) => frame,
+            None => return,
+        };
+
+        let fill_style = self.state.fill_style.clone();
+        let paint = self.paint("fill", &fill_style, frame);
+        let _ = writeln!(
+            self.body,
+            "<path d=\"{}\"{}{}{}/>",
+            self.path.trim_end(),
+            paint,
+            self.alpha_attribute(),
+            self.transform_attribute()
+        );
+    }
+
+    fn stroke(&mut self) {
+        let frame = match self.path_rect.and_then(|r| r.get_rect()) {
+            Some(frame) => frame,
+            None => return,
+        };
+
+        let stroke_style = self.state.stroke_style.clone();
+        let paint = self.paint("stroke", &stroke_style, frame);
+        let _ = writeln!(
+            self.body,
+            "<path d=\"{}\" fill=\"none\"{}{}{}{}/>",
+            self.path.trim_end(),
+            paint,
+            self.stroke_attributes(),
+            self.alpha_attribute(),
+            self.transform_attribute()
+        );
+    }
+
+    fn fill_rect(&mut self, x: f64, y: f64, width: f64, height: f64) {
+        let fill_style = self.state.fill_style.clone();
+        let paint = self.paint("fill", &fill_style, Rectangle::new((x, y), (width, height)));
+        let _ = writeln!(
+            self.body,
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"{}{}{}/>",
+            number(x),
+            number(y),
+            number(width),
+            number(height),
+            paint,
+            self.alpha_attribute(),
+            self.transform_attribute()
+        );
+    }
+
+    fn fill_text(&mut self, text: &str, x: f64, y: f64) {
+        if text.is_empty() {
+            return;
+        }
+
+        // texts are only drawn with solid colors
+        let color = match self.state.fill_style {
+            Brush::SolidColor(color) => color,
+            _ => Color::from("#000000"),
+        };
+        let paint = self.paint("fill", &Brush::from(color), Rectangle::default());
+        let font_config = &self.state.font_config;
+        let mut font = format!(
+            " font-family=\"{}\" font-size=\"{}\"",
+            escape(&font_config.families().join(", ")),
+            number(font_config.font_size)
+        );
+
+        if font_config.weight != FontWeight::NORMAL {
+            let _ = write!(font, " font-weight=\"{}\"", font_config.weight.value());
+        }
+
+        match font_config.style {
+            FontStyle::Normal => {}
+            FontStyle::Italic => font.push_str(" font-style=\"italic\""),
+            FontStyle::Oblique => font.push_str(" font-style=\"oblique\""),
+        }
+
+        if font_config.stretch != FontStretch::Normal {
+            let _ = write!(
+                font,
+                " font-stretch=\"{}%\"",
+                number(font_config.stretch.percentage())
+            );
+        }
+
+        // the top of the line is placed like in the web backend, from the middle of the line
+        let (baseline, y) = match self.state.text_baseline {
+            TextBaseline::Top => ("middle", y + font_config.font_size.ceil() / 2.0),
+            TextBaseline::Hanging => ("hanging", y),
+            TextBaseline::Middle => ("middle", y),
+            TextBaseline::Alphabetic => ("alphabetic", y),
+            TextBaseline::Ideographic => ("ideographic", y),
+            TextBaseline::Bottom => ("text-after-edge", y),
+        };
+
+        let _ = writeln!(
+            self.body,
+            "<text x=\"{}\" y=\"{}\" dominant-baseline=\"{}\" xml:space=\"preserve\"{}{}{}{}>{}</text>",
+            number(x),
+            number(y),
+            baseline,
+            font,
+            paint,
+            self.alpha_attribute(),
+            self.transform_attribute(),
+            escape(text)
+        );
+    }
+
+    fn draw_image(
+        &mut self,
+        image: &ImageSource,
+        clip: Option<Rectangle>,
+        x: f64,
+        y: f64,
+        size: Option<(f64, f64)>,
+    ) {
+        let (href, natural_size) = match image_href(image) {
+            Some(image) => image,
+            None => return,
+        };
+
+        let natural_size = match natural_size {
+            Some((width, height)) => format!(" width=\"{}\" height=\"{}\"", width, height),
+            None => String::new(),
+        };
+
+        // a scaled image is not kept in aspect ratio
+        let aspect_ratio = if size.is_some() {
+            " preserveAspectRatio=\"none\""
+        } else {
+            ""
+        };
+
+        match clip {
+            Some(clip) => {
+                let (width, height) = size.unwrap_or((clip.width(), clip.height()));
+                let svg = format!(
+                    "<svg x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" viewBox=\"{} {} {} {}\"{}{}><image{} href=\"{}\"/></svg>",
+                    number(x),
+                    number(y),
+                    number(width),
+                    number(height),
+                    number(clip.x()),
+                    number(clip.y()),
+                    number(clip.width()),
+                    number(clip.height()),
+                    aspect_ratio,
+                    self.alpha_attribute(),
+                    natural_size,
+                    href
+                );
+
+                // nested svg elements have no transform attribute
+                let transform = self.transform_attribute();
+
+                if transform.is_empty() {
+                    let _ = writeln!(self.body, "{}", svg);
+                } else {
+                    let _ = writeln!(self.body, "<g{}>{}</g>", transform, svg);
+                }
+            }
+            None => {
+                let size = match size {
+                    Some((width, height)) => {
+                        format!(" width=\"{}\" height=\"{}\"", number(width), number(height))
+                    }
+                    None => natural_size,
+                };
+                let _ = writeln!(
+                    self.body,
+                    "<image x=\"{}\" y=\"{}\"{}{} href=\"{}\"{}{}/>",
+                    number(x),
+                    number(y),
+                    size,
+                    aspect_ratio,
+                    href,
+                    self.alpha_attribute(),
+                    self.transform_attribute()
+                );
+            }
+        }
+    }
+
+    fn draw_box_shadow(
+        &mut self,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        border_radius: f64,
+        box_shadow: &BoxShadow,
+    ) {
+        let width = width + 2.0 * box_shadow.spread;
+        let height = height + 2.0 * box_shadow.spread;
+
+        if box_shadow.is_transparent() || width <= 0.0 || height <= 0.0 {
+            return;
+        }
+
+        let mut filter = String::new();
+
+        if box_shadow.blur > 0.0 {
+            let id = self.id("shadow");
+            let _ = writeln!(
+                self.defs,
+                "<filter id=\"{}\" x=\"-50%\" y=\"-50%\" width=\"200%\" height=\"200%\"><feGaussianBlur stdDeviation=\"{}\"/></filter>",
+                id,
+                number(box_shadow.blur / 2.0)
+            );
+            filter = format!(" filter=\"url(#{})\"", id);
+        }
+
+        let radius = rounded_rect_radius(width, height, border_radius + box_shadow.spread);
+        let paint = self.paint("fill", &Brush::from(box_shadow.color), Rectangle::default());
+        let _ = writeln!(
+            self.body,
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"{}\"{}{}{}{}/>",
+            number(x + box_shadow.offset.x() - box_shadow.spread),
+            number(y + box_shadow.offset.y() - box_shadow.spread),
+            number(width),
+            number(height),
+            number(radius),
+            paint,
+            filter,
+            self.alpha_attribute(),
+            self.transform_attribute()
+        );
+    }
+
+    fn clip(&mut self) {
+        if self.path_rect.is_none() {
+            return;
+        }
+
+        let id = self.id("clip");
+        let _ = writeln!(
+            self.defs,
+            "<clipPath id=\"{}\"><path d=\"{}\"{}/></clipPath>",
+            id,
+            self.path.trim_end(),
+            self.transform_attribute()
+        );
+        self.open_group(&format!(" clip-path=\"url(#{})\"", id));
+        self.state.clip_groups += 1;
+    }
+
+    fn push_layer(&mut self, opacity: f64, effect: Effect, region: Option<Rectangle>) {
+        self.layers.push((self.groups, self.state.clip_groups));
+
+        let mut attributes = String::new();
+
+        if opacity < 1.0 {
+            let _ = write!(attributes, " opacity=\"{}\"", number(opacity));
+        }
+
+        let mut primitives = String::new();
+
+        if let Effect::Blur(radius) = effect {
+            let _ = write!(
+                primitives,
+                "<feGaussianBlur stdDeviation=\"{}\"/>",
+                number(radius / 2.0)
+            );
+        }
+
+        if let Some(matrix) = effect.color_matrix() {
+            let values: Vec<String> = matrix.iter().map(|v| number(*v)).collect();
+            let _ = write!(
+                primitives,
+                "<feColorMatrix type=\"matrix\" values=\"{}\"/>",
+                values.join(" ")
+            );
+        }
+
+        if !primitives.is_empty() {
+            let id = self.id("effect");
+            let region = region.unwrap_or_default();
+            let _ = writeln!(
+                self.defs,
+                "<filter id=\"{}\" filterUnits=\"userSpaceOnUse\" x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" color-interpolation-filters=\"sRGB\">{}</filter>",
+                id,
+                number(region.x()),
+                number(region.y()),
+                number(region.width()),
+                number(region.height()),
+                primitives
+            );
+            let _ = write!(attributes, " filter=\"url(#{})\"", id);
+        } else if let Some(region) = region {
+            let id = self.id("clip");
+            let _ = writeln!(
+                self.defs,
+                "<clipPath id=\"{}\"><rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/></clipPath>",
+                id,
+                number(region.x()),
+                number(region.y()),
+                number(region.width()),
+                number(region.height())
+            );
+            let _ = write!(attributes, " clip-path=\"url(#{})\"", id);
+        }
+
+        self.open_group(&attributes);
+    }
+
+    fn pop_layer(&mut self) {
+        if let Some((groups, clip_groups)) = self.layers.pop() {
+            // closes the clips of the layer and the layer itself
+            self.close_groups(self.groups - groups);
+            self.state.clip_groups = clip_groups;
+        }
+    }
+
+    fn save(&mut self) {
+        self.saved_states.push(self.state.clone());
+        self.state.clip_groups = 0;
+    }
+
+    fn restore(&mut self) {
+        if let Some(state) = self.saved_states.pop() {
+            self.close_groups(self.state.clip_groups);
+            self.state = state;
+        }
+    }
+
+    fn draw(&mut self, command: &DrawCommand) {
+        match command {
+            DrawCommand::FillRect {
+                x,
+                y,
+                width,
+                height,
+            } => self.fill_rect(*x, *y, *width, *height),
+            DrawCommand::StrokeRect {
+                x,
+                y,
+                width,
+                height,
+            } => {
+                self.rect(*x, *y, *width, *height);
+                self.stroke();
+            }
+            DrawCommand::DrawBoxShadow {
+                x,
+                y,
+                width,
+                height,
+                border_radius,
+                box_shadow,
+            } => self.draw_box_shadow(*x, *y, *width, *height, *border_radius, box_shadow),
+            DrawCommand::FillText { text, x, y } => self.fill_text(text, *x, *y),
+            DrawCommand::Fill => self.fill(),
+            DrawCommand::Stroke => self.stroke(),
+            DrawCommand::BeginPath => self.begin_path(),
+            DrawCommand::ClosePath => self.path.push_str("Z "),
+            DrawCommand::Rect {
+                x,
+                y,
+                width,
+                height,
+            } => self.rect(*x, *y, *width, *height),
+            DrawCommand::RoundedRect {
+                x,
+                y,
+                width,
+                height,
+                radius,
+            } => self.rounded_rect(*x, *y, *width, *height, *radius),
+            DrawCommand::Arc {
+                x,
+                y,
+                radius,
+                start_angle,
+                end_angle,
+            } => self.arc(*x, *y, *radius, *start_angle, *end_angle),
+            DrawCommand::MoveTo { x, y } => self.move_to(*x, *y),
+            DrawCommand::LineTo { x, y } => self.line_to(*x, *y),
+            DrawCommand::QuadraticCurveTo { cpx, cpy, x, y } => {
+                let _ = write!(
+                    self.path,
+                    "Q{} {} {} {} ",
+                    number(*cpx),
+                    number(*cpy),
+                    number(*x),
+                    number(*y)
+                );
+                self.path_rect()
+                    .record_quadratic_curve_to(*cpx, *cpy, *x, *y);
+            }
+            DrawCommand::BezierCurveTo {
+                cp1x,
+                cp1y,
+                cp2x,
+                cp2y,
+                x,
+                y,
+            } => {
+                let _ = write!(
+                    self.path,
+                    "C{} {} {} {} {} {} ",
+                    number(*cp1x),
+                    number(*cp1y),
+                    number(*cp2x),
+                    number(*cp2y),
+                    number(*x),
+                    number(*y)
+                );
+                self.path_rect()
+                    .record_bezier_curve_to(*cp1x, *cp1y, *cp2x, *cp2y, *x, *y);
+            }
+            DrawCommand::DrawImage { image, x, y } => self.draw_image(image, None, *x, *y, None),
+            DrawCommand::DrawImageWithClip { image, clip, x, y } => {
+                self.draw_image(image, Some(*clip), *x, *y, None)
+            }
+            DrawCommand::DrawImageWithSize {
+                image,
+                x,
+                y,
+                width,
+                height,
+            } => self.draw_image(image, None, *x, *y, Some((*width, *height))),
+            DrawCommand::DrawImageWithClipAndSize {
+                image,
+                clip,
+                x,
+                y,
+                width,
+                height,
+            } => self.draw_image(image, Some(*clip), *x, *y, Some((*width, *height))),
+            DrawCommand::Clip => self.clip(),
+            DrawCommand::PushLayer { opacity, clip } => {
+                self.push_layer(*opacity as f64, Effect::None, *clip)
+            }
+            DrawCommand::PushEffect { effect, bounds } => {
+                let extent = effect.extent();
+                let region = Rectangle::new(
+                    (bounds.x() - extent, bounds.y() - extent),
+                    (
+                        bounds.width() + 2.0 * extent,
+                        bounds.height() + 2.0 * extent,
+                    ),
+                );
+                self.push_layer(effect.opacity(), *effect, Some(region));
+            }
+            DrawCommand::PopLayer | DrawCommand::PopEffect => self.pop_layer(),
+            // svg renderers do not support filters of the background, the content behind is kept
+            DrawCommand::BackdropEffect { .. } => {}
+            DrawCommand::SetLineWidth { line_width } => self.state.line_width = *line_width,
+            DrawCommand::SetLineCap { line_cap } => self.state.line_cap = *line_cap,
+            DrawCommand::SetLineJoin { line_join } => self.state.line_join = *line_join,
+            DrawCommand::SetMiterLimit { miter_limit } => {
+                if miter_limit.is_finite() && *miter_limit > 0.0 {
+                    self.state.miter_limit = *miter_limit;
+                }
+            }
+            DrawCommand::SetLineDash { line_dash } => {
+                if let Some(line_dash) = line_dash_segments(line_dash) {
+                    self.state.line_dash = line_dash;
+                }
+            }
+            DrawCommand::SetLineDashOffset { line_dash_offset } => {
+                self.state.line_dash_offset = *line_dash_offset
+            }
+            DrawCommand::SetAlpha { alpha } => self.state.alpha = *alpha,
+            DrawCommand::SetFontFamily { family } => self.state.font_config.family = family.clone(),
+            DrawCommand::SetFontSize { size } => self.state.font_config.font_size = *size,
+            DrawCommand::SetFontWeight { weight } => self.state.font_config.weight = *weight,
+            DrawCommand::SetFontStyle { style } => self.state.font_config.style = *style,
+            DrawCommand::SetFontStretch { stretch } => self.state.font_config.stretch = *stretch,
+            DrawCommand::SetTextBaseline { text_baseline } => {
+                self.state.text_baseline = *text_baseline
+            }
+            DrawCommand::SetFillStyle { fill_style } => self.state.fill_style = fill_style.clone(),
+            DrawCommand::SetStrokeStyle { stroke_style } => {
+                self.state.stroke_style = stroke_style.clone()
+            }
+            DrawCommand::SetTransform {
+                h_scaling,
+                h_skewing,
+                v_skewing,
+                v_scaling,
+                h_moving,
+                v_moving,
+            } => {
+                self.state.transform = Matrix::new(
+                    *h_scaling, *h_skewing, *v_skewing, *v_scaling, *h_moving, *v_moving,
+                )
+            }
+            DrawCommand::Save => self.save(),
+            DrawCommand::Restore => self.restore(),
+            DrawCommand::Clear { brush } => {
+                // the whole canvas, transformations are ignored like on clear
+                let transform = std::mem::replace(&mut self.state.transform, Matrix::identity());
+                let alpha = std::mem::replace(&mut self.state.alpha, 1.0);
+                let paint = self.paint("fill", brush, Rectangle::default());
+                let _ = writeln!(self.body, "<rect width=\"100%\" height=\"100%\"{}/>", paint);
+                self.state.transform = transform;
+                self.state.alpha = alpha;
+            }
+        }
+    }
+}
+
+impl DisplayList {
+    /// Converts the display list to a svg document that shows the given part of the drawing.
+    ///
+    /// Texts are written as `<text>` elements with the font families of the draw calls, so the
+    /// fonts have to be installed where the document is shown. Conic gradients are not supported
+    /// by svg, the color of their first stop is used instead.
+    pub fn to_svg(&self, viewport: Rectangle) -> String {
+        let mut writer = SvgWriter::default();
+
+        for command in self.commands() {
+            writer.draw(command);
+        }
+
+        let groups = writer.groups;
+        writer.close_groups(groups);
+
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"{} {} {} {}\">\n",
+            number(viewport.width()),
+            number(viewport.height()),
+            number(viewport.x()),
+            number(viewport.y()),
+            number(viewport.width()),
+            number(viewport.height())
+        );
+
+        if !writer.defs.is_empty() {
+            let _ = write!(svg, "<defs>\n{}</defs>\n", writer.defs);
+        }
+
+        svg.push_str(&writer.body);
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_number() {
+        assert_eq!(number(10.0), "10");
+        assert_eq!(number(0.5), "0.5");
+        assert_eq!(number(1.23456), "1.235");
+        assert_eq!(number(-0.0001), "0");
+    }
+
+    #[test]
+    fn test_png_data_url() {
+        assert_eq!(png_data_url(0, 1, &[]), None);
+        assert_eq!(png_data_url(2, 1, &[0xff00_0000]), None);
+
+        let url = png_data_url(2, 1, &[0xffff_0000, 0x8000_0080]).unwrap();
+        let png = base64::decode(url.trim_start_matches("data:image/png;base64,")).unwrap();
+        let image = image::load_from_memory(&png).unwrap().to_rgba8();
+
+        assert_eq!(image.dimensions(), (2, 1));
+        assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(1, 0).0, [0, 0, 255, 128]);
+    }
+
+    #[test]
+    fn test_to_svg() {
+        let mut display_list = DisplayList::new();
+        display_list.push(DrawCommand::SetFillStyle {
+            fill_style: Brush::from("#ff0000"),
+        });
+        display_list.push(DrawCommand::FillRect {
+            x: 0.0,
+            y: 0.0,
+            width: 10.0,
+            height: 5.0,
+        });
+        display_list.push(DrawCommand::Save);
+        display_list.push(DrawCommand::BeginPath);
+        display_list.push(DrawCommand::Rect {
+            x: 2.0,
+            y: 2.0,
+            width: 4.0,
+            height: 4.0,
+        });
+        display_list.push(DrawCommand::Clip);
+        display_list.push(DrawCommand::SetFontFamily {
+            family: "Roboto".to_string(),
+        });
+        display_list.push(DrawCommand::SetFontSize { size: 12.0 });
+        display_list.push(DrawCommand::FillText {
+            text: "a < b".to_string(),
+            x: 1.0,
+            y: 2.0,
+        });
+        display_list.push(DrawCommand::Restore);
+        display_list.push(DrawCommand::SetFillStyle {
+            fill_style: Brush::Gradient(Gradient::default()),
+        });
+        display_list.push(DrawCommand::BeginPath);
+        display_list.push(DrawCommand::MoveTo { x: 0.0, y: 0.0 });
+        display_list.push(DrawCommand::LineTo { x: 10.0, y: 10.0 });
+        display_list.push(DrawCommand::Fill);
+
+        let svg = display_list.to_svg(Rectangle::new((0.0, 0.0), (20.0, 10.0)));
+
+        assert!(svg.starts_with(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"20\" height=\"10\" viewBox=\"0 0 20 10\">"
+        ));
+        assert!(svg.contains("<rect x=\"0\" y=\"0\" width=\"10\" height=\"5\" fill=\"#ff0000\"/>"));
+        assert!(svg.contains("<clipPath id=\"clip1\"><path d=\"M2 2 h4 v4 h-4 Z\"/></clipPath>"));
+        assert!(svg.contains("<g clip-path=\"url(#clip1)\">\n<text x=\"1\" y=\"8\""));
+        assert!(svg.contains(
+            " font-family=\"Roboto\" font-size=\"12\" fill=\"#ff0000\">a &lt; b</text>\n</g>"
+        ));
+        assert!(svg.contains("<linearGradient id=\"gradient2\""));
+        assert!(svg.contains("<path d=\"M0 0 L10 10\" fill=\"url(#gradient2)\"/>"));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+
+    #[test]
+    fn test_clipped_image() {
+        let image = ImageSource::Data {
+            width: 2,
+            height: 2,
+            data: std::sync::Arc::new(vec![0xff00_0000; 4]),
+        };
+
+        let mut display_list = DisplayList::new();
+        display_list.push(DrawCommand::DrawImageWithClip {
+            image: image.clone(),
+            clip: Rectangle::new((1.0, 0.0), (1.0, 2.0)),
+            x: 4.0,
+            y: 5.0,
+        });
+        display_list.push(DrawCommand::SetTransform {
+            h_scaling: 2.0,
+            h_skewing: 0.0,
+            v_skewing: 0.0,
+            v_scaling: 2.0,
+            h_moving: 10.0,
+            v_moving: 0.0,
+        });
+        display_list.push(DrawCommand::DrawImageWithClip {
+            image,
+            clip: Rectangle::new((1.0, 0.0), (1.0, 2.0)),
+            x: 4.0,
+            y: 5.0,
+        });
+
+        let svg = display_list.to_svg(Rectangle::new((0.0, 0.0), (20.0, 10.0)));
+
+        assert!(svg.contains(
+            "\n<svg x=\"4\" y=\"5\" width=\"1\" height=\"2\" viewBox=\"1 0 1 2\"><image width=\"2\" height=\"2\" href=\"data:image/png;base64,"
+        ));
+        assert!(svg.contains(
+            "<g transform=\"matrix(2 0 0 2 10 0)\"><svg x=\"4\" y=\"5\" width=\"1\" height=\"2\" viewBox=\"1 0 1 2\">"
+        ));
+        assert!(svg.contains("</svg></g>\n"));
+    }
+
+    #[test]
+    fn test_text_baseline() {
+        let mut display_list = DisplayList::new();
+        display_list.push(DrawCommand::SetTextBaseline {
+            text_baseline: TextBaseline::Alphabetic,
+        });
+        display_list.push(DrawCommand::FillText {
+            text: "a".to_string(),
+            x: 1.0,
+            y: 10.0,
+        });
+
+        let svg = display_list.to_svg(Rectangle::new((0.0, 0.0), (20.0, 10.0)));
+
+        assert!(svg.contains("<text x=\"1\" y=\"10\" dominant-baseline=\"alphabetic\""));
+    }
+}
@@ -0,0 +1,465 @@
+use std::ops::Range;
+
+use crate::utils::{TextAlignment, TextTrimming, TextWrap};
+
+// Replaces the parts of a trimmed text.
+const ELLIPSIS: &str = "…";
+
+/// Defines how a `TextLayout` breaks, trims and aligns the lines of a text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextLayoutOptions {
+    /// The font size the text is measured with.
+    pub font_size: f64,
+    /// Distance between the tops of two lines relative to the font size.
+    pub line_height: f64,
+    /// Width of the layout box, the lines are wrapped, trimmed and aligned in it. Lines are not
+    /// limited if it is infinite or `f64::MAX`.
+    pub max_width: f64,
+    /// Maximum number of lines, `0` for no limit.
+    pub max_lines: usize,
+    pub wrap: TextWrap,
+    pub trimming: TextTrimming,
+    pub alignment: TextAlignment,
+}
+
+impl Default for TextLayoutOptions {
+    fn default() -> Self {
+        TextLayoutOptions {
+            font_size: 12.0,
+            line_height: 1.15,
+            max_width: f64::MAX,
+            max_lines: 0,
+            wrap: TextWrap::default(),
+            trimming: TextTrimming::default(),
+            alignment: TextAlignment::default(),
+        }
+    }
+}
+
+/// A line of a `TextLayout`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextLine {
+    /// Byte range of the line in the text, with the whitespaces the line is wrapped after and
+    /// without the line break.
+    pub range: Range<usize>,
+    /// The text that is drawn, without trailing whitespaces. It contains an ellipsis if the line
+    /// is trimmed.
+    pub text: String,
+    /// Position of the line in the layout box.
+    pub x: f64,
+    pub y: f64,
+    /// Width of the drawn text.
+    pub width: f64,
+}
+
+/// Breaks a text into lines at line breaks and, with a limited width, between words or chars.
+/// The lines are measured with the given function, e.g. `measure_text` of a render context
+/// with the font of the text.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextLayout {
+    lines: Vec<TextLine>,
+    width: f64,
+    height: f64,
+}
+
+impl TextLayout {
+    /// Lays out the text with the given options, `measure` returns the width of a part of the
+    /// text.
+    pub fn new<F>(text: &str, options: &TextLayoutOptions, mut measure: F) -> Self
+    where
+        F: FnMut(&str) -> f64,
+    {
+        if text.is_empty() {
+            return TextLayout::default();
+        }
+
+        let limited = options.max_width.is_finite() && options.max_width < f64::MAX;
+        let max_width = options.max_width.max(0.0);
+        let mut ranges = vec![];
+        let mut start = 0;
+
+        for paragraph in text.split('\n') {
+            let end = start + paragraph.trim_end_matches('\r').len();
+
+            match options.wrap {
+                TextWrap::Word if limited => {
+                    wrap_words(text, start..end, max_width, &mut measure, &mut ranges)
+                }
+                TextWrap::Character if limited => {
+                    wrap_chars(text, start..end, max_width, &mut measure, &mut ranges)
+                }
+                _ => ranges.push(start..end),
+            }
+
+            start += paragraph.len() + 1;
+        }
+
+        let truncated = options.max_lines > 0 && ranges.len() > options.max_lines;
+        ranges.truncate(if truncated {
+            options.max_lines
+        } else {
+            ranges.len()
+        });
+
+        let advance = options.font_size * options.line_height;
+        let count = ranges.len();
+        let mut lines = Vec::with_capacity(count);
+
+        for (index, range) in ranges.into_iter().enumerate() {
+            let cut = truncated && index + 1 == count;
+            let mut line_text = text[range.clone()].trim_end().to_string();
+            let mut width = measure(&line_text);
+
+            if options.trimming != TextTrimming::None && (cut || (limited && width > max_width)) {
+                line_text = match options.trimming {
+                    // the start of the line and the end of the whole text are kept
+                    TextTrimming::Middle if cut => {
+                        let rest = text[range.start..].replace('\n', " ");
+                        trim_middle(rest.trim_end(), limited, max_width, &mut measure)
+                    }
+                    TextTrimming::Middle => {
+                        trim_middle(&line_text, limited, max_width, &mut measure)
+                    }
+                    _ => trim_end(&line_text, limited, max_width, &mut measure),
+                };
+                width = measure(&line_text);
+            }
+
+            lines.push(TextLine {
+                range,
+                text: line_text,
+                x: 0.0,
+                y: index as f64 * advance,
+                width,
+            });
+        }
+
+        let width = lines.iter().map(|line| line.width).fold(0.0, f64::max);
+        let available_width = if limited { max_width } else { width };
+
+        for line in &mut lines {
+            line.x = options.alignment.offset(line.width, available_width);
+        }
+
+        TextLayout {
+            height: options.font_size.ceil() + (count - 1) as f64 * advance,
+            lines,
+            width,
+        }
+    }
+
+    /// Gets the lines from top to bottom.
+    pub fn lines(&self) -> &[TextLine] {
+        &self.lines
+    }
+
+    /// Gets the width of the widest line.
+    pub fn width(&self) -> f64 {
+        self.width
+    }
+
+    /// Gets the height of all lines.
+    pub fn height(&self) -> f64 {
+        self.height
+    }
+}
+
+// Returns the largest count in `0..=max` that fits, the counts that fit have to be a range
+// starting at `0`. `None` if no count fits.
+fn largest_fitting(max: usize, mut fits: impl FnMut(usize) -> bool) -> Option<usize> {
+    if !fits(0) {
+        return None;
+    }
+
+    let (mut low, mut high) = (0, max);
+
+    while low < high {
+        let middle = high - (high - low) / 2;
+
+        if fits(middle) {
+            low = middle;
+        } else {
+            high = middle - 1;
+        }
+    }
+
+    Some(low)
+}
+
+// Returns the byte indices of the chars of the range and its end.
+fn char_boundaries(text: &str, range: Range<usize>) -> Vec<usize> {
+    text[range.clone()]
+        .char_indices()
+        .map(|(index, _)| range.start + index)
+        .chain(std::iter::once(range.end))
+        .collect()
+}
+
+// Breaks the range into lines of as many chars as fit, at least one char per line.
+fn wrap_chars<F: FnMut(&str) -> f64>(
+    text: &str,
+    range: Range<usize>,
+    max_width: f64,
+    measure: &mut F,
+    lines: &mut Vec<Range<usize>>,
+) {
+    let boundaries = char_boundaries(text, range.clone());
+
+    if boundaries.len() == 1 {
+        lines.push(range);
+        return;
+    }
+
+    let mut first = 0;
+
+    while first + 1 < boundaries.len() {
+        let count = largest_fitting(boundaries.len() - 1 - first, |count| {
+            measure(&text[boundaries[first]..boundaries[first + count]]) <= max_width
+        })
+        .unwrap_or(0)
+        .max(1);
+
+        lines.push(boundaries[first]..boundaries[first + count]);
+        first += count;
+    }
+}
+
+// Breaks the range into lines between words, a word is followed by its whitespaces. Words that
+// are wider than a line are broken between chars.
+fn wrap_words<F: FnMut(&str) -> f64>(
+    text: &str,
+    range: Range<usize>,
+    max_width: f64,
+    measure: &mut F,
+    lines: &mut Vec<Range<usize>>,
+) {
+    let mut words = vec![];
+    let mut word_start = range.start;
+    let mut after_whitespace = false;
+
+    for (index, c) in text[range.clone()].char_indices() {
+        let index = range.start + index;
+
+        if c.is_whitespace() {
+            after_whitespace = index > word_start;
+        } else if after_whitespace {
+            words.push(word_start..index);
+            word_start = index;
+            after_whitespace = false;
+        }
+    }
+
+    words.push(word_start..range.end);
+
+    let mut line = range.start..range.start;
+
+    for word in words {
+        let word_end = word.start + text[word.clone()].trim_end().len();
+
+        if measure(&text[line.start..word_end]) <= max_width {
+            line.end = word.end;
+            continue;
+        }
+
+        if !line.is_empty() {
+            lines.push(line.clone());
+        }
+
+        line = word.clone();
+
+        if measure(&text[word.start..word_end]) > max_width {
+            // the last part of the broken word is continued by the next words
+            wrap_chars(text, word.start..word_end, max_width, measure, lines);
+
+            if let Some(last) = lines.pop() {
+                line = last.start..word.end;
+            }
+        }
+    }
+
+    lines.push(line);
+}
+
+// Removes chars from the end of the text until it fits with an ellipsis.
+fn trim_end<F: FnMut(&str) -> f64>(
+    text: &str,
+    limited: bool,
+    max_width: f64,
+    measure: &mut F,
+) -> String {
+    let boundaries = char_boundaries(text, 0..text.len());
+    let trimmed = |count: usize| format!("{}{}", text[..boundaries[count]].trim_end(), ELLIPSIS);
+
+    if !limited {
+        return trimmed(boundaries.len() - 1);
+    }
+
+    largest_fitting(boundaries.len() - 1, |count| {
+        measure(&trimmed(count)) <= max_width
+    })
+    .map_or_else(|| ELLIPSIS.to_string(), trimmed)
+}
+
+// Removes chars from the middle of the text until it fits with an ellipsis.
+fn trim_middle<F: FnMut(&str) -> f64>(
+    text: &str,
+    limited: bool,
+    max_width: f64,
+    measure: &mut F,
+) -> String {
+    let boundaries = char_boundaries(text, 0..text.len());
+    let chars = boundaries.len() - 1;
+    let trimmed = |count: usize| {
+        let head = boundaries[count - count / 2];
+        let tail = boundaries[chars - count / 2];
+
+        format!(
+            "{}{}{}",
+            text[..head].trim_end(),
+            ELLIPSIS,
+            text[tail..].trim_start()
+        )
+    };
+
+    if !limited {
+        return format!("{}{}", text, ELLIPSIS);
+    }
+
+    largest_fitting(chars.saturating_sub(1), |count| {
+        measure(&trimmed(count)) <= max_width
+    })
+    .map_or_else(|| ELLIPSIS.to_string(), trimmed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // every char is 10 pixels wide
+    fn measure(text: &str) -> f64 {
+        text.chars().count() as f64 * 10.0
+    }
+
+    fn layout(text: &str, options: TextLayoutOptions) -> Vec<String> {
+        TextLayout::new(text, &options, measure)
+            .lines()
+            .iter()
+            .map(|line| line.text.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_line_breaks() {
+        let text_layout = TextLayout::new("ab\r\ncde\n", &TextLayoutOptions::default(), measure);
+        let lines = text_layout.lines();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].range, 0..2);
+        assert_eq!(lines[1].range, 4..7);
+        assert_eq!(lines[1].text, "cde");
+        assert_eq!(lines[1].y, 12.0 * 1.15);
+        assert_eq!(text_layout.width(), 30.0);
+        assert_eq!(text_layout.height(), 12.0 + 2.0 * 12.0 * 1.15);
+        assert_eq!(
+            TextLayout::new("", &TextLayoutOptions::default(), measure),
+            TextLayout::default()
+        );
+    }
+
+    #[test]
+    fn test_wrap() {
+        let word = TextLayoutOptions {
+            max_width: 60.0,
+            wrap: TextWrap::Word,
+            ..Default::default()
+        };
+        assert_eq!(
+            layout("one two three four", word.clone()),
+            vec!["one", "two", "three", "four"]
+        );
+        assert_eq!(layout("ab cd ef", word.clone()), vec!["ab cd", "ef"]);
+
+        // long words are broken between chars and continued by the next words
+        assert_eq!(
+            layout("abcdefghij kl", word.clone()),
+            vec!["abcdef", "ghij", "kl"]
+        );
+        assert_eq!(layout("abcdefgh i", word), vec!["abcdef", "gh i"]);
+
+        let character = TextLayoutOptions {
+            max_width: 40.0,
+            wrap: TextWrap::Character,
+            ..Default::default()
+        };
+        assert_eq!(layout("abcdefghij", character), vec!["abcd", "efgh", "ij"]);
+
+        // without a limited width the lines are not wrapped
+        assert_eq!(
+            layout(
+                "one two",
+                TextLayoutOptions {
+                    wrap: TextWrap::Word,
+                    ..Default::default()
+                }
+            ),
+            vec!["one two"]
+        );
+    }
+
+    #[test]
+    fn test_trimming() {
+        let end = TextLayoutOptions {
+            max_width: 50.0,
+            trimming: TextTrimming::End,
+            ..Default::default()
+        };
+        assert_eq!(layout("abcdefgh", end.clone()), vec!["abcd…"]);
+        assert_eq!(layout("abc", end), vec!["abc"]);
+
+        let middle = TextLayoutOptions {
+            max_width: 50.0,
+            trimming: TextTrimming::Middle,
+            ..Default::default()
+        };
+        assert_eq!(layout("abcdefgh", middle), vec!["ab…gh"]);
+
+        let max_lines = TextLayoutOptions {
+            max_width: 60.0,
+            max_lines: 2,
+            wrap: TextWrap::Word,
+            trimming: TextTrimming::End,
+            ..Default::default()
+        };
+        assert_eq!(layout("one two three four", max_lines), vec!["one", "two…"]);
+
+        let max_lines = TextLayoutOptions {
+            max_lines: 1,
+            trimming: TextTrimming::Middle,
+            max_width: 70.0,
+            ..Default::default()
+        };
+        assert_eq!(layout("abc\ndefgh", max_lines), vec!["abc…fgh"]);
+    }
+
+    #[test]
+    fn test_alignment() {
+        let options = TextLayoutOptions {
+            max_width: 100.0,
+            alignment: TextAlignment::Center,
+            ..Default::default()
+        };
+        let text_layout = TextLayout::new("ab\nabcd", &options, measure);
+
+        assert_eq!(text_layout.lines()[0].x, 40.0);
+        assert_eq!(text_layout.lines()[1].x, 30.0);
+
+        let options = TextLayoutOptions {
+            alignment: TextAlignment::End,
+            ..Default::default()
+        };
+        let text_layout = TextLayout::new("ab\nabcd", &options, measure);
+
+        assert_eq!(text_layout.lines()[0].x, 20.0);
+        assert_eq!(text_layout.lines()[1].x, 0.0);
+    }
+}
@@ -0,0 +1,1586 @@
+use smallvec::SmallVec;
+use std::{
+    f64::consts::{FRAC_PI_2, PI},
+    path::Path,
+    sync::Arc,
+};
+
+use tiny_skia as skia;
+
+use crate::{
+    common::*, utils::*, DisplayList, DrawCommand, GlyphMetrics, PipelineTrait, RenderConfig,
+    RenderTarget, TextMetrics,
+};
+
+pub use self::font::*;
+pub use self::image::Image;
+
+// fonts and images do not depend on the rasterizer, they are shared with the raqote backend
+#[path = "../raqote/font.rs"]
+mod font;
+#[path = "../raqote/image.rs"]
+mod image;
+
+type StatesOnStack = [(RenderConfig, PathRect, Option<Arc<skia::ClipMask>>, Matrix); 2];
+
+// A segment of the current path. The tiny-skia path is built when it is filled, stroked or used
+// as clip.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum PathOp {
+    MoveTo(f32, f32),
+    LineTo(f32, f32),
+    QuadTo(f32, f32, f32, f32),
+    CubicTo(f32, f32, f32, f32, f32, f32),
+    Close,
+}
+
+// Offscreen layer that is composited on its parent pixmap when it is popped.
+struct Layer {
+    // the pixmap the layer is composited on
+    pixmap: skia::Pixmap,
+    clip_mask: Option<Arc<skia::ClipMask>>,
+    effect: Effect,
+    opacity: f32,
+    region: Option<Rectangle>,
+}
+
+// Pixels of a pattern brush with the transformation from pattern space to path space.
+struct Pattern {
+    pixmap: skia::Pixmap,
+    transform: Matrix,
+    spread_mode: skia::SpreadMode,
+}
+
+/// The RenderContext2D trait, provides the rendering ctx. It is used for drawing shapes, text, images, and other objects.
+///
+/// This render context rasterizes with tiny-skia, it is enabled by the `orbskia` feature.
+pub struct RenderContext2D {
+    pixmap: skia::Pixmap,
+    // premultiplied ARGB pixels of the pixmap, they are updated when a frame is finished
+    data: Vec<u32>,
+    // `data` is changed by `data_mut` and copied into the pixmap before the next draw call
+    data_changed: bool,
+    path: Vec<PathOp>,
+    config: RenderConfig,
+    transform: Matrix,
+    clip_mask: Option<Arc<skia::ClipMask>>,
+    saved_states: SmallVec<StatesOnStack>,
+    fonts: FontRegistry,
+    box_shadows: BoxShadowCache,
+    brush_images: BrushImageCache<Image>,
+    path_rect: PathRect,
+    layers: Vec<Layer>,
+    // parts of the pixmap that are redrawn since the last `take_damaged_regions`
+    damaged_regions: Vec<Rectangle>,
+    // number of damaged regions that are already copied into `data`
+    synced_regions: usize,
+    // draw calls that are recorded since `start_recording`
+    recording: Option<DisplayList>,
+
+    background: Color,
+}
+
+impl RenderContext2D {
+    /// Creates a new render ctx 2d.
+    pub fn new(width: f64, height: f64) -> Self {
+        RenderContext2D {
+            pixmap: new_pixmap(width, height),
+            data: vec![0; pixel_count(width, height)],
+            data_changed: false,
+            path: vec![],
+            config: RenderConfig::default(),
+            transform: Matrix::identity(),
+            clip_mask: None,
+            saved_states: SmallVec::<StatesOnStack>::new(),
+            fonts: FontRegistry::new(),
+            box_shadows: BoxShadowCache::new(),
+            brush_images: BrushImageCache::new(),
+            path_rect: PathRect::new(None),
+            layers: vec![],
+            damaged_regions: vec![],
+            synced_regions: 0,
+            recording: None,
+            background: Color::default(),
+        }
+    }
+
+    /// Set the background of the render context.
+    pub fn set_background(&mut self, background: Color) {
+        self.background = background;
+    }
+
+    pub fn resize(&mut self, width: f64, height: f64) {
+        self.pixmap = new_pixmap(width, height);
+        self.data = vec![0; pixel_count(width, height)];
+        self.data_changed = false;
+        self.clip_mask = None;
+    }
+
+    // Rectangles
+
+    /// Draws a filled rectangle whose starting point is at the coordinates {x, y} with the specified width and height and whose style is determined by the fillStyle attribute.
+    pub fn fill_rect(&mut self, x: f64, y: f64, width: f64, height: f64) {
+        self.record(|| DrawCommand::FillRect {
+            x,
+            y,
+            width,
+            height,
+        });
+
+        if let Some(rect) = skia::Rect::from_xywh(x as f32, y as f32, width as f32, height as f32) {
+            let path = skia::PathBuilder::from_rect(rect);
+            self.draw_path(&path, Rectangle::new((x, y), (width, height)), true);
+        }
+    }
+
+    /// Draws a rectangle that is stroked (outlined) according to the current strokeStyle and other ctx settings.
+    pub fn stroke_rect(&mut self, x: f64, y: f64, width: f64, height: f64) {
+        self.rect(x, y, width, height);
+        self.stroke();
+    }
+
+    // Shadows
+
+    /// Draws the blurred shadow of a (rounded) rectangle with the given position, size and corner
+    /// radius. The shadow is moved by the offset of the box shadow and grows or shrinks by its spread.
+    pub fn draw_box_shadow(
+        &mut self,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        border_radius: f64,
+        box_shadow: &BoxShadow,
+    ) {
+        self.record(|| DrawCommand::DrawBoxShadow {
+            x,
+            y,
+            width,
+            height,
+            border_radius,
+            box_shadow: *box_shadow,
+        });
+        if box_shadow.is_transparent()
+            || width + 2.0 * box_shadow.spread <= 0.0
+            || height + 2.0 * box_shadow.spread <= 0.0
+        {
+            return;
+        }
+
+        let shadow = self
+            .box_shadows
+            .get(width, height, border_radius, box_shadow);
+
+        self.draw_pixels(
+            &shadow.data,
+            shadow.width as u32,
+            shadow.height as u32,
+            Matrix::translation(
+                x + box_shadow.offset.x() - box_shadow.spread - shadow.padding,
+                y + box_shadow.offset.y() - box_shadow.spread - shadow.padding,
+            ),
+            self.config.alpha,
+        );
+    }
+
+    // Text
+
+    /// Draws (fills) a given text at the given (x, y) position. The text is transformed and
+    /// clipped like all other draw calls.
+    pub fn fill_text(&mut self, text: &str, x: f64, y: f64) {
+        self.record(|| DrawCommand::FillText {
+            text: text.to_string(),
+            x,
+            y,
+        });
+        if text.is_empty() {
+            return;
+        }
+
+        let color = match self.config.fill_style {
+            Brush::SolidColor(color) => color,
+            _ => Color::from("#000000"),
+        };
+
+        if color.a() == 0 || self.config.alpha == 0.0 {
+            return;
+        }
+
+        let font_size = self.config.font_config.font_size;
+
+        // the text is rendered into its own pixels, they are drawn like an image
+        let (data, width, height, top) = {
+            let font_chain = self.fonts.chain(&self.config.font_config);
+
+            if font_chain.is_empty() {
+                return;
+            }
+
+            let (width, height) = font_chain.measure_text(text, font_size);
+            let (width, height) = (width.ceil().max(1.0), height.max(1.0));
+
+            // the pixels start at the top of the line
+            let (ascent, descent) = font_chain.vertical_metrics(text, font_size);
+            let top = y + self.config.text_baseline.offset(ascent, descent) - ascent;
+            let mut data = vec![0; (width * height) as usize];
+
+            font_chain.render_text(
+                text,
+                &mut data,
+                width,
+                height,
+                (font_size, color, self.config.alpha),
+                (0.0, 0.0),
+            );
+
+            (data, width as u32, height as u32, top)
+        };
+
+        self.draw_pixels(&data, width, height, Matrix::translation(x, top), 1.0);
+    }
+
+    /// Fills the current or given path with the current file style.
+    pub fn fill(&mut self) {
+        self.record(|| DrawCommand::Fill);
+        let rect = match self.path_rect.get_rect() {
+            Some(rect) => rect,
+            None => return, // The path is empty, do nothing
+        };
+
+        if let Some(path) = build_path(&self.path) {
+            self.draw_path(&path, rect, true);
+        }
+    }
+
+    /// Strokes {outlines} the current or given path with the current stroke style.
+    pub fn stroke(&mut self) {
+        self.record(|| DrawCommand::Stroke);
+        let rect = match self.path_rect.get_rect() {
+            Some(rect) => rect,
+            None => return, // The path is empty, do nothing
+        };
+
+        if let Some(path) = build_path(&self.path) {
+            self.draw_path(&path, rect, false);
+        }
+    }
+
+    // Fills or strokes the path with the fill or stroke style. The brush is placed relative to
+    // the frame.
+    fn draw_path(&mut self, path: &skia::Path, frame: Rectangle, fill: bool) {
+        self.sync_pixmap();
+        self.load_brush_image(fill);
+
+        let brush = if fill {
+            &self.config.fill_style
+        } else {
+            &self.config.stroke_style
+        };
+
+        let pattern = brush_pattern(brush, frame, &self.brush_images);
+        let paint = brush_to_paint(brush, frame, self.config.alpha, pattern.as_ref());
+        let transform = to_skia_transform(&self.transform);
+
+        // image brushes that are not repeated in both directions are clipped to the image bounds
+        let brush_clip_mask = image_brush_clip(brush, frame, &self.brush_images)
+            .and_then(|clip| clip.transform(transform))
+            .and_then(|clip| {
+                intersect_clip_mask(
+                    self.clip_mask.as_deref(),
+                    &clip,
+                    self.pixmap.width(),
+                    self.pixmap.height(),
+                )
+            });
+        let clip_mask = match &brush_clip_mask {
+            Some(clip_mask) => Some(clip_mask),
+            None => self.clip_mask.as_deref(),
+        };
+
+        if fill {
+            self.pixmap
+                .fill_path(path, &paint, skia::FillRule::Winding, transform, clip_mask);
+        } else {
+            self.pixmap
+                .stroke_path(path, &paint, &stroke(&self.config), transform, clip_mask);
+        }
+    }
+
+    // Draws premultiplied ARGB pixels, the transformation maps the pixels to the user space.
+    fn draw_pixels(
+        &mut self,
+        data: &[u32],
+        width: u32,
+        height: u32,
+        transform: Matrix,
+        alpha: f32,
+    ) {
+        self.sync_pixmap();
+
+        let pixmap = match pixmap_from_argb(data, width, height) {
+            Some(pixmap) => pixmap,
+            None => return,
+        };
+
+        self.pixmap.draw_pixmap(
+            0,
+            0,
+            pixmap.as_ref(),
+            &skia::PixmapPaint {
+                opacity: alpha,
+                quality: skia::FilterQuality::Bilinear,
+                ..Default::default()
+            },
+            to_skia_transform(&transform.then(&self.transform)),
+            self.clip_mask.as_deref(),
+        );
+    }
+
+    // Loads the image of the fill or stroke style if it is an image brush with a not yet loaded path.
+    fn load_brush_image(&mut self, fill: bool) {
+        let brush = if fill {
+            &self.config.fill_style
+        } else {
+            &self.config.stroke_style
+        };
+
+        if let Brush::Image(ImageBrush {
+            source: ImageSource::Path(path),
+            ..
+        }) = brush
+        {
+            // shares the pixels with images of the same path that are still used
+            self.brush_images.load(path, |path| {
+                Image::shared(image::os_path(path.to_string())).ok()
+            });
+        }
+    }
+
+    // Returns `true` if the current sub-path has a start point.
+    fn has_current_point(&self) -> bool {
+        !matches!(self.path.last(), None | Some(PathOp::Close))
+    }
+
+    /// Starts a new path by emptying the list of sub-paths. Call this when you want to create a new path.
+    pub fn begin_path(&mut self) {
+        self.record(|| DrawCommand::BeginPath);
+        self.path.clear();
+        self.path_rect.rebirth();
+    }
+
+    /// Attempts to add a straight line from the current point to the start of the current sub-path. If the shape has already been closed or has only one point, this function does nothing.
+    pub fn close_path(&mut self) {
+        self.record(|| DrawCommand::ClosePath);
+        if self.has_current_point() {
+            self.path.push(PathOp::Close);
+        }
+        self.path_rect.record_path_close();
+    }
+
+    /// Adds a rectangle to the current path.
+    pub fn rect(&mut self, x: f64, y: f64, width: f64, height: f64) {
+        self.record(|| DrawCommand::Rect {
+            x,
+            y,
+            width,
+            height,
+        });
+        let r = (x + width) as f32;
+        let b = (y + height) as f32;
+        let (x, y) = (x as f32, y as f32);
+
+        self.path.extend_from_slice(&[
+            PathOp::MoveTo(x, y),
+            PathOp::LineTo(r, y),
+            PathOp::LineTo(r, b),
+            PathOp::LineTo(x, b),
+            PathOp::Close,
+        ]);
+        self.path_rect
+            .record_rect(x as f64, y as f64, width, height);
+    }
+
+    /// Adds a rectangle with rounded corners to the current path. The radius is limited to half of
+    /// the width and height. Could be used with `clip` to clip to the shape of a rounded border.
+    pub fn rounded_rect(&mut self, x: f64, y: f64, width: f64, height: f64, radius: f64) {
+        self.record(|| DrawCommand::RoundedRect {
+            x,
+            y,
+            width,
+            height,
+            radius,
+        });
+        self.path_rect.record_rect(x, y, width, height);
+
+        let radius = rounded_rect_radius(width, height, radius);
+        let r = (x + width) as f32;
+        let b = (y + height) as f32;
+        let (x, y, radius) = (x as f32, y as f32, radius as f32);
+
+        self.path.extend_from_slice(&[
+            PathOp::MoveTo(x + radius, y),
+            PathOp::LineTo(r - radius, y),
+            PathOp::QuadTo(r, y, r, y + radius),
+            PathOp::LineTo(r, b - radius),
+            PathOp::QuadTo(r, b, r - radius, b),
+            PathOp::LineTo(x + radius, b),
+            PathOp::QuadTo(x, b, x, b - radius),
+            PathOp::LineTo(x, y + radius),
+            PathOp::QuadTo(x, y, x + radius, y),
+            PathOp::Close,
+        ]);
+    }
+
+    /// Creates a circular arc centered at (x, y) with a radius of radius. The path starts at startAngle and ends at endAngle.
+    pub fn arc(&mut self, x: f64, y: f64, radius: f64, start_angle: f64, end_angle: f64) {
+        self.record(|| DrawCommand::Arc {
+            x,
+            y,
+            radius,
+            start_angle,
+            end_angle,
+        });
+
+        let start = arc_point(x, y, radius, start_angle);
+        if self.has_current_point() {
+            self.path.push(PathOp::LineTo(start.0, start.1));
+        } else {
+            self.path.push(PathOp::MoveTo(start.0, start.1));
+        }
+        self.path
+            .extend(arc_segments(x, y, radius, start_angle, end_angle));
+        self.path_rect
+            .record_arc(x, y, radius, start_angle, end_angle);
+    }
+
+    /// Begins a new sub-path at the point specified by the given {x, y} coordinates.
+    pub fn move_to(&mut self, x: f64, y: f64) {
+        self.record(|| DrawCommand::MoveTo { x, y });
+        self.path.push(PathOp::MoveTo(x as f32, y as f32));
+        self.path_rect.record_move_to(x, y);
+    }
+
+    /// Adds a straight line to the current sub-path by connecting the sub-path's last point to the specified {x, y} coordinates.
+    pub fn line_to(&mut self, x: f64, y: f64) {
+        self.record(|| DrawCommand::LineTo { x, y });
+        self.path.push(PathOp::LineTo(x as f32, y as f32));
+        self.path_rect.record_line_to(x, y);
+    }
+
+    /// Adds a quadratic Bézier curve to the current sub-path.
+    pub fn quadratic_curve_to(&mut self, cpx: f64, cpy: f64, x: f64, y: f64) {
+        self.record(|| DrawCommand::QuadraticCurveTo { cpx, cpy, x, y });
+        self.path
+            .push(PathOp::QuadTo(cpx as f32, cpy as f32, x as f32, y as f32));
+        self.path_rect.record_quadratic_curve_to(cpx, cpy, x, y);
+    }
+
+    /// Adds a cubic Bézier curve to the current sub-path.
+    /// It requires three points: the first two are control points and the third one is the end point.
+    /// The starting point is the latest point in the current path, which can be changed using MoveTo{} before creating the Bézier curve.
+    pub fn bezier_curve_to(&mut self, cp1x: f64, cp1y: f64, cp2x: f64, cp2y: f64, x: f64, y: f64) {
+        self.record(|| DrawCommand::BezierCurveTo {
+            cp1x,
+            cp1y,
+            cp2x,
+            cp2y,
+            x,
+            y,
+        });
+        self.path.push(PathOp::CubicTo(
+            cp1x as f32,
+            cp1y as f32,
+            cp2x as f32,
+            cp2y as f32,
+            x as f32,
+            y as f32,
+        ));
+        self.path_rect
+            .record_bezier_curve_to(cp1x, cp1y, cp2x, cp2y, x, y);
+    }
+
+    /// Draws a render target.
+    pub fn draw_render_target(&mut self, render_target: &RenderTarget, x: f64, y: f64) {
+        self.record(|| DrawCommand::DrawImage {
+            image: ImageBrush::from(render_target.clone()).source,
+            x,
+            y,
+        });
+        self.draw_pixels(
+            render_target.data(),
+            render_target.width() as u32,
+            render_target.height() as u32,
+            Matrix::translation(x, y),
+            self.config.alpha,
+        );
+    }
+
+    /// Draws the image.
+    pub fn draw_image(&mut self, image: &Image, x: f64, y: f64) {
+        self.record(|| DrawCommand::DrawImage {
+            image: ImageBrush::from(image.clone()).source,
+            x,
+            y,
+        });
+        self.draw_pixels(
+            image.data(),
+            image.width() as u32,
+            image.height() as u32,
+            Matrix::translation(x, y),
+            self.config.alpha,
+        );
+    }
+
+    /// Draws the given part of the image.
+    pub fn draw_image_with_clip(&mut self, image: &Image, clip: Rectangle, x: f64, y: f64) {
+        self.record(|| DrawCommand::DrawImageWithClip {
+            image: ImageBrush::from(image.clone()).source,
+            clip,
+            x,
+            y,
+        });
+
+        if let Some((data, width, height)) = clipped_pixels(image, clip) {
+            self.draw_pixels(
+                &data,
+                width,
+                height,
+                Matrix::translation(x, y),
+                self.config.alpha,
+            );
+        }
+    }
+
+    /// Draws the image scaled to the given size.
+    pub fn draw_image_with_size(&mut self, image: &Image, x: f64, y: f64, width: f64, height: f64) {
+        self.record(|| DrawCommand::DrawImageWithSize {
+            image: ImageBrush::from(image.clone()).source,
+            x,
+            y,
+            width,
+            height,
+        });
+
+        if width <= 0.0 || height <= 0.0 || image.width() <= 0.0 || image.height() <= 0.0 {
+            return;
+        }
+
+        self.draw_pixels(
+            image.data(),
+            image.width() as u32,
+            image.height() as u32,
+            Matrix::scaling(width / image.width(), height / image.height())
+                .then(&Matrix::translation(x, y)),
+            self.config.alpha,
+        );
+    }
+
+    /// Draws the given part of the image scaled to the given size.
+    pub fn draw_image_with_clip_and_size(
+        &mut self,
+        image: &Image,
+        clip: Rectangle,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    ) {
+        self.record(|| DrawCommand::DrawImageWithClipAndSize {
+            image: ImageBrush::from(image.clone()).source,
+            clip,
+            x,
+            y,
+            width,
+            height,
+        });
+
+        if width <= 0.0 || height <= 0.0 {
+            return;
+        }
+
+        if let Some((data, clip_width, clip_height)) = clipped_pixels(image, clip) {
+            self.draw_pixels(
+                &data,
+                clip_width,
+                clip_height,
+                Matrix::scaling(width / clip_width as f64, height / clip_height as f64)
+                    .then(&Matrix::translation(x, y)),
+                self.config.alpha,
+            );
+        }
+    }
+
+    pub fn draw_pipeline(
+        &mut self,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        pipeline: Box<dyn PipelineTrait>,
+    ) {
+        let mut render_target = RenderTarget::new(width as u32, height as u32);
+        pipeline.draw_pipeline(&mut render_target);
+        self.draw_render_target(&render_target, x, y);
+    }
+
+    /// Creates a clipping path from the current sub-paths. Everything drawn after clip() is called appears inside the clipping path only.
+    pub fn clip(&mut self) {
+        self.record(|| DrawCommand::Clip);
+
+        let clip_mask = build_path(&self.path)
+            .and_then(|path| path.transform(to_skia_transform(&self.transform)))
+            .and_then(|path| {
+                intersect_clip_mask(
+                    self.clip_mask.as_deref(),
+                    &path,
+                    self.pixmap.width(),
+                    self.pixmap.height(),
+                )
+            });
+
+        if let Some(clip_mask) = clip_mask {
+            self.clip_mask = Some(Arc::new(clip_mask));
+        }
+
+        self.path_rect.record_clip();
+    }
+
+    // Layers
+
+    /// Starts an offscreen layer. Everything drawn until `pop_layer` is called is rendered into the
+    /// layer and then composited as a whole with the given opacity, so overlapping shapes do not
+    /// shine through each other. If `clip` is set only this part of the layer is composited.
+    /// Calls of `save` and `restore` must be balanced inside of the layer.
+    pub fn push_layer(&mut self, opacity: f32, clip: Option<Rectangle>) {
+        self.record(|| DrawCommand::PushLayer { opacity, clip });
+        self.push_layer_with_effect(Effect::None, opacity, clip);
+    }
+
+    /// Starts an offscreen layer like `push_layer`. When the layer is popped the effect is applied
+    /// on the given bounds (extended by the extent of the effect).
+    pub fn push_effect(&mut self, effect: Effect, bounds: Rectangle) {
+        self.record(|| DrawCommand::PushEffect { effect, bounds });
+        let extent = effect.extent();
+        let region = Rectangle::new(
+            (bounds.x() - extent, bounds.y() - extent),
+            (
+                bounds.width() + 2.0 * extent,
+                bounds.height() + 2.0 * extent,
+            ),
+        );
+        self.push_layer_with_effect(effect, 1.0, Some(region));
+    }
+
+    fn push_layer_with_effect(&mut self, effect: Effect, opacity: f32, region: Option<Rectangle>) {
+        self.sync_pixmap();
+
+        let pixmap = new_pixmap(self.pixmap.width() as f64, self.pixmap.height() as f64);
+        let parent = std::mem::replace(&mut self.pixmap, pixmap);
+
+        self.layers.push(Layer {
+            pixmap: parent,
+            clip_mask: self.clip_mask.take(),
+            effect,
+            opacity,
+            region,
+        });
+    }
+
+    /// Composites the current layer with its opacity on the previous pixmap. The effect of a
+    /// layer that is started by `push_effect` is applied before.
+    pub fn pop_layer(&mut self) {
+        let layer = match self.layers.pop() {
+            Some(layer) => layer,
+            None => return,
+        };
+        // also records the end of effects, `pop_effect` pops the layer
+        self.record(|| DrawCommand::PopLayer);
+
+        let mut content = std::mem::replace(&mut self.pixmap, layer.pixmap);
+        self.clip_mask = layer.clip_mask;
+
+        let width = content.width() as usize;
+        let height = content.height() as usize;
+        let region = layer
+            .region
+            .unwrap_or_else(|| Rectangle::new((0.0, 0.0), (width as f64, height as f64)));
+
+        // the effects work on premultiplied ARGB pixels
+        let color_matrix = layer.effect.color_matrix();
+        if let Effect::Blur(_) | Effect::ColorMatrix(_) = layer.effect {
+            let mut data = vec![0; width * height];
+            rgba_to_argb(content.data(), &mut data);
+
+            if let Effect::Blur(radius) = layer.effect {
+                blur_argb(&mut data, width, height, region, radius / 2.0);
+            }
+
+            if let Some(matrix) = color_matrix {
+                apply_color_matrix(&mut data, width, height, region, &matrix);
+            }
+
+            argb_to_rgba(&data, content.data_mut());
+        } else if let Some(matrix) = color_matrix {
+            let mut data = vec![0; width * height];
+            rgba_to_argb(content.data(), &mut data);
+            apply_color_matrix(&mut data, width, height, region, &matrix);
+            argb_to_rgba(&data, content.data_mut());
+        }
+
+        let rect = match skia::Rect::from_xywh(
+            region.x() as f32,
+            region.y() as f32,
+            region.width() as f32,
+            region.height() as f32,
+        ) {
+            Some(rect) => rect,
+            None => return,
+        };
+
+        // the layer is already transformed
+        let paint = skia::Paint {
+            shader: skia::Pattern::new(
+                content.as_ref(),
+                skia::SpreadMode::Pad,
+                skia::FilterQuality::Nearest,
+                layer.opacity * layer.effect.opacity() as f32,
+                skia::Transform::identity(),
+            ),
+            ..Default::default()
+        };
+
+        self.pixmap.fill_rect(
+            rect,
+            &paint,
+            skia::Transform::identity(),
+            self.clip_mask.as_deref(),
+        );
+    }
+
+    /// Pops the current layer, same as `pop_layer`.
+    pub fn pop_effect(&mut self) {
+        self.pop_layer();
+    }
+
+    /// Applies the effect on the pixels that are already drawn inside of the bounds, e.g. to blur
+    /// the content behind a widget. The bounds are not transformed, the changed pixels are
+    /// clipped like other draw calls.
+    pub fn apply_backdrop_effect(&mut self, effect: Effect, bounds: Rectangle) {
+        self.record(|| DrawCommand::BackdropEffect { effect, bounds });
+
+        if effect.is_none() {
+            return;
+        }
+
+        self.sync_pixmap();
+
+        let width = self.pixmap.width() as usize;
+        let height = self.pixmap.height() as usize;
+        let mut data = vec![0; width * height];
+        rgba_to_argb(self.pixmap.data(), &mut data);
+
+        let (region, pixels) = match backdrop_effect(&data, width, height, effect, bounds) {
+            Some(backdrop) => backdrop,
+            None => return,
+        };
+
+        let mut backdrop = new_pixmap(region.width(), region.height());
+        argb_to_rgba(&pixels, backdrop.data_mut());
+
+        let rect = match skia::Rect::from_xywh(
+            region.x() as f32,
+            region.y() as f32,
+            region.width() as f32,
+            region.height() as f32,
+        ) {
+            Some(rect) => rect,
+            None => return,
+        };
+
+        // the pixels are replaced, tiny-skia does not clip the source blend mode correctly, so
+        // they are cleared before the backdrop is drawn over them
+        let mut clear = skia::Paint::default();
+        clear.set_color(skia::Color::BLACK);
+        clear.blend_mode = skia::BlendMode::DestinationOut;
+
+        let paint = skia::Paint {
+            shader: skia::Pattern::new(
+                backdrop.as_ref(),
+                skia::SpreadMode::Pad,
+                skia::FilterQuality::Nearest,
+                1.0,
+                skia::Transform::from_translate(region.x() as f32, region.y() as f32),
+            ),
+            ..Default::default()
+        };
+
+        for paint in &[clear, paint] {
+            self.pixmap.fill_rect(
+                rect,
+                paint,
+                skia::Transform::identity(),
+                self.clip_mask.as_deref(),
+            );
+        }
+    }
+
+    // Transformations
+
+    /// Sets the transformation.
+    pub fn set_transform(
+        &mut self,
+        h_scaling: f64,
+        h_skewing: f64,
+        v_skewing: f64,
+        v_scaling: f64,
+        h_moving: f64,
+        v_moving: f64,
+    ) {
+        self.record(|| DrawCommand::SetTransform {
+            h_scaling,
+            h_skewing,
+            v_skewing,
+            v_scaling,
+            h_moving,
+            v_moving,
+        });
+        self.transform = Matrix::new(
+            h_scaling, h_skewing, v_skewing, v_scaling, h_moving, v_moving,
+        );
+    }
+
+    // Canvas states
+
+    /// Saves the entire state of the canvas by pushing the current state onto a stack.
+    pub fn save(&mut self) {
+        self.record(|| DrawCommand::Save);
+        self.saved_states.push((
+            self.config.clone(),
+            self.path_rect,
+            self.clip_mask.clone(),
+            self.transform,
+        ));
+    }
+
+    /// Restores the most recently saved canvas state by popping the top entry in the drawing state stack.
+    /// If there is no saved state, this method does nothing.
+    pub fn restore(&mut self) {
+        self.record(|| DrawCommand::Restore);
+        if let Some((config, path_rect, clip_mask, transform)) = self.saved_states.pop() {
+            self.config = config;
+            self.path_rect = path_rect;
+            self.clip_mask = clip_mask;
+            self.transform = transform;
+        }
+    }
+
+    /// Sets the drawing state back to the one of a new render context. The styles, the
+    /// transform, the clips and the saved states are reset, open layers are composited.
+    pub fn reset_state(&mut self) {
+        while !self.layers.is_empty() {
+            self.pop_layer();
+        }
+
+        self.clip_mask = None;
+        self.saved_states.clear();
+        self.path_rect = PathRect::new(None);
+        self.config = RenderConfig::default();
+        self.transform = Matrix::identity();
+    }
+
+    pub fn clear(&mut self, brush: &Brush) {
+        self.record(|| DrawCommand::Clear {
+            brush: brush.clone(),
+        });
+        self.sync_pixmap();
+
+        let color = match *brush {
+            Brush::SolidColor(color) => color,
+            _ => Color::rgba(0x0, 0x0, 0x80, 0x80),
+        };
+
+        self.pixmap.fill(to_skia_color(color, 1.0));
+    }
+
+    /// Returns the premultiplied ARGB pixels of the last finished frame.
+    pub fn data(&self) -> &[u32] {
+        &self.data
+    }
+
+    /// Returns the pixels of the render context to change them, the changes are drawn on the
+    /// render context before the next draw call.
+    pub fn data_mut(&mut self) -> &mut [u32] {
+        self.sync_data(true);
+        self.data_changed = true;
+        &mut self.data
+    }
+
+    pub fn data_u8_mut(&mut self) -> &mut [u8] {
+        let data = self.data_mut();
+        let p = data.as_mut_ptr();
+        let len = data.len();
+        // we want to return an [u8] slice instead of a [u32] slice. This is a safe thing to
+        // do because requirements of a [u32] slice are stricter.
+        unsafe { std::slice::from_raw_parts_mut(p as *mut u8, len * std::mem::size_of::<u32>()) }
+    }
+
+    // Copies the damaged regions of the pixmap, or the whole pixmap, into `data`.
+    fn sync_data(&mut self, full: bool) {
+        let width = self.pixmap.width() as usize;
+        let height = self.pixmap.height() as usize;
+
+        if full || self.damaged_regions.is_empty() {
+            rgba_to_argb(self.pixmap.data(), &mut self.data);
+        } else {
+            for region in &self.damaged_regions[self.synced_regions..] {
+                let (x, y, region_width, region_height) = pixel_region(*region, width, height);
+
+                for row in y..y + region_height {
+                    let start = row * width + x;
+                    rgba_to_argb(
+                        &self.pixmap.data()[start * 4..(start + region_width) * 4],
+                        &mut self.data[start..start + region_width],
+                    );
+                }
+            }
+        }
+
+        self.synced_regions = self.damaged_regions.len();
+    }
+
+    // Copies the pixels that are changed by `data_mut` into the pixmap.
+    fn sync_pixmap(&mut self) {
+        if self.data_changed {
+            self.data_changed = false;
+            argb_to_rgba(&self.data, self.pixmap.data_mut());
+        }
+    }
+
+    pub fn start(&mut self) {
+        self.clear(&Brush::from(self.background));
+        self.damaged_regions = vec![Rectangle::new(
+            (0.0, 0.0),
+            (self.pixmap.width() as f64, self.pixmap.height() as f64),
+        )];
+        self.synced_regions = 0;
+    }
+
+    pub fn finish(&mut self) {
+        while !self.layers.is_empty() {
+            self.pop_layer();
+        }
+
+        self.sync_data(false);
+    }
+
+    /// Starts to redraw the given region of the pixmap. The region is cleared with the
+    /// background and everything that is drawn until `finish_region` is clipped to it.
+    pub fn start_region(&mut self, region: Rectangle) {
+        self.damaged_regions.push(region);
+        self.save();
+        self.begin_path();
+        self.rect(region.x(), region.y(), region.width(), region.height());
+        self.clip();
+        self.sync_pixmap();
+
+        if let Some(rect) = skia::Rect::from_xywh(
+            region.x() as f32,
+            region.y() as f32,
+            region.width() as f32,
+            region.height() as f32,
+        ) {
+            let mut paint = skia::Paint::default();
+            paint.set_color(to_skia_color(self.background, 1.0));
+            paint.blend_mode = skia::BlendMode::Source;
+
+            self.pixmap.fill_rect(
+                rect,
+                &paint,
+                to_skia_transform(&self.transform),
+                self.clip_mask.as_deref(),
+            );
+        }
+    }
+
+    /// Finishes the redraw of the region that is started with `start_region`.
+    pub fn finish_region(&mut self) {
+        self.finish();
+        self.restore();
+    }
+
+    /// Returns the regions of the pixmap that are redrawn since the last call, to present
+    /// only the changed parts of a frame. A full frame is returned as one region.
+    pub fn take_damaged_regions(&mut self) -> Vec<Rectangle> {
+        self.synced_regions = 0;
+        std::mem::take(&mut self.damaged_regions)
+    }
+}
+
+impl_render_context_common!();
+
+fn pixel_count(width: f64, height: f64) -> usize {
+    width.max(0.0) as usize * height.max(0.0) as usize
+}
+
+// tiny-skia could not create empty pixmaps, they have at least one pixel.
+fn new_pixmap(width: f64, height: f64) -> skia::Pixmap {
+    skia::Pixmap::new(width.max(1.0) as u32, height.max(1.0) as u32)
+        .expect("Could not create pixmap.")
+}
+
+// Converts premultiplied ARGB pixels to the premultiplied RGBA bytes of tiny-skia.
+fn argb_to_rgba(source: &[u32], target: &mut [u8]) {
+    for (pixel, bytes) in source.iter().zip(target.chunks_exact_mut(4)) {
+        let a = (pixel >> 24) as u8;
+        // premultiplied components could not be greater than alpha
+        bytes[0] = ((pixel >> 16) as u8).min(a);
+        bytes[1] = ((pixel >> 8) as u8).min(a);
+        bytes[2] = (*pixel as u8).min(a);
+        bytes[3] = a;
+    }
+}
+
+// Converts the premultiplied RGBA bytes of tiny-skia to premultiplied ARGB pixels.
+fn rgba_to_argb(source: &[u8], target: &mut [u32]) {
+    for (bytes, pixel) in source.chunks_exact(4).zip(target.iter_mut()) {
+        *pixel = (bytes[3] as u32) << 24
+            | (bytes[0] as u32) << 16
+            | (bytes[1] as u32) << 8
+            | bytes[2] as u32;
+    }
+}
+
+fn pixmap_from_argb(data: &[u32], width: u32, height: u32) -> Option<skia::Pixmap> {
+    if data.len() < width as usize * height as usize {
+        return None;
+    }
+
+    let mut pixmap = skia::Pixmap::new(width, height)?;
+    argb_to_rgba(data, pixmap.data_mut());
+    Some(pixmap)
+}
+
+// Copies the given part of the image, the rows of tiny-skia pixmaps are continuous.
+fn clipped_pixels(image: &Image, clip: Rectangle) -> Option<(Vec<u32>, u32, u32)> {
+    let stride = image.width() as usize;
+    let clip_x = clip.x().max(0.0) as usize;
+    let clip_y = clip.y().max(0.0) as usize;
+    let clip_width = ((clip.x() + clip.width()).min(image.width()) as usize).saturating_sub(clip_x);
+    let clip_height =
+        ((clip.y() + clip.height()).min(image.height()) as usize).saturating_sub(clip_y);
+
+    if clip_width == 0 || clip_height == 0 {
+        return None;
+    }
+
+    let mut data = Vec::with_capacity(clip_width * clip_height);
+    for row in clip_y..clip_y + clip_height {
+        let offset = row * stride + clip_x;
+        data.extend_from_slice(image.data().get(offset..offset + clip_width)?);
+    }
+
+    Some((data, clip_width as u32, clip_height as u32))
+}
+
+fn build_path(ops: &[PathOp]) -> Option<skia::Path> {
+    let mut path_builder = skia::PathBuilder::new();
+
+    for op in ops {
+        match *op {
+            PathOp::MoveTo(x, y) => path_builder.move_to(x, y),
+            PathOp::LineTo(x, y) => path_builder.line_to(x, y),
+            PathOp::QuadTo(x1, y1, x, y) => path_builder.quad_to(x1, y1, x, y),
+            PathOp::CubicTo(x1, y1, x2, y2, x, y) => path_builder.cubic_to(x1, y1, x2, y2, x, y),
+            PathOp::Close => path_builder.close(),
+        }
+    }
+
+    path_builder.finish()
+}
+
+fn arc_point(x: f64, y: f64, radius: f64, angle: f64) -> (f32, f32) {
+    (
+        (x + radius * angle.cos()) as f32,
+        (y + radius * angle.sin()) as f32,
+    )
+}
+
+// Approximates a circular arc with cubic curves, one for each quarter of the circle. The angles
+// grow clockwise like the arcs of the canvas api.
+fn arc_segments(x: f64, y: f64, radius: f64, start_angle: f64, end_angle: f64) -> Vec<PathOp> {
+    let mut sweep = end_angle - start_angle;
+    if sweep < 0.0 {
+        sweep = sweep.rem_euclid(2.0 * PI);
+    }
+    let sweep = sweep.min(2.0 * PI);
+
+    if sweep == 0.0 || !sweep.is_finite() {
+        return vec![];
+    }
+
+    let count = (sweep / FRAC_PI_2).ceil() as usize;
+    let step = sweep / count as f64;
+    // distance of the control points to the end points of a segment
+    let k = radius * 4.0 / 3.0 * (step / 4.0).tan();
+
+    (0..count)
+        .map(|i| {
+            let from = start_angle + step * i as f64;
+            let to = from + step;
+
+            PathOp::CubicTo(
+                (x + radius * from.cos() - k * from.sin()) as f32,
+                (y + radius * from.sin() + k * from.cos()) as f32,
+                (x + radius * to.cos() + k * to.sin()) as f32,
+                (y + radius * to.sin() - k * to.cos()) as f32,
+                (x + radius * to.cos()) as f32,
+                (y + radius * to.sin()) as f32,
+            )
+        })
+        .collect()
+}
+
+// Returns the intersection of the clip mask with the path, or a new clip mask of the path.
+fn intersect_clip_mask(
+    clip_mask: Option<&skia::ClipMask>,
+    path: &skia::Path,
+    width: u32,
+    height: u32,
+) -> Option<skia::ClipMask> {
+    match clip_mask {
+        Some(clip_mask) => {
+            let mut clip_mask = clip_mask.clone();
+            clip_mask.intersect_path(path, skia::FillRule::Winding, true)?;
+            Some(clip_mask)
+        }
+        None => {
+            let mut clip_mask = skia::ClipMask::new();
+            clip_mask.set_path(width, height, path, skia::FillRule::Winding, true)?;
+            Some(clip_mask)
+        }
+    }
+}
+
+fn to_skia_transform(matrix: &Matrix) -> skia::Transform {
+    skia::Transform::from_row(
+        matrix.m11 as f32,
+        matrix.m12 as f32,
+        matrix.m21 as f32,
+        matrix.m22 as f32,
+        matrix.m31 as f32,
+        matrix.m32 as f32,
+    )
+}
+
+fn to_skia_color(color: Color, alpha: f32) -> skia::Color {
+    skia::Color::from_rgba8(
+        color.r(),
+        color.g(),
+        color.b(),
+        (color.a() as f32 * alpha).round().clamp(0.0, 255.0) as u8,
+    )
+}
+
+fn stroke(config: &RenderConfig) -> skia::Stroke {
+    skia::Stroke {
+        width: config.line_width as f32,
+        miter_limit: config.miter_limit as f32,
+        line_cap: match config.line_cap {
+            LineCap::Butt => skia::LineCap::Butt,
+            LineCap::Round => skia::LineCap::Round,
+            LineCap::Square => skia::LineCap::Square,
+        },
+        line_join: match config.line_join {
+            LineJoin::Miter => skia::LineJoin::Miter,
+            LineJoin::Round => skia::LineJoin::Round,
+            LineJoin::Bevel => skia::LineJoin::Bevel,
+        },
+        dash: skia::StrokeDash::new(
+            config.line_dash.iter().map(|d| *d as f32).collect(),
+            config.line_dash_offset as f32,
+        ),
+    }
+}
+
+fn spread_mode(spread: SpreadMethod) -> skia::SpreadMode {
+    match spread {
+        SpreadMethod::Pad => skia::SpreadMode::Pad,
+        SpreadMethod::Repeat => skia::SpreadMode::Repeat,
+        SpreadMethod::Reflect => skia::SpreadMode::Reflect,
+    }
+}
+
+fn gradient_stops(gradient: &Gradient, length: f64, alpha: f32) -> Vec<skia::GradientStop> {
+    build_gradient_stops(&gradient.stops, length, gradient.interpolation, |p, c| {
+        skia::GradientStop::new(p as f32, to_skia_color(c, alpha))
+    })
+}
+
+// Returns the color of a gradient with the given stops at the position (0.0 to 1.0).
+fn gradient_color(stops: &[(f64, Color)], position: f64) -> Color {
+    let (first, last) = match (stops.first(), stops.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return Color::rgba(0, 0, 0, 0),
+    };
+
+    if position <= first.0 {
+        return first.1;
+    }
+
+    for pair in stops.windows(2) {
+        let ((from, from_color), (to, to_color)) = (pair[0], pair[1]);
+
+        if position <= to {
+            let t = if to > from {
+                (position - from) / (to - from)
+            } else {
+                1.0
+            };
+            let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+
+            return Color::rgba(
+                mix(from_color.r(), to_color.r()),
+                mix(from_color.g(), to_color.g()),
+                mix(from_color.b(), to_color.b()),
+                mix(from_color.a(), to_color.a()),
+            );
+        }
+    }
+
+    last.1
+}
+
+// tiny-skia has no sweep gradients, conic gradients are rendered into a pattern with the size of
+// the frame.
+fn conic_pattern(conic: &ConicGradient, gradient: &Gradient, frame: Rectangle) -> Option<Pattern> {
+    let width = frame.width().ceil().max(0.0) as u32;
+    let height = frame.height().ceil().max(0.0) as u32;
+    let mut pixmap = skia::Pixmap::new(width, height)?;
+
+    // pixel positions of conic stops are interpreted as degrees
+    let stops = build_gradient_stops(&gradient.stops, 360.0, gradient.interpolation, |p, c| {
+        (p, c)
+    });
+    let center = conic.center.pixels(frame.size());
+    let start_angle = conic.angle.to_degrees();
+
+    for (i, bytes) in pixmap.data_mut().chunks_exact_mut(4).enumerate() {
+        let x = (i % width as usize) as f64 + 0.5 - center.x();
+        let y = (i / width as usize) as f64 + 0.5 - center.y();
+
+        // clockwise from the top
+        let angle = (x.atan2(-y).to_degrees() - start_angle).rem_euclid(360.0);
+        let color = gradient_color(&stops, angle / 360.0);
+
+        let a = color.a() as u32;
+        let premultiply = |c: u8| ((c as u32 * a + 127) / 255) as u8;
+        bytes[0] = premultiply(color.r());
+        bytes[1] = premultiply(color.g());
+        bytes[2] = premultiply(color.b());
+        bytes[3] = color.a();
+    }
+
+    Some(Pattern {
+        pixmap,
+        transform: Matrix::translation(frame.x(), frame.y()),
+        spread_mode: skia::SpreadMode::Pad,
+    })
+}
+
+// Returns the pixels of the brushes that are drawn as pattern.
+fn brush_pattern(
+    brush: &Brush,
+    frame: Rectangle,
+    brush_images: &BrushImageCache<Image>,
+) -> Option<Pattern> {
+    match brush {
+        Brush::Gradient(gradient) => match &gradient.kind {
+            GradientKind::Conic(conic) => conic_pattern(conic, gradient, frame),
+            _ => None,
+        },
+        Brush::Image(image_brush) => {
+            let (width, height, data) = match &image_brush.source {
+                ImageSource::Path(path) => {
+                    let image = brush_images.get(path)?;
+                    (image.width() as u32, image.height() as u32, image.data())
+                }
+                ImageSource::Data {
+                    width,
+                    height,
+                    data,
+                } => (*width, *height, &data[..]),
+            };
+
+            let spread_mode = if image_brush.repeat == ImageRepeat::NoRepeat {
+                skia::SpreadMode::Pad
+            } else {
+                skia::SpreadMode::Repeat
+            };
+
+            Some(Pattern {
+                pixmap: pixmap_from_argb(data, width, height)?,
+                transform: image_brush
+                    .transform
+                    .then(&Matrix::translation(frame.x(), frame.y())),
+                spread_mode,
+            })
+        }
+        _ => None,
+    }
+}
+
+// tiny-skia could only repeat a pattern in both directions. Image brushes that are not repeated in
+// one or both directions are clipped to the image bounds in pattern space.
+fn image_brush_clip(
+    brush: &Brush,
+    frame: Rectangle,
+    brush_images: &BrushImageCache<Image>,
+) -> Option<skia::Path> {
+    // used as "infinite" extent of the repeated directions
+    const EXTENT: f64 = 1_000_000.0;
+
+    let image_brush = match brush {
+        Brush::Image(image_brush) if image_brush.repeat != ImageRepeat::Repeat => image_brush,
+        _ => return None,
+    };
+
+    let (width, height) = match &image_brush.source {
+        ImageSource::Path(path) => {
+            let image = brush_images.get(path)?;
+            (image.width(), image.height())
+        }
+        ImageSource::Data { width, height, .. } => (*width as f64, *height as f64),
+    };
+
+    let (left, right) = if image_brush.repeat.repeat_x() {
+        (-EXTENT, EXTENT)
+    } else {
+        (0.0, width)
+    };
+    let (top, bottom) = if image_brush.repeat.repeat_y() {
+        (-EXTENT, EXTENT)
+    } else {
+        (0.0, height)
+    };
+
+    let transform = image_brush
+        .transform
+        .then(&Matrix::translation(frame.x(), frame.y()));
+
+    let mut path_builder = skia::PathBuilder::new();
+    for (i, corner) in [(left, top), (right, top), (right, bottom), (left, bottom)]
+        .iter()
+        .enumerate()
+    {
+        let corner = transform.transform_point(*corner);
+        if i == 0 {
+            path_builder.move_to(corner.x() as f32, corner.y() as f32);
+        } else {
+            path_builder.line_to(corner.x() as f32, corner.y() as f32);
+        }
+    }
+    path_builder.close();
+
+    path_builder.finish()
+}
+
+fn brush_to_paint<'a>(
+    brush: &Brush,
+    frame: Rectangle,
+    alpha: f32,
+    pattern: Option<&'a Pattern>,
+) -> skia::Paint<'a> {
+    let transparent = skia::Shader::SolidColor(skia::Color::TRANSPARENT);
+
+    let shader = match brush {
+        Brush::SolidColor(color) => skia::Shader::SolidColor(to_skia_color(*color, alpha)),
+        Brush::Gradient(gradient) => match &gradient.kind {
+            GradientKind::Linear(LinearGradientCoords::Ends { start, end }) => {
+                let stops = gradient_stops(gradient, end.distance(*start), alpha);
+                let start = frame.position() + *start;
+                let end = frame.position() + *end;
+                skia::LinearGradient::new(
+                    skia::Point::from_xy(start.x() as f32, start.y() as f32),
+                    skia::Point::from_xy(end.x() as f32, end.y() as f32),
+                    stops,
+                    spread_mode(gradient.spread),
+                    skia::Transform::identity(),
+                )
+                .unwrap_or(transparent)
+            }
+            GradientKind::Radial(radial) => {
+                let (center, focal, radius) = radial.resolve(frame.size());
+                let stops = gradient_stops(gradient, radius, alpha);
+                let center = frame.position() + center;
+                let focal = frame.position() + focal;
+                skia::RadialGradient::new(
+                    skia::Point::from_xy(focal.x() as f32, focal.y() as f32),
+                    skia::Point::from_xy(center.x() as f32, center.y() as f32),
+                    radius as f32,
+                    stops,
+                    spread_mode(gradient.spread),
+                    skia::Transform::identity(),
+                )
+                .unwrap_or(transparent)
+            }
+            GradientKind::Conic(_) => pattern_shader(pattern, alpha).unwrap_or(transparent),
+        },
+        Brush::Image(_) => pattern_shader(pattern, alpha).unwrap_or(transparent),
+    };
+
+    skia::Paint {
+        shader,
+        anti_alias: true,
+        ..Default::default()
+    }
+}
+
+fn pattern_shader(pattern: Option<&Pattern>, alpha: f32) -> Option<skia::Shader<'_>> {
+    pattern.map(|pattern| {
+        skia::Pattern::new(
+            pattern.pixmap.as_ref(),
+            pattern.spread_mode,
+            skia::FilterQuality::Bilinear,
+            alpha,
+            to_skia_transform(&pattern.transform),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixel(render_context_2_d: &RenderContext2D, x: usize, y: usize) -> u32 {
+        let width = render_context_2_d.pixmap.width() as usize;
+        render_context_2_d.data()[y * width + x]
+    }
+
+    #[test]
+    fn test_fill_rect() {
+        let mut render_context_2_d = RenderContext2D::new(10.0, 10.0);
+        render_context_2_d.set_fill_style(Brush::from("#FF0000"));
+        render_context_2_d.fill_rect(0.0, 0.0, 5.0, 10.0);
+        render_context_2_d.finish();
+
+        assert_eq!(pixel(&render_context_2_d, 2, 5), 0xFFFF_0000);
+        assert_eq!(pixel(&render_context_2_d, 7, 5), 0);
+    }
+
+    #[test]
+    fn test_apply_backdrop_effect() {
+        let mut render_context_2_d = RenderContext2D::new(10.0, 10.0);
+        render_context_2_d.set_fill_style(Brush::from("#FF0000"));
+        render_context_2_d.fill_rect(0.0, 0.0, 10.0, 10.0);
+
+        // only the clipped part of the bounds is changed
+        render_context_2_d.save();
+        render_context_2_d.begin_path();
+        render_context_2_d.rect(0.0, 0.0, 5.0, 10.0);
+        render_context_2_d.clip();
+        render_context_2_d.apply_backdrop_effect(
+            Effect::Grayscale(1.0),
+            Rectangle::new((0.0, 0.0), (10.0, 5.0)),
+        );
+        render_context_2_d.restore();
+        render_context_2_d.finish();
+
+        assert_eq!(pixel(&render_context_2_d, 2, 2), 0xFF36_3636);
+        assert_eq!(pixel(&render_context_2_d, 7, 2), 0xFFFF_0000);
+        assert_eq!(pixel(&render_context_2_d, 2, 7), 0xFFFF_0000);
+    }
+
+    #[test]
+    fn test_clip_and_transform() {
+        let mut render_context_2_d = RenderContext2D::new(10.0, 10.0);
+        render_context_2_d.set_fill_style(Brush::from("#0000FF"));
+
+        render_context_2_d.save();
+        render_context_2_d.begin_path();
+        render_context_2_d.rect(0.0, 0.0, 5.0, 5.0);
+        render_context_2_d.clip();
+        render_context_2_d.fill_rect(0.0, 0.0, 10.0, 10.0);
+        render_context_2_d.restore();
+
+        render_context_2_d.save();
+        render_context_2_d.set_transform(1.0, 0.0, 0.0, 1.0, 8.0, 8.0);
+        render_context_2_d.fill_rect(0.0, 0.0, 2.0, 2.0);
+        render_context_2_d.restore();
+
+        render_context_2_d.finish();
+
+        assert_eq!(pixel(&render_context_2_d, 2, 2), 0xFF00_00FF);
+        assert_eq!(pixel(&render_context_2_d, 6, 6), 0);
+        assert_eq!(pixel(&render_context_2_d, 9, 9), 0xFF00_00FF);
+    }
+
+    #[test]
+    fn test_layer() {
+        let mut render_context_2_d = RenderContext2D::new(10.0, 10.0);
+        render_context_2_d.set_fill_style(Brush::from("#FFFFFF"));
+
+        // overlapping shapes of a layer are composited as a whole
+        render_context_2_d.push_layer(0.5, None);
+        render_context_2_d.fill_rect(0.0, 0.0, 10.0, 10.0);
+        render_context_2_d.fill_rect(0.0, 0.0, 10.0, 10.0);
+        render_context_2_d.pop_layer();
+        render_context_2_d.finish();
+
+        let alpha = pixel(&render_context_2_d, 5, 5) >> 24;
+        assert!((alpha as i32 - 128).abs() <= 1);
+    }
+
+    #[test]
+    fn test_data_mut() {
+        let mut render_context_2_d = RenderContext2D::new(2.0, 1.0);
+        render_context_2_d.data_mut()[1] = 0xFF00_FF00;
+
+        // the changed pixels are kept when the next frame is drawn
+        render_context_2_d.set_fill_style(Brush::from("#FF0000"));
+        render_context_2_d.fill_rect(0.0, 0.0, 1.0, 1.0);
+        render_context_2_d.finish();
+
+        assert_eq!(render_context_2_d.data(), &[0xFFFF_0000, 0xFF00_FF00]);
+    }
+
+    #[test]
+    fn test_arc_segments() {
+        let segments = arc_segments(0.0, 0.0, 10.0, 0.0, 2.0 * PI);
+        assert_eq!(segments.len(), 4);
+
+        match segments[0] {
+            PathOp::CubicTo(_, _, _, _, x, y) => {
+                assert!(x.abs() < 0.001);
+                assert!((y - 10.0).abs() < 0.001);
+            }
+            _ => panic!("expected a cubic curve"),
+        }
+
+        assert!(arc_segments(0.0, 0.0, 10.0, 1.0, 1.0).is_empty());
+    }
+
+    #[test]
+    fn test_render_to_image() {
+        let mut display_list = DisplayList::new();
+        display_list.push(DrawCommand::SetFillStyle {
+            fill_style: Brush::from("#00FF00"),
+        });
+        display_list.push(DrawCommand::FillRect {
+            x: 0.0,
+            y: 0.0,
+            width: 2.0,
+            height: 4.0,
+        });
+
+        let render_context_2_d = RenderContext2D::new(1.0, 1.0);
+        let image = render_context_2_d.render_to_image(&display_list, 4, 4);
+
+        assert_eq!(image.width(), 4.0);
+        assert_eq!(image.data()[0], 0xFF00_FF00);
+        assert_eq!(image.data()[3], 0);
+        assert!(render_context_2_d
+            .render_to_image(&display_list, 0, 4)
+            .data()
+            .is_empty());
+    }
+}
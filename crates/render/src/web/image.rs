@@ -1,17 +1,23 @@
 use std::path::Path;
 use stdweb::{js, unstable::TryInto};
 
+use crate::utils::Color;
+
 #[derive(Default, Clone, Debug, PartialEq)]
 pub struct Image {
     pub source: String,
+    // the size the image is drawn with, the browser scales the source to it
+    size: Option<(u32, u32)>,
+    // the color the image is filled with inside of its alpha channel
+    tint: Option<Color>,
+    // the index of the frame of an animated image that is drawn
+    frame: usize,
 }
 
 impl Image {
     /// Creates a new render ctx 2d.
     pub fn new(_: f64, _: f64) -> Self {
-        Image {
-            source: String::default(),
-        }
+        Image::default()
     }
 
     /// Load an image from file path. Supports BMP and PNG extensions.
@@ -22,7 +28,68 @@ impl Image {
         js!(
             if(!document.hasOwnProperty("image_store")) {
                 document.image_store = {
-                    images: {}
+                    images: {},
+                    frames: {},
+                    variants: {}
+                };
+
+                // decodes the frames of an animated image to canvases with their delay in
+                // milliseconds, browsers without ImageDecoder draw the first frame only
+                document.image_store.load_frames = function (src) {
+                    if(!("ImageDecoder" in window)) {
+                        return Promise.resolve();
+                    }
+
+                    return fetch(src)
+                        .then(function (response) { return response.blob(); })
+                        .then(function (blob) {
+                            if(!blob.type.startsWith("image/")) {
+                                return;
+                            }
+
+                            return ImageDecoder.isTypeSupported(blob.type).then(function (supported) {
+                                if(!supported) {
+                                    return;
+                                }
+
+                                var decoder = new ImageDecoder({ data: blob.stream(), type: blob.type });
+
+                                return decoder.tracks.ready.then(function () {
+                                    var count = decoder.tracks.selectedTrack.frameCount;
+
+                                    if(count < 2) {
+                                        return;
+                                    }
+
+                                    var frames = [];
+                                    var decode = function (index) {
+                                        if(index == count) {
+                                            this.frames[src] = frames;
+                                            return;
+                                        }
+
+                                        return decoder.decode({ frameIndex: index }).then(function (result) {
+                                            var frame = result.image;
+                                            var canvas = document.createElement("canvas");
+                                            canvas.width = frame.displayWidth;
+                                            canvas.height = frame.displayHeight;
+                                            canvas.getContext("2d").drawImage(frame, 0, 0);
+
+                                            frames.push({
+                                                canvas: canvas,
+                                                delay: frame.duration == null ? null : frame.duration / 1000
+                                            });
+                                            frame.close();
+
+                                            return decode(index + 1);
+                                        });
+                                    }.bind(this);
+
+                                    return decode(0);
+                                }.bind(this));
+                            }.bind(this));
+                        }.bind(this))
+                        .catch(function () {});
                 };
 
                 document.image_store.load_image = function (src) {
@@ -30,8 +97,11 @@ impl Image {
 
                     var d = new Promise(function (resolve, reject) {
                         img.onload = function () {
-                            this.images[src] = img;
-                            resolve(img);
+                            // the image is available after its frames are decoded
+                            this.load_frames(src).then(function () {
+                                this.images[src] = img;
+                                resolve(img);
+                            }.bind(this));
                         }.bind(this);
 
                         img.onerror = function () {
@@ -42,6 +112,41 @@ impl Image {
                     img.src = src;
                     return d;
                 };
+
+                // the loaded image resized and tinted on a canvas, the variants are drawn again
+                // only if the size or the color changes
+                document.image_store.variant = function (src, frame, size, color) {
+                    var img = this.image(src);
+
+                    if(img != null && (src in this.frames)) {
+                        img = this.frames[src][frame].canvas;
+                    }
+
+                    if(img == null || (size == null && color == null)) {
+                        return img;
+                    }
+
+                    var key = src + "|" + frame + "|" + size + "|" + color;
+
+                    if(!(key in this.variants)) {
+                        var canvas = document.createElement("canvas");
+                        canvas.width = size == null ? img.width : size[0];
+                        canvas.height = size == null ? img.height : size[1];
+
+                        var ctx = canvas.getContext("2d");
+                        ctx.drawImage(img, 0, 0, canvas.width, canvas.height);
+
+                        if(color != null) {
+                            ctx.globalCompositeOperation = "source-in";
+                            ctx.fillStyle = color;
+                            ctx.fillRect(0, 0, canvas.width, canvas.height);
+                        }
+
+                        this.variants[key] = canvas;
+                    }
+
+                    return this.variants[key];
+                };
             }
         );
 
@@ -56,7 +161,120 @@ impl Image {
             document.image_store.load_image(@{&source});
         );
 
-        Ok(Image { source })
+        Ok(Image {
+            source,
+            ..Image::default()
+        })
+    }
+
+    /// Loads an image from the file path like `from_path`, the browser shares the images with
+    /// the same path.
+    pub fn shared(path: impl Into<String>) -> Result<Self, String> {
+        Self::from_path(path.into())
+    }
+
+    /// Creates a new vector image from a svg document.
+    pub fn from_svg_str(svg: &str) -> Result<Self, String> {
+        let mut source = String::from("data:image/svg+xml;charset=utf-8,");
+
+        for byte in svg.bytes() {
+            match byte {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                    source.push(byte as char)
+                }
+                _ => source.push_str(&format!("%{:02X}", byte)),
+            }
+        }
+
+        Self::from_path(source)
+    }
+
+    /// Returns `true` if the image is created from a svg document.
+    pub fn is_vector(&self) -> bool {
+        self.source.starts_with("data:image/svg+xml") || self.source.ends_with(".svg")
+    }
+
+    /// Returns `true` if the image has more than one frame. The frames are decoded by the
+    /// `ImageDecoder` of the browser.
+    pub fn is_animated(&self) -> bool {
+        self.frame_count() > 1
+    }
+
+    /// Gets the number of frames, `1` for still images and images that are not yet loaded.
+    pub fn frame_count(&self) -> usize {
+        let count: u64 = js!(
+            var store = document.image_store;
+
+            if(store == null || !(@{&self.source} in store.frames)) {
+                return 1;
+            }
+
+            return store.frames[@{&self.source}].length;
+        )
+        .try_into()
+        .unwrap();
+
+        count as usize
+    }
+
+    /// Gets the index of the frame that is drawn.
+    pub fn current_frame(&self) -> usize {
+        self.frame
+    }
+
+    /// Gets the time the frame with the given index is shown, `None` for still images.
+    pub fn frame_delay(&self, index: usize) -> Option<std::time::Duration> {
+        let delay: Option<f64> = js!(
+            var store = document.image_store;
+
+            if(store == null || !(@{&self.source} in store.frames)) {
+                return null;
+            }
+
+            var frame = store.frames[@{&self.source}][@{index as u32}];
+            return frame == null ? null : frame.delay;
+        )
+        .try_into()
+        .unwrap();
+
+        delay.map(|delay| std::time::Duration::from_micros((delay * 1000.0) as u64))
+    }
+
+    /// Sets the frame of an animated image that is drawn. Indices out of range are ignored.
+    pub fn set_frame(&mut self, index: usize) {
+        if index < self.frame_count() {
+            self.frame = index;
+        }
+    }
+
+    /// Resizes the image, the browser scales the source when the image is drawn, so vector
+    /// images stay sharp.
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<(), String> {
+        if width == 0 || height == 0 {
+            return Err("Could not resize an image to an empty size.".to_string());
+        }
+
+        self.size = Some((width, height));
+        Ok(())
+    }
+
+    /// Fills the image with the given color, only the alpha channel of the image is kept.
+    pub fn tint(&mut self, color: Color) {
+        self.tint = Some(color);
+    }
+
+    /// Returns `true` if both images are drawn from the same source with the same size and tint.
+    pub fn shares_pixels(&self, other: &Image) -> bool {
+        self == other
+    }
+
+    // Arguments of `document.image_store.variant` that apply the frame, the size and the tint.
+    pub(crate) fn variant(&self) -> (u32, Option<Vec<u32>>, Option<String>) {
+        (
+            self.frame as u32,
+            self.size.map(|(width, height)| vec![width, height]),
+            self.tint.map(|color| color.to_string()),
+        )
     }
 
     /// Draws a u32 slice into the image.
@@ -66,6 +284,10 @@ impl Image {
 
     /// Gets the width.
     pub fn width(&self) -> f64 {
+        if let Some((width, _)) = self.size {
+            return width as f64;
+        }
+
         let width: u64 = js!(
             var image = document.image_store.image(@{&self.source});
 
@@ -82,6 +304,10 @@ impl Image {
 
     /// Gets the height.
     pub fn height(&self) -> f64 {
+        if let Some((_, height)) = self.size {
+            return height as f64;
+        }
+
         let height: u64 = js!(
             var image = document.image_store.image(@{&self.source});
 
@@ -4,12 +4,14 @@ use stdweb::{
     unstable::TryInto,
     web::{
         document, html_element::CanvasElement, CanvasGradient, CanvasRenderingContext2d, FillRule,
+        TypedArray,
     },
 };
 
 // pub use crate::image::Image as InnerImage;
 use crate::{
-    common::*, utils::*, FontConfig, PipelineTrait, RenderConfig, RenderTarget, TextMetrics,
+    common::*, premultiply, unpremultiply, utils::*, FontConfig, GlyphMetrics, PipelineTrait,
+    RenderConfig, RenderTarget, TextMetrics,
 };
 
 pub use self::image::*;
@@ -18,6 +20,19 @@ mod image;
 
 type StatesOnStack = [(RenderConfig, PathRect); 2];
 
+// Part of the font size above the baseline, the canvas does not measure the ascent of a font.
+const WEB_ASCENT: f64 = 0.8;
+
+// Offscreen layer that is composited on its parent context when it is popped.
+struct Layer {
+    // the context the layer is composited on
+    canvas_render_context_2_d: CanvasRenderingContext2d,
+    effect: Effect,
+    opacity: f32,
+    // part of the layer that is composited, the whole layer if it is `None`
+    region: Option<Rectangle>,
+}
+
 /// The RenderContext2D trait, provides the rendering ctx. It is used for drawing shapes, text, images, and other objects.
 pub struct RenderContext2D {
     canvas_render_context_2_d: CanvasRenderingContext2d,
@@ -26,6 +41,7 @@ pub struct RenderContext2D {
     saved_state: SmallVec<StatesOnStack>,
     path_rect: PathRect,
     export_data: Vec<u32>,
+    layers: Vec<Layer>,
     background: Color,
 }
 
@@ -52,6 +68,7 @@ impl RenderContext2D {
             font_config: FontConfig::default(),
             path_rect: PathRect::new(None),
             export_data,
+            layers: vec![],
             background: Color::default(),
         }
     }
@@ -77,6 +94,7 @@ impl RenderContext2D {
             font_config: FontConfig::default(),
             path_rect: PathRect::new(None),
             export_data,
+            layers: vec![],
             background: Color::default(),
         }
     }
@@ -98,19 +116,86 @@ impl RenderContext2D {
             .stroke_rect(x, y, width, height);
     }
 
+    // Shadows
+
+    /// Draws the blurred shadow of a (rounded) rectangle with the given position, size and corner
+    /// radius. The shadow is moved by the offset of the box shadow and grows or shrinks by its spread.
+    pub fn draw_box_shadow(
+        &mut self,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        border_radius: f64,
+        box_shadow: &BoxShadow,
+    ) {
+        // The canvas api draws shadows only together with a shape. The shape is moved out of the
+        // visible area and the shadow offset compensates it, so only the shadow is visible.
+        const SHAPE_OFFSET: f64 = 100_000.0;
+
+        let width = width + 2.0 * box_shadow.spread;
+        let height = height + 2.0 * box_shadow.spread;
+
+        if box_shadow.is_transparent() || width <= 0.0 || height <= 0.0 {
+            return;
+        }
+
+        let x = x - box_shadow.spread - SHAPE_OFFSET;
+        let y = y - box_shadow.spread;
+        let radius = (border_radius + box_shadow.spread)
+            .max(0.0)
+            .min(width / 2.0)
+            .min(height / 2.0);
+
+        js!(
+            var ctx = @{&self.canvas_render_context_2_d};
+            var x = @{x};
+            var y = @{y};
+            var r = x + @{width};
+            var b = y + @{height};
+            var radius = @{radius};
+
+            ctx.save();
+            ctx.shadowColor = @{box_shadow.color.to_string()};
+            ctx.shadowBlur = @{box_shadow.blur};
+            ctx.shadowOffsetX = @{box_shadow.offset.x() + SHAPE_OFFSET};
+            ctx.shadowOffsetY = @{box_shadow.offset.y()};
+            ctx.fillStyle = "#000000";
+
+            var path = new Path2D();
+            path.moveTo(x + radius, y);
+            path.arcTo(r, y, r, b, radius);
+            path.arcTo(r, b, x, b, radius);
+            path.arcTo(x, b, x, y, radius);
+            path.arcTo(x, y, r, y, radius);
+            path.closePath();
+            ctx.fill(path);
+            ctx.restore();
+        );
+    }
+
     // Text
 
     /// Draws (fills) a given text at the given (x, y) position.
     pub fn fill_text(&mut self, text: &str, x: f64, y: f64) {
         self.fill_style(&self.config.fill_style);
+
+        // the top of the line is placed from its middle, the canvas has no line top baseline
+        let (text_baseline, y) = match self.config.text_baseline {
+            TextBaseline::Top => (
+                stdweb::web::TextBaseline::Middle,
+                y + self.font_config.font_size.ceil() / 2.0,
+            ),
+            TextBaseline::Hanging => (stdweb::web::TextBaseline::Hanging, y),
+            TextBaseline::Middle => (stdweb::web::TextBaseline::Middle, y),
+            TextBaseline::Alphabetic => (stdweb::web::TextBaseline::Alphabetic, y),
+            TextBaseline::Ideographic => (stdweb::web::TextBaseline::Ideographic, y),
+            TextBaseline::Bottom => (stdweb::web::TextBaseline::Bottom, y),
+        };
+
         self.canvas_render_context_2_d
-            .set_text_baseline(stdweb::web::TextBaseline::Middle);
-        self.canvas_render_context_2_d.fill_text(
-            text,
-            x,
-            y + self.font_config.font_size.ceil() / 2.0,
-            None,
-        );
+            .set_text_baseline(text_baseline);
+        self.canvas_render_context_2_d.fill_text(text, x, y, None);
     }
 
     pub fn measure(
@@ -124,18 +209,98 @@ impl RenderContext2D {
         self.measure_text(text)
     }
 
-    /// Returns a TextMetrics object.
+    /// Returns a TextMetrics object. The canvas only measures the width of a text, the vertical
+    /// metrics are estimated from the font size.
     pub fn measure_text(&mut self, text: &str) -> TextMetrics {
+        let font_size = self.font_config.font_size;
+        let ascent = font_size * WEB_ASCENT;
+        let descent = font_size - ascent;
+        let baseline = self.config.text_baseline.offset(ascent, descent);
+        let width = self.text_width(text);
+
         TextMetrics {
-            width: self
-                .canvas_render_context_2_d
-                .measure_text(text)
-                .unwrap()
-                .get_width(),
-            height: self.font_config.font_size.ceil(),
+            width,
+            height: font_size.ceil(),
+            ascent,
+            descent,
+            line_gap: 0.0,
+            baseline,
+            bounds: Rectangle::new((0.0, baseline - ascent), (width, font_size)),
         }
     }
 
+    /// Measures the glyphs of the text with the current font. The canvas shapes the text itself,
+    /// a glyph is estimated for each char from the widths of the single chars.
+    pub fn measure_glyphs(&mut self, text: &str) -> Vec<GlyphMetrics> {
+        let font_size = self.font_config.font_size;
+        let ascent = font_size * WEB_ASCENT;
+        let top = self.config.text_baseline.offset(ascent, font_size - ascent) - ascent;
+        let mut x = 0.0;
+
+        text.char_indices()
+            .zip(self.char_widths(text))
+            .map(|((index, _), advance)| {
+                let glyph = GlyphMetrics {
+                    index,
+                    x,
+                    advance,
+                    bounds: Rectangle::new((x, top), (advance, font_size)),
+                };
+                x += advance;
+                glyph
+            })
+            .collect()
+    }
+
+    // Measures the width of the text with the canvas.
+    fn text_width(&self, text: &str) -> f64 {
+        self.canvas_render_context_2_d
+            .measure_text(text)
+            .unwrap()
+            .get_width()
+    }
+
+    // Measures each char of the text once and scales the widths to the width of the whole
+    // text, so kerning and shaping of the canvas are spread over the chars.
+    fn char_widths(&self, text: &str) -> Vec<f64> {
+        let mut widths: Vec<f64> = text
+            .char_indices()
+            .map(|(index, c)| self.text_width(&text[index..index + c.len_utf8()]))
+            .collect();
+
+        let sum: f64 = widths.iter().sum();
+
+        if sum > 0.0 {
+            let factor = self.text_width(text) / sum;
+            widths.iter_mut().for_each(|width| *width *= factor);
+        }
+
+        widths
+    }
+
+    /// Returns the x position of a caret placed before each char of the text, measured with the
+    /// given font. The last entry is the position after the last char. The canvas shapes the
+    /// text itself, the positions are estimated from the widths of the single chars.
+    pub fn caret_positions(
+        &mut self,
+        text: &str,
+        font_size: f64,
+        family: impl Into<String>,
+    ) -> Vec<f64> {
+        self.set_font_family(family);
+        self.set_font_size(font_size);
+
+        let mut positions = vec![0.0];
+        let mut x = 0.0;
+
+        for width in self.char_widths(text) {
+            x += width;
+            positions.push(x);
+        }
+
+        positions
+    }
+
     /// Fills the current or given path with the current file style.
     pub fn fill(&mut self) {
         self.fill_style(&self.config.fill_style);
@@ -167,6 +332,29 @@ impl RenderContext2D {
         self.path_rect.record_rect(x, y, width, height);
     }
 
+    /// Adds a rectangle with rounded corners to the current path. The radius is limited to half of
+    /// the width and height. Could be used with `clip` to clip to the shape of a rounded border.
+    pub fn rounded_rect(&mut self, x: f64, y: f64, width: f64, height: f64, radius: f64) {
+        let radius = rounded_rect_radius(width, height, radius);
+        let r = x + width;
+        let b = y + height;
+
+        js!(
+            var ctx = @{&self.canvas_render_context_2_d};
+            ctx.moveTo(@{x + radius}, @{y});
+            ctx.lineTo(@{r - radius}, @{y});
+            ctx.quadraticCurveTo(@{r}, @{y}, @{r}, @{y + radius});
+            ctx.lineTo(@{r}, @{b - radius});
+            ctx.quadraticCurveTo(@{r}, @{b}, @{r - radius}, @{b});
+            ctx.lineTo(@{x + radius}, @{b});
+            ctx.quadraticCurveTo(@{x}, @{b}, @{x}, @{b - radius});
+            ctx.lineTo(@{x}, @{y + radius});
+            ctx.quadraticCurveTo(@{x}, @{y}, @{x + radius}, @{y});
+            ctx.closePath();
+        );
+        self.path_rect.record_rect(x, y, width, height);
+    }
+
     /// Creates a circular arc centered at (x, y) with a radius of radius. The path starts at startAngle and ends at endAngle.
     pub fn arc(&mut self, x: f64, y: f64, radius: f64, start_angle: f64, end_angle: f64) {
         self.canvas_render_context_2_d
@@ -214,36 +402,74 @@ impl RenderContext2D {
 
     /// Draws the image.
     pub fn draw_image(&mut self, image: &Image, x: f64, y: f64) {
-        js!(
-            var img = document.image_store.image(@{&image.source});
-
-            if(img == null) {
-                img = document.image_store.load_image(@{&image.source});
-                img.then(
-                    function(i) {
-                         @{&self.canvas_render_context_2_d}.drawImage(i, @{&x}, @{&y});
-                    }
-                )
-            } else {
-                @{&self.canvas_render_context_2_d}.drawImage(img, @{&x}, @{&y});
-            }
-        );
+        self.draw_image_variant(image, vec![x, y]);
     }
 
     /// Draws the given part of the image.
     pub fn draw_image_with_clip(&mut self, image: &Image, clip: Rectangle, x: f64, y: f64) {
+        self.draw_image_variant(
+            image,
+            vec![
+                clip.x(),
+                clip.y(),
+                clip.width(),
+                clip.height(),
+                x,
+                y,
+                clip.width(),
+                clip.height(),
+            ],
+        );
+    }
+
+    /// Draws the image scaled to the given size.
+    pub fn draw_image_with_size(&mut self, image: &Image, x: f64, y: f64, width: f64, height: f64) {
+        self.draw_image_variant(image, vec![x, y, width, height]);
+    }
+
+    /// Draws the given part of the image scaled to the given size.
+    pub fn draw_image_with_clip_and_size(
+        &mut self,
+        image: &Image,
+        clip: Rectangle,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    ) {
+        self.draw_image_variant(
+            image,
+            vec![
+                clip.x(),
+                clip.y(),
+                clip.width(),
+                clip.height(),
+                x,
+                y,
+                width,
+                height,
+            ],
+        );
+    }
+
+    // Draws the resized and tinted image with the given arguments of `drawImage`, images that
+    // are not loaded yet are drawn when they are loaded.
+    fn draw_image_variant(&mut self, image: &Image, args: Vec<f64>) {
+        let (frame, size, tint) = image.variant();
+
         js!(
-            var img = document.image_store.image(@{&image.source});
-
-            if(img == null) {
-                img = document.image_store.load_image(@{&image.source});
-                img.then(
-                    function(i) {
-                         @{&self.canvas_render_context_2_d}.drawImage(img, @{&clip.x()}, @{&clip.y()}, @{&clip.width()}, @{&clip.height()}, @{&x}, @{&y}, @{&clip.width()}, @{&clip.height()});
-                    }
-                )
+            var store = document.image_store;
+            var ctx = @{&self.canvas_render_context_2_d};
+            var source = @{&image.source};
+            var args = @{args};
+            var draw = function() {
+                ctx.drawImage.apply(ctx, [store.variant(source, @{frame}, @{size}, @{tint})].concat(args));
+            };
+
+            if(store.image(source) == null) {
+                store.load_image(source).then(draw);
             } else {
-                 @{&self.canvas_render_context_2_d}.drawImage(img, @{&x}, @{&y});
+                draw();
             }
         );
     }
@@ -264,8 +490,9 @@ impl RenderContext2D {
             .create_image_data(width, height)
             .unwrap();
 
+        // canvas image data has straight alpha
         for i in 0..(render_target.data.len() - 1) {
-            let pixel = render_target.data.get(i).unwrap();
+            let pixel = unpremultiply(*render_target.data.get(i).unwrap());
             let r = ((pixel & 0x00FF0000) >> 16) as u8;
             let g = ((pixel & 0x0000FF00) >> 8) as u8;
             let b = (pixel & 0x000000FF) as u8;
@@ -312,6 +539,174 @@ impl RenderContext2D {
         self.path_rect.record_clip();
     }
 
+    // Layers
+
+    /// Starts an offscreen layer. Everything drawn until `pop_layer` is called is rendered into the
+    /// layer and then composited as a whole with the given opacity, so overlapping shapes do not
+    /// shine through each other. If `clip` is set only this part of the layer is composited.
+    /// Calls of `save` and `restore` must be balanced inside of the layer.
+    pub fn push_layer(&mut self, opacity: f32, clip: Option<Rectangle>) {
+        self.push_layer_with_effect(Effect::None, opacity, clip);
+    }
+
+    /// Starts an offscreen layer like `push_layer`. When the layer is popped the effect is applied
+    /// on the given bounds (extended by the extent of the effect).
+    pub fn push_effect(&mut self, effect: Effect, bounds: Rectangle) {
+        let extent = effect.extent();
+        let region = Rectangle::new(
+            (bounds.x() - extent, bounds.y() - extent),
+            (
+                bounds.width() + 2.0 * extent,
+                bounds.height() + 2.0 * extent,
+            ),
+        );
+        self.push_layer_with_effect(effect, 1.0, Some(region));
+    }
+
+    fn push_layer_with_effect(&mut self, effect: Effect, opacity: f32, region: Option<Rectangle>) {
+        let parent_canvas = self.canvas_render_context_2_d.get_canvas();
+        let canvas: CanvasElement = document()
+            .create_element("canvas")
+            .unwrap()
+            .try_into()
+            .unwrap();
+
+        canvas.set_width(parent_canvas.width());
+        canvas.set_height(parent_canvas.height());
+
+        let ctx: CanvasRenderingContext2d = canvas.get_context().unwrap();
+
+        // the layer starts with the drawing state of its parent
+        js!(
+            var parent = @{&self.canvas_render_context_2_d};
+            var layer = @{&ctx};
+            [
+                "globalAlpha", "fillStyle", "strokeStyle", "lineWidth", "lineCap", "lineJoin",
+                "miterLimit", "lineDashOffset", "font", "textAlign", "textBaseline"
+            ].forEach(function (key) {
+                layer[key] = parent[key];
+            });
+            layer.setLineDash(parent.getLineDash());
+            layer.setTransform(parent.getTransform());
+        );
+
+        let parent = std::mem::replace(&mut self.canvas_render_context_2_d, ctx);
+        self.layers.push(Layer {
+            canvas_render_context_2_d: parent,
+            effect,
+            opacity,
+            region,
+        });
+    }
+
+    /// Composites the current layer with its opacity on the previous context. The effect of a
+    /// layer that is started by `push_effect` is applied before.
+    pub fn pop_layer(&mut self) {
+        let layer = match self.layers.pop() {
+            Some(layer) => layer,
+            None => return,
+        };
+
+        let content = std::mem::replace(
+            &mut self.canvas_render_context_2_d,
+            layer.canvas_render_context_2_d,
+        );
+
+        let canvas = content.get_canvas();
+        let region = layer.region.unwrap_or_else(|| {
+            Rectangle::new((0.0, 0.0), (canvas.width() as f64, canvas.height() as f64))
+        });
+        let x = region.x().floor().max(0.0);
+        let y = region.y().floor().max(0.0);
+        let width = (region.x() + region.width()).ceil() - x;
+        let height = (region.y() + region.height()).ceil() - y;
+
+        if width <= 0.0 || height <= 0.0 {
+            return;
+        }
+
+        apply_canvas_color_matrix(
+            &content,
+            layer.effect,
+            Rectangle::new((x, y), (width, height)),
+        );
+        let filter = canvas_filter(layer.effect);
+
+        js!(
+            var ctx = @{&self.canvas_render_context_2_d};
+            ctx.save();
+            // the layer is already transformed
+            ctx.setTransform(1, 0, 0, 1, 0, 0);
+            ctx.globalAlpha = @{layer.opacity as f64 * layer.effect.opacity()};
+            ctx.filter = @{filter};
+            ctx.drawImage(@{&content}.canvas, @{x}, @{y}, @{width}, @{height}, @{x}, @{y}, @{width}, @{height});
+            ctx.restore();
+        );
+    }
+
+    /// Pops the current layer, same as `pop_layer`.
+    pub fn pop_effect(&mut self) {
+        self.pop_layer();
+    }
+
+    /// Applies the effect on the pixels that are already drawn inside of the bounds, e.g. to blur
+    /// the content behind a widget. The bounds are in window coordinates, the changed pixels are
+    /// clipped like other draw calls.
+    pub fn apply_backdrop_effect(&mut self, effect: Effect, bounds: Rectangle) {
+        if effect.is_none() {
+            return;
+        }
+
+        let x = bounds.x().floor();
+        let y = bounds.y().floor();
+        let width = (bounds.x() + bounds.width()).ceil() - x;
+        let height = (bounds.y() + bounds.height()).ceil() - y;
+
+        if width <= 0.0 || height <= 0.0 {
+            return;
+        }
+
+        // the backdrop is copied with the pixels around that the blur reads
+        let extent = effect.extent();
+        let area = Rectangle::new(
+            (x - extent, y - extent),
+            (width + 2.0 * extent, height + 2.0 * extent),
+        );
+        let backdrop: CanvasRenderingContext2d = js!(
+            var canvas = document.createElement("canvas");
+            canvas.width = @{area.width()};
+            canvas.height = @{area.height()};
+            var backdrop = canvas.getContext("2d");
+            backdrop.drawImage(@{&self.canvas_render_context_2_d}.canvas, @{area.x()}, @{area.y()},
+                @{area.width()}, @{area.height()}, 0, 0, @{area.width()}, @{area.height()});
+            return backdrop;
+        )
+        .try_into()
+        .unwrap();
+
+        apply_canvas_color_matrix(&backdrop, effect, Rectangle::new((0.0, 0.0), area.size()));
+        let filter = canvas_filter(effect);
+
+        js!(
+            var backdrop = @{&backdrop};
+            var canvas = document.createElement("canvas");
+            canvas.width = backdrop.canvas.width;
+            canvas.height = backdrop.canvas.height;
+            var filtered = canvas.getContext("2d");
+            filtered.filter = @{filter};
+            filtered.drawImage(backdrop.canvas, 0, 0);
+
+            var ctx = @{&self.canvas_render_context_2_d};
+            ctx.save();
+            ctx.setTransform(1, 0, 0, 1, 0, 0);
+            // the pixels inside of the clip are replaced
+            ctx.clearRect(@{x}, @{y}, @{width}, @{height});
+            ctx.globalAlpha = @{effect.opacity()};
+            ctx.drawImage(canvas, @{extent}, @{extent}, @{width}, @{height}, @{x}, @{y}, @{width}, @{height});
+            ctx.restore();
+        );
+    }
+
     // Line styles
 
     /// Sets the thickness of lines.
@@ -320,6 +715,53 @@ impl RenderContext2D {
         self.canvas_render_context_2_d.set_line_width(line_width);
     }
 
+    /// Sets the shape used to draw the end points of lines.
+    pub fn set_line_cap(&mut self, line_cap: LineCap) {
+        self.config.line_cap = line_cap;
+        self.canvas_render_context_2_d.set_line_cap(match line_cap {
+            LineCap::Butt => stdweb::web::LineCap::Butt,
+            LineCap::Round => stdweb::web::LineCap::Round,
+            LineCap::Square => stdweb::web::LineCap::Square,
+        });
+    }
+
+    /// Sets the shape used to join two line segments where they meet.
+    pub fn set_line_join(&mut self, line_join: LineJoin) {
+        self.config.line_join = line_join;
+        self.canvas_render_context_2_d
+            .set_line_join(match line_join {
+                LineJoin::Miter => stdweb::web::LineJoin::Miter,
+                LineJoin::Round => stdweb::web::LineJoin::Round,
+                LineJoin::Bevel => stdweb::web::LineJoin::Bevel,
+            });
+    }
+
+    /// Sets the miter limit ratio. Zero, negative and not finite values are ignored.
+    pub fn set_miter_limit(&mut self, miter_limit: f64) {
+        if miter_limit.is_finite() && miter_limit > 0.0 {
+            self.config.miter_limit = miter_limit;
+            self.canvas_render_context_2_d.set_miter_limit(miter_limit);
+        }
+    }
+
+    /// Sets the line dash pattern used when stroking lines. An empty list draws solid lines.
+    pub fn set_line_dash(&mut self, segments: &[f64]) {
+        if let Some(line_dash) = line_dash_segments(segments) {
+            self.canvas_render_context_2_d
+                .set_line_dash(line_dash.clone());
+            self.config.line_dash = line_dash;
+        }
+    }
+
+    /// Sets the line dash offset.
+    pub fn set_line_dash_offset(&mut self, line_dash_offset: f64) {
+        if line_dash_offset.is_finite() {
+            self.config.line_dash_offset = line_dash_offset;
+            self.canvas_render_context_2_d
+                .set_line_dash_offset(line_dash_offset);
+        }
+    }
+
     /// Sets the alpha value,
     pub fn set_alpha(&mut self, alpha: f32) {
         self.canvas_render_context_2_d
@@ -340,6 +782,33 @@ impl RenderContext2D {
             .set_font(&self.font_config.to_string());
     }
 
+    /// Specifies the font weight.
+    pub fn set_font_weight(&mut self, weight: FontWeight) {
+        self.font_config.weight = weight;
+        self.canvas_render_context_2_d
+            .set_font(&self.font_config.to_string());
+    }
+
+    /// Specifies the font style (normal, italic or oblique).
+    pub fn set_font_style(&mut self, style: FontStyle) {
+        self.font_config.style = style;
+        self.canvas_render_context_2_d
+            .set_font(&self.font_config.to_string());
+    }
+
+    /// Specifies the font stretch (condensed or expanded width).
+    pub fn set_font_stretch(&mut self, stretch: FontStretch) {
+        self.font_config.stretch = stretch;
+        self.canvas_render_context_2_d
+            .set_font(&self.font_config.to_string());
+    }
+
+    /// Specifies the baseline the y position of `fill_text` refers to, `TextBaseline::Top` by
+    /// default.
+    pub fn set_text_baseline(&mut self, text_baseline: TextBaseline) {
+        self.config.text_baseline = text_baseline;
+    }
+
     // Fill and stroke style
 
     /// Specifies the fill color to use inside shapes.
@@ -462,8 +931,7 @@ impl RenderContext2D {
 
             );
 
-            self.export_data[i] =
-                ((a as u32) << 24) | ((r as u32) << 16) | ((g as u32) << 8) | (b as u32);
+            self.export_data[i] = premultiply(u32::from_be_bytes([a, r, g, b]));
         }
 
         &self.export_data
@@ -473,7 +941,41 @@ impl RenderContext2D {
         let background = Brush::from(self.background);
         self.clear(&background)
     }
-    pub fn finish(&mut self) {}
+    pub fn finish(&mut self) {
+        while !self.layers.is_empty() {
+            self.pop_layer();
+        }
+    }
+
+    /// Starts to redraw the given region of the canvas. The region is cleared with the
+    /// background and everything that is drawn until `finish_region` is clipped to it.
+    pub fn start_region(&mut self, region: Rectangle) {
+        self.save();
+        self.begin_path();
+        self.rect(region.x(), region.y(), region.width(), region.height());
+        self.clip();
+
+        self.canvas_render_context_2_d.clear_rect(
+            region.x(),
+            region.y(),
+            region.width(),
+            region.height(),
+        );
+        self.canvas_render_context_2_d
+            .set_fill_style_color(&self.background.to_string());
+        self.canvas_render_context_2_d.fill_rect(
+            region.x(),
+            region.y(),
+            region.width(),
+            region.height(),
+        );
+    }
+
+    /// Finishes the redraw of the region that is started with `start_region`.
+    pub fn finish_region(&mut self) {
+        self.finish();
+        self.restore();
+    }
 
     fn fill_style<'a>(&self, brush: &Brush) {
         match brush {
@@ -486,13 +988,16 @@ impl RenderContext2D {
                     Some(frame) => frame,
                     None => return, // There are no path to fill
                 };
-                match gradient.kind {
-                    GradientKind::Linear(coords) => {
-                        let web_gradient = self.linear_gradient(&coords, &gradient.stops, frame);
-                        self.canvas_render_context_2_d
-                            .set_fill_style_gradient(&web_gradient);
-                    }
-                }
+                let web_gradient = self.gradient(gradient, frame);
+                self.canvas_render_context_2_d
+                    .set_fill_style_gradient(&web_gradient);
+            }
+            Brush::Image(image_brush) => {
+                let frame = match self.path_rect.get_rect() {
+                    Some(frame) => frame,
+                    None => return, // There are no path to fill
+                };
+                self.pattern(image_brush, frame, true);
             }
         }
     }
@@ -508,13 +1013,167 @@ impl RenderContext2D {
                     Some(frame) => frame,
                     None => return, // There are no path to fill
                 };
-                match gradient.kind {
-                    GradientKind::Linear(coords) => {
-                        let web_gradient = self.linear_gradient(&coords, &gradient.stops, frame);
-                        self.canvas_render_context_2_d
-                            .set_stroke_style_gradient(&web_gradient);
-                    }
+                let web_gradient = self.gradient(gradient, frame);
+                self.canvas_render_context_2_d
+                    .set_stroke_style_gradient(&web_gradient);
+            }
+            Brush::Image(image_brush) => {
+                let frame = match self.path_rect.get_rect() {
+                    Some(frame) => frame,
+                    None => return, // There are no path to fill
+                };
+                self.pattern(image_brush, frame, false);
+            }
+        }
+    }
+
+    // Sets a pattern created from the image brush as fill or stroke style. Images that are not
+    // loaded yet are painted transparent.
+    fn pattern(&self, image_brush: &ImageBrush, frame: Rectangle, fill: bool) {
+        let transform = image_brush
+            .transform
+            .then(&Matrix::translation(frame.x(), frame.y()));
+        let repeat = image_brush.repeat.to_string();
+
+        let source = match &image_brush.source {
+            ImageSource::Path(path) => {
+                let loaded: bool = js!(
+                    return document.hasOwnProperty("image_store") && document.image_store.image(@{path}) != null;
+                )
+                .try_into()
+                .unwrap();
+
+                if !loaded {
+                    // registers the image store and starts loading
+                    let _ = Image::from_path(path);
+                }
+
+                js!(
+                    return document.image_store.image(@{path});
+                )
+            }
+            ImageSource::Data {
+                width,
+                height,
+                data,
+            } => {
+                // canvas image data has straight alpha
+                let bytes: Vec<u8> = data
+                    .iter()
+                    .flat_map(|p| {
+                        let [a, r, g, b] = unpremultiply(*p).to_be_bytes();
+                        vec![r, g, b, a]
+                    })
+                    .collect();
+                let bytes = TypedArray::<u8>::from(&bytes[..]);
+
+                js!(
+                    var canvas = document.createElement("canvas");
+                    canvas.width = @{width};
+                    canvas.height = @{height};
+                    var ctx = canvas.getContext("2d");
+                    var image_data = ctx.createImageData(@{width}, @{height});
+                    image_data.data.set(@{bytes});
+                    ctx.putImageData(image_data, 0, 0);
+                    return canvas;
+                )
+            }
+        };
+
+        js!(
+            var ctx = @{&self.canvas_render_context_2_d};
+            var source = @{source};
+            var style = "rgba(0, 0, 0, 0)";
+
+            if(source != null && source.width > 0 && source.height > 0) {
+                style = ctx.createPattern(source, @{repeat});
+                style.setTransform(new DOMMatrix([@{transform.m11}, @{transform.m12}, @{transform.m21}, @{transform.m22}, @{transform.m31}, @{transform.m32}]));
+            }
+
+            if(@{fill}) {
+                ctx.fillStyle = style;
+            } else {
+                ctx.strokeStyle = style;
+            }
+        );
+    }
+
+    fn gradient(&self, gradient: &Gradient, frame: Rectangle) -> CanvasGradient {
+        match gradient.kind {
+            GradientKind::Linear(coords) => self.linear_gradient(&coords, gradient, frame),
+            GradientKind::Radial(radial) => {
+                let (center, focal, radius) = radial.resolve(frame.size());
+                let center = frame.position() + center;
+                let focal = frame.position() + focal;
+
+                // the canvas api throws on negative radii
+                let radius = if radius.is_finite() {
+                    radius.max(0.0)
+                } else {
+                    0.0
+                };
+                let stops = build_gradient_stops(
+                    &gradient.stops,
+                    radius,
+                    gradient.interpolation,
+                    |p, c| (p, c),
+                );
+
+                // the circles of the gradient are continued until they cover the frame, the
+                // circle of the period `n` has the radius `n * radius` and its center moves
+                // from the focal point through the center
+                let focal_offset = center.distance(focal);
+                let periods = if gradient.spread != SpreadMethod::Pad && radius > focal_offset {
+                    let farthest = frame_corners(frame)
+                        .iter()
+                        .map(|corner| corner.distance(focal))
+                        .fold(0.0, f64::max);
+                    spread_periods(0.0, farthest / (radius - focal_offset))
+                } else {
+                    (0, 1)
+                };
+                let stops = spread_gradient_stops(&stops, gradient.spread, periods.0, periods.1);
+                let scale = periods.1 as f64;
+
+                let web_gradient = self
+                    .canvas_render_context_2_d
+                    .create_radial_gradient(
+                        focal.x(),
+                        focal.y(),
+                        0.0,
+                        focal.x() + (center.x() - focal.x()) * scale,
+                        focal.y() + (center.y() - focal.y()) * scale,
+                        radius * scale,
+                    )
+                    .unwrap();
+
+                for (p, c) in stops {
+                    web_gradient
+                        .add_color_stop(p, c.to_string().as_str())
+                        .unwrap();
                 }
+
+                web_gradient
+            }
+            GradientKind::Conic(conic) => {
+                let center = frame.position() + conic.center.pixels(frame.size());
+                // the canvas api starts the sweep on the positive x axis, css like conic gradients on the top
+                let start_angle = conic.angle - std::f64::consts::FRAC_PI_2;
+
+                let web_gradient: CanvasGradient = js!(
+                    return @{&self.canvas_render_context_2_d}.createConicGradient(@{start_angle}, @{center.x()}, @{center.y()});
+                )
+                .try_into()
+                .unwrap();
+
+                // pixel positions of conic stops are interpreted as degrees
+                build_gradient_stops(&gradient.stops, 360.0, gradient.interpolation, |p, c| {
+                    web_gradient
+                        .add_color_stop(p, c.to_string().as_str())
+                        .unwrap();
+                });
+
+                web_gradient
             }
         }
     }
@@ -522,26 +1181,51 @@ impl RenderContext2D {
     fn linear_gradient(
         &self,
         coords: &LinearGradientCoords,
-        stops: &[GradientStop],
+        gradient: &Gradient,
         frame: Rectangle,
     ) -> CanvasGradient {
         match coords {
             LinearGradientCoords::Ends { start, end } => {
                 let start = frame.position() + *start;
                 let end = frame.position() + *end;
+                let (dx, dy) = (end.x() - start.x(), end.y() - start.y());
+                let length_sq = dx * dx + dy * dy;
+
+                let stops = build_gradient_stops(
+                    &gradient.stops,
+                    end.distance(start),
+                    gradient.interpolation,
+                    |p, c| (p, c),
+                );
+
+                // the gradient line is extended over the periods that cover the frame
+                let periods = if gradient.spread != SpreadMethod::Pad && length_sq > 0.0 {
+                    let (min, max) = frame_corners(frame)
+                        .iter()
+                        .map(|c| ((c.x() - start.x()) * dx + (c.y() - start.y()) * dy) / length_sq)
+                        .fold((f64::MAX, f64::MIN), |(min, max), t| {
+                            (min.min(t), max.max(t))
+                        });
+                    spread_periods(min, max)
+                } else {
+                    (0, 1)
+                };
+                let stops = spread_gradient_stops(&stops, gradient.spread, periods.0, periods.1);
+                let from = periods.0 as f64;
+                let to = from + periods.1 as f64;
 
                 let web_gradient = self.canvas_render_context_2_d.create_linear_gradient(
-                    start.x(),
-                    start.y(),
-                    end.x(),
-                    end.y(),
+                    start.x() + dx * from,
+                    start.y() + dy * from,
+                    start.x() + dx * to,
+                    start.y() + dy * to,
                 );
 
-                build_unit_percent_gradient(stops, end.distance(start), |p, c| {
+                for (p, c) in stops {
                     web_gradient
                         .add_color_stop(p, c.to_string().as_str())
                         .unwrap();
-                });
+                }
 
                 web_gradient
             }
@@ -549,6 +1233,70 @@ impl RenderContext2D {
     }
 }
 
+// Gets the canvas filter of the effect, color matrices and opacity are not applied by filters.
+fn canvas_filter(effect: Effect) -> String {
+    match effect {
+        Effect::Blur(radius) => format!("blur({}px)", radius / 2.0),
+        Effect::Grayscale(amount) => format!("grayscale({})", amount),
+        _ => "none".to_string(),
+    }
+}
+
+// Applies a color matrix effect on the pixels inside of the region of the context, color
+// matrices are not supported by canvas filters.
+fn apply_canvas_color_matrix(ctx: &CanvasRenderingContext2d, effect: Effect, region: Rectangle) {
+    let matrix = match effect {
+        Effect::ColorMatrix(matrix) => matrix.to_vec(),
+        _ => return,
+    };
+
+    js!(
+        var ctx = @{ctx};
+        var m = @{matrix};
+        var image_data = ctx.getImageData(@{region.x()}, @{region.y()}, @{region.width()}, @{region.height()});
+        var data = image_data.data;
+
+        for(var i = 0; i < data.length; i += 4) {
+            var r = data[i] / 255;
+            var g = data[i + 1] / 255;
+            var b = data[i + 2] / 255;
+            var a = data[i + 3] / 255;
+
+            for(var row = 0; row < 4; row++) {
+                var v = m[row * 5] * r + m[row * 5 + 1] * g + m[row * 5 + 2] * b
+                    + m[row * 5 + 3] * a + m[row * 5 + 4];
+                data[i + row] = Math.round(Math.min(Math.max(v, 0), 1) * 255);
+            }
+        }
+
+        ctx.putImageData(image_data, @{region.x()}, @{region.y()});
+    );
+}
+
+// Maximal number of periods of a repeated or reflected gradient, the gradient is padded beyond.
+const MAX_SPREAD_PERIODS: usize = 256;
+
+fn frame_corners(frame: Rectangle) -> [Point; 4] {
+    let (x, y) = (frame.x(), frame.y());
+    let (right, bottom) = (x + frame.width(), y + frame.height());
+
+    [
+        Point::new(x, y),
+        Point::new(right, y),
+        Point::new(x, bottom),
+        Point::new(right, bottom),
+    ]
+}
+
+// Gets the first period and the number of periods of a spread gradient that cover the gradient
+// positions from `min` to `max`.
+fn spread_periods(min: f64, max: f64) -> (i64, usize) {
+    let first = min.floor().max(-(MAX_SPREAD_PERIODS as f64));
+    let count = (max.ceil() - first).max(1.0).min(MAX_SPREAD_PERIODS as f64);
+
+    (first as i64, count as usize)
+}
+
 // --- Conversions ---
 
 impl From<&str> for Image {
@@ -562,3 +1310,10 @@ impl From<String> for Image {
         Image::from_path(s).unwrap()
     }
 }
+
+// Used by themes, an image that could not be loaded is drawn empty.
+impl From<Value> for Image {
+    fn from(v: Value) -> Image {
+        Image::from_path(v.get::<String>()).unwrap_or_default()
+    }
+}
@@ -362,20 +362,30 @@ where
         self.redraw.store(true, Ordering::Relaxed)
     }
 
-    /// Swaps the current frame buffer.
+    /// Swaps the current frame buffer. Only the regions that are redrawn since the last swap are
+    /// copied to the window.
     pub fn render(&mut self) {
         if self.redraw.load(Ordering::Relaxed) {
-            let color_data: Vec<orbclient::Color> = self
-                .render_context
-                .data()
-                .iter()
-                .map(|v| orbclient::Color { data: *v })
-                .collect();
-
-            if color_data.len() == self.window.data().len() {
-                self.window
-                    .data_mut()
-                    .clone_from_slice(color_data.as_slice());
+            let width = self.window.width() as usize;
+
+            if self.render_context.data().len() == self.window.data().len() {
+                for region in self.render_context.take_damaged_regions() {
+                    let left = (region.x().max(0.0) as usize).min(width);
+                    let right = ((region.x() + region.width()).ceil().max(0.0) as usize).min(width);
+                    let top = region.y().max(0.0) as usize;
+                    let bottom = (region.y() + region.height()).ceil().max(0.0) as usize;
+
+                    for y in top..bottom.min(self.window.height() as usize) {
+                        let start = y * width;
+
+                        for (target, source) in self.window.data_mut()[start + left..start + right]
+                            .iter_mut()
+                            .zip(&self.render_context.data()[start + left..start + right])
+                        {
+                            *target = orbclient::Color { data: *source };
+                        }
+                    }
+                }
 
                 // CONSOLE.time_end("render");
                 self.redraw.store(false, Ordering::Relaxed)
@@ -0,0 +1,150 @@
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Describes a blurred shadow that is painted below a box, e.g. to show the elevation of a popup.
+///
+/// Could be created from a css like string `<offset-x> <offset-y> [<blur> [<spread>]] <color>`.
+///
+/// # Examples
+/// ```rust
+/// use orbtk_utils::prelude::*;
+///
+/// let box_shadow = BoxShadow::from("0 2px 6px rgba(0, 0, 0, 0.4)");
+///
+/// assert_eq!(box_shadow.offset, Point::new(0.0, 2.0));
+/// assert_eq!(box_shadow.blur, 6.0);
+/// ```
+#[derive(Copy, Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BoxShadow {
+    /// Offset of the shadow relative to the box.
+    pub offset: Point,
+
+    /// Blur radius of the shadow. Zero draws a sharp shadow.
+    pub blur: f64,
+
+    /// Grows (positive) or shrinks (negative) the shadow on each side.
+    pub spread: f64,
+
+    /// Color of the shadow.
+    pub color: Color,
+}
+
+impl BoxShadow {
+    /// Creates a new box shadow with the given parameters.
+    pub fn new(offset: impl Into<Point>, blur: f64, spread: f64, color: impl Into<Color>) -> Self {
+        BoxShadow {
+            offset: offset.into(),
+            blur: blur.max(0.0),
+            spread,
+            color: color.into(),
+        }
+    }
+
+    /// Returns `true` if the shadow is not visible.
+    pub fn is_transparent(&self) -> bool {
+        self.color.a() == 0
+    }
+
+    /// Returns the bounds of the area that is covered by the shadow of the given box.
+    pub fn bounds(&self, rect: Rectangle) -> Rectangle {
+        let extent = self.spread + self.blur;
+
+        Rectangle::new(
+            (
+                rect.x() + self.offset.x() - extent,
+                rect.y() + self.offset.y() - extent,
+            ),
+            (
+                (rect.width() + 2.0 * extent).max(0.0),
+                (rect.height() + 2.0 * extent).max(0.0),
+            ),
+        )
+    }
+}
+
+// --- Conversions ---
+
+impl From<&str> for BoxShadow {
+    fn from(s: &str) -> BoxShadow {
+        let tokens = match Expression::from(s) {
+            Expression::Complex(tokens) => tokens,
+            _ => return BoxShadow::default(),
+        };
+
+        let mut lengths = Vec::new();
+        let mut color = None;
+
+        for token in &tokens {
+            match token {
+                Expression::Number(v, u) if u.is_empty() || u == "px" => {
+                    lengths.push((*v).into());
+                }
+                _ => match token.color() {
+                    Some(c) if color.is_none() => color = Some(c),
+                    _ => return BoxShadow::default(),
+                },
+            }
+        }
+
+        if lengths.len() < 2 || lengths.len() > 4 {
+            return BoxShadow::default();
+        }
+
+        BoxShadow::new(
+            (lengths[0], lengths[1]),
+            lengths.get(2).cloned().unwrap_or_default(),
+            lengths.get(3).cloned().unwrap_or_default(),
+            color.unwrap_or_else(|| Color::rgba(0, 0, 0, 255)),
+        )
+    }
+}
+
+impl From<String> for BoxShadow {
+    fn from(s: String) -> BoxShadow {
+        BoxShadow::from(&s[..])
+    }
+}
+
+impl From<Value> for BoxShadow {
+    fn from(v: Value) -> BoxShadow {
+        let value = v.get::<String>();
+        BoxShadow::from(value)
+    }
+}
+
+// --- Conversions ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_into() {
+        let box_shadow: BoxShadow = "2px 4px 8px -1px #000000".into();
+
+        assert_eq!(box_shadow.offset, Point::new(2.0, 4.0));
+        assert_eq!(box_shadow.blur, 8.0);
+        assert_eq!(box_shadow.spread, -1.0);
+        assert_eq!(box_shadow.color, Color::rgb(0, 0, 0));
+
+        let box_shadow: BoxShadow = "0 1px rgba(0, 0, 0, 0.5)".into();
+
+        assert_eq!(box_shadow.offset, Point::new(0.0, 1.0));
+        assert_eq!(box_shadow.blur, 0.0);
+        assert_eq!(box_shadow.color.a(), 127);
+
+        let box_shadow: BoxShadow = "none".into();
+        assert!(box_shadow.is_transparent());
+
+        let box_shadow: BoxShadow = "1px 1px 1em red".into();
+        assert!(box_shadow.is_transparent());
+    }
+
+    #[test]
+    fn test_bounds() {
+        let box_shadow = BoxShadow::new((2.0, 4.0), 3.0, 1.0, "#000000");
+        let bounds = box_shadow.bounds(Rectangle::new((10.0, 10.0), (20.0, 30.0)));
+
+        assert_eq!(bounds, Rectangle::new((8.0, 10.0), (28.0, 38.0)));
+    }
+}
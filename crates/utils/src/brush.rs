@@ -1,13 +1,17 @@
 use crate::prelude::*;
+use serde::{Deserialize, Serialize};
 
 /// A `Brush`describes how a shape is filled or stroked.
-#[derive(Clone, PartialEq, Debug)]
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub enum Brush {
     /// Paints an area with a solid color.
     SolidColor(Color),
 
     /// Paints an area with a gradient.
     Gradient(Gradient),
+
+    /// Paints an area with an image pattern.
+    Image(ImageBrush),
 }
 
 impl Brush {
@@ -55,6 +59,12 @@ impl From<Gradient> for Brush {
     }
 }
 
+impl From<ImageBrush> for Brush {
+    fn from(i: ImageBrush) -> Brush {
+        Brush::Image(i)
+    }
+}
+
 impl From<&str> for Brush {
     fn from(s: &str) -> Brush {
         Expression::from(s).brush().unwrap_or_default()
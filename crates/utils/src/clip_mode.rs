@@ -0,0 +1,73 @@
+use crate::prelude::*;
+
+/// Describes the shape a widget clips its content to, if its `clip` property is `true`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ClipMode {
+    /// Content is clipped to the bounds of the widget. (default)
+    Bounds,
+
+    /// Content is clipped to the bounds of the widget with the corners rounded by its
+    /// `border_radius`.
+    BorderRadius,
+}
+
+impl Default for ClipMode {
+    fn default() -> Self {
+        ClipMode::Bounds
+    }
+}
+
+// --- Conversions ---
+
+impl From<&str> for ClipMode {
+    fn from(t: &str) -> Self {
+        match t {
+            "BorderRadius" | "border-radius" | "border_radius" => ClipMode::BorderRadius,
+            _ => ClipMode::Bounds,
+        }
+    }
+}
+
+impl From<String> for ClipMode {
+    fn from(s: String) -> Self {
+        ClipMode::from(&s[..])
+    }
+}
+
+impl From<Value> for ClipMode {
+    fn from(v: Value) -> Self {
+        let value = v.get::<String>();
+        ClipMode::from(value)
+    }
+}
+
+impl ToString for ClipMode {
+    fn to_string(&self) -> String {
+        match self {
+            ClipMode::Bounds => "bounds".to_string(),
+            ClipMode::BorderRadius => "border-radius".to_string(),
+        }
+    }
+}
+
+// --- Conversions ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_into() {
+        let clip_mode: ClipMode = "BorderRadius".into();
+        assert_eq!(clip_mode, ClipMode::BorderRadius);
+
+        let clip_mode: ClipMode = "border-radius".into();
+        assert_eq!(clip_mode, ClipMode::BorderRadius);
+
+        let clip_mode: ClipMode = "bounds".into();
+        assert_eq!(clip_mode, ClipMode::Bounds);
+
+        let clip_mode: ClipMode = "other".into();
+        assert_eq!(clip_mode, ClipMode::Bounds);
+    }
+}
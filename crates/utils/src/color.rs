@@ -1,10 +1,11 @@
 include!(concat!(env!("OUT_DIR"), "/colors.rs"));
 
+use serde::{Deserialize, Serialize};
 #[cfg(not(feature = "no_std"))]
 use std::fmt;
 
 /// A r g b a color.
-#[derive(Copy, Clone, PartialOrd, Default)]
+#[derive(Copy, Clone, PartialOrd, Default, Serialize, Deserialize)]
 #[repr(packed)]
 pub struct Color {
     pub data: u32,
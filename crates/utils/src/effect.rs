@@ -0,0 +1,228 @@
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Describes a graphical effect that is applied on a widget and its children after they are
+/// rendered into an offscreen layer.
+///
+/// Could be created from a css filter like string e.g. `blur(4px)`, `grayscale(100%)`,
+/// `opacity(0.5)` or `color-matrix(<20 numbers>)`.
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Effect {
+    /// No effect is applied. (default)
+    None,
+
+    /// Blurs the content with a gaussian blur. The radius in pixels is twice the standard deviation.
+    Blur(f64),
+
+    /// Converts the content to grayscale. `1.0` is completely gray, `0.0` leaves the content unchanged.
+    Grayscale(f64),
+
+    /// Multiplies the opacity of the content as a whole.
+    Opacity(f64),
+
+    /// Transforms the colors of the content with a row major 4x5 matrix like the `feColorMatrix`
+    /// of svg. The matrix is applied on not premultiplied colors with components from 0.0 to 1.0:
+    ///
+    /// ```text
+    /// | r' |   | m0  m1  m2  m3  m4  |   | r |
+    /// | g' |   | m5  m6  m7  m8  m9  |   | g |
+    /// | b' | = | m10 m11 m12 m13 m14 | * | b |
+    /// | a' |   | m15 m16 m17 m18 m19 |   | a |
+    ///                                    | 1 |
+    /// ```
+    ColorMatrix([f64; 20]),
+}
+
+impl Effect {
+    /// Returns `true` if no effect is applied.
+    pub fn is_none(&self) -> bool {
+        match self {
+            Effect::None => true,
+            Effect::Blur(radius) => *radius <= 0.0,
+            Effect::Grayscale(amount) => *amount <= 0.0,
+            Effect::Opacity(opacity) => *opacity >= 1.0,
+            Effect::ColorMatrix(_) => false,
+        }
+    }
+
+    /// Returns how far (in pixels) the effect spreads the content beyond its bounds.
+    pub fn extent(&self) -> f64 {
+        match self {
+            // three times of the standard deviation
+            Effect::Blur(radius) => (radius.max(0.0) * 1.5).ceil(),
+            _ => 0.0,
+        }
+    }
+
+    /// Returns the opacity the content is composited with.
+    pub fn opacity(&self) -> f64 {
+        match self {
+            Effect::Opacity(opacity) => opacity.max(0.0).min(1.0),
+            _ => 1.0,
+        }
+    }
+
+    /// Returns the color matrix of the effect, if the effect transforms colors.
+    pub fn color_matrix(&self) -> Option<[f64; 20]> {
+        match self {
+            Effect::Grayscale(amount) => {
+                // grayscale matrix of the filter effects specification
+                let a = 1.0 - amount.max(0.0).min(1.0);
+                Some([
+                    0.2126 + 0.7874 * a,
+                    0.7152 - 0.7152 * a,
+                    0.0722 - 0.0722 * a,
+                    0.0,
+                    0.0,
+                    0.2126 - 0.2126 * a,
+                    0.7152 + 0.2848 * a,
+                    0.0722 - 0.0722 * a,
+                    0.0,
+                    0.0,
+                    0.2126 - 0.2126 * a,
+                    0.7152 - 0.7152 * a,
+                    0.0722 + 0.9278 * a,
+                    0.0,
+                    0.0,
+                    0.0,
+                    0.0,
+                    0.0,
+                    1.0,
+                    0.0,
+                ])
+            }
+            Effect::ColorMatrix(matrix) => Some(*matrix),
+            _ => None,
+        }
+    }
+}
+
+impl Default for Effect {
+    fn default() -> Self {
+        Effect::None
+    }
+}
+
+// Reads a number or a percentage as factor.
+fn effect_factor(expression: &Expression) -> Option<f64> {
+    match expression {
+        Expression::Number(v, u) if u.is_empty() => Some((*v).into()),
+        Expression::Number(v, u) if u == "%" => {
+            let v: f64 = (*v).into();
+            Some(v / 100.0)
+        }
+        _ => None,
+    }
+}
+
+// Reads an effect from a css filter like method expression.
+fn effect(expression: &Expression) -> Option<Effect> {
+    let (name, args) = match expression {
+        Expression::Method(name, args) => (name, args),
+        _ => return None,
+    };
+
+    match (&name[..], &args[..]) {
+        ("blur", [Expression::Number(v, u)]) if u.is_empty() || u == "px" => {
+            Some(Effect::Blur((*v).into()))
+        }
+        ("grayscale", [arg]) => effect_factor(arg).map(Effect::Grayscale),
+        ("opacity", [arg]) => effect_factor(arg).map(Effect::Opacity),
+        ("color-matrix", args) => {
+            let mut values = Vec::new();
+            for arg in args {
+                match arg {
+                    Expression::Complex(numbers) => {
+                        for number in numbers {
+                            values.push(number.number()?.into());
+                        }
+                    }
+                    _ => values.push(arg.number()?.into()),
+                }
+            }
+
+            if values.len() == 20 {
+                let mut matrix = [0.0; 20];
+                matrix.copy_from_slice(&values);
+                Some(Effect::ColorMatrix(matrix))
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+// --- Conversions ---
+
+impl From<&str> for Effect {
+    fn from(s: &str) -> Effect {
+        effect(&Expression::from(s)).unwrap_or_default()
+    }
+}
+
+impl From<String> for Effect {
+    fn from(s: String) -> Effect {
+        Effect::from(&s[..])
+    }
+}
+
+impl From<Value> for Effect {
+    fn from(v: Value) -> Effect {
+        let value = v.get::<String>();
+        Effect::from(value)
+    }
+}
+
+// --- Conversions ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_into() {
+        let effect: Effect = "blur(4px)".into();
+        assert_eq!(effect, Effect::Blur(4.0));
+
+        let effect: Effect = "grayscale(100%)".into();
+        assert_eq!(effect, Effect::Grayscale(1.0));
+
+        let effect: Effect = "opacity(0.5)".into();
+        assert_eq!(effect, Effect::Opacity(0.5));
+
+        let effect: Effect = "color-matrix(1 0 0 0 0, 0 1 0 0 0, 0 0 1 0 0, 0 0 0 0.5 0)".into();
+        match effect {
+            Effect::ColorMatrix(matrix) => assert!((matrix[18] - 0.5).abs() < f64::EPSILON),
+            _ => panic!("expected color matrix"),
+        }
+
+        let effect: Effect = "none".into();
+        assert_eq!(effect, Effect::None);
+
+        let effect: Effect = "blur(1em)".into();
+        assert_eq!(effect, Effect::None);
+    }
+
+    #[test]
+    fn test_is_none() {
+        assert!(Effect::None.is_none());
+        assert!(Effect::Blur(0.0).is_none());
+        assert!(Effect::Opacity(1.0).is_none());
+        assert!(!Effect::Grayscale(0.5).is_none());
+    }
+
+    #[test]
+    fn test_grayscale_matrix() {
+        let identity = Effect::Grayscale(0.0).color_matrix().unwrap();
+        for (i, value) in identity.iter().enumerate() {
+            let expected = if i % 6 == 0 { 1.0 } else { 0.0 };
+            assert!((value - expected).abs() < 0.0001);
+        }
+
+        let gray = Effect::Grayscale(1.0).color_matrix().unwrap();
+        let sum: f64 = gray[0..3].iter().sum();
+        assert!((sum - 1.0).abs() < 0.0001);
+        assert!((gray[0] - gray[5]).abs() < 0.0001);
+    }
+}
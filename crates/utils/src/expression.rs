@@ -1,4 +1,5 @@
 use crate::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::f64;
 use std::iter::Peekable;
 use std::ops::Neg;
@@ -76,14 +77,249 @@ impl Expression {
         }
     }
 
+    /// Try to convert `self` into a `Gradient`. Supports the css like methods `radial-gradient`,
+    /// `conic-gradient` and their `repeating-` variants. The stops are interpolated in linear
+    /// light if the shape contains `in srgb-linear`.
+    ///
+    /// # Examples
+    ///
+    /// ```text
+    /// radial-gradient(circle 40px at 50% 50%, #000000, #ffffff 80%)
+    /// conic-gradient(from 90deg at center, red, yellow 120deg, red)
+    /// radial-gradient(in srgb-linear, red, blue)
+    /// ```
+    pub fn gradient(&self) -> Option<Gradient> {
+        let (name, args) = match self {
+            Expression::Method(name, args) => (name, args),
+            _ => return None,
+        };
+
+        let (name, spread) = match name.strip_prefix("repeating-") {
+            Some(name) => (name, SpreadMethod::Repeat),
+            None => (&name[..], SpreadMethod::Pad),
+        };
+
+        let conic = match name {
+            "radial-gradient" => false,
+            "conic-gradient" => true,
+            _ => return None,
+        };
+
+        let mut args = args.iter().peekable();
+        let mut kind = if conic {
+            GradientKind::Conic(ConicGradient::default())
+        } else {
+            GradientKind::Radial(RadialGradient::default())
+        };
+        let mut interpolation = ColorInterpolation::default();
+
+        // the first argument describes the shape if it is not a color stop
+        if let Some(arg) = args.peek() {
+            if gradient_stop(arg, conic).is_none() {
+                let shape = gradient_shape(arg, conic)?;
+                kind = shape.0;
+                interpolation = shape.1;
+                args.next();
+            }
+        }
+
+        let mut stops = Vec::new();
+        for arg in args {
+            stops.push(gradient_stop(arg, conic)?);
+        }
+
+        if stops.is_empty() {
+            return None;
+        }
+
+        Some(Gradient {
+            kind,
+            stops,
+            spread,
+            interpolation,
+        })
+    }
+
+    /// Try to convert `self` into an `ImageBrush`. Supports the methods `image` and `url` with
+    /// the path of the image and an optional repeat mode.
+    ///
+    /// # Examples
+    ///
+    /// ```text
+    /// image(res/background.png)
+    /// image("res/background.png", repeat-x)
+    /// url(res/background.png)
+    /// ```
+    pub fn image_brush(&self) -> Option<ImageBrush> {
+        let args = match self {
+            Expression::Method(name, args) if name == "image" || name == "url" => args,
+            _ => return None,
+        };
+
+        let path = match args.first()? {
+            Expression::Other(path) => path.trim_matches(|c| c == '"' || c == '\''),
+            _ => return None,
+        };
+
+        if path.is_empty() {
+            return None;
+        }
+
+        let repeat = match args.get(1) {
+            Some(Expression::Other(repeat)) => match &repeat[..] {
+                "repeat" | "repeat-x" | "repeat-y" | "no-repeat" => ImageRepeat::from(&repeat[..]),
+                _ => return None,
+            },
+            Some(_) => return None,
+            None => ImageRepeat::default(),
+        };
+
+        if args.len() > 2 {
+            return None;
+        }
+
+        Some(ImageBrush::from_path(path).with_repeat(repeat))
+    }
+
     pub fn brush(&self) -> Option<Brush> {
         if let Some(color) = self.color() {
             return Some(Brush::from(color));
         }
+        if let Some(gradient) = self.gradient() {
+            return Some(Brush::from(gradient));
+        }
+        if let Some(image_brush) = self.image_brush() {
+            return Some(Brush::from(image_brush));
+        }
         None
     }
 }
 
+// Converts an angle with the given unit into radians.
+pub(crate) fn angle_in_radians(value: f64, unit: &str) -> Option<f64> {
+    match unit {
+        "deg" => Some(value.to_radians()),
+        "rad" => Some(value),
+        "grad" => Some(value * f64::consts::PI / 200.0),
+        "turn" => Some(value * 2.0 * f64::consts::PI),
+        _ => None,
+    }
+}
+
+// Reads a position on a line described by a number with unit or by a keyword.
+fn on_line_pos(expression: &Expression) -> Option<OnLinePos> {
+    match expression {
+        Expression::Number(v, u) => {
+            let v: f64 = (*v).into();
+            if u.is_empty() && v == 0.0 {
+                return Some(OnLinePos::new(0.0, OnLinePosKind::Pixels));
+            }
+            OnLinePos::try_from((v, &u[..])).ok()
+        }
+        Expression::Other(keyword) => match &keyword[..] {
+            "left" | "top" => Some(OnLinePos::from_unit_percent(0.0)),
+            "center" => Some(OnLinePos::from_unit_percent(0.5)),
+            "right" | "bottom" => Some(OnLinePos::from_unit_percent(1.0)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// Reads the shape (first argument) of a radial or conic gradient and the color space of its
+// interpolation.
+fn gradient_shape(
+    expression: &Expression,
+    conic: bool,
+) -> Option<(GradientKind, ColorInterpolation)> {
+    let tokens = match expression {
+        Expression::Complex(tokens) => tokens.iter().collect(),
+        _ => vec![expression],
+    };
+
+    let mut radial = RadialGradient::default();
+    let mut conic_gradient = ConicGradient::default();
+    let mut interpolation = ColorInterpolation::default();
+    let mut tokens = tokens.into_iter().peekable();
+
+    while let Some(token) = tokens.next() {
+        match token {
+            Expression::Other(keyword) if keyword == "at" => {
+                let x = on_line_pos(tokens.next()?)?;
+                let y = match tokens.peek() {
+                    Some(token) => match on_line_pos(token) {
+                        Some(y) => {
+                            tokens.next();
+                            y
+                        }
+                        None => OnLinePos::from_unit_percent(0.5),
+                    },
+                    None => OnLinePos::from_unit_percent(0.5),
+                };
+                radial.center = OnPlanePos::new(x, y);
+                conic_gradient.center = OnPlanePos::new(x, y);
+            }
+            Expression::Other(keyword) if conic && keyword == "from" => {
+                conic_gradient.angle = match tokens.next()? {
+                    Expression::Number(v, u) => angle_in_radians((*v).into(), u)?,
+                    _ => return None,
+                };
+            }
+            Expression::Other(keyword) if keyword == "in" => {
+                interpolation = match tokens.next()? {
+                    Expression::Other(space) if space == "srgb" => ColorInterpolation::Srgb,
+                    Expression::Other(space) if space == "srgb-linear" => {
+                        ColorInterpolation::LinearRgb
+                    }
+                    _ => return None,
+                };
+            }
+            Expression::Other(keyword)
+                if !conic && (keyword == "circle" || keyword == "farthest-corner") => {}
+            Expression::Number(_, _) if !conic => {
+                radial.radius = on_line_pos(token)?;
+            }
+            _ => return None,
+        }
+    }
+
+    if conic {
+        Some((GradientKind::Conic(conic_gradient), interpolation))
+    } else {
+        Some((GradientKind::Radial(radial), interpolation))
+    }
+}
+
+// Reads a color stop of a gradient. The position of a conic gradient stop could also be an angle.
+fn gradient_stop(expression: &Expression, conic: bool) -> Option<GradientStop> {
+    if let Some(color) = expression.color() {
+        return Some(GradientStop { pos: None, color });
+    }
+
+    if let Expression::Complex(tokens) = expression {
+        if tokens.len() != 2 {
+            return None;
+        }
+
+        let color = tokens[0].color()?;
+        let pos = match &tokens[1] {
+            Expression::Number(v, u) if conic && u != "%" => {
+                let angle = angle_in_radians((*v).into(), u)?;
+                OnLinePos::from_unit_percent(angle / (2.0 * f64::consts::PI))
+            }
+            Expression::Number(_, _) => on_line_pos(&tokens[1])?,
+            _ => return None,
+        };
+
+        return Some(GradientStop {
+            pos: Some(pos),
+            color,
+        });
+    }
+
+    None
+}
+
 impl Default for Expression {
     fn default() -> Self {
         Expression::Complex(Vec::new())
@@ -203,7 +439,7 @@ impl From<String> for Expression {
 }
 
 /// Describes a position on a plane
-#[derive(Debug, Copy, Clone, PartialOrd, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialOrd, PartialEq, Serialize, Deserialize)]
 pub struct OnPlanePos {
     x: OnLinePos,
     y: OnLinePos,
@@ -256,7 +492,7 @@ impl Default for OnPlanePos {
 }
 
 /// Describes a position on a line
-#[derive(Debug, Copy, Clone, PartialOrd, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialOrd, PartialEq, Serialize, Deserialize)]
 pub struct OnLinePos {
     pos: f64,
     kind: OnLinePosKind,
@@ -316,7 +552,7 @@ impl TryFrom<(f64, &str)> for OnLinePos {
 }
 
 /// This only is used to communicate the kind of `OnLinePos` we are using
-#[derive(Debug, Copy, Clone, PartialOrd, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialOrd, PartialEq, Serialize, Deserialize)]
 pub enum OnLinePosKind {
     /// A number from 0.0 to 100.0
     Percentage,
@@ -350,3 +586,127 @@ impl Neg for OnLinePos {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_radial_gradient() {
+        let gradient =
+            Expression::from("radial-gradient(circle 40px at 25% top, #000000, red 80%)")
+                .gradient()
+                .unwrap();
+
+        assert_eq!(
+            gradient.kind,
+            GradientKind::Radial(RadialGradient {
+                center: OnPlanePos::new(
+                    OnLinePos::new(25.0, OnLinePosKind::Percentage),
+                    OnLinePos::new(0.0, OnLinePosKind::Percentage)
+                ),
+                radius: OnLinePos::new(40.0, OnLinePosKind::Pixels),
+                focal: None,
+            })
+        );
+        assert_eq!(gradient.spread, SpreadMethod::Pad);
+        assert_eq!(gradient.stops.len(), 2);
+        assert_eq!(gradient.stops[0].pos, None);
+        assert_eq!(gradient.stops[1].color, Color::rgb(255, 0, 0));
+        assert_eq!(
+            gradient.stops[1].pos,
+            Some(OnLinePos::new(80.0, OnLinePosKind::Percentage))
+        );
+
+        let gradient = Expression::from("repeating-radial-gradient(#000000, #ffffff 10px)")
+            .gradient()
+            .unwrap();
+        assert_eq!(
+            gradient.kind,
+            GradientKind::Radial(RadialGradient::default())
+        );
+        assert_eq!(gradient.spread, SpreadMethod::Repeat);
+        assert_eq!(gradient.interpolation, ColorInterpolation::Srgb);
+
+        let gradient =
+            Expression::from("radial-gradient(circle at center in srgb-linear, red, blue)")
+                .gradient()
+                .unwrap();
+        assert_eq!(gradient.interpolation, ColorInterpolation::LinearRgb);
+    }
+
+    #[test]
+    fn test_conic_gradient() {
+        let gradient =
+            Expression::from("conic-gradient(from 90deg at 10px 20px, red, blue 0.5turn)")
+                .gradient()
+                .unwrap();
+
+        match gradient.kind {
+            GradientKind::Conic(conic) => {
+                assert!((conic.angle - f64::consts::FRAC_PI_2).abs() < f64::EPSILON);
+                assert_eq!(
+                    conic.center,
+                    OnPlanePos::new(
+                        OnLinePos::new(10.0, OnLinePosKind::Pixels),
+                        OnLinePos::new(20.0, OnLinePosKind::Pixels)
+                    )
+                );
+            }
+            _ => panic!("expected conic gradient"),
+        }
+
+        assert_eq!(
+            gradient.stops[1].pos,
+            Some(OnLinePos::new(50.0, OnLinePosKind::Percentage))
+        );
+    }
+
+    #[test]
+    fn test_gradient_invalid() {
+        assert_eq!(Expression::from("radial-gradient()").gradient(), None);
+        assert_eq!(
+            Expression::from("radial-gradient(foo, red)").gradient(),
+            None
+        );
+        assert_eq!(Expression::from("rgb(0, 0, 0)").gradient(), None);
+    }
+
+    #[test]
+    fn test_brush_from_gradient() {
+        match Expression::from("conic-gradient(red, blue)").brush() {
+            Some(Brush::Gradient(_)) => {}
+            _ => panic!("expected gradient brush"),
+        }
+    }
+
+    #[test]
+    fn test_image_brush() {
+        let brush = Expression::from("image(res/background.png)")
+            .image_brush()
+            .unwrap();
+        assert_eq!(
+            brush.source,
+            ImageSource::Path("res/background.png".to_string())
+        );
+        assert_eq!(brush.repeat, ImageRepeat::Repeat);
+        assert!(brush.transform.is_identity());
+
+        let brush = Expression::from("url(\"res/background.png\", no-repeat)")
+            .image_brush()
+            .unwrap();
+        assert_eq!(
+            brush.source,
+            ImageSource::Path("res/background.png".to_string())
+        );
+        assert_eq!(brush.repeat, ImageRepeat::NoRepeat);
+
+        assert_eq!(Expression::from("image()").image_brush(), None);
+        assert_eq!(Expression::from("image(a.png, foo)").image_brush(), None);
+
+        match Brush::from("image(res/background.png, repeat-y)") {
+            Brush::Image(brush) => assert_eq!(brush.repeat, ImageRepeat::RepeatY),
+            _ => panic!("expected image brush"),
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Describes the width of a font face compared to its normal width.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Hash, Serialize, Deserialize)]
+pub enum FontStretch {
+    /// 50% of the normal width.
+    UltraCondensed,
+
+    /// 62.5% of the normal width.
+    ExtraCondensed,
+
+    /// 75% of the normal width.
+    Condensed,
+
+    /// 87.5% of the normal width.
+    SemiCondensed,
+
+    /// Normal width. (default)
+    Normal,
+
+    /// 112.5% of the normal width.
+    SemiExpanded,
+
+    /// 125% of the normal width.
+    Expanded,
+
+    /// 150% of the normal width.
+    ExtraExpanded,
+
+    /// 200% of the normal width.
+    UltraExpanded,
+}
+
+impl FontStretch {
+    /// Gets the width in percent of the normal width.
+    pub fn percentage(&self) -> f64 {
+        match self {
+            FontStretch::UltraCondensed => 50.0,
+            FontStretch::ExtraCondensed => 62.5,
+            FontStretch::Condensed => 75.0,
+            FontStretch::SemiCondensed => 87.5,
+            FontStretch::Normal => 100.0,
+            FontStretch::SemiExpanded => 112.5,
+            FontStretch::Expanded => 125.0,
+            FontStretch::ExtraExpanded => 150.0,
+            FontStretch::UltraExpanded => 200.0,
+        }
+    }
+
+    /// Gets the stretch for the OpenType width class (`1` to `9`).
+    pub fn from_width_class(class: u16) -> Self {
+        match class {
+            0 | 1 => FontStretch::UltraCondensed,
+            2 => FontStretch::ExtraCondensed,
+            3 => FontStretch::Condensed,
+            4 => FontStretch::SemiCondensed,
+            5 => FontStretch::Normal,
+            6 => FontStretch::SemiExpanded,
+            7 => FontStretch::Expanded,
+            8 => FontStretch::ExtraExpanded,
+            _ => FontStretch::UltraExpanded,
+        }
+    }
+}
+
+impl Default for FontStretch {
+    fn default() -> Self {
+        FontStretch::Normal
+    }
+}
+
+// --- Conversions ---
+
+impl From<&str> for FontStretch {
+    fn from(t: &str) -> Self {
+        match t {
+            "UltraCondensed" | "ultra-condensed" | "ultra_condensed" => FontStretch::UltraCondensed,
+            "ExtraCondensed" | "extra-condensed" | "extra_condensed" => FontStretch::ExtraCondensed,
+            "Condensed" | "condensed" => FontStretch::Condensed,
+            "SemiCondensed" | "semi-condensed" | "semi_condensed" => FontStretch::SemiCondensed,
+            "SemiExpanded" | "semi-expanded" | "semi_expanded" => FontStretch::SemiExpanded,
+            "Expanded" | "expanded" => FontStretch::Expanded,
+            "ExtraExpanded" | "extra-expanded" | "extra_expanded" => FontStretch::ExtraExpanded,
+            "UltraExpanded" | "ultra-expanded" | "ultra_expanded" => FontStretch::UltraExpanded,
+            _ => FontStretch::Normal,
+        }
+    }
+}
+
+impl From<String> for FontStretch {
+    fn from(s: String) -> Self {
+        FontStretch::from(&s[..])
+    }
+}
+
+impl From<Value> for FontStretch {
+    fn from(v: Value) -> Self {
+        let value = v.get::<String>();
+        FontStretch::from(value)
+    }
+}
+
+impl ToString for FontStretch {
+    fn to_string(&self) -> String {
+        match self {
+            FontStretch::UltraCondensed => "ultra-condensed".to_string(),
+            FontStretch::ExtraCondensed => "extra-condensed".to_string(),
+            FontStretch::Condensed => "condensed".to_string(),
+            FontStretch::SemiCondensed => "semi-condensed".to_string(),
+            FontStretch::Normal => "normal".to_string(),
+            FontStretch::SemiExpanded => "semi-expanded".to_string(),
+            FontStretch::Expanded => "expanded".to_string(),
+            FontStretch::ExtraExpanded => "extra-expanded".to_string(),
+            FontStretch::UltraExpanded => "ultra-expanded".to_string(),
+        }
+    }
+}
+
+// --- Conversions ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_into() {
+        let stretch: FontStretch = "condensed".into();
+        assert_eq!(stretch, FontStretch::Condensed);
+
+        let stretch: FontStretch = "SemiExpanded".into();
+        assert_eq!(stretch, FontStretch::SemiExpanded);
+
+        let stretch: FontStretch = "other".into();
+        assert_eq!(stretch, FontStretch::Normal);
+    }
+
+    #[test]
+    fn test_from_width_class() {
+        assert_eq!(FontStretch::from_width_class(3), FontStretch::Condensed);
+        assert_eq!(FontStretch::from_width_class(5), FontStretch::Normal);
+        assert_eq!(FontStretch::from_width_class(9).percentage(), 200.0);
+    }
+}
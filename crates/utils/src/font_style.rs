@@ -0,0 +1,75 @@
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Describes the slant of a font.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FontStyle {
+    /// Upright face. (default)
+    Normal,
+
+    /// Italic face, falls back to an oblique face if the family has no italic one.
+    Italic,
+
+    /// Oblique (slanted) face, falls back to an italic face if the family has no oblique one.
+    Oblique,
+}
+
+impl Default for FontStyle {
+    fn default() -> Self {
+        FontStyle::Normal
+    }
+}
+
+// --- Conversions ---
+
+impl From<&str> for FontStyle {
+    fn from(t: &str) -> Self {
+        match t {
+            "Italic" | "italic" => FontStyle::Italic,
+            "Oblique" | "oblique" => FontStyle::Oblique,
+            _ => FontStyle::Normal,
+        }
+    }
+}
+
+impl From<String> for FontStyle {
+    fn from(s: String) -> Self {
+        FontStyle::from(&s[..])
+    }
+}
+
+impl From<Value> for FontStyle {
+    fn from(v: Value) -> Self {
+        let value = v.get::<String>();
+        FontStyle::from(value)
+    }
+}
+
+impl ToString for FontStyle {
+    fn to_string(&self) -> String {
+        match self {
+            FontStyle::Normal => "normal".to_string(),
+            FontStyle::Italic => "italic".to_string(),
+            FontStyle::Oblique => "oblique".to_string(),
+        }
+    }
+}
+
+// --- Conversions ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_into() {
+        let style: FontStyle = "Italic".into();
+        assert_eq!(style, FontStyle::Italic);
+
+        let style: FontStyle = "oblique".into();
+        assert_eq!(style, FontStyle::Oblique);
+
+        let style: FontStyle = "other".into();
+        assert_eq!(style, FontStyle::Normal);
+    }
+}
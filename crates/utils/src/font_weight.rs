@@ -0,0 +1,137 @@
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Describes the weight (boldness) of a font as a value between `1` and `1000`. The common
+/// weights are multiples of 100, e.g. `400` for normal and `700` for bold text.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Hash, Serialize, Deserialize)]
+pub struct FontWeight(pub u16);
+
+impl FontWeight {
+    /// Thin weight (100).
+    pub const THIN: FontWeight = FontWeight(100);
+
+    /// Extra light weight (200).
+    pub const EXTRA_LIGHT: FontWeight = FontWeight(200);
+
+    /// Light weight (300).
+    pub const LIGHT: FontWeight = FontWeight(300);
+
+    /// Normal weight (400).
+    pub const NORMAL: FontWeight = FontWeight(400);
+
+    /// Medium weight (500).
+    pub const MEDIUM: FontWeight = FontWeight(500);
+
+    /// Semi bold weight (600).
+    pub const SEMI_BOLD: FontWeight = FontWeight(600);
+
+    /// Bold weight (700).
+    pub const BOLD: FontWeight = FontWeight(700);
+
+    /// Extra bold weight (800).
+    pub const EXTRA_BOLD: FontWeight = FontWeight(800);
+
+    /// Black weight (900).
+    pub const BLACK: FontWeight = FontWeight(900);
+
+    /// Gets the numeric weight value.
+    pub fn value(&self) -> u16 {
+        self.0
+    }
+}
+
+impl Default for FontWeight {
+    fn default() -> Self {
+        FontWeight::NORMAL
+    }
+}
+
+// --- Conversions ---
+
+impl From<u16> for FontWeight {
+    fn from(w: u16) -> Self {
+        FontWeight(w.max(1).min(1000))
+    }
+}
+
+impl From<f64> for FontWeight {
+    fn from(w: f64) -> Self {
+        FontWeight(w.round().max(1.0).min(1000.0) as u16)
+    }
+}
+
+impl From<&str> for FontWeight {
+    fn from(t: &str) -> Self {
+        if let Ok(weight) = t.trim().parse::<u16>() {
+            return FontWeight::from(weight);
+        }
+
+        match t {
+            "Thin" | "thin" | "Hairline" | "hairline" => FontWeight::THIN,
+            "ExtraLight" | "extra-light" | "extra_light" | "UltraLight" | "ultra-light"
+            | "ultra_light" => FontWeight::EXTRA_LIGHT,
+            "Light" | "light" => FontWeight::LIGHT,
+            "Medium" | "medium" => FontWeight::MEDIUM,
+            "SemiBold" | "semi-bold" | "semi_bold" | "DemiBold" | "demi-bold" | "demi_bold" => {
+                FontWeight::SEMI_BOLD
+            }
+            "Bold" | "bold" => FontWeight::BOLD,
+            "ExtraBold" | "extra-bold" | "extra_bold" | "UltraBold" | "ultra-bold"
+            | "ultra_bold" => FontWeight::EXTRA_BOLD,
+            "Black" | "black" | "Heavy" | "heavy" => FontWeight::BLACK,
+            _ => FontWeight::NORMAL,
+        }
+    }
+}
+
+impl From<String> for FontWeight {
+    fn from(s: String) -> Self {
+        FontWeight::from(&s[..])
+    }
+}
+
+impl From<Value> for FontWeight {
+    fn from(v: Value) -> Self {
+        match v.0 {
+            ron::Value::Number(value) => FontWeight::from(value.into_f64()),
+            value => FontWeight::from(Value(value).get::<String>()),
+        }
+    }
+}
+
+impl ToString for FontWeight {
+    fn to_string(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+// --- Conversions ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_into() {
+        let weight: FontWeight = "bold".into();
+        assert_eq!(weight, FontWeight::BOLD);
+
+        let weight: FontWeight = "SemiBold".into();
+        assert_eq!(weight, FontWeight::SEMI_BOLD);
+
+        let weight: FontWeight = "350".into();
+        assert_eq!(weight, FontWeight(350));
+
+        let weight: FontWeight = "other".into();
+        assert_eq!(weight, FontWeight::NORMAL);
+
+        let weight: FontWeight = 1200.0.into();
+        assert_eq!(weight, FontWeight(1000));
+
+        let weight: FontWeight = Value(ron::Value::Number(ron::Number::from(500.0))).into();
+        assert_eq!(weight, FontWeight::MEDIUM);
+
+        let weight: FontWeight = Value(ron::Value::String("black".to_string())).into();
+        assert_eq!(weight, FontWeight::BLACK);
+    }
+}
@@ -1,7 +1,8 @@
-use crate::{Color, OnLinePos, Point};
+use crate::{Color, OnLinePos, OnLinePosKind, OnPlanePos, Point, Size};
+use serde::{Deserialize, Serialize};
 
 /// Describes a position on a colorful gradient.
-#[derive(Copy, Clone, PartialEq, Debug)]
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct GradientStop {
     pub pos: Option<OnLinePos>,
     pub color: Color,
@@ -17,7 +18,7 @@ impl Default for GradientStop {
 }
 
 /// Describes the coordinates of a colorful linear gradient.
-#[derive(Copy, Clone, PartialEq, Debug)]
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub enum LinearGradientCoords {
     /// Defines the linear gradient by point A to point B.
     Ends { start: Point, end: Point },
@@ -32,12 +33,147 @@ impl Default for LinearGradientCoords {
     }
 }
 
+/// Describes the coordinates of a colorful radial gradient. All positions are relative to the
+/// path the gradient is painted on.
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct RadialGradient {
+    /// Center of the gradient circle.
+    pub center: OnPlanePos,
+
+    /// Radius of the gradient circle. A percentage is relative to the distance between the
+    /// center and the farthest corner of the path.
+    pub radius: OnLinePos,
+
+    /// Point where the gradient starts. If it is `None` the center is used.
+    pub focal: Option<OnPlanePos>,
+}
+
+impl RadialGradient {
+    /// Resolves the center, the focal point and the radius in pixels for a path of the given size.
+    pub fn resolve(&self, size: Size) -> (Point, Point, f64) {
+        let center = self.center.pixels(size);
+        let focal = self.focal.map_or(center, |focal| focal.pixels(size));
+        let farthest_corner = [
+            Point::new(0.0, 0.0),
+            Point::new(size.width(), 0.0),
+            Point::new(0.0, size.height()),
+            Point::new(size.width(), size.height()),
+        ]
+        .iter()
+        .map(|corner| corner.distance(center))
+        .fold(0.0, f64::max);
+
+        (center, focal, self.radius.pixels(farthest_corner))
+    }
+}
+
+impl Default for RadialGradient {
+    fn default() -> RadialGradient {
+        RadialGradient {
+            center: OnPlanePos::new(
+                OnLinePos::new(50.0, OnLinePosKind::Percentage),
+                OnLinePos::new(50.0, OnLinePosKind::Percentage),
+            ),
+            radius: OnLinePos::new(100.0, OnLinePosKind::Percentage),
+            focal: None,
+        }
+    }
+}
+
+/// Describes the coordinates of a colorful conic (sweep) gradient. The colors are swept
+/// clockwise around the center.
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ConicGradient {
+    /// Center of the gradient, relative to the path the gradient is painted on.
+    pub center: OnPlanePos,
+
+    /// Start angle in radians, measured clockwise from the top.
+    pub angle: f64,
+}
+
+impl Default for ConicGradient {
+    fn default() -> ConicGradient {
+        ConicGradient {
+            center: RadialGradient::default().center,
+            angle: 0.0,
+        }
+    }
+}
+
+/// Describes how a gradient is painted outside of its start and end. Conic gradients always
+/// sweep the whole circle, so they are padded after the last stop with every spread method.
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum SpreadMethod {
+    /// The colors of the start and end stops are extended. (default)
+    Pad,
+
+    /// The gradient is repeated.
+    Repeat,
+
+    /// The gradient is repeated and every second repetition is mirrored.
+    Reflect,
+}
+
+impl Default for SpreadMethod {
+    fn default() -> SpreadMethod {
+        SpreadMethod::Pad
+    }
+}
+
+impl From<&str> for SpreadMethod {
+    fn from(s: &str) -> SpreadMethod {
+        match s {
+            "Repeat" | "repeat" => SpreadMethod::Repeat,
+            "Reflect" | "reflect" => SpreadMethod::Reflect,
+            _ => SpreadMethod::Pad,
+        }
+    }
+}
+
+/// Describes the color space the colors between two gradient stops are interpolated in.
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum ColorInterpolation {
+    /// The gamma encoded sRGB components are interpolated, like css gradients do. (default)
+    Srgb,
+
+    /// The components are interpolated in linear light. Mixes of saturated colors keep their
+    /// brightness and do not get muddy.
+    LinearRgb,
+}
+
+impl Default for ColorInterpolation {
+    fn default() -> ColorInterpolation {
+        ColorInterpolation::Srgb
+    }
+}
+
+impl From<&str> for ColorInterpolation {
+    fn from(s: &str) -> ColorInterpolation {
+        match s {
+            "LinearRgb" | "linear-rgb" | "linearRGB" | "srgb-linear" => {
+                ColorInterpolation::LinearRgb
+            }
+            _ => ColorInterpolation::Srgb,
+        }
+    }
+}
+
 /// Describes a colorful gradient.
-#[derive(Clone, PartialEq, Debug)]
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct Gradient {
     pub kind: GradientKind,
     pub stops: Vec<GradientStop>,
-    pub repeat: bool,
+    pub spread: SpreadMethod,
+    #[serde(default)]
+    pub interpolation: ColorInterpolation,
+}
+
+impl Gradient {
+    /// Builder method that sets the color space the stops are interpolated in.
+    pub fn with_interpolation(mut self, interpolation: ColorInterpolation) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
 }
 
 impl Default for Gradient {
@@ -54,15 +190,17 @@ impl Default for Gradient {
                     color: Color::rgb(255, 255, 255),
                 },
             ],
-            repeat: false,
+            spread: SpreadMethod::default(),
+            interpolation: ColorInterpolation::default(),
         }
     }
 }
 
-#[derive(Copy, Clone, PartialEq, Debug)]
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub enum GradientKind {
     Linear(LinearGradientCoords),
-    //    Radial(RadialGradient),
+    Radial(RadialGradient),
+    Conic(ConicGradient),
 }
 
 impl Default for GradientKind {
@@ -70,3 +208,53 @@ impl Default for GradientKind {
         GradientKind::Linear(LinearGradientCoords::default())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_radial_gradient_resolve() {
+        let (center, focal, radius) = RadialGradient::default().resolve(Size::new(60.0, 80.0));
+        assert_eq!(center, Point::new(30.0, 40.0));
+        assert_eq!(focal, center);
+        assert!((radius - 50.0).abs() < f64::EPSILON);
+
+        let radial = RadialGradient {
+            center: OnPlanePos::new(
+                OnLinePos::new(0.0, OnLinePosKind::Pixels),
+                OnLinePos::new(0.0, OnLinePosKind::Pixels),
+            ),
+            radius: OnLinePos::new(20.0, OnLinePosKind::Pixels),
+            focal: Some(OnPlanePos::new(
+                OnLinePos::new(10.0, OnLinePosKind::Pixels),
+                OnLinePos::new(50.0, OnLinePosKind::Percentage),
+            )),
+        };
+        let (center, focal, radius) = radial.resolve(Size::new(60.0, 80.0));
+        assert_eq!(center, Point::new(0.0, 0.0));
+        assert_eq!(focal, Point::new(10.0, 40.0));
+        assert!((radius - 20.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_spread_method_into() {
+        let spread: SpreadMethod = "repeat".into();
+        assert_eq!(spread, SpreadMethod::Repeat);
+
+        let spread: SpreadMethod = "Reflect".into();
+        assert_eq!(spread, SpreadMethod::Reflect);
+
+        let spread: SpreadMethod = "other".into();
+        assert_eq!(spread, SpreadMethod::Pad);
+    }
+
+    #[test]
+    fn test_color_interpolation_into() {
+        let interpolation: ColorInterpolation = "linear-rgb".into();
+        assert_eq!(interpolation, ColorInterpolation::LinearRgb);
+
+        let interpolation: ColorInterpolation = "srgb".into();
+        assert_eq!(interpolation, ColorInterpolation::Srgb);
+    }
+}
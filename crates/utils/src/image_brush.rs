@@ -0,0 +1,148 @@
+use std::sync::Arc;
+
+use crate::Matrix;
+use serde::{Deserialize, Serialize};
+
+/// Describes where the pixels of an `ImageBrush` come from.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum ImageSource {
+    /// The image is loaded by the render backend from the given path.
+    Path(String),
+
+    /// Raw pixels in ARGB format, e.g. taken from an `Image` or a `RenderTarget`.
+    Data {
+        width: u32,
+        height: u32,
+        data: Arc<Vec<u32>>,
+    },
+}
+
+impl Default for ImageSource {
+    fn default() -> Self {
+        ImageSource::Path(String::default())
+    }
+}
+
+/// Describes how an `ImageBrush` is repeated to fill an area.
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum ImageRepeat {
+    /// The image is repeated horizontally and vertically. (default)
+    Repeat,
+
+    /// The image is only repeated horizontally.
+    RepeatX,
+
+    /// The image is only repeated vertically.
+    RepeatY,
+
+    /// The image is painted once.
+    NoRepeat,
+}
+
+impl ImageRepeat {
+    /// Returns `true` if the image is repeated horizontally.
+    pub fn repeat_x(self) -> bool {
+        self == ImageRepeat::Repeat || self == ImageRepeat::RepeatX
+    }
+
+    /// Returns `true` if the image is repeated vertically.
+    pub fn repeat_y(self) -> bool {
+        self == ImageRepeat::Repeat || self == ImageRepeat::RepeatY
+    }
+}
+
+impl Default for ImageRepeat {
+    fn default() -> Self {
+        ImageRepeat::Repeat
+    }
+}
+
+impl From<&str> for ImageRepeat {
+    fn from(s: &str) -> ImageRepeat {
+        match s {
+            "RepeatX" | "repeat-x" => ImageRepeat::RepeatX,
+            "RepeatY" | "repeat-y" => ImageRepeat::RepeatY,
+            "NoRepeat" | "no-repeat" => ImageRepeat::NoRepeat,
+            _ => ImageRepeat::Repeat,
+        }
+    }
+}
+
+impl ToString for ImageRepeat {
+    fn to_string(&self) -> String {
+        match self {
+            ImageRepeat::Repeat => "repeat".to_string(),
+            ImageRepeat::RepeatX => "repeat-x".to_string(),
+            ImageRepeat::RepeatY => "repeat-y".to_string(),
+            ImageRepeat::NoRepeat => "no-repeat".to_string(),
+        }
+    }
+}
+
+/// Describes an image that is used as pattern to fill or stroke a shape. The image is placed
+/// relative to the path it is painted on and could be moved, scaled or skewed by `transform`.
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct ImageBrush {
+    pub source: ImageSource,
+    pub repeat: ImageRepeat,
+    pub transform: Matrix,
+}
+
+impl ImageBrush {
+    /// Creates a repeated image brush that loads the image from the given path.
+    pub fn from_path(path: impl Into<String>) -> Self {
+        ImageBrush {
+            source: ImageSource::Path(path.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Creates a repeated image brush from the given ARGB pixels.
+    pub fn from_data(width: u32, height: u32, data: impl Into<Arc<Vec<u32>>>) -> Self {
+        ImageBrush {
+            source: ImageSource::Data {
+                width,
+                height,
+                data: data.into(),
+            },
+            ..Default::default()
+        }
+    }
+
+    /// Builder method that sets the repeat mode.
+    pub fn with_repeat(mut self, repeat: impl Into<ImageRepeat>) -> Self {
+        self.repeat = repeat.into();
+        self
+    }
+
+    /// Builder method that sets the transform of the image.
+    pub fn with_transform(mut self, transform: impl Into<Matrix>) -> Self {
+        self.transform = transform.into();
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_repeat_into() {
+        let repeat: ImageRepeat = "repeat-x".into();
+        assert_eq!(repeat, ImageRepeat::RepeatX);
+
+        let repeat: ImageRepeat = "NoRepeat".into();
+        assert_eq!(repeat, ImageRepeat::NoRepeat);
+
+        let repeat: ImageRepeat = "other".into();
+        assert_eq!(repeat, ImageRepeat::Repeat);
+    }
+
+    #[test]
+    fn test_image_repeat_axes() {
+        assert!(ImageRepeat::Repeat.repeat_x() && ImageRepeat::Repeat.repeat_y());
+        assert!(ImageRepeat::RepeatX.repeat_x() && !ImageRepeat::RepeatX.repeat_y());
+        assert!(!ImageRepeat::RepeatY.repeat_x() && ImageRepeat::RepeatY.repeat_y());
+        assert!(!ImageRepeat::NoRepeat.repeat_x() && !ImageRepeat::NoRepeat.repeat_y());
+    }
+}
@@ -1,45 +1,73 @@
 pub use self::alignment::*;
 pub use self::border::*;
+pub use self::box_shadow::*;
 pub use self::brush::*;
+pub use self::clip_mode::*;
 pub use self::color::*;
 pub use self::constraint::*;
 pub use self::dirty_size::*;
+pub use self::effect::*;
 pub use self::expression::*;
 pub use self::filter::*;
+pub use self::font_stretch::*;
+pub use self::font_style::*;
+pub use self::font_weight::*;
 pub use self::gradients::*;
+pub use self::image_brush::*;
+pub use self::line_cap::*;
+pub use self::line_join::*;
+pub use self::matrix::*;
 pub use self::number::*;
 pub use self::orientation::*;
 pub use self::point::*;
 pub use self::rectangle::*;
+pub use self::render_transform::*;
 pub use self::selection_mode::*;
 pub use self::size::*;
+pub use self::stretch::*;
 pub use self::string16::*;
 pub use self::text_alignment::*;
 pub use self::text_baseline::*;
+pub use self::text_trimming::*;
+pub use self::text_wrap::*;
 pub use self::thickness::*;
 pub use self::value::*;
 pub use self::visibility::*;
 
 mod alignment;
 mod border;
+mod box_shadow;
 mod brush;
+mod clip_mode;
 mod color;
 mod constraint;
 mod dirty_size;
+mod effect;
 mod expression;
 mod filter;
+mod font_stretch;
+mod font_style;
+mod font_weight;
 mod gradients;
+mod image_brush;
+mod line_cap;
+mod line_join;
+mod matrix;
 mod number;
 mod orientation;
 mod point;
 pub mod prelude;
 mod rectangle;
+mod render_transform;
 mod selection_mode;
 mod size;
 mod spacer;
+mod stretch;
 mod string16;
 mod text_alignment;
 mod text_baseline;
+mod text_trimming;
+mod text_wrap;
 mod thickness;
 mod value;
 mod visibility;
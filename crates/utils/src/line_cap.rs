@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+
+/// Describes how the end points of every line are drawn.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum LineCap {
+    /// The ends of lines are squared off at the endpoints. (default)
+    Butt,
+
+    /// The ends of lines are rounded.
+    Round,
+
+    /// The ends of lines are squared off by adding a box with an equal width and half the height of the line's thickness.
+    Square,
+}
+
+impl Default for LineCap {
+    fn default() -> Self {
+        LineCap::Butt
+    }
+}
+
+// --- Conversions ---
+
+impl From<&str> for LineCap {
+    fn from(t: &str) -> Self {
+        match t {
+            "Round" | "round" => LineCap::Round,
+            "Square" | "square" => LineCap::Square,
+            _ => LineCap::Butt,
+        }
+    }
+}
+
+impl ToString for LineCap {
+    fn to_string(&self) -> String {
+        match self {
+            LineCap::Butt => "butt".to_string(),
+            LineCap::Round => "round".to_string(),
+            LineCap::Square => "square".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_into() {
+        let line_cap: LineCap = "Round".into();
+        assert_eq!(line_cap, LineCap::Round);
+
+        let line_cap: LineCap = "round".into();
+        assert_eq!(line_cap, LineCap::Round);
+
+        let line_cap: LineCap = "Square".into();
+        assert_eq!(line_cap, LineCap::Square);
+
+        let line_cap: LineCap = "square".into();
+        assert_eq!(line_cap, LineCap::Square);
+
+        let line_cap: LineCap = "butt".into();
+        assert_eq!(line_cap, LineCap::Butt);
+
+        let line_cap: LineCap = "other".into();
+        assert_eq!(line_cap, LineCap::Butt);
+    }
+}
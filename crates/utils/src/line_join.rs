@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+
+/// Describes how two connecting segments of a line are joined together.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum LineJoin {
+    /// Connected segments are joined by extending their outside edges to connect at a single point. (default)
+    Miter,
+
+    /// Rounds off the corners of a shape.
+    Round,
+
+    /// Fills an additional triangular area between the common endpoint of connected segments.
+    Bevel,
+}
+
+impl Default for LineJoin {
+    fn default() -> Self {
+        LineJoin::Miter
+    }
+}
+
+// --- Conversions ---
+
+impl From<&str> for LineJoin {
+    fn from(t: &str) -> Self {
+        match t {
+            "Round" | "round" => LineJoin::Round,
+            "Bevel" | "bevel" => LineJoin::Bevel,
+            _ => LineJoin::Miter,
+        }
+    }
+}
+
+impl ToString for LineJoin {
+    fn to_string(&self) -> String {
+        match self {
+            LineJoin::Miter => "miter".to_string(),
+            LineJoin::Round => "round".to_string(),
+            LineJoin::Bevel => "bevel".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_into() {
+        let line_join: LineJoin = "Round".into();
+        assert_eq!(line_join, LineJoin::Round);
+
+        let line_join: LineJoin = "round".into();
+        assert_eq!(line_join, LineJoin::Round);
+
+        let line_join: LineJoin = "Bevel".into();
+        assert_eq!(line_join, LineJoin::Bevel);
+
+        let line_join: LineJoin = "bevel".into();
+        assert_eq!(line_join, LineJoin::Bevel);
+
+        let line_join: LineJoin = "miter".into();
+        assert_eq!(line_join, LineJoin::Miter);
+
+        let line_join: LineJoin = "other".into();
+        assert_eq!(line_join, LineJoin::Miter);
+    }
+}
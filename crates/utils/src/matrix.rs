@@ -0,0 +1,210 @@
+use crate::{Point, Rectangle};
+use serde::{Deserialize, Serialize};
+
+/// A `Matrix` describes a 2D affine transformation. A point is transformed by
+///
+/// ```text
+/// x' = m11 * x + m21 * y + m31
+/// y' = m12 * x + m22 * y + m32
+/// ```
+///
+/// The order of the components matches the arguments of `RenderContext2D::set_transform`.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Matrix {
+    pub m11: f64,
+    pub m12: f64,
+    pub m21: f64,
+    pub m22: f64,
+    pub m31: f64,
+    pub m32: f64,
+}
+
+impl Matrix {
+    /// Creates a new matrix from the given components.
+    pub fn new(m11: f64, m12: f64, m21: f64, m22: f64, m31: f64, m32: f64) -> Self {
+        Matrix {
+            m11,
+            m12,
+            m21,
+            m22,
+            m31,
+            m32,
+        }
+    }
+
+    /// Creates a matrix that does not transform.
+    pub fn identity() -> Self {
+        Matrix::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0)
+    }
+
+    /// Creates a matrix that moves by the given offset.
+    pub fn translation(x: f64, y: f64) -> Self {
+        Matrix::new(1.0, 0.0, 0.0, 1.0, x, y)
+    }
+
+    /// Creates a matrix that scales by the given factors.
+    pub fn scaling(x: f64, y: f64) -> Self {
+        Matrix::new(x, 0.0, 0.0, y, 0.0, 0.0)
+    }
+
+    /// Creates a matrix that rotates clockwise by the given angle in radians.
+    pub fn rotation(angle: f64) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Matrix::new(cos, sin, -sin, cos, 0.0, 0.0)
+    }
+
+    /// Creates a matrix that skews by the given angles in radians along the x and the y axis.
+    pub fn skewing(x_angle: f64, y_angle: f64) -> Self {
+        Matrix::new(1.0, y_angle.tan(), x_angle.tan(), 1.0, 0.0, 0.0)
+    }
+
+    /// Returns `true` if the matrix does not transform.
+    pub fn is_identity(&self) -> bool {
+        *self == Matrix::identity()
+    }
+
+    /// Returns a matrix that applies `self` first and `other` afterwards.
+    pub fn then(&self, other: &Matrix) -> Matrix {
+        Matrix::new(
+            self.m11 * other.m11 + self.m12 * other.m21,
+            self.m11 * other.m12 + self.m12 * other.m22,
+            self.m21 * other.m11 + self.m22 * other.m21,
+            self.m21 * other.m12 + self.m22 * other.m22,
+            self.m31 * other.m11 + self.m32 * other.m21 + other.m31,
+            self.m31 * other.m12 + self.m32 * other.m22 + other.m32,
+        )
+    }
+
+    /// Returns the determinant of the matrix.
+    pub fn determinant(&self) -> f64 {
+        self.m11 * self.m22 - self.m12 * self.m21
+    }
+
+    /// Returns the inverse of the matrix or `None` if it cannot be inverted.
+    pub fn invert(&self) -> Option<Matrix> {
+        let det = self.determinant();
+
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+
+        let inv_det = 1.0 / det;
+
+        Some(Matrix::new(
+            self.m22 * inv_det,
+            -self.m12 * inv_det,
+            -self.m21 * inv_det,
+            self.m11 * inv_det,
+            (self.m21 * self.m32 - self.m22 * self.m31) * inv_det,
+            (self.m12 * self.m31 - self.m11 * self.m32) * inv_det,
+        ))
+    }
+
+    /// Transforms the given point.
+    pub fn transform_point(&self, point: impl Into<Point>) -> Point {
+        let point = point.into();
+
+        Point::new(
+            self.m11 * point.x() + self.m21 * point.y() + self.m31,
+            self.m12 * point.x() + self.m22 * point.y() + self.m32,
+        )
+    }
+
+    /// Transforms the given rectangle and returns the bounding box of the result.
+    pub fn transform_rect(&self, rect: Rectangle) -> Rectangle {
+        let corners = [
+            self.transform_point((rect.x(), rect.y())),
+            self.transform_point((rect.x() + rect.width(), rect.y())),
+            self.transform_point((rect.x(), rect.y() + rect.height())),
+            self.transform_point((rect.x() + rect.width(), rect.y() + rect.height())),
+        ];
+
+        let mut min = corners[0];
+        let mut max = corners[0];
+
+        for corner in &corners[1..] {
+            min = Point::new(min.x().min(corner.x()), min.y().min(corner.y()));
+            max = Point::new(max.x().max(corner.x()), max.y().max(corner.y()));
+        }
+
+        Rectangle::new(min, (max.x() - min.x(), max.y() - min.y()))
+    }
+}
+
+impl Default for Matrix {
+    fn default() -> Self {
+        Matrix::identity()
+    }
+}
+
+// --- Conversions ---
+
+impl From<(f64, f64, f64, f64, f64, f64)> for Matrix {
+    fn from(t: (f64, f64, f64, f64, f64, f64)) -> Self {
+        Matrix::new(t.0, t.1, t.2, t.3, t.4, t.5)
+    }
+}
+
+impl From<[f64; 6]> for Matrix {
+    fn from(a: [f64; 6]) -> Self {
+        Matrix::new(a[0], a[1], a[2], a[3], a[4], a[5])
+    }
+}
+
+// --- Conversions ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ERROR_MARGIN: f64 = 0.00001;
+
+    fn assert_point_eq(left: Point, right: Point) {
+        assert!((left.x() - right.x()).abs() < ERROR_MARGIN);
+        assert!((left.y() - right.y()).abs() < ERROR_MARGIN);
+    }
+
+    #[test]
+    fn test_then() {
+        let matrix = Matrix::scaling(2.0, 3.0).then(&Matrix::translation(10.0, 20.0));
+        assert_point_eq(matrix.transform_point((1.0, 1.0)), Point::new(12.0, 23.0));
+
+        let matrix = Matrix::translation(10.0, 20.0).then(&Matrix::scaling(2.0, 3.0));
+        assert_point_eq(matrix.transform_point((1.0, 1.0)), Point::new(22.0, 63.0));
+    }
+
+    #[test]
+    fn test_invert() {
+        let matrix = Matrix::new(2.0, 1.0, -1.0, 3.0, 5.0, -7.0);
+        let inverse = matrix.invert().unwrap();
+        let point = Point::new(4.0, 9.0);
+
+        assert_point_eq(
+            inverse.transform_point(matrix.transform_point(point)),
+            point,
+        );
+        assert_point_eq(matrix.then(&inverse).transform_point(point), point);
+
+        assert_eq!(Matrix::scaling(0.0, 1.0).invert(), None);
+    }
+
+    #[test]
+    fn test_rotation_skewing() {
+        let matrix = Matrix::rotation(std::f64::consts::FRAC_PI_2);
+        assert_point_eq(matrix.transform_point((1.0, 0.0)), Point::new(0.0, 1.0));
+
+        let matrix = Matrix::skewing(std::f64::consts::FRAC_PI_4, 0.0);
+        assert_point_eq(matrix.transform_point((0.0, 2.0)), Point::new(2.0, 2.0));
+    }
+
+    #[test]
+    fn test_transform_rect() {
+        let rect = Matrix::rotation(std::f64::consts::FRAC_PI_2)
+            .transform_rect(Rectangle::new((0.0, 0.0), (20.0, 10.0)));
+        assert_point_eq(rect.position(), Point::new(-10.0, 0.0));
+        assert_point_eq(
+            Point::new(rect.width(), rect.height()),
+            Point::new(10.0, 20.0),
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use crate::Size;
 use derive_more::{Add, Constructor, From, Sub};
+use serde::{Deserialize, Serialize};
 use std::ops::{Add, Div, Mul, Neg};
 
 /// A `Point` is specified by a x coordinate and an y coordinate.
@@ -13,7 +14,9 @@ use std::ops::{Add, Div, Mul, Neg};
 /// assert_eq!(result.x(), 5.);
 /// assert_eq!(result.y(), 3.);
 /// ```
-#[derive(Constructor, Add, Sub, Copy, From, Clone, Default, Debug, PartialEq)]
+#[derive(
+    Constructor, Add, Sub, Copy, From, Clone, Default, Debug, PartialEq, Serialize, Deserialize,
+)]
 pub struct Point {
     x: f64,
     y: f64,
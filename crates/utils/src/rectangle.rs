@@ -1,4 +1,5 @@
 use crate::{Point, Size};
+use serde::{Deserialize, Serialize};
 
 /// A `Rectangle` is normally expressed as a top-left corner and a size
 ///
@@ -11,7 +12,7 @@ use crate::{Point, Size};
 /// assert_eq!(rectangle.width(), 10.);
 /// assert_eq!(rectangle.height(), 7.);
 /// ```
-#[derive(Copy, Clone, Default, Debug, PartialEq)]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Rectangle {
     /// Position of the rectangle.
     position: Point,
@@ -0,0 +1,265 @@
+use crate::prelude::*;
+
+/// Describes a transformation that is applied on a widget and its children when they are drawn.
+/// It does not affect the layout, but the hit testing of the mouse.
+///
+/// Could be created from a css transform like string e.g. `translate(10px, 5px)`, `scale(1.1)`,
+/// `rotate(45deg)`, `skew(10deg, 0deg)` or `matrix(1, 0, 0, 1, 10, 5)`. Several functions are
+/// separated by whitespace and applied from right to left. The origin of the transformation is
+/// set with `origin(<x>, <y>)` relative to the size of the widget, e.g. `rotate(90deg) origin(0%, 0%)`.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct RenderTransform {
+    /// The transformation relative to the origin.
+    pub matrix: Matrix,
+
+    /// The origin of the transformation relative to the size of the widget. `(0.5, 0.5)` is the
+    /// center of the widget. (default)
+    pub origin: Point,
+}
+
+impl Default for RenderTransform {
+    fn default() -> Self {
+        RenderTransform {
+            matrix: Matrix::identity(),
+            origin: Point::new(0.5, 0.5),
+        }
+    }
+}
+
+impl RenderTransform {
+    /// Creates a new render transform with the given matrix around the center of the widget.
+    pub fn new(matrix: impl Into<Matrix>) -> Self {
+        RenderTransform {
+            matrix: matrix.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Creates a render transform that moves the widget by the given offset.
+    pub fn translate(x: f64, y: f64) -> Self {
+        RenderTransform::new(Matrix::translation(x, y))
+    }
+
+    /// Creates a render transform that scales the widget by the given factors.
+    pub fn scale(x: f64, y: f64) -> Self {
+        RenderTransform::new(Matrix::scaling(x, y))
+    }
+
+    /// Creates a render transform that rotates the widget clockwise by the given angle in radians.
+    pub fn rotate(angle: f64) -> Self {
+        RenderTransform::new(Matrix::rotation(angle))
+    }
+
+    /// Creates a render transform that skews the widget by the given angles in radians.
+    pub fn skew(x_angle: f64, y_angle: f64) -> Self {
+        RenderTransform::new(Matrix::skewing(x_angle, y_angle))
+    }
+
+    /// Builder method that sets the origin relative to the size of the widget.
+    pub fn with_origin(mut self, x: f64, y: f64) -> Self {
+        self.origin = Point::new(x, y);
+        self
+    }
+
+    /// Returns a render transform that applies `self` first and `other` afterwards. The origin of
+    /// `self` is kept.
+    pub fn then(&self, other: &RenderTransform) -> RenderTransform {
+        RenderTransform {
+            matrix: self.matrix.then(&other.matrix),
+            origin: self.origin,
+        }
+    }
+
+    /// Returns `true` if the widget is not transformed.
+    pub fn is_identity(&self) -> bool {
+        self.matrix.is_identity()
+    }
+
+    /// Calculates the matrix that transforms a widget with the given bounds. The origin is placed
+    /// relative to the bounds.
+    pub fn matrix_for(&self, bounds: Rectangle) -> Matrix {
+        if self.is_identity() {
+            return Matrix::identity();
+        }
+
+        let x = bounds.x() + bounds.width() * self.origin.x();
+        let y = bounds.y() + bounds.height() * self.origin.y();
+
+        Matrix::translation(-x, -y)
+            .then(&self.matrix)
+            .then(&Matrix::translation(x, y))
+    }
+}
+
+// Reads a length in pixels.
+fn length(expression: &Expression) -> Option<f64> {
+    match expression {
+        Expression::Number(v, u) if u.is_empty() || u == "px" => Some((*v).into()),
+        _ => None,
+    }
+}
+
+// Reads an angle, numbers without unit are degrees.
+fn angle(expression: &Expression) -> Option<f64> {
+    match expression {
+        Expression::Number(v, u) if u.is_empty() => {
+            let v: f64 = (*v).into();
+            Some(v.to_radians())
+        }
+        Expression::Number(v, u) => angle_in_radians((*v).into(), u),
+        _ => None,
+    }
+}
+
+// Reads a number or a percentage as factor.
+fn factor(expression: &Expression) -> Option<f64> {
+    match expression {
+        Expression::Number(v, u) if u.is_empty() => Some((*v).into()),
+        Expression::Number(v, u) if u == "%" => {
+            let v: f64 = (*v).into();
+            Some(v / 100.0)
+        }
+        _ => None,
+    }
+}
+
+// Applies a css transform like method expression on the given render transform.
+fn apply(expression: &Expression, render_transform: &mut RenderTransform) -> Option<()> {
+    let (name, args) = match expression {
+        Expression::Method(name, args) => (name, args),
+        Expression::Other(name) if name == "none" => return Some(()),
+        _ => return None,
+    };
+
+    let matrix = match (&name[..], &args[..]) {
+        ("translate", [x]) => Matrix::translation(length(x)?, 0.0),
+        ("translate", [x, y]) => Matrix::translation(length(x)?, length(y)?),
+        ("scale", [s]) => Matrix::scaling(factor(s)?, factor(s)?),
+        ("scale", [x, y]) => Matrix::scaling(factor(x)?, factor(y)?),
+        ("rotate", [a]) => Matrix::rotation(angle(a)?),
+        ("skew", [x]) => Matrix::skewing(angle(x)?, 0.0),
+        ("skew", [x, y]) => Matrix::skewing(angle(x)?, angle(y)?),
+        ("matrix", args) if args.len() == 6 => {
+            let mut values = [0.0; 6];
+            for (value, arg) in values.iter_mut().zip(args) {
+                *value = arg.number()?.into();
+            }
+            Matrix::from(values)
+        }
+        ("origin", [x, y]) => {
+            render_transform.origin = Point::new(factor(x)?, factor(y)?);
+            return Some(());
+        }
+        _ => return None,
+    };
+
+    // the functions are applied from right to left like css transforms
+    render_transform.matrix = matrix.then(&render_transform.matrix);
+
+    Some(())
+}
+
+fn render_transform(expression: &Expression) -> Option<RenderTransform> {
+    let mut render_transform = RenderTransform::default();
+
+    match expression {
+        Expression::Complex(expressions) => {
+            for expression in expressions {
+                apply(expression, &mut render_transform)?;
+            }
+        }
+        _ => apply(expression, &mut render_transform)?,
+    }
+
+    Some(render_transform)
+}
+
+// --- Conversions ---
+
+impl From<Matrix> for RenderTransform {
+    fn from(matrix: Matrix) -> RenderTransform {
+        RenderTransform::new(matrix)
+    }
+}
+
+impl From<&str> for RenderTransform {
+    fn from(s: &str) -> RenderTransform {
+        render_transform(&Expression::from(s)).unwrap_or_default()
+    }
+}
+
+impl From<String> for RenderTransform {
+    fn from(s: String) -> RenderTransform {
+        RenderTransform::from(&s[..])
+    }
+}
+
+impl From<Value> for RenderTransform {
+    fn from(v: Value) -> RenderTransform {
+        let value = v.get::<String>();
+        RenderTransform::from(value)
+    }
+}
+
+// --- Conversions ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ERROR_MARGIN: f64 = 0.00001;
+
+    fn assert_point_eq(left: Point, right: Point) {
+        assert!((left.x() - right.x()).abs() < ERROR_MARGIN);
+        assert!((left.y() - right.y()).abs() < ERROR_MARGIN);
+    }
+
+    #[test]
+    fn test_into() {
+        let render_transform: RenderTransform = "scale(2)".into();
+        assert_eq!(render_transform, RenderTransform::scale(2.0, 2.0));
+
+        let render_transform: RenderTransform = "translate(10px, 5px)".into();
+        assert_eq!(render_transform, RenderTransform::translate(10.0, 5.0));
+
+        // rotates first and moves afterwards
+        let render_transform: RenderTransform = "translate(10, 0) rotate(90deg)".into();
+        assert_point_eq(
+            render_transform.matrix.transform_point((1.0, 0.0)),
+            Point::new(10.0, 1.0),
+        );
+
+        let render_transform: RenderTransform = "skew(45deg) origin(0%, 100%)".into();
+        assert_eq!(render_transform.origin, Point::new(0.0, 1.0));
+        assert_point_eq(
+            render_transform.matrix.transform_point((0.0, 1.0)),
+            Point::new(1.0, 1.0),
+        );
+
+        let render_transform: RenderTransform = "matrix(1, 0, 0, 1, 3, 4)".into();
+        assert_eq!(render_transform.matrix, Matrix::translation(3.0, 4.0));
+
+        let render_transform: RenderTransform = "none".into();
+        assert!(render_transform.is_identity());
+
+        let render_transform: RenderTransform = "rotate(foo)".into();
+        assert!(render_transform.is_identity());
+    }
+
+    #[test]
+    fn test_matrix_for() {
+        let bounds = Rectangle::new((10.0, 10.0), (20.0, 10.0));
+
+        // scales around the center
+        let matrix = RenderTransform::scale(2.0, 2.0).matrix_for(bounds);
+        assert_point_eq(matrix.transform_point((20.0, 15.0)), Point::new(20.0, 15.0));
+        assert_point_eq(matrix.transform_point((10.0, 10.0)), Point::new(0.0, 5.0));
+
+        // scales around the top left corner
+        let matrix = RenderTransform::scale(2.0, 2.0)
+            .with_origin(0.0, 0.0)
+            .matrix_for(bounds);
+        assert_point_eq(matrix.transform_point((10.0, 10.0)), Point::new(10.0, 10.0));
+        assert_point_eq(matrix.transform_point((20.0, 15.0)), Point::new(30.0, 20.0));
+    }
+}
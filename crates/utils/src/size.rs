@@ -1,4 +1,5 @@
 use derive_more::{Add, Constructor, From, Sub};
+use serde::{Deserialize, Serialize};
 use std::ops::Div;
 
 /// A `Size` specified by width and height.
@@ -12,7 +13,9 @@ use std::ops::Div;
 /// assert_eq!(result.width(), 5.);
 /// assert_eq!(result.height(), 3.);
 /// ```
-#[derive(Constructor, Add, Sub, Copy, From, Clone, Default, Debug, PartialEq)]
+#[derive(
+    Constructor, Add, Sub, Copy, From, Clone, Default, Debug, PartialEq, Serialize, Deserialize,
+)]
 pub struct Size {
     width: f64,
     height: f64,
@@ -0,0 +1,179 @@
+use crate::prelude::*;
+
+/// Describes how content, e.g. an image, is resized to fill the available space.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Stretch {
+    /// The content keeps its natural size. (default)
+    None,
+
+    /// The content is resized to fill the available space, the aspect ratio is not preserved.
+    Fill,
+
+    /// The content is resized to fit into the available space and keeps its aspect ratio.
+    Uniform,
+
+    /// The content is resized to cover the available space and keeps its aspect ratio. The parts
+    /// that do not fit are clipped.
+    UniformToFill,
+}
+
+impl Default for Stretch {
+    fn default() -> Self {
+        Stretch::None
+    }
+}
+
+impl Stretch {
+    /// Calculates the size of content with the given natural size that is stretched into the
+    /// available size.
+    pub fn size(
+        self,
+        width: f64,
+        height: f64,
+        available_width: f64,
+        available_height: f64,
+    ) -> (f64, f64) {
+        if width <= 0.0 || height <= 0.0 {
+            return (width, height);
+        }
+
+        let scale_x = available_width / width;
+        let scale_y = available_height / height;
+
+        match self {
+            Stretch::None => (width, height),
+            Stretch::Fill => (available_width, available_height),
+            Stretch::Uniform => {
+                let scale = scale_x.min(scale_y);
+                (width * scale, height * scale)
+            }
+            Stretch::UniformToFill => {
+                let scale = scale_x.max(scale_y);
+                (width * scale, height * scale)
+            }
+        }
+    }
+
+    /// Calculates the size that content with the given natural size requests from a layout with
+    /// the given maximal size. A side without limit follows the other side by the aspect ratio of
+    /// the content, without any limit the content keeps its natural size.
+    pub fn measure(self, width: f64, height: f64, max_width: f64, max_height: f64) -> (f64, f64) {
+        if self == Stretch::None || width <= 0.0 || height <= 0.0 {
+            return (width, height);
+        }
+
+        let bounded = |max: f64| max.is_finite() && max < f64::MAX;
+
+        match (bounded(max_width), bounded(max_height)) {
+            (true, true) if self == Stretch::Uniform => {
+                self.size(width, height, max_width, max_height)
+            }
+            (true, true) => (max_width, max_height),
+            (true, false) => (max_width, height * max_width / width),
+            (false, true) => (width * max_height / height, max_height),
+            (false, false) => (width, height),
+        }
+    }
+}
+
+// --- Conversions ---
+
+impl From<&str> for Stretch {
+    fn from(t: &str) -> Self {
+        match t {
+            "Fill" | "fill" => Stretch::Fill,
+            "Uniform" | "uniform" => Stretch::Uniform,
+            "UniformToFill" | "uniform-to-fill" | "uniform_to_fill" => Stretch::UniformToFill,
+            _ => Stretch::None,
+        }
+    }
+}
+
+impl From<String> for Stretch {
+    fn from(s: String) -> Self {
+        Stretch::from(&s[..])
+    }
+}
+
+impl From<Value> for Stretch {
+    fn from(v: Value) -> Self {
+        let value = v.get::<String>();
+        Stretch::from(value)
+    }
+}
+
+impl ToString for Stretch {
+    fn to_string(&self) -> String {
+        match self {
+            Stretch::None => "none".to_string(),
+            Stretch::Fill => "fill".to_string(),
+            Stretch::Uniform => "uniform".to_string(),
+            Stretch::UniformToFill => "uniform-to-fill".to_string(),
+        }
+    }
+}
+
+// --- Conversions ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_into() {
+        let stretch: Stretch = "Fill".into();
+        assert_eq!(stretch, Stretch::Fill);
+
+        let stretch: Stretch = "uniform".into();
+        assert_eq!(stretch, Stretch::Uniform);
+
+        let stretch: Stretch = "uniform-to-fill".into();
+        assert_eq!(stretch, Stretch::UniformToFill);
+
+        let stretch: Stretch = "other".into();
+        assert_eq!(stretch, Stretch::None);
+    }
+
+    #[test]
+    fn test_measure() {
+        assert_eq!(
+            Stretch::None.measure(20.0, 10.0, 100.0, 100.0),
+            (20.0, 10.0)
+        );
+        assert_eq!(
+            Stretch::Uniform.measure(20.0, 10.0, 100.0, 100.0),
+            (100.0, 50.0)
+        );
+        assert_eq!(
+            Stretch::UniformToFill.measure(20.0, 10.0, 100.0, 100.0),
+            (100.0, 100.0)
+        );
+        assert_eq!(
+            Stretch::Fill.measure(20.0, 10.0, 100.0, f64::MAX),
+            (100.0, 50.0)
+        );
+        assert_eq!(
+            Stretch::Uniform.measure(20.0, 10.0, f64::MAX, 30.0),
+            (60.0, 30.0)
+        );
+        assert_eq!(
+            Stretch::Uniform.measure(20.0, 10.0, f64::MAX, f64::MAX),
+            (20.0, 10.0)
+        );
+    }
+
+    #[test]
+    fn test_size() {
+        assert_eq!(Stretch::None.size(20.0, 10.0, 100.0, 100.0), (20.0, 10.0));
+        assert_eq!(Stretch::Fill.size(20.0, 10.0, 100.0, 100.0), (100.0, 100.0));
+        assert_eq!(
+            Stretch::Uniform.size(20.0, 10.0, 100.0, 100.0),
+            (100.0, 50.0)
+        );
+        assert_eq!(
+            Stretch::UniformToFill.size(20.0, 10.0, 100.0, 100.0),
+            (200.0, 100.0)
+        );
+        assert_eq!(Stretch::Uniform.size(0.0, 10.0, 100.0, 100.0), (0.0, 10.0));
+    }
+}
@@ -1,3 +1,5 @@
+use crate::prelude::*;
+
 /// Used to align a text.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum TextAlignment {
@@ -8,6 +10,54 @@ pub enum TextAlignment {
     End,
 }
 
+impl Default for TextAlignment {
+    fn default() -> Self {
+        TextAlignment::Start
+    }
+}
+
+impl TextAlignment {
+    /// Calculates the horizontal offset of a line with the given width in the available width.
+    /// Start and end are the left and right side, lines that are wider than the available width
+    /// start at the left side.
+    pub fn offset(self, width: f64, available_width: f64) -> f64 {
+        let space = (available_width - width).max(0.0);
+
+        match self {
+            TextAlignment::Left | TextAlignment::Start => 0.0,
+            TextAlignment::Right | TextAlignment::End => space,
+            TextAlignment::Center => space / 2.0,
+        }
+    }
+}
+
+// --- Conversions ---
+
+impl From<&str> for TextAlignment {
+    fn from(t: &str) -> Self {
+        match t {
+            "Left" | "left" => TextAlignment::Left,
+            "Right" | "right" => TextAlignment::Right,
+            "Center" | "center" => TextAlignment::Center,
+            "End" | "end" => TextAlignment::End,
+            _ => TextAlignment::Start,
+        }
+    }
+}
+
+impl From<String> for TextAlignment {
+    fn from(s: String) -> Self {
+        TextAlignment::from(&s[..])
+    }
+}
+
+impl From<Value> for TextAlignment {
+    fn from(v: Value) -> Self {
+        let value = v.get::<String>();
+        TextAlignment::from(value)
+    }
+}
+
 impl ToString for TextAlignment {
     fn to_string(&self) -> String {
         match self {
@@ -19,3 +69,30 @@ impl ToString for TextAlignment {
         }
     }
 }
+
+// --- Conversions ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_into() {
+        let text_alignment: TextAlignment = "center".into();
+        assert_eq!(text_alignment, TextAlignment::Center);
+
+        let text_alignment: TextAlignment = "Right".into();
+        assert_eq!(text_alignment, TextAlignment::Right);
+
+        let text_alignment: TextAlignment = "other".into();
+        assert_eq!(text_alignment, TextAlignment::Start);
+    }
+
+    #[test]
+    fn test_offset() {
+        assert_eq!(TextAlignment::Start.offset(20.0, 100.0), 0.0);
+        assert_eq!(TextAlignment::End.offset(20.0, 100.0), 80.0);
+        assert_eq!(TextAlignment::Center.offset(20.0, 100.0), 40.0);
+        assert_eq!(TextAlignment::Center.offset(120.0, 100.0), 0.0);
+    }
+}
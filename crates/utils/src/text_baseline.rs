@@ -1,4 +1,7 @@
+use serde::{Deserialize, Serialize};
+
 /// Text baseline being used when drawing text
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub enum TextBaseline {
     /// Text baseline is top of the em square
     Top,
@@ -24,3 +27,79 @@ impl Default for TextBaseline {
         TextBaseline::Alphabetic
     }
 }
+
+// Height of the hanging baseline relative to the ascent, fonts have no hanging baseline table
+// that is read, so it is approximated like browsers do.
+const HANGING_BASELINE: f64 = 0.8;
+
+impl TextBaseline {
+    /// Returns the distance from the y position of a text to its alphabetic baseline for a line
+    /// with the given ascent and descent, negative if the baseline is above the position.
+    pub fn offset(&self, ascent: f64, descent: f64) -> f64 {
+        match self {
+            TextBaseline::Top => ascent,
+            TextBaseline::Hanging => ascent * HANGING_BASELINE,
+            TextBaseline::Middle => (ascent - descent) / 2.0,
+            TextBaseline::Alphabetic => 0.0,
+            TextBaseline::Ideographic | TextBaseline::Bottom => -descent,
+        }
+    }
+}
+
+// --- Conversions ---
+
+impl From<&str> for TextBaseline {
+    fn from(t: &str) -> Self {
+        match t {
+            "Top" | "top" => TextBaseline::Top,
+            "Hanging" | "hanging" => TextBaseline::Hanging,
+            "Middle" | "middle" => TextBaseline::Middle,
+            "Ideographic" | "ideographic" => TextBaseline::Ideographic,
+            "Bottom" | "bottom" => TextBaseline::Bottom,
+            _ => TextBaseline::Alphabetic,
+        }
+    }
+}
+
+impl ToString for TextBaseline {
+    fn to_string(&self) -> String {
+        match self {
+            TextBaseline::Top => "top".to_string(),
+            TextBaseline::Hanging => "hanging".to_string(),
+            TextBaseline::Middle => "middle".to_string(),
+            TextBaseline::Alphabetic => "alphabetic".to_string(),
+            TextBaseline::Ideographic => "ideographic".to_string(),
+            TextBaseline::Bottom => "bottom".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_into() {
+        let text_baseline: TextBaseline = "Top".into();
+        assert_eq!(text_baseline, TextBaseline::Top);
+
+        let text_baseline: TextBaseline = "middle".into();
+        assert_eq!(text_baseline, TextBaseline::Middle);
+
+        let text_baseline: TextBaseline = "bottom".into();
+        assert_eq!(text_baseline, TextBaseline::Bottom);
+
+        let text_baseline: TextBaseline = "other".into();
+        assert_eq!(text_baseline, TextBaseline::Alphabetic);
+
+        assert_eq!(TextBaseline::Hanging.to_string(), "hanging");
+    }
+
+    #[test]
+    fn test_offset() {
+        assert_eq!(TextBaseline::Top.offset(8.0, 2.0), 8.0);
+        assert_eq!(TextBaseline::Middle.offset(8.0, 2.0), 3.0);
+        assert_eq!(TextBaseline::Alphabetic.offset(8.0, 2.0), 0.0);
+        assert_eq!(TextBaseline::Bottom.offset(8.0, 2.0), -2.0);
+    }
+}
@@ -0,0 +1,76 @@
+use crate::prelude::*;
+
+/// Describes how a text that does not fit into the available width or the maximum number of
+/// lines is shortened.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TextTrimming {
+    /// The text is not trimmed, it overflows the available space. (default)
+    None,
+
+    /// The end of the text is replaced by an ellipsis.
+    End,
+
+    /// The middle of the text is replaced by an ellipsis, the start and the end are kept, e.g.
+    /// for file paths.
+    Middle,
+}
+
+impl Default for TextTrimming {
+    fn default() -> Self {
+        TextTrimming::None
+    }
+}
+
+// --- Conversions ---
+
+impl From<&str> for TextTrimming {
+    fn from(t: &str) -> Self {
+        match t {
+            "End" | "end" | "ellipsis" => TextTrimming::End,
+            "Middle" | "middle" => TextTrimming::Middle,
+            _ => TextTrimming::None,
+        }
+    }
+}
+
+impl From<String> for TextTrimming {
+    fn from(s: String) -> Self {
+        TextTrimming::from(&s[..])
+    }
+}
+
+impl From<Value> for TextTrimming {
+    fn from(v: Value) -> Self {
+        let value = v.get::<String>();
+        TextTrimming::from(value)
+    }
+}
+
+impl ToString for TextTrimming {
+    fn to_string(&self) -> String {
+        match self {
+            TextTrimming::None => "none".to_string(),
+            TextTrimming::End => "end".to_string(),
+            TextTrimming::Middle => "middle".to_string(),
+        }
+    }
+}
+
+// --- Conversions ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_into() {
+        let text_trimming: TextTrimming = "end".into();
+        assert_eq!(text_trimming, TextTrimming::End);
+
+        let text_trimming: TextTrimming = "Middle".into();
+        assert_eq!(text_trimming, TextTrimming::Middle);
+
+        let text_trimming: TextTrimming = "other".into();
+        assert_eq!(text_trimming, TextTrimming::None);
+    }
+}
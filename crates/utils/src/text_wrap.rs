@@ -0,0 +1,78 @@
+use crate::prelude::*;
+
+/// Describes how the lines of a text are broken if they are wider than the available width.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TextWrap {
+    /// Lines are only broken at explicit line breaks. (default)
+    NoWrap,
+
+    /// Lines are broken between words, words that are wider than a line are broken between
+    /// chars.
+    Word,
+
+    /// Lines are broken between any chars.
+    Character,
+}
+
+impl Default for TextWrap {
+    fn default() -> Self {
+        TextWrap::NoWrap
+    }
+}
+
+// --- Conversions ---
+
+impl From<&str> for TextWrap {
+    fn from(t: &str) -> Self {
+        match t {
+            "Word" | "word" | "wrap" => TextWrap::Word,
+            "Character" | "character" | "char" => TextWrap::Character,
+            _ => TextWrap::NoWrap,
+        }
+    }
+}
+
+impl From<String> for TextWrap {
+    fn from(s: String) -> Self {
+        TextWrap::from(&s[..])
+    }
+}
+
+impl From<Value> for TextWrap {
+    fn from(v: Value) -> Self {
+        let value = v.get::<String>();
+        TextWrap::from(value)
+    }
+}
+
+impl ToString for TextWrap {
+    fn to_string(&self) -> String {
+        match self {
+            TextWrap::NoWrap => "no-wrap".to_string(),
+            TextWrap::Word => "word".to_string(),
+            TextWrap::Character => "character".to_string(),
+        }
+    }
+}
+
+// --- Conversions ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_into() {
+        let text_wrap: TextWrap = "word".into();
+        assert_eq!(text_wrap, TextWrap::Word);
+
+        let text_wrap: TextWrap = "Character".into();
+        assert_eq!(text_wrap, TextWrap::Character);
+
+        let text_wrap: TextWrap = "no-wrap".into();
+        assert_eq!(text_wrap, TextWrap::NoWrap);
+
+        let text_wrap: TextWrap = "other".into();
+        assert_eq!(text_wrap, TextWrap::NoWrap);
+    }
+}
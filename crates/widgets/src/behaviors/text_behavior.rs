@@ -225,13 +225,22 @@ impl TextBehaviorState {
         let selection = self.selection(ctx);
         let (start, end) = self.selection_start_end(selection);
 
-        let cursor_start_measure = self.measure(ctx, 0, selection.start());
-        Cursor::cursor_x_set(&mut ctx.get_widget(self.cursor), cursor_start_measure.width);
-
-        let start_measure = self.measure(ctx, 0, start);
-        Cursor::selection_x_set(&mut ctx.get_widget(self.cursor), start_measure.width);
-        let length_measure = self.measure(ctx, start, end);
-        Cursor::selection_width_set(&mut ctx.get_widget(self.cursor), length_measure.width);
+        let caret_positions = self.caret_positions(ctx);
+        let caret_position = |index: usize| {
+            caret_positions
+                .get(index)
+                .or_else(|| caret_positions.last())
+                .cloned()
+                .unwrap_or_default()
+        };
+
+        let cursor_x = caret_position(selection.start());
+        Cursor::cursor_x_set(&mut ctx.get_widget(self.cursor), cursor_x);
+
+        let start_x = caret_position(start);
+        let end_x = caret_position(end);
+        Cursor::selection_x_set(&mut ctx.get_widget(self.cursor), start_x.min(end_x));
+        Cursor::selection_width_set(&mut ctx.get_widget(self.cursor), (end_x - start_x).abs());
 
         if self.direction == Direction::None {
             return;
@@ -242,14 +251,14 @@ impl TextBehaviorState {
         let width = Cursor::bounds_ref(&ctx.get_widget(self.cursor)).width();
         let delta = width - offset;
 
-        if self.direction == Direction::Right && cursor_start_measure.width > delta {
-            let offset_delta = delta - cursor_start_measure.width;
+        if self.direction == Direction::Right && cursor_x > delta {
+            let offset_delta = delta - cursor_x;
             Cursor::offset_set(&mut ctx.get_widget(self.cursor), offset + offset_delta);
             TextBlock::offset_set(&mut ctx.get_widget(self.text_block), offset + offset_delta);
         }
 
-        if self.direction == Direction::Left && cursor_start_measure.width + offset < 0. {
-            let offset_delta = cursor_start_measure.width + offset;
+        if self.direction == Direction::Left && cursor_x + offset < 0. {
+            let offset_delta = cursor_x + offset;
             Cursor::offset_set(&mut ctx.get_widget(self.cursor), offset - offset_delta);
             TextBlock::offset_set(&mut ctx.get_widget(self.text_block), offset - offset_delta);
         }
@@ -555,32 +564,44 @@ impl TextBehaviorState {
 
     // Returns a vector with a tuple of each char's starting index (usize) and position (f64)
     fn map_chars_index_to_position(&self, ctx: &mut Context) -> Vec<(usize, f64)> {
-        let len = self.len(ctx);
-
         // start x position of the cursor is start position of the text element + padding left
         let start_position: f64 = ctx.widget().get::<Point>("position").x()
             + ctx.get_widget(self.target).get::<Thickness>("padding").left
             + *TextBlock::offset_ref(&ctx.get_widget(self.text_block));
 
-        // array which will hold char index and it's x position
-        let mut position_index: Vec<(usize, f64)> = Vec::with_capacity(len);
-        position_index.push((0, start_position));
+        // the positions follow the shaped clusters, so they are not ascending for right to left text
+        self.caret_positions(ctx)
+            .iter()
+            .enumerate()
+            .map(|(index, x)| (index, start_position + x))
+            .collect()
+    }
+
+    // Sets the font weight and style on the render context and returns the font and font size
+    fn font(&self, ctx: &mut Context) -> (String, f64) {
+        let font = TextBehavior::font_clone(&ctx.widget());
+        let font_size = *TextBehavior::font_size_ref(&ctx.widget());
+        let font_weight = *TextBehavior::font_weight_ref(&ctx.widget());
+        let font_style = *TextBehavior::font_style_ref(&ctx.widget());
 
-        for i in 0..len {
-            let bound_width: f64 = self.measure(ctx, 0, i + 1).width;
+        ctx.render_context_2_d().set_font_weight(font_weight);
+        ctx.render_context_2_d().set_font_style(font_style);
 
-            let next_position: f64 = start_position + bound_width;
+        (font, font_size)
+    }
 
-            position_index.push((i + 1, next_position));
-        }
+    // Returns the x position of the caret before each char of the text relative to the text start
+    fn caret_positions(&self, ctx: &mut Context) -> Vec<f64> {
+        let (font, font_size) = self.font(ctx);
+        let text = TextBlock::text_clone(&ctx.get_widget(self.text_block));
 
-        position_index
+        ctx.render_context_2_d()
+            .caret_positions(text.as_str(), font_size, font)
     }
 
     // measure text part
     fn measure(&self, ctx: &mut Context, start: usize, end: usize) -> TextMetrics {
-        let font = TextBehavior::font_clone(&ctx.widget());
-        let font_size = *TextBehavior::font_size_ref(&ctx.widget());
+        let (font, font_size) = self.font(ctx);
 
         if let Some(text_part) =
             String16::from(TextBlock::text_ref(&ctx.get_widget(self.text_block)).as_str())
@@ -731,6 +752,8 @@ widget!(
     ///     * focused
     ///     * font
     ///     * font_size
+    ///     * font_weight
+    ///     * font_style
     ///     * lose_focus_on_activation
     ///     * request_focus
     ///     * text
@@ -806,6 +829,12 @@ widget!(
         /// Sets or shares the font size property.
         font_size: f64,
 
+        /// Sets or shares the font weight property.
+        font_weight: FontWeight,
+
+        /// Sets or shares the font style property.
+        font_style: FontStyle,
+
         /// Sets or shares ta value that describes if the widget should lose focus on activation (when Enter pressed).
         lose_focus_on_activation: bool,
 
@@ -828,6 +857,8 @@ impl Template for TextBehavior {
         self.name("TextBehavior")
             .font_size(fonts::FONT_SIZE_12)
             .font("Roboto-Regular")
+            .font_weight("normal")
+            .font_style("normal")
             .text("")
             .selection(TextSelection::default())
             .focused(false)
@@ -1,6 +1,6 @@
 use super::behaviors::MouseBehavior;
 
-use crate::{api::prelude::*, prelude::*, proc_macros::*, theme::prelude::*};
+use crate::{api::prelude::*, prelude::*, proc_macros::*, render::Image, theme::prelude::*};
 
 widget!(
     /// The `Button` widget can be clicked by user. It's used to perform an action.
@@ -19,6 +19,14 @@ widget!(
         /// Sets or shares the border brush property.
         border_brush: Brush,
 
+        /// Sets or shares the border image property. It could be used to skin the button with a
+        /// bitmap that is scaled by nine-slice.
+        border_image: Image,
+
+        /// Sets or shares the border image slice property, the size of the borders of the border
+        /// image in pixels.
+        border_image_slice: Thickness,
+
         /// Sets or shares the padding property.
         padding: Thickness,
 
@@ -34,6 +42,12 @@ widget!(
         /// Sets or shares the font property.
         font: String,
 
+        /// Sets or shares the font weight property.
+        font_weight: FontWeight,
+
+        /// Sets or shares the font style property.
+        font_style: FontStyle,
+
         /// Sets or shares the icon property.
         icon: String,
 
@@ -64,11 +78,14 @@ impl Template for Button {
             .border_radius(4.0)
             .border_width(0.0)
             .border_brush("transparent")
+            .border_image_slice(0.0)
             .padding((16.0, 0.0, 16.0, 0.0))
             .foreground(colors::LINK_WATER_COLOR)
             .text("")
             .font_size(fonts::FONT_SIZE_12)
             .font("Roboto-Regular")
+            .font_weight("normal")
+            .font_style("normal")
             .icon("")
             .icon_font("MaterialIcons-Regular")
             .icon_size(fonts::ICON_FONT_SIZE_12)
@@ -86,6 +103,8 @@ impl Template for Button {
                             .border_radius(id)
                             .border_width(id)
                             .border_brush(id)
+                            .border_image(id)
+                            .border_image_slice(id)
                             .padding(id)
                             .opacity(id)
                             .child(
@@ -110,6 +129,8 @@ impl Template for Button {
                                             .text(id)
                                             .font_size(id)
                                             .font(id)
+                                            .font_weight(id)
+                                            .font_style(id)
                                             .opacity(id)
                                             .build(ctx),
                                     )
@@ -33,6 +33,12 @@ widget!(
         /// Sets or shares the font property.
         font: String,
 
+        /// Sets or shares the font weight property.
+        font_weight: FontWeight,
+
+        /// Sets or shares the font style property.
+        font_style: FontStyle,
+
         /// Sets or shares the icon property.
         icon: String,
 
@@ -68,6 +74,8 @@ impl Template for CheckBox {
             .text("")
             .font_size(fonts::FONT_SIZE_12)
             .font("Roboto-Regular")
+            .font_weight("normal")
+            .font_style("normal")
             .icon(material_icons_font::MD_CHECK)
             .icon_font("MaterialIcons-Regular")
             .icon_size(fonts::ICON_FONT_SIZE_12)
@@ -116,6 +124,8 @@ impl Template for CheckBox {
                                             .text(id)
                                             .font_size(id)
                                             .font(id)
+                                            .font_weight(id)
+                                            .font_style(id)
                                             .opacity(id)
                                             .build(ctx),
                                     )
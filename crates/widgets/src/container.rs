@@ -1,4 +1,4 @@
-use crate::{api::prelude::*, proc_macros::*};
+use crate::{api::prelude::*, proc_macros::*, render::Image};
 
 widget!(
     /// The `Container` layout widget surrounds its child with a padding. Draws a box around the child.
@@ -15,6 +15,18 @@ widget!(
         /// Sets or shares the border brush property.
         border_brush: Brush,
 
+        /// Sets or shares the border image property. The image is drawn above the background
+        /// with nine-slice scaling.
+        border_image: Image,
+
+        /// Sets or shares the border image slice property, the size of the borders of the border
+        /// image in pixels. The borders keep their size, only the edges and the center are
+        /// stretched.
+        border_image_slice: Thickness,
+
+        /// Sets or shares the box shadow property.
+        box_shadow: BoxShadow,
+
         /// Sets or shares the padding property.
         padding: Thickness
     }
@@ -28,6 +40,8 @@ impl Template for Container {
             .border_radius(0.0)
             .border_width(0.0)
             .border_brush("transparent")
+            .border_image_slice(0.0)
+            .box_shadow(BoxShadow::default())
     }
 
     fn render_object(&self) -> Box<dyn RenderObject> {
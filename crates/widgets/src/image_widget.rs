@@ -1,23 +1,240 @@
+use std::time::Duration;
+
 use crate::{api::prelude::*, proc_macros::*, render::prelude::*};
 
+// Delay of a frame of an animated image that does not define it.
+const DEFAULT_FRAME_DELAY: Duration = Duration::from_millis(100);
+
+/// The `ImageWidgetState` loads the image from the source with the image cache and plays
+/// animated images.
+#[derive(Default, AsAny)]
+pub struct ImageWidgetState {
+    source: String,
+    loading: bool,
+    // the delay of the current frame is over
+    frame_due: bool,
+    // a frame event is requested from the timer
+    scheduled: bool,
+    // the animation waits until the widget is visible again
+    paused: bool,
+    loops: usize,
+}
+
+impl ImageWidgetState {
+    fn load(&mut self, registry: &mut Registry, ctx: &mut Context) {
+        let source = ImageWidget::source_clone(&ctx.widget());
+
+        if source == self.source && !self.loading {
+            return;
+        }
+
+        self.source = source.clone();
+        self.loading = false;
+
+        if source.is_empty() {
+            ImageWidget::load_state_set(&mut ctx.widget(), ImageLoadState::Empty);
+            return;
+        }
+
+        let event_adapter = ctx.event_adapter();
+        let status =
+            registry
+                .get_mut::<ImageCache>("image_cache")
+                .load(source, ctx.entity, event_adapter);
+
+        match status {
+            ImageStatus::Loading => {
+                self.loading = true;
+
+                if let Some(placeholder) = ctx.widget().try_clone::<Image>("placeholder") {
+                    ImageWidget::image_set(&mut ctx.widget(), placeholder);
+                }
+
+                ImageWidget::load_state_set(&mut ctx.widget(), ImageLoadState::Loading);
+            }
+            ImageStatus::Loaded(image) => {
+                self.loops = 0;
+                ImageWidget::current_frame_set(&mut ctx.widget(), 0);
+                ImageWidget::image_set(&mut ctx.widget(), image);
+                ImageWidget::load_state_set(&mut ctx.widget(), ImageLoadState::Loaded);
+            }
+            ImageStatus::Failed(error) => {
+                if let Some(error_image) = ctx.widget().try_clone::<Image>("error_image") {
+                    ImageWidget::image_set(&mut ctx.widget(), error_image);
+                }
+
+                ImageWidget::load_state_set(&mut ctx.widget(), ImageLoadState::Failed(error));
+            }
+        }
+    }
+
+    // Returns the frame that follows the given frame and counts the loops of the animation, `None`
+    // if the last loop is over, the animation stops on its last frame.
+    fn next_frame(&mut self, frame: usize, frame_count: usize, loop_count: usize) -> Option<usize> {
+        if frame + 1 < frame_count {
+            return Some(frame + 1);
+        }
+
+        self.loops += 1;
+
+        if loop_count > 0 && self.loops >= loop_count {
+            self.loops = 0;
+            return None;
+        }
+
+        Some(0)
+    }
+
+    // Advances the frame of an animated image if its delay is over and requests the next frame.
+    fn animate(&mut self, registry: &mut Registry, ctx: &mut Context) {
+        let frame_due = std::mem::replace(&mut self.frame_due, false);
+
+        if frame_due {
+            self.scheduled = false;
+        }
+
+        let frame_count = ImageWidget::image_ref(&ctx.widget()).frame_count();
+
+        if frame_count < 2 || !*ImageWidget::playing_ref(&ctx.widget()) || self.scheduled {
+            return;
+        }
+
+        let frame_timer = registry.get_mut::<FrameTimer>("frame_timer");
+
+        // the animation is paused while the widget is hidden, the event state system marks the
+        // widget as dirty if it is visible again
+        if !ctx.is_visible(ctx.entity) {
+            if !self.paused {
+                self.paused = true;
+                frame_timer.pause(ctx.entity);
+            }
+            return;
+        }
+
+        if self.paused {
+            self.paused = false;
+            frame_timer.remove(ctx.entity);
+        }
+
+        let mut frame = (*ImageWidget::current_frame_ref(&ctx.widget())).min(frame_count - 1);
+
+        if frame_due {
+            let loop_count = *ImageWidget::loop_count_ref(&ctx.widget());
+
+            frame = match self.next_frame(frame, frame_count, loop_count) {
+                Some(frame) => frame,
+                None => {
+                    ImageWidget::playing_set(&mut ctx.widget(), false);
+                    return;
+                }
+            };
+
+            ImageWidget::current_frame_set(&mut ctx.widget(), frame);
+        }
+
+        let delay = ImageWidget::image_ref(&ctx.widget())
+            .frame_delay(frame)
+            .unwrap_or(DEFAULT_FRAME_DELAY);
+
+        self.scheduled = true;
+        frame_timer.schedule(ctx.entity, delay, ctx.event_adapter());
+    }
+}
+
+impl State for ImageWidgetState {
+    fn init(&mut self, registry: &mut Registry, ctx: &mut Context) {
+        self.load(registry, ctx);
+        self.animate(registry, ctx);
+    }
+
+    fn cleanup(&mut self, registry: &mut Registry, ctx: &mut Context) {
+        registry
+            .get_mut::<FrameTimer>("frame_timer")
+            .remove(ctx.entity);
+    }
+
+    fn update(&mut self, registry: &mut Registry, ctx: &mut Context) {
+        self.load(registry, ctx);
+        self.animate(registry, ctx);
+    }
+}
+
 widget!(
     /// The `ImageWidget` widget is used to draw an image. It is not interactive.
     ///
     /// **style:** `image-widget`
-    ImageWidget {
+    ImageWidget<ImageWidgetState>: ImageFrameHandler, ImageLoadedHandler {
         /// Sets or shares the image property.
         ///
         /// Set image property:
         /// * &str: `Image::new().image("path/to/image.png").build(xt)`
         /// * String: `Image::new().image(String::from()).build(xt)`
         /// * (width: u32, height: u32, data: Vec<u32>): `Image::new().image((width, height, vec![0; width * height]));`
-        image: Image
+        image: Image,
+
+        /// Sets or shares the source property. The image at the path is decoded on a worker thread
+        /// by the image cache and set as image after it is loaded.
+        source: String,
+
+        /// Sets or shares the placeholder property, the image that is shown while the source is loaded.
+        placeholder: Image,
+
+        /// Sets or shares the error image property, the image that is shown if the source could
+        /// not be loaded.
+        error_image: Image,
+
+        /// Gets the loading state of the source.
+        load_state: ImageLoadState,
+
+        /// Sets or shares the playing property. Animated images are played if it is `true`, the
+        /// animation is paused while the widget is hidden.
+        playing: bool,
+
+        /// Sets or shares the loop count property, the number of times an animated image is
+        /// played. `0` plays it endlessly.
+        loop_count: usize,
+
+        /// Sets or shares the index of the frame of an animated image that is drawn.
+        current_frame: usize,
+
+        /// Sets or shares the stretch property, describes how the image is resized to fill the
+        /// bounds of the widget. A stretched image is not used as size of the widget.
+        stretch: Stretch,
+
+        /// Sets or shares the horizontal alignment of the image inside of the bounds.
+        image_h_align: Alignment,
+
+        /// Sets or shares the vertical alignment of the image inside of the bounds.
+        image_v_align: Alignment,
+
+        /// Sets or shares the border radius property. The corners of the image are rounded by it.
+        border_radius: f64
     }
 );
 
 impl Template for ImageWidget {
-    fn template(self, _: Entity, _: &mut BuildContext) -> Self {
-        self.name("ImageWidget").style("image-widget").image("")
+    fn template(self, id: Entity, _: &mut BuildContext) -> Self {
+        self.name("ImageWidget")
+            .style("image-widget")
+            .image("")
+            .source("")
+            .load_state(ImageLoadState::Empty)
+            .playing(true)
+            .loop_count(0)
+            .current_frame(0)
+            .stretch("none")
+            .image_h_align("center")
+            .image_v_align("center")
+            .border_radius(0.0)
+            .on_image_loaded(move |states, _| {
+                // marks the widget as dirty to take the image from the cache
+                states.get_mut::<ImageWidgetState>(id);
+                true
+            })
+            .on_image_frame(move |states| {
+                states.get_mut::<ImageWidgetState>(id).frame_due = true;
+                true
+            })
     }
 
     fn render_object(&self) -> Box<dyn RenderObject> {
@@ -28,3 +245,29 @@ impl Template for ImageWidget {
         FixedSizeLayout::new().into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_frame() {
+        let mut state = ImageWidgetState::default();
+
+        assert_eq!(state.next_frame(0, 3, 2), Some(1));
+        assert_eq!(state.next_frame(1, 3, 2), Some(2));
+
+        // starts the second loop
+        assert_eq!(state.next_frame(2, 3, 2), Some(0));
+        assert_eq!(state.loops, 1);
+
+        // stops on the last frame of the second loop
+        assert_eq!(state.next_frame(2, 3, 2), None);
+        assert_eq!(state.loops, 0);
+
+        // a loop count of 0 plays endlessly
+        for _ in 0..10 {
+            assert_eq!(state.next_frame(2, 3, 0), Some(0));
+        }
+    }
+}
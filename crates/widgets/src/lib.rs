@@ -32,6 +32,7 @@ pub use self::scroll_indicator::*;
 pub use self::scroll_viewer::*;
 pub use self::slider::*;
 pub use self::stack::*;
+pub use self::svg_icon_block::*;
 pub use self::switch::*;
 pub use self::tab_widget::*;
 pub use self::text_block::*;
@@ -62,6 +63,7 @@ mod scroll_indicator;
 mod scroll_viewer;
 mod slider;
 mod stack;
+mod svg_icon_block;
 mod switch;
 mod tab_widget;
 mod text_block;
@@ -60,6 +60,9 @@ widget!(
         /// Sets or shares the border brush property.
         border_brush: Brush,
 
+        /// Sets or shares the box shadow property.
+        box_shadow: BoxShadow,
+
         /// Sets or shares the padding property.
         padding: Thickness,
 
@@ -81,6 +84,7 @@ impl Template for Popup {
             .border_radius(0.0)
             .border_width(0.0)
             .border_brush("transparent")
+            .box_shadow(BoxShadow::default())
             .on_mouse_down(|_, _| true)
     }
 
@@ -0,0 +1,40 @@
+use crate::{api::prelude::*, proc_macros::*, render::prelude::*, theme::prelude::*};
+
+widget!(
+    /// The `SvgIconBlock` widget is used to draw a vector icon loaded from a svg document. The
+    /// icon is rasterized at the icon size and drawn in the color of the icon brush. It is not
+    /// interactive.
+    ///
+    /// **style:** `svg_icon_block`
+    SvgIconBlock {
+        /// Sets or shares the icon property.
+        ///
+        /// Set icon property:
+        /// * &str: `SvgIconBlock::new().icon("path/to/icon.svg").build(ctx)`
+        /// * Image: `SvgIconBlock::new().icon(Image::from_svg_str(svg).unwrap()).build(ctx)`
+        icon: Image,
+
+        /// Sets or shares the icon brush property.
+        icon_brush: Brush,
+
+        /// Sets or share the icon size property.
+        icon_size: f64
+    }
+);
+
+impl Template for SvgIconBlock {
+    fn template(self, _: Entity, _: &mut BuildContext) -> Self {
+        self.name("SvgIconBlock")
+            .style("svg_icon_block")
+            .icon_brush(colors::LINK_WATER_COLOR)
+            .icon_size(fonts::ICON_FONT_SIZE_12)
+    }
+
+    fn render_object(&self) -> Box<dyn RenderObject> {
+        SvgIconRenderObject::new().into()
+    }
+
+    fn layout(&self) -> Box<dyn Layout> {
+        FixedSizeLayout::new().into()
+    }
+}
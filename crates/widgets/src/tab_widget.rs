@@ -89,6 +89,12 @@ widget!(
         /// Sets or shares the font property.
         font: String,
 
+        /// Sets or shares the font weight property.
+        font_weight: FontWeight,
+
+        /// Sets or shares the font style property.
+        font_style: FontStyle,
+
         /// Sets or shares the icon property.
         icon: String,
 
@@ -167,6 +173,8 @@ impl Template for TabHeader {
             .text("Unnamed Tab")
             .font_size(fonts::FONT_SIZE_12)
             .font("Roboto-Regular")
+            .font_weight("normal")
+            .font_style("normal")
             .icon("")
             .icon_font("MaterialIcons-Regular")
             .icon_size(fonts::ICON_FONT_SIZE_12)
@@ -188,6 +196,8 @@ impl Template for TabHeader {
                                     .v_align("center")
                                     .h_align("start")
                                     .font(id)
+                                    .font_weight(id)
+                                    .font_style(id)
                                     .font_size(id)
                                     .foreground(id)
                                     .build(ctx),
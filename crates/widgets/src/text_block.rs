@@ -30,6 +30,21 @@ impl State for TextBlockState {
 widget!(
     /// The `TextBlock` widget is used to draw text. It is not interactive.
     ///
+    /// Lines are broken at line breaks. With `text_wrap` they are also wrapped at the max width
+    /// of the widget, `text_trimming` shortens texts that do not fit with an ellipsis.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// TextBlock::new()
+    ///     .text("A long text that is wrapped between words")
+    ///     .max_width(120)
+    ///     .text_wrap("word")
+    ///     .max_lines(2)
+    ///     .text_trimming("end")
+    ///     .build(ctx);
+    /// ```
+    ///
     /// **style:** `text-block`
     TextBlock<TextBlockState> {
         /// Sets or shares the text property.
@@ -50,9 +65,31 @@ widget!(
         /// Sets or shares the font property.
         font: String,
 
+        /// Sets or shares the font weight property.
+        font_weight: FontWeight,
+
+        /// Sets or shares the font style property.
+        font_style: FontStyle,
+
         /// Defines an extra offset that can be used to the text on x axis.
         offset: f64,
 
+        /// Sets or shares how the lines are broken if they are wider than the max width.
+        text_wrap: TextWrap,
+
+        /// Sets or shares how the text is shortened with an ellipsis if it does not fit into the
+        /// max width or the max lines.
+        text_trimming: TextTrimming,
+
+        /// Sets or shares the horizontal alignment of the lines.
+        text_align: TextAlignment,
+
+        /// Sets or shares the distance between the tops of two lines relative to the font size.
+        line_height: f64,
+
+        /// Sets or shares the maximum number of lines, `0` for no limit.
+        max_lines: usize,
+
         /// Defines if the text is localizable. If set to `false` the text will not be localized.
         localizable: bool
     }
@@ -65,6 +102,9 @@ impl Template for TextBlock {
             .foreground(colors::LINK_WATER_COLOR)
             .font_size(fonts::FONT_SIZE_12)
             .font("Roboto-Regular")
+            .font_weight("normal")
+            .font_style("normal")
+            .line_height(1.15)
             .localizable(true)
     }
 
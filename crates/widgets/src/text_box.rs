@@ -31,6 +31,12 @@ widget!(
         /// Sets or shares the font property.
         font: String,
 
+        /// Sets or shares the font weight property.
+        font_weight: FontWeight,
+
+        /// Sets or shares the font style property.
+        font_style: FontStyle,
+
         /// Sets or shares the background property.
         background: Brush,
 
@@ -69,6 +75,8 @@ impl Template for TextBox {
             .text(id)
             .water_mark(id)
             .font(id)
+            .font_weight(id)
+            .font_style(id)
             .font_size(id)
             .localizable(false)
             .build(ctx);
@@ -81,6 +89,8 @@ impl Template for TextBox {
             .text_block(text_block.0)
             .focused(id)
             .font(id)
+            .font_weight(id)
+            .font_style(id)
             .font_size(id)
             .lose_focus_on_activation(id)
             .select_all_on_focus(id)
@@ -95,6 +105,8 @@ impl Template for TextBox {
             .foreground(colors::LINK_WATER_COLOR)
             .font_size(fonts::FONT_SIZE_12)
             .font("Roboto-Regular")
+            .font_weight("normal")
+            .font_style("normal")
             .selection(TextSelection::default())
             .padding(4.0)
             .background(colors::LYNCH_COLOR)
@@ -35,6 +35,12 @@ widget!(
         /// Sets or shares the font property.
         font: String,
 
+        /// Sets or shares the font weight property.
+        font_weight: FontWeight,
+
+        /// Sets or shares the font style property.
+        font_style: FontStyle,
+
         /// Sets or shares the icon property.
         icon: String,
 
@@ -74,6 +80,8 @@ impl Template for ToggleButton {
             .text("")
             .font_size(fonts::FONT_SIZE_12)
             .font("Roboto-Regular")
+            .font_weight("normal")
+            .font_style("normal")
             .icon("")
             .icon_font("MaterialIcons-Regular")
             .icon_size(fonts::ICON_FONT_SIZE_12)
@@ -119,6 +127,8 @@ impl Template for ToggleButton {
                                                     .text(id)
                                                     .font_size(id)
                                                     .font(id)
+                                                    .font_weight(id)
+                                                    .font_style(id)
                                                     .build(ctx),
                                             )
                                             .build(ctx),
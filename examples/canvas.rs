@@ -170,9 +170,16 @@ impl RenderPipeline for Graphic2DPipeline {
                     ..Default::default()
                 },
             ],
-            repeat: false,
+            spread: SpreadMethod::Pad,
+            interpolation: ColorInterpolation::LinearRgb,
         }));
         render_context.fill_rect(x, y, width, height);
+
+        render_context.set_stroke_style(utils::Brush::SolidColor(Color::from("#FFFFFF")));
+        render_context.set_line_width(2.0);
+        render_context.set_line_cap(LineCap::Round);
+        render_context.set_line_dash(&[8.0, 4.0]);
+        render_context.stroke_rect(x - 8.0, y - 8.0, width + 16.0, height + 16.0);
         render_target.draw(render_context.data());
     }
 }